@@ -43,6 +43,30 @@ fn bindgen_rocksdb() {
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("unable to write rocksdb bindings");
+
+    // version.h is C++, so the version is parsed instead of generated
+    let version_h = rocksdb_include_dir() + "/rocksdb/version.h";
+    let version_h = fs::read_to_string(version_h).expect("unable to read the rocksdb version");
+    let version_part = |name: &str| {
+        version_h
+            .lines()
+            .find_map(|line| {
+                let mut words = line.split_whitespace();
+                match (words.next(), words.next(), words.next()) {
+                    (Some("#define"), Some(define), Some(value)) if define == name => {
+                        Some(value.to_owned())
+                    }
+                    _ => None,
+                }
+            })
+            .unwrap_or_else(|| panic!("{name} is not defined by rocksdb/version.h"))
+    };
+    println!(
+        "cargo:rustc-env=ROCKSDB_VERSION={}.{}.{}",
+        version_part("ROCKSDB_MAJOR"),
+        version_part("ROCKSDB_MINOR"),
+        version_part("ROCKSDB_PATCH")
+    );
 }
 
 fn build_rocksdb() {
@@ -28,3 +28,7 @@ extern crate lz4_sys;
 extern crate zstd_sys;
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+/// Version of RocksDB the bindings were generated for, e.g. `8.9.1`, read
+/// from the `rocksdb/version.h` header next to `rocksdb/c.h`.
+pub const ROCKSDB_VERSION: &str = env!("ROCKSDB_VERSION");
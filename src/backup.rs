@@ -14,11 +14,19 @@
 //
 
 use crate::env::Env;
-use crate::{db::DBInner, ffi, ffi_util::to_cpath, DBCommon, Error, ThreadMode};
+use crate::{
+    db::DBInner, ffi, ffi_util::to_cpath, ChecksumGenType, DBCommon, Error, FileChecksum,
+    ThreadMode,
+};
 
 use libc::c_uchar;
+use std::collections::HashSet;
 use std::ffi::CString;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
 /// Represents information of a backup including timestamp of the backup
 /// and the size (please note that sum of all backups' sizes is bigger than the actual
@@ -35,13 +43,86 @@ pub struct BackupEngineInfo {
     pub num_files: u32,
 }
 
+/// Progress of a restore, see [`BackupEngine::restore_from_backup_with_progress`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RestoreProgress {
+    /// Approximate number of bytes restored so far
+    pub bytes_restored: u64,
+    /// Size of the backup being restored
+    pub total_bytes: u64,
+}
+
+/// Statistics of a backup computed from its metadata, see
+/// [`BackupEngine::get_backup_statistics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupStatistics {
+    /// ID of the backup
+    pub backup_id: u32,
+    /// Number of files of the backup
+    pub num_files: u32,
+    /// Total size of the files of the backup
+    pub size: u64,
+    /// Size of the files added by the backup, i.e. not shared with an older
+    /// backup
+    pub incremental_size: u64,
+}
+
+/// A file of a backup, see [`BackupEngine::get_backup_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupFile {
+    /// Path of the file relative to the backup directory, e.g.
+    /// `shared_checksum/000009_s<session id>_<size>.sst`
+    pub path: String,
+    /// CRC32c of the file recorded when the backup was created, comparable
+    /// with the checksums of [`LiveFile`](crate::LiveFile)
+    pub file_checksum: FileChecksum,
+}
+
+/// A backup engine managing the backups stored in a directory.
+///
+/// The engine can be sent to and shared with other threads: the methods
+/// taking `&self`, which read the backups, can run concurrently with each
+/// other, while the methods taking `&mut self`, which create, delete or
+/// restore backups, run alone. Backups of a DB shared with an `Arc` can thus
+/// be created from a background thread.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{
+///     backup::{BackupEngine, BackupEngineOptions},
+///     Env, Options, DB,
+/// };
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let path = "_path_for_rocksdb_storage_background_backup";
+/// let backup_path = "_path_for_rocksdb_storage_background_backup_backups";
+/// {
+///     let db = Arc::new(DB::open_default(path).unwrap());
+///     let opts = BackupEngineOptions::new(backup_path).unwrap();
+///     let mut engine = BackupEngine::open(&opts, &Env::new().unwrap()).unwrap();
+///     let backup_db = Arc::clone(&db);
+///     let engine = thread::spawn(move || {
+///         engine.create_new_backup_flush(&backup_db, true).unwrap();
+///         engine
+///     })
+///     .join()
+///     .unwrap();
+///     assert_eq!(engine.get_backup_info().len(), 1);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// let _ = std::fs::remove_dir_all(backup_path);
+/// ```
 pub struct BackupEngine {
     inner: *mut ffi::rocksdb_backup_engine_t,
+    backup_dir: PathBuf,
     _outlive: Env,
 }
 
 pub struct BackupEngineOptions {
     inner: *mut ffi::rocksdb_backup_engine_options_t,
+    backup_dir: PathBuf,
 }
 
 pub struct RestoreOptions {
@@ -65,6 +146,7 @@ impl BackupEngine {
 
         Ok(Self {
             inner: be,
+            backup_dir: opts.backup_dir.clone(),
             _outlive: env.clone(),
         })
     }
@@ -176,6 +258,77 @@ impl BackupEngine {
         Ok(())
     }
 
+    /// Restore from a specified backup, calling `progress` every `interval`
+    /// while the files are restored and once more when the restore succeeded.
+    ///
+    /// The C API of RocksDB does not report the progress of restores, so the
+    /// bytes restored are estimated from the size of the files written to
+    /// `db_dir` and `wal_dir` since the restore started, capped by the size of
+    /// the backup. Files already in the directories, e.g. the WAL files kept
+    /// with [`RestoreOptions::set_keep_log_files`], are not counted.
+    pub fn restore_from_backup_with_progress<D, W, F>(
+        &mut self,
+        db_dir: D,
+        wal_dir: W,
+        opts: &RestoreOptions,
+        backup_id: u32,
+        interval: Duration,
+        mut progress: F,
+    ) -> Result<(), Error>
+    where
+        D: AsRef<Path>,
+        W: AsRef<Path>,
+        F: FnMut(RestoreProgress) + Send,
+    {
+        let (db_dir, wal_dir) = (db_dir.as_ref(), wal_dir.as_ref());
+        let total_bytes = self
+            .get_backup_info()
+            .iter()
+            .find(|info| info.backup_id == backup_id)
+            .map(|info| info.size)
+            .ok_or_else(|| Error::new(format!("Not found: backup {backup_id}")))?;
+        let mut existing = dir_files(db_dir);
+        if wal_dir != db_dir {
+            existing.extend(dir_files(wal_dir));
+        }
+        let bytes_restored = || {
+            let mut files = dir_files(db_dir);
+            if wal_dir != db_dir {
+                files.extend(dir_files(wal_dir));
+            }
+            let bytes: u64 = files.difference(&existing).map(|(_, len, _)| len).sum();
+            bytes.min(total_bytes)
+        };
+
+        let finished = Mutex::new(false);
+        let condvar = Condvar::new();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut done = finished.lock().unwrap();
+                loop {
+                    done = condvar.wait_timeout(done, interval).unwrap().0;
+                    if *done {
+                        break;
+                    }
+                    progress(RestoreProgress {
+                        bytes_restored: bytes_restored(),
+                        total_bytes,
+                    });
+                }
+            });
+            let result = self.restore_from_backup(db_dir, wal_dir, opts, backup_id);
+            *finished.lock().unwrap() = true;
+            condvar.notify_all();
+            result
+        })?;
+
+        progress(RestoreProgress {
+            bytes_restored: total_bytes,
+            total_bytes,
+        });
+        Ok(())
+    }
+
     /// Checks that each file exists and that the size of the file matches our
     /// expectations. it does not check file checksum.
     ///
@@ -221,6 +374,119 @@ impl BackupEngine {
             info
         }
     }
+
+    /// Checks that each file of the backup exists and that its content
+    /// matches the checksum recorded when the backup was created.
+    ///
+    /// Unlike [`verify_backup`](Self::verify_backup), this reads every file in
+    /// chunks, so it detects corrupted content but takes time proportional to
+    /// the size of the backup.
+    pub fn verify_backup_with_checksums(&self, backup_id: u32) -> Result<(), Error> {
+        self.verify_backup(backup_id)?;
+        for (file, checksum) in self.backup_files(backup_id)? {
+            let actual = ChecksumGenType::Crc32c.checksum_file(self.backup_dir.join(&file))?;
+            if actual.value != checksum.to_be_bytes() {
+                return Err(Error::new(format!(
+                    "Corruption: checksum mismatch for {file} in backup {backup_id}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the statistics of all backups, from the oldest to the latest.
+    ///
+    /// The incremental size of a backup is the size of the files it does not
+    /// share with older backups, which tells how much the deduplication of
+    /// unchanged SST files saves.
+    pub fn get_backup_statistics(&self) -> Result<Vec<BackupStatistics>, Error> {
+        let mut infos = self.get_backup_info();
+        infos.sort_by_key(|info| info.backup_id);
+
+        let mut seen = HashSet::new();
+        let mut statistics = Vec::with_capacity(infos.len());
+        for info in infos {
+            let mut incremental_size = 0;
+            for (file, _) in self.backup_files(info.backup_id)? {
+                if seen.insert(file.clone()) {
+                    incremental_size += fs::metadata(self.backup_dir.join(&file))
+                        .map_err(|err| {
+                            Error::new(format!("IO error: failed to read {file}: {err}"))
+                        })?
+                        .len();
+                }
+            }
+            statistics.push(BackupStatistics {
+                backup_id: info.backup_id,
+                num_files: info.num_files,
+                size: info.size,
+                incremental_size,
+            });
+        }
+        Ok(statistics)
+    }
+
+    /// Returns the files of a backup with the checksums recorded when the
+    /// backup was created, e.g. to validate a copy of the backup with an
+    /// external tool.
+    pub fn get_backup_files(&self, backup_id: u32) -> Result<Vec<BackupFile>, Error> {
+        Ok(self
+            .backup_files(backup_id)?
+            .into_iter()
+            .map(|(path, crc)| BackupFile {
+                path,
+                file_checksum: FileChecksum::crc32c(crc),
+            })
+            .collect())
+    }
+
+    /// Returns the files of a backup with their checksum, read from the
+    /// `meta/<backup_id>` file of the backup directory, which lists one file
+    /// per line as `<path> crc32 <checksum>`.
+    fn backup_files(&self, backup_id: u32) -> Result<Vec<(String, u32)>, Error> {
+        let meta_path = self.backup_dir.join("meta").join(backup_id.to_string());
+        let meta = fs::read_to_string(&meta_path).map_err(|err| {
+            Error::new(format!(
+                "IO error: failed to read the metadata of backup {backup_id}: {err}"
+            ))
+        })?;
+        meta.lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                match (fields.next(), fields.next(), fields.next()) {
+                    (Some(file), Some("crc32"), Some(checksum)) => Some((file, checksum)),
+                    _ => None,
+                }
+            })
+            .map(|(file, checksum)| {
+                let checksum = checksum.parse().map_err(|err| {
+                    Error::new(format!(
+                        "Corruption: invalid checksum of {file} in backup {backup_id}: {err}"
+                    ))
+                })?;
+                Ok((file.to_owned(), checksum))
+            })
+            .collect()
+    }
+}
+
+/// Returns the path, size and modification time of the files in `dir`, a
+/// file overwritten by the restore differing from the original in one of
+/// them.
+fn dir_files(dir: &Path) -> HashSet<(PathBuf, u64, Option<SystemTime>)> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    let entry = entry.ok()?;
+                    let metadata = entry.metadata().ok()?;
+                    metadata
+                        .is_file()
+                        .then(|| (entry.path(), metadata.len(), metadata.modified().ok()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 impl BackupEngineOptions {
@@ -240,7 +506,10 @@ impl BackupEngineOptions {
             let opts = ffi::rocksdb_backup_engine_options_create(c_backup_dir.as_ptr());
             assert!(!opts.is_null(), "Could not create RocksDB backup options");
 
-            Ok(Self { inner: opts })
+            Ok(Self {
+                inner: opts,
+                backup_dir: backup_dir.to_path_buf(),
+            })
         }
     }
 
@@ -256,6 +525,40 @@ impl BackupEngineOptions {
             );
         }
     }
+
+    /// Sets the maximum number of bytes per second written to the backup
+    /// directory while creating backups, 0 meaning no limit.
+    ///
+    /// Default: 0
+    pub fn set_backup_rate_limit(&mut self, bytes_per_sec: u64) {
+        unsafe {
+            ffi::rocksdb_backup_engine_options_set_backup_rate_limit(self.inner, bytes_per_sec);
+        }
+    }
+
+    /// Sets the maximum number of bytes per second written to the DB
+    /// directory while restoring backups, 0 meaning no limit.
+    ///
+    /// Default: 0
+    pub fn set_restore_rate_limit(&mut self, bytes_per_sec: u64) {
+        unsafe {
+            ffi::rocksdb_backup_engine_options_set_restore_rate_limit(self.inner, bytes_per_sec);
+        }
+    }
+
+    /// Sets whether the WAL files are backed up. If disabled, the memtables
+    /// should be flushed before each backup, or the unflushed writes are not
+    /// in the backup.
+    ///
+    /// Default: true
+    pub fn set_backup_log_files(&mut self, backup_log_files: bool) {
+        unsafe {
+            ffi::rocksdb_backup_engine_options_set_backup_log_files(
+                self.inner,
+                c_uchar::from(backup_log_files),
+            );
+        }
+    }
 }
 
 impl RestoreOptions {
@@ -306,3 +609,13 @@ impl Drop for RestoreOptions {
         }
     }
 }
+
+// Safety note: the backup engine of RocksDB guards its state with a
+// read-write lock, the operations reading the backups taking it shared, and
+// the options are not mutated by RocksDB.
+unsafe impl Send for BackupEngine {}
+unsafe impl Sync for BackupEngine {}
+unsafe impl Send for BackupEngineOptions {}
+unsafe impl Sync for BackupEngineOptions {}
+unsafe impl Send for RestoreOptions {}
+unsafe impl Sync for RestoreOptions {}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{db::DBRole, periodic_thread::PeriodicThread, DBWithThreadMode, Error, ThreadMode};
+
+/// A background thread keeping a secondary instance up to date with its
+/// primary, see [`DBWithThreadMode::spawn_catch_up_with_primary`].
+///
+/// The thread is stopped when the handle is dropped.
+pub struct CatchUpThread(PeriodicThread);
+
+impl CatchUpThread {
+    /// Returns the error of the last failed catch up, if any, and clears it.
+    /// The thread keeps trying to catch up after an error.
+    pub fn take_error(&self) -> Option<Error> {
+        self.0.take_error()
+    }
+
+    /// Stops the thread and waits for it to finish.
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl<T: ThreadMode + Send + 'static> DBWithThreadMode<T> {
+    /// Spawns a thread calling [`try_catch_up_with_primary`] on the secondary
+    /// instance `db` every `interval`.
+    ///
+    /// Returns an error if `db` was not opened as a secondary.
+    ///
+    /// [`try_catch_up_with_primary`]: crate::DBCommon::try_catch_up_with_primary
+    pub fn spawn_catch_up_with_primary(
+        db: Arc<Self>,
+        interval: Duration,
+    ) -> Result<CatchUpThread, Error> {
+        if db.role() != DBRole::Secondary {
+            return Err(Error::new(
+                "Invalid argument: only a secondary instance can catch up with the primary"
+                    .to_owned(),
+            ));
+        }
+
+        PeriodicThread::spawn(
+            "rocksdb-catch-up",
+            move || db.try_catch_up_with_primary(),
+            interval,
+        )
+        .map(CatchUpThread)
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use crate::Error;
+
+/// Compaction statistics of a single level of a column family, or of all
+/// levels summed up.
+///
+/// Sizes and rates are reported by RocksDB in human readable units, so the
+/// values are approximations of the exact counters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CfLevelStats {
+    /// Number of files
+    pub num_files: u64,
+    /// Number of files currently being compacted
+    pub num_files_being_compacted: u64,
+    /// Total size of the files in bytes
+    pub size_bytes: u64,
+    /// Compaction score, a level with a score above 1 needs compaction
+    pub score: f64,
+    /// Data read by compactions (GB)
+    pub read_gb: f64,
+    /// Data written by flushes and compactions (GB)
+    pub write_gb: f64,
+    /// Write amplification
+    pub write_amp: f64,
+    /// Compaction read rate (MB/s)
+    pub read_mb_per_sec: f64,
+    /// Compaction write rate (MB/s)
+    pub write_mb_per_sec: f64,
+    /// Time spent in compactions (seconds)
+    pub compaction_secs: f64,
+    /// Number of compactions
+    pub compaction_count: u64,
+    /// Number of keys read by compactions
+    pub keys_in: u64,
+    /// Number of keys dropped by compactions
+    pub keys_dropped: u64,
+}
+
+/// Statistics of a column family, parsed from the
+/// `rocksdb.cfstats-no-file-histogram` property.
+///
+/// See [`DBCommon::get_cf_stats`](crate::DBCommon::get_cf_stats).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CfStats {
+    /// Statistics of the levels holding files or having been compacted,
+    /// keyed by level number
+    pub levels: BTreeMap<usize, CfLevelStats>,
+    /// Statistics summed up over all levels
+    pub sum: CfLevelStats,
+    /// Write stall counters keyed by the stall cause, e.g.
+    /// `"l0-file-count-limit-stops"` or `"total-delays"`
+    pub write_stalls: BTreeMap<String, u64>,
+}
+
+impl CfStats {
+    pub(crate) fn parse(text: &str) -> Result<Self, Error> {
+        let mut stats = Self::default();
+        let mut columns: Option<Vec<&str>> = None;
+
+        for line in text.lines() {
+            if let Some(stalls) = line.strip_prefix("Write Stall (count):") {
+                for entry in stalls.split(',') {
+                    if let Some((name, value)) = entry.split_once(':') {
+                        stats
+                            .write_stalls
+                            .insert(name.trim().to_owned(), parse_count(value.trim())?);
+                    }
+                }
+                continue;
+            }
+
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.first() {
+                // header of the per-level table
+                Some(&"Level") => columns = Some(tokens),
+                // the per-priority table and the end of a table are skipped
+                Some(&"Priority") | None => columns = None,
+                Some(&name) => {
+                    if let Some(columns) = &columns {
+                        if name == "Sum" {
+                            stats.sum = parse_level_row(columns, &tokens)?;
+                        } else if let Some(level) =
+                            name.strip_prefix('L').and_then(|l| l.parse().ok())
+                        {
+                            stats
+                                .levels
+                                .insert(level, parse_level_row(columns, &tokens)?);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+fn parse_level_row(columns: &[&str], tokens: &[&str]) -> Result<CfLevelStats, Error> {
+    let size_idx = column_index(columns, "Size")?;
+    let value = |name: &str| -> Result<&str, Error> {
+        let idx = column_index(columns, name)?;
+        // the size column is printed as a value followed by its unit, so
+        // every column after it is shifted by one token
+        let idx = if idx > size_idx { idx + 1 } else { idx };
+        tokens
+            .get(idx)
+            .copied()
+            .ok_or_else(|| Error::new(format!("Missing cfstats value for column {name}")))
+    };
+
+    let files = value("Files")?;
+    let (num_files, num_files_being_compacted) = match files.split_once('/') {
+        Some((total, compacting)) => (parse_count(total)?, parse_count(compacting)?),
+        None => (parse_count(files)?, 0),
+    };
+    let size_bytes = match tokens.get(size_idx + 1) {
+        Some(unit) => parse_size(tokens[size_idx], unit)?,
+        None => {
+            return Err(Error::new(
+                "Missing cfstats value for column Size".to_owned(),
+            ))
+        }
+    };
+
+    Ok(CfLevelStats {
+        num_files,
+        num_files_being_compacted,
+        size_bytes,
+        score: parse_float(value("Score")?)?,
+        read_gb: parse_float(value("Read(GB)")?)?,
+        write_gb: parse_float(value("Write(GB)")?)?,
+        write_amp: parse_float(value("W-Amp")?)?,
+        read_mb_per_sec: parse_float(value("Rd(MB/s)")?)?,
+        write_mb_per_sec: parse_float(value("Wr(MB/s)")?)?,
+        compaction_secs: parse_float(value("Comp(sec)")?)?,
+        compaction_count: parse_count(value("Comp(cnt)")?)?,
+        keys_in: parse_count(value("KeyIn")?)?,
+        keys_dropped: parse_count(value("KeyDrop")?)?,
+    })
+}
+
+fn column_index(columns: &[&str], name: &str) -> Result<usize, Error> {
+    columns
+        .iter()
+        .position(|column| *column == name)
+        .ok_or_else(|| Error::new(format!("Missing cfstats column {name}")))
+}
+
+fn parse_float(value: &str) -> Result<f64, Error> {
+    value.parse::<f64>().map_err(|err| {
+        Error::new(format!(
+            "Failed to convert cfstats value {value} to float: {err}"
+        ))
+    })
+}
+
+/// Parses counters which RocksDB abbreviates with a `K`, `M` or `G` suffix
+/// once they get large.
+fn parse_count(value: &str) -> Result<u64, Error> {
+    let (digits, multiplier) = match value.as_bytes().last() {
+        Some(b'K') => (&value[..value.len() - 1], 1_000),
+        Some(b'M') => (&value[..value.len() - 1], 1_000_000),
+        Some(b'G') => (&value[..value.len() - 1], 1_000_000_000),
+        _ => (value, 1),
+    };
+    let count = digits.parse::<u64>().map_err(|err| {
+        Error::new(format!(
+            "Failed to convert cfstats value {value} to int: {err}"
+        ))
+    })?;
+    count.checked_mul(multiplier).ok_or_else(|| {
+        Error::new(format!(
+            "Failed to convert cfstats value {value} to int: overflow"
+        ))
+    })
+}
+
+fn parse_size(value: &str, unit: &str) -> Result<u64, Error> {
+    let multiplier: f64 = match unit {
+        "B" => 1.0,
+        "KB" => 1024.0,
+        "MB" => 1024.0 * 1024.0,
+        "GB" => 1024.0 * 1024.0 * 1024.0,
+        "TB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(Error::new(format!("Unknown cfstats size unit {unit}"))),
+    };
+    Ok((parse_float(value)? * multiplier) as u64)
+}
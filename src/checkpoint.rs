@@ -17,19 +17,42 @@
 //!
 //! [1]: https://github.com/facebook/rocksdb/wiki/Checkpoints
 
-use crate::{db::DBInner, ffi, ffi_util::to_cpath, DBCommon, Error, ThreadMode};
-use std::{marker::PhantomData, path::Path};
+use crate::{
+    db::DBInner, ffi, ffi_util::to_cpath, manifest, periodic_thread::PeriodicThread,
+    wal_file::read_wal_from, DBCommon, Error, ThreadMode, WalPosition,
+};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-/// Undocumented parameter for `ffi::rocksdb_checkpoint_create` function. Zero by default.
+/// Default `log_size_for_flush` of `ffi::rocksdb_checkpoint_create`, the
+/// memtables are always flushed before the checkpoint is created.
 const LOG_SIZE_FOR_FLUSH: u64 = 0_u64;
 
 /// Database's checkpoint object.
 /// Used to create checkpoints of the specified DB from time to time.
+///
+/// A checkpoint object can be sent to and shared with other threads, and
+/// checkpoints can be created concurrently into different directories.
+/// Use [`new_shared`](Self::new_shared) to create checkpoints from a thread
+/// not borrowing the DB, e.g. a background thread holding an `Arc` of it.
 pub struct Checkpoint<'db> {
     inner: *mut ffi::rocksdb_checkpoint_t,
     _db: PhantomData<&'db ()>,
+    _owner: Option<Arc<dyn Send + Sync>>,
 }
 
+// Safety note: RocksDB checkpoint objects only hold a pointer to their DB,
+// which is thread-safe, and creating a checkpoint does not mutate them.
+unsafe impl<'db> Send for Checkpoint<'db> {}
+unsafe impl<'db> Sync for Checkpoint<'db> {}
+
 impl<'db> Checkpoint<'db> {
     /// Creates new checkpoint object for specific DB.
     ///
@@ -49,21 +72,235 @@ impl<'db> Checkpoint<'db> {
         Ok(Self {
             inner: checkpoint,
             _db: PhantomData,
+            _owner: None,
+        })
+    }
+
+    /// Creates new checkpoint object for a DB shared with an `Arc`, keeping
+    /// the DB open as long as the checkpoint object lives.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{checkpoint::Checkpoint, Options, DB};
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// let path = "_path_for_rocksdb_storage_shared_checkpoint";
+    /// let checkpoint_path = "_path_for_rocksdb_storage_shared_checkpoint_copy";
+    /// {
+    ///     let db = Arc::new(DB::open_default(path).unwrap());
+    ///     let checkpoint = Checkpoint::new_shared(Arc::clone(&db)).unwrap();
+    ///     thread::spawn(move || checkpoint.create_checkpoint(checkpoint_path))
+    ///         .join()
+    ///         .unwrap()
+    ///         .unwrap();
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// let _ = DB::destroy(&Options::default(), checkpoint_path);
+    /// ```
+    pub fn new_shared<T, I>(db: Arc<DBCommon<T, I>>) -> Result<Checkpoint<'static>, Error>
+    where
+        T: ThreadMode + Send + 'static,
+        I: DBInner + 'static,
+    {
+        // the object is moved into a checkpoint owning the DB
+        let checkpoint = ManuallyDrop::new(Checkpoint::new(&*db)?);
+        Ok(Checkpoint {
+            inner: checkpoint.inner,
+            _db: PhantomData,
+            _owner: Some(db as Arc<dyn Send + Sync>),
         })
     }
 
     /// Creates new physical DB checkpoint in directory specified by `path`.
     pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.create(path.as_ref(), LOG_SIZE_FOR_FLUSH)
+    }
+
+    /// Creates new physical DB checkpoint in directory specified by `path`,
+    /// flushing the memtables first only if the total size of the WAL files
+    /// is at least `log_size_for_flush` bytes.
+    ///
+    /// Without a flush the live WAL files are copied into the checkpoint, so
+    /// it still contains every write, provided writes did not disable the WAL.
+    /// Pass `u64::MAX` to never flush, e.g. to avoid creating many small SST
+    /// files when checkpoints are taken often.
+    ///
+    /// Returns the sequence number of the latest write contained in the
+    /// checkpoint, read from its MANIFEST and WAL files once it is created:
+    /// the checkpoint contains every write up to it, and none after it.
+    pub fn create_checkpoint_with_log_size_for_flush<P: AsRef<Path>>(
+        &self,
+        path: P,
+        log_size_for_flush: u64,
+    ) -> Result<u64, Error> {
+        self.create(path.as_ref(), log_size_for_flush)?;
+        checkpoint_sequence_number(path.as_ref())
+    }
+
+    fn create(&self, path: &Path, log_size_for_flush: u64) -> Result<(), Error> {
         let cpath = to_cpath(path)?;
         unsafe {
             ffi_try!(ffi::rocksdb_checkpoint_create(
                 self.inner,
                 cpath.as_ptr(),
-                LOG_SIZE_FOR_FLUSH,
+                log_size_for_flush,
             ));
         }
         Ok(())
     }
+
+    /// Creates a checkpoint and writes it to `writer` as a tar archive, e.g.
+    /// to upload it to an object storage without keeping a copy on disk.
+    ///
+    /// The checkpoint is first created in `staging_path`, which must not
+    /// exist and should be on the same filesystem as the DB so the SST files
+    /// are hard linked rather than copied. The directory is removed once the
+    /// archive is written, whether writing succeeded or not.
+    ///
+    /// The files are stored at the root of the archive. Returns the sequence
+    /// number of the latest write contained in the checkpoint, see
+    /// [`create_checkpoint_with_log_size_for_flush`].
+    ///
+    /// [`create_checkpoint_with_log_size_for_flush`]: Self::create_checkpoint_with_log_size_for_flush
+    pub fn create_checkpoint_stream<P: AsRef<Path>, W: Write>(
+        &self,
+        staging_path: P,
+        writer: W,
+    ) -> Result<u64, Error> {
+        let staging_path = staging_path.as_ref();
+        let sequence_number =
+            self.create_checkpoint_with_log_size_for_flush(staging_path, LOG_SIZE_FOR_FLUSH)?;
+        let result = write_tar(staging_path, writer);
+        let cleanup = fs::remove_dir_all(staging_path);
+        result
+            .and(cleanup)
+            .map_err(|err| Error::new(format!("Failed to stream the checkpoint: {err}")))?;
+        Ok(sequence_number)
+    }
+}
+
+/// Returns the sequence number of the latest write of the checkpoint in
+/// `path`: the last sequence number recorded in its MANIFEST, or the last
+/// one of the writes in its WAL files if they were not flushed.
+fn checkpoint_sequence_number(path: &Path) -> Result<u64, Error> {
+    let mut sequence_number = manifest::describe(path)?.last_sequence;
+    let mut reader = read_wal_from(path, WalPosition::default())?;
+    while let Some(record) = reader.next_record() {
+        let record = record?;
+        let last = (record.sequence() + u64::from(record.count())).saturating_sub(1);
+        sequence_number = sequence_number.max(last);
+    }
+    Ok(sequence_number)
+}
+
+/// Writes the files of `dir` as a ustar archive, with PAX extended headers
+/// for the names longer than 100 bytes and the files of 8 GiB or more.
+fn write_tar<W: Write>(dir: &Path, mut writer: W) -> io::Result<()> {
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .map(|entry| {
+            let name = entry?.file_name();
+            name.into_string().map_err(|name| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid file name {name:?}"),
+                )
+            })
+        })
+        .collect::<io::Result<_>>()?;
+    names.sort();
+
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    for name in names {
+        let mut file = File::open(dir.join(&name))?;
+        let size = file.metadata()?.len();
+
+        let mut extended = String::new();
+        if name.len() > TAR_NAME_SIZE {
+            extended.push_str(&pax_record("path", &name));
+        }
+        if size > TAR_MAX_SIZE {
+            extended.push_str(&pax_record("size", &size.to_string()));
+        }
+        if !extended.is_empty() {
+            writer.write_all(&tar_header(
+                &format!("PaxHeader/{name}"),
+                extended.len() as u64,
+                mtime,
+                b'x',
+            ))?;
+            write_tar_data(&mut writer, extended.as_bytes())?;
+        }
+
+        writer.write_all(&tar_header(&name, size, mtime, b'0'))?;
+        let copied = io::copy(&mut file, &mut writer)?;
+        if copied != size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{name} changed while being archived"),
+            ));
+        }
+        write_tar_padding(&mut writer, size)?;
+    }
+    // the end of the archive is marked by two empty blocks
+    writer.write_all(&[0; 1024])?;
+    writer.flush()
+}
+
+/// Size of the name field of a ustar header.
+const TAR_NAME_SIZE: usize = 100;
+/// Largest size stored in the 11 octal digits of a ustar header.
+const TAR_MAX_SIZE: u64 = (1 << 33) - 1;
+
+fn write_tar_data<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    writer.write_all(data)?;
+    write_tar_padding(writer, data.len() as u64)
+}
+
+/// Pads the data of an entry to a whole number of 512 bytes blocks.
+fn write_tar_padding<W: Write>(writer: &mut W, size: u64) -> io::Result<()> {
+    let padding = (512 - size % 512) % 512;
+    writer.write_all(&[0; 512][..padding as usize])
+}
+
+/// Formats a record of a PAX extended header, `<length> <key>=<value>\n`,
+/// whose length counts its own digits.
+fn pax_record(key: &str, value: &str) -> String {
+    let record = format!(" {key}={value}\n");
+    let mut len = record.len();
+    loop {
+        let total = len.to_string().len() + record.len();
+        if total == len {
+            return format!("{len}{record}");
+        }
+        len = total;
+    }
+}
+
+/// Returns the ustar header of an entry, with its name truncated and its
+/// size zeroed if they do not fit, in which case they must be given in a
+/// PAX extended header before it.
+fn tar_header(name: &str, size: u64, mtime: u64, type_flag: u8) -> [u8; 512] {
+    let mut header = [0_u8; 512];
+    let name = &name.as_bytes()[..name.len().min(TAR_NAME_SIZE)];
+    header[..name.len()].copy_from_slice(name);
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    let size = if size > TAR_MAX_SIZE { 0 } else { size };
+    header[124..136].copy_from_slice(format!("{size:011o}\0").as_bytes());
+    header[136..148].copy_from_slice(format!("{:011o}\0", mtime & 0o777_7777_7777).as_bytes());
+    header[156] = type_flag;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    // the checksum is computed with its own field filled with spaces
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|b| u32::from(*b)).sum();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+    header
 }
 
 impl<'db> Drop for Checkpoint<'db> {
@@ -73,3 +310,258 @@ impl<'db> Drop for Checkpoint<'db> {
         }
     }
 }
+
+/// Prefix of the names of the checkpoints of a [`CheckpointManager`].
+const MANAGED_CHECKPOINT_PREFIX: &str = "checkpoint-";
+
+/// A checkpoint maintained by a [`CheckpointManager`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedCheckpoint {
+    /// Number of the checkpoint, increasing with each checkpoint
+    pub id: u64,
+    /// Directory of the checkpoint
+    pub path: PathBuf,
+    /// Time the checkpoint was created
+    pub created: SystemTime,
+}
+
+/// Maintains rotating checkpoints of a DB in a directory.
+///
+/// Each run creates a checkpoint in a new subdirectory named after its
+/// number, e.g. `checkpoint-0000000042`, verifies it if enabled with
+/// [`verify`](Self::verify), and deletes the oldest checkpoints beyond the
+/// retention policy. Runs are triggered by
+/// [`run_once`](Self::run_once), or periodically on a thread of the manager
+/// with [`spawn`](Self::spawn).
+///
+/// The directory should be on the same filesystem as the DB, so the SST
+/// files of the checkpoints are hard linked rather than copied. Other files
+/// of the directory are left untouched.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{checkpoint::CheckpointManager, Options, DB};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let path = "_path_for_rocksdb_storage_checkpoint_manager";
+/// let checkpoints_path = "_path_for_rocksdb_storage_checkpoint_manager_checkpoints";
+/// {
+///     let db = Arc::new(DB::open_default(path).unwrap());
+///     let manager = CheckpointManager::new(Arc::clone(&db), checkpoints_path, 3)
+///         .unwrap()
+///         .max_age(Duration::from_secs(24 * 3600))
+///         .verify(true);
+///     let checkpoint = manager.run_once().unwrap();
+///     assert_eq!(manager.checkpoints().unwrap(), vec![checkpoint]);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// let _ = std::fs::remove_dir_all(checkpoints_path);
+/// ```
+pub struct CheckpointManager {
+    checkpoint: Checkpoint<'static>,
+    dir: PathBuf,
+    keep_last: usize,
+    max_age: Option<Duration>,
+    log_size_for_flush: u64,
+    verify: bool,
+}
+
+impl CheckpointManager {
+    /// Creates a manager keeping the `keep_last` latest checkpoints of `db`
+    /// in `dir`, which is created if missing.
+    ///
+    /// The checkpoints are not verified by default, see
+    /// [`verify`](Self::verify).
+    pub fn new<T, I>(
+        db: Arc<DBCommon<T, I>>,
+        dir: impl Into<PathBuf>,
+        keep_last: usize,
+    ) -> Result<Self, Error>
+    where
+        T: ThreadMode + Send + 'static,
+        I: DBInner + 'static,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|err| {
+            Error::new(format!(
+                "IO error: failed to create {}: {err}",
+                dir.display()
+            ))
+        })?;
+        Ok(Self {
+            checkpoint: Checkpoint::new_shared(db)?,
+            dir,
+            keep_last: keep_last.max(1),
+            max_age: None,
+            log_size_for_flush: LOG_SIZE_FOR_FLUSH,
+            verify: false,
+        })
+    }
+
+    /// Also deletes the checkpoints created `age` ago or more, except the
+    /// latest one.
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Sets the size of the WAL files from which the memtables are flushed
+    /// before a checkpoint, see
+    /// [`Checkpoint::create_checkpoint_with_log_size_for_flush`].
+    pub fn log_size_for_flush(mut self, log_size_for_flush: u64) -> Self {
+        self.log_size_for_flush = log_size_for_flush;
+        self
+    }
+
+    /// Sets whether each new checkpoint is verified by reading its MANIFEST
+    /// and checking that the table files of its current version are in the
+    /// checkpoint with their recorded sizes. A checkpoint failing
+    /// verification is deleted, and the older ones are kept.
+    ///
+    /// The checkpoint is not opened: verifying it writes nothing to it and
+    /// does not read its entries, whose checksums were verified when they
+    /// were written to the DB.
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Returns the checkpoints of the directory, oldest first.
+    pub fn checkpoints(&self) -> Result<Vec<ManagedCheckpoint>, Error> {
+        let io_error = |err: io::Error| {
+            Error::new(format!(
+                "IO error: failed to list {}: {err}",
+                self.dir.display()
+            ))
+        };
+        let mut checkpoints = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            let id = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix(MANAGED_CHECKPOINT_PREFIX))
+                .and_then(|id| id.parse().ok());
+            if let Some(id) = id {
+                let created = entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .map_err(io_error)?;
+                checkpoints.push(ManagedCheckpoint {
+                    id,
+                    path: entry.path(),
+                    created,
+                });
+            }
+        }
+        checkpoints.sort_by_key(|checkpoint| checkpoint.id);
+        Ok(checkpoints)
+    }
+
+    /// Creates and verifies a new checkpoint, then deletes the checkpoints
+    /// beyond the retention policy, and returns the new checkpoint.
+    pub fn run_once(&self) -> Result<ManagedCheckpoint, Error> {
+        let id = self
+            .checkpoints()?
+            .last()
+            .map_or(1, |checkpoint| checkpoint.id + 1);
+        let path = self
+            .dir
+            .join(format!("{MANAGED_CHECKPOINT_PREFIX}{id:010}"));
+        self.checkpoint.create(&path, self.log_size_for_flush)?;
+        if self.verify {
+            if let Err(err) = verify_checkpoint(&path) {
+                let _ = fs::remove_dir_all(&path);
+                return Err(err);
+            }
+        }
+
+        let mut checkpoints = self.checkpoints()?;
+        // the new checkpoint is never deleted
+        let latest = checkpoints.pop().filter(|checkpoint| checkpoint.id == id);
+        let latest = latest.ok_or_else(|| {
+            Error::new(format!(
+                "Resource busy: another checkpoint was created in {} concurrently",
+                self.dir.display()
+            ))
+        })?;
+        let now = SystemTime::now();
+        let kept = self.keep_last - 1;
+        let expired = checkpoints.len().saturating_sub(kept);
+        for (i, checkpoint) in checkpoints.iter().enumerate() {
+            let too_old = self.max_age.map_or(false, |max_age| {
+                now.duration_since(checkpoint.created)
+                    .map_or(false, |age| age >= max_age)
+            });
+            if i < expired || too_old {
+                fs::remove_dir_all(&checkpoint.path).map_err(|err| {
+                    Error::new(format!(
+                        "IO error: failed to delete {}: {err}",
+                        checkpoint.path.display()
+                    ))
+                })?;
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Spawns a thread calling [`run_once`](Self::run_once) every
+    /// `interval`, the first time right away.
+    pub fn spawn(self, interval: Duration) -> Result<CheckpointManagerThread, Error> {
+        PeriodicThread::spawn(
+            "rocksdb-checkpoint-manager",
+            move || self.run_once().map(drop),
+            interval,
+        )
+        .map(CheckpointManagerThread)
+    }
+}
+
+/// Checks that the table files of the current version of the checkpoint at
+/// `path` are in the checkpoint, with the sizes recorded in its MANIFEST.
+fn verify_checkpoint(path: &Path) -> Result<(), Error> {
+    for cf in manifest::describe(path)?.column_families {
+        for file in cf.levels.iter().flatten() {
+            let file_path = path.join(file.name());
+            let size = fs::metadata(&file_path)
+                .map_err(|err| {
+                    Error::new(format!(
+                        "Corruption: the checkpoint at {} misses {}: {err}",
+                        path.display(),
+                        file.name()
+                    ))
+                })?
+                .len();
+            if size != file.size {
+                return Err(Error::new(format!(
+                    "Corruption: {} has {size} bytes, the MANIFEST of the checkpoint records {}",
+                    file_path.display(),
+                    file.size
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A background thread running a [`CheckpointManager`], see
+/// [`CheckpointManager::spawn`].
+///
+/// The thread is stopped when the handle is dropped, after the checkpoint it
+/// is creating completes.
+pub struct CheckpointManagerThread(PeriodicThread);
+
+impl CheckpointManagerThread {
+    /// Returns the error of the last failed run, if any, and clears it. The
+    /// thread keeps running after an error.
+    pub fn take_error(&self) -> Option<Error> {
+        self.0.take_error()
+    }
+
+    /// Stops the thread and waits for it to finish.
+    pub fn stop(self) {
+        drop(self);
+    }
+}
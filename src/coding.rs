@@ -0,0 +1,36 @@
+use std::convert::TryFrom;
+
+/// Decodes a varint64 from the start of `input` and advances past it, as
+/// `GetVarint64` of RocksDB's util/coding.h does.
+pub(crate) fn read_varint64(input: &mut &[u8]) -> Option<u64> {
+    let mut value: u64 = 0;
+    for (i, &byte) in input.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            *input = &input[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Decodes a varint32 from the start of `input` and advances past it.
+pub(crate) fn read_varint32(input: &mut &[u8]) -> Option<u32> {
+    let mut rest = *input;
+    let value = u32::try_from(read_varint64(&mut rest)?).ok()?;
+    *input = rest;
+    Some(value)
+}
+
+/// Decodes a slice prefixed by its varint32 length from the start of `input`
+/// and advances past it.
+pub(crate) fn read_length_prefixed_slice<'a>(input: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let mut rest = *input;
+    let len = read_varint32(&mut rest)? as usize;
+    if rest.len() < len {
+        return None;
+    }
+    let (slice, rest) = rest.split_at(len);
+    *input = rest;
+    Some(slice)
+}
@@ -0,0 +1,8 @@
+//! Persistent data structures built on a column family, enabled by the
+//! crate feature `collections`.
+
+mod queue;
+mod secondary_index;
+
+pub use queue::{queue_options, Queue};
+pub use secondary_index::{IndexEntries, IndexEntry, SecondaryIndex};
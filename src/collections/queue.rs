@@ -0,0 +1,221 @@
+use std::convert::TryInto;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{CfId, DBWithThreadMode, Error, Options, ReadOptions, ThreadMode, WriteBatch};
+
+/// Returns the options suited to the column family of a [`Queue`].
+///
+/// The files holding mostly popped items are compacted as soon as they are
+/// written, so that reading the head of the queue does not skip over the
+/// tombstones of the items popped before. FIFO compaction is not suited to
+/// queues: it drops the oldest files, whether their items were popped or not.
+pub fn queue_options() -> Options {
+    let mut opts = Options::default();
+    opts.add_compact_on_deletion_collector_factory(128, 64, 0.5);
+    opts
+}
+
+/// A persistent FIFO queue stored in a dedicated column family.
+///
+/// Items are stored under monotonic keys, the big-endian encoding of a
+/// counter, and removed with single deletions when popped, so compactions
+/// drop them with their tombstones. The column family must not be written
+/// to otherwise, and should be created with [`queue_options`].
+///
+/// Pushes and pops are serialized by the queue, which can be shared by
+/// threads, but not by several queues opened on the same column family.
+///
+/// # Crash safety
+///
+/// Every push and every pop is a single atomic write: after a crash, the
+/// queue holds the items pushed and not popped by the writes which were
+/// persisted, in the order they were pushed. Items are deleted before being
+/// returned, so an item popped by a process which crashed before handling it
+/// is lost. To handle every item at least once, [`peek`](Self::peek) it and
+/// pop it once it is handled.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::collections::{queue_options, Queue};
+/// use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+/// use std::sync::Arc;
+///
+/// let path = "_path_for_rocksdb_storage_queue";
+/// {
+///     let mut opts = Options::default();
+///     opts.create_if_missing(true);
+///     opts.create_missing_column_families(true);
+///     let cf = ColumnFamilyDescriptor::new("jobs", queue_options());
+///     let db = Arc::new(DB::open_cf_descriptors(&opts, path, vec![cf]).unwrap());
+///     let queue = Queue::open(db, "jobs").unwrap();
+///     queue.push(b"job 1").unwrap();
+///     queue.push(b"job 2").unwrap();
+///     assert_eq!(queue.pop_batch(10).unwrap(), vec![b"job 1".to_vec(), b"job 2".to_vec()]);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct Queue<T: ThreadMode> {
+    db: Arc<DBWithThreadMode<T>>,
+    column_family: String,
+    bounds: Mutex<Bounds>,
+}
+
+/// The keys of the items of a queue.
+#[derive(Clone, Copy)]
+struct Bounds {
+    /// Key of the first item
+    head: u64,
+    /// Key of the next item pushed
+    tail: u64,
+}
+
+impl<T: ThreadMode> Queue<T> {
+    /// Opens the queue stored in the column family `column_family` of `db`,
+    /// which must be opened, with the items it holds.
+    pub fn open(
+        db: Arc<DBWithThreadMode<T>>,
+        column_family: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let queue = Self {
+            db,
+            column_family: column_family.into(),
+            bounds: Mutex::new(Bounds { head: 0, tail: 0 }),
+        };
+        let bounds = queue.with_cf(|cf| {
+            let mut iter = queue.db.raw_iterator_cf_opt(&cf, ReadOptions::default());
+            iter.seek_to_first();
+            let head = iter.key().map(|key| queue.decode_key(key)).transpose()?;
+            iter.seek_to_last();
+            let last = iter.key().map(|key| queue.decode_key(key)).transpose()?;
+            iter.status()?;
+            match (head, last) {
+                (Some(head), Some(last)) => Ok(Bounds {
+                    head,
+                    tail: last.checked_add(1).ok_or_else(|| queue.full())?,
+                }),
+                _ => Ok(Bounds { head: 0, tail: 0 }),
+            }
+        })?;
+        *queue.lock() = bounds;
+        Ok(queue)
+    }
+
+    /// Appends `item` to the queue.
+    pub fn push(&self, item: impl AsRef<[u8]>) -> Result<(), Error> {
+        let mut bounds = self.lock();
+        let tail = bounds.tail.checked_add(1).ok_or_else(|| self.full())?;
+        let key = bounds.tail.to_be_bytes();
+        self.with_cf(|cf| self.db.put_cf(&cf, key, item))?;
+        bounds.tail = tail;
+        Ok(())
+    }
+
+    /// Returns the first item of the queue without removing it, `None` if
+    /// the queue is empty.
+    pub fn peek(&self) -> Result<Option<Vec<u8>>, Error> {
+        let bounds = *self.lock();
+        if bounds.head == bounds.tail {
+            return Ok(None);
+        }
+        let item = self.with_cf(|cf| self.db.get_cf(&cf, bounds.head.to_be_bytes()))?;
+        match item {
+            Some(item) => Ok(Some(item)),
+            None => Err(self.missing_items()),
+        }
+    }
+
+    /// Removes the first item of the queue and returns it, `None` if the
+    /// queue is empty.
+    pub fn pop(&self) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.pop_batch(1)?.pop())
+    }
+
+    /// Removes up to `max_items` items from the head of the queue with a
+    /// single write, and returns them in order.
+    pub fn pop_batch(&self, max_items: usize) -> Result<Vec<Vec<u8>>, Error> {
+        let mut bounds = self.lock();
+        let head = bounds.head;
+        let end = bounds
+            .tail
+            .min(head.saturating_add(max_items.try_into().unwrap_or(u64::MAX)));
+        if head == end {
+            return Ok(Vec::new());
+        }
+        let items = self.with_cf(|cf| {
+            // the bounds skip the tombstones of the items popped before
+            let mut readopts = ReadOptions::default();
+            readopts.set_iterate_lower_bound(head.to_be_bytes());
+            readopts.set_iterate_upper_bound(end.to_be_bytes());
+            let mut iter = self.db.raw_iterator_cf_opt(&cf, readopts);
+            iter.seek_to_first();
+            let mut items = Vec::new();
+            let mut batch = WriteBatch::default();
+            while let Some((key, value)) = iter.item() {
+                batch.single_delete_cf(&cf, key);
+                items.push(value.to_vec());
+                iter.next();
+            }
+            iter.status()?;
+            if items.len() as u64 != end - head {
+                return Err(self.missing_items());
+            }
+            self.db.write(batch)?;
+            Ok(items)
+        })?;
+        bounds.head = end;
+        Ok(items)
+    }
+
+    /// Returns the number of items in the queue.
+    pub fn len(&self) -> u64 {
+        let bounds = self.lock();
+        bounds.tail - bounds.head
+    }
+
+    /// Returns `true` if the queue holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Bounds> {
+        self.bounds.lock().unwrap()
+    }
+
+    fn with_cf<R>(&self, f: impl FnOnce(CfId) -> Result<R, Error>) -> Result<R, Error> {
+        self.db
+            .cfs
+            .with_cf_internal(&self.column_family, f)
+            .unwrap_or_else(|| {
+                Err(Error::new(format!(
+                    "Invalid argument: column family {} is not opened",
+                    self.column_family
+                )))
+            })
+    }
+
+    fn decode_key(&self, key: &[u8]) -> Result<u64, Error> {
+        key.try_into().map(u64::from_be_bytes).map_err(|_| {
+            Error::new(format!(
+                "Corruption: invalid key of {} bytes in the queue of column family {}",
+                key.len(),
+                self.column_family
+            ))
+        })
+    }
+
+    fn full(&self) -> Error {
+        Error::new(format!(
+            "Invalid argument: the queue of column family {} is full, its keys reached u64::MAX",
+            self.column_family
+        ))
+    }
+
+    fn missing_items(&self) -> Error {
+        Error::new(format!(
+            "Corruption: items are missing from the queue of column family {}, \
+             which was written to by another writer",
+            self.column_family
+        ))
+    }
+}
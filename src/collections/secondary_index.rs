@@ -0,0 +1,424 @@
+use std::collections::BTreeSet;
+use std::iter::FusedIterator;
+use std::sync::Mutex;
+
+use crate::{
+    column_family::column_family_name,
+    db::{DBAccess, DBInner},
+    AsColumnFamilyRef, CfId, DBCommon, DBRawIteratorWithThreadMode, DBWithThreadMode, Error,
+    IterateBounds, ReadOptions, ThreadMode, Transaction, WriteBatch,
+};
+
+/// An entry of a [`SecondaryIndex`]: a value extracted from the value of a
+/// key of the primary column family.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// The indexed value
+    pub value: Vec<u8>,
+    /// The key of the primary column family
+    pub key: Vec<u8>,
+}
+
+/// A secondary index of the values of a primary column family, stored in an
+/// index column family.
+///
+/// The indexed values of a key are extracted from its value by a function
+/// returning zero, one or several values, e.g. a field of a serialized
+/// record. The index holds an entry per indexed value and key, sorted by
+/// indexed value then key, read by [`range`](Self::range).
+///
+/// The index is maintained by writing the primary column family through it:
+///
+/// * [`put`](Self::put) and [`delete`](Self::delete) write a key and its
+///   index entries in a single batch, and are serialized by the index.
+/// * [`put_in_batch`](Self::put_in_batch) and
+///   [`delete_in_batch`](Self::delete_in_batch) add them to a batch of the
+///   application, to update several keys or column families atomically.
+/// * [`put_in_transaction`](Self::put_in_transaction) and
+///   [`delete_in_transaction`](Self::delete_in_transaction) write them in a
+///   transaction, which conflicts with the transactions updating the same
+///   key concurrently.
+///
+/// Writing the primary column family otherwise leaves the index stale.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::collections::SecondaryIndex;
+/// use rocksdb::{Options, DB};
+///
+/// let path = "_path_for_rocksdb_storage_secondary_index";
+/// {
+///     let mut opts = Options::default();
+///     opts.create_if_missing(true);
+///     opts.create_missing_column_families(true);
+///     let db = DB::open_cf(&opts, path, ["users", "users_by_city"]).unwrap();
+///     // the values are "name,city"
+///     let by_city = SecondaryIndex::new("users", "users_by_city", |_key: &[u8], value: &[u8]| {
+///         value.splitn(2, |b| *b == b',').skip(1).map(<[u8]>::to_vec).collect()
+///     });
+///     by_city.put(&db, b"1", b"alice,paris").unwrap();
+///     by_city.put(&db, b"2", b"bob,oslo").unwrap();
+///     by_city.put(&db, b"1", b"alice,rome").unwrap();
+///
+///     let keys: Vec<_> = by_city
+///         .range(&db, &b"paris"[..]..&b"zurich"[..])
+///         .map(|entry| entry.unwrap().key)
+///         .collect();
+///     assert_eq!(keys, vec![b"1".to_vec()]);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct SecondaryIndex<F> {
+    primary: String,
+    index: String,
+    extract: F,
+    lock: Mutex<()>,
+}
+
+impl<F> SecondaryIndex<F>
+where
+    F: Fn(&[u8], &[u8]) -> Vec<Vec<u8>>,
+{
+    /// Creates an index of the column family `primary` stored in the column
+    /// family `index`, `extract` returning the indexed values of a key and
+    /// its value.
+    ///
+    /// `extract` must return the same values for the same key and value,
+    /// otherwise the entries of a key cannot be deleted when it is updated.
+    pub fn new(primary: impl Into<String>, index: impl Into<String>, extract: F) -> Self {
+        Self {
+            primary: primary.into(),
+            index: index.into(),
+            extract,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Writes `value` under `key` in the primary column family and updates
+    /// its index entries, in a single batch.
+    pub fn put<T: ThreadMode>(
+        &self,
+        db: &DBWithThreadMode<T>,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        // the value read to find the entries to delete must not change
+        // until the batch is written
+        let _guard = self.lock.lock().unwrap();
+        let mut batch = WriteBatch::default();
+        self.put_in_batch(db, &mut batch, key, value)?;
+        db.write(batch)
+    }
+
+    /// Deletes `key` from the primary column family along with its index
+    /// entries, in a single batch.
+    pub fn delete<T: ThreadMode>(
+        &self,
+        db: &DBWithThreadMode<T>,
+        key: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let _guard = self.lock.lock().unwrap();
+        let mut batch = WriteBatch::default();
+        self.delete_in_batch(db, &mut batch, key)?;
+        db.write(batch)
+    }
+
+    /// Adds the write of `value` under `key` in the primary column family,
+    /// and the update of its index entries, to `batch`.
+    ///
+    /// The entries to delete are those of the value of the key in `db`: the
+    /// key must not be written until the batch is, neither concurrently nor
+    /// earlier in the same batch.
+    pub fn put_in_batch<T: ThreadMode, I: DBInner>(
+        &self,
+        db: &DBCommon<T, I>,
+        batch: &mut WriteBatch,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        self.with_cfs(db, |primary, index| {
+            let old = db.get_cf(&primary, key)?;
+            let (deleted, added) = self.changes(key, old.as_deref(), Some(value));
+            for entry in deleted {
+                batch.delete_cf(&index, entry);
+            }
+            for entry in added {
+                batch.put_cf(&index, entry, b"");
+            }
+            batch.put_cf(&primary, key, value);
+            Ok(())
+        })
+    }
+
+    /// Adds the deletion of `key` from the primary column family, and of
+    /// its index entries, to `batch`, see
+    /// [`put_in_batch`](Self::put_in_batch).
+    pub fn delete_in_batch<T: ThreadMode, I: DBInner>(
+        &self,
+        db: &DBCommon<T, I>,
+        batch: &mut WriteBatch,
+        key: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let key = key.as_ref();
+        self.with_cfs(db, |primary, index| {
+            let old = db.get_cf(&primary, key)?;
+            let (deleted, _) = self.changes(key, old.as_deref(), None);
+            for entry in deleted {
+                batch.delete_cf(&index, entry);
+            }
+            batch.delete_cf(&primary, key);
+            Ok(())
+        })
+    }
+
+    /// Writes `value` under `key` in the primary column family and updates
+    /// its index entries in the transaction `txn`, `primary` and `index`
+    /// being the handles of the column families of the index.
+    ///
+    /// The key is read for update, so the transaction fails to commit, or to
+    /// lock the key, if another one updates it concurrently.
+    pub fn put_in_transaction<D>(
+        &self,
+        txn: &Transaction<'_, D>,
+        primary: &impl AsColumnFamilyRef,
+        index: &impl AsColumnFamilyRef,
+        key: impl AsRef<[u8]>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let (key, value) = (key.as_ref(), value.as_ref());
+        self.check_cfs(primary, index)?;
+        let old = txn.get_for_update_cf(primary, key, true)?;
+        let (deleted, added) = self.changes(key, old.as_deref(), Some(value));
+        for entry in deleted {
+            txn.delete_cf(index, entry)?;
+        }
+        for entry in added {
+            txn.put_cf(index, entry, b"")?;
+        }
+        txn.put_cf(primary, key, value)
+    }
+
+    /// Deletes `key` from the primary column family along with its index
+    /// entries in the transaction `txn`, see
+    /// [`put_in_transaction`](Self::put_in_transaction).
+    pub fn delete_in_transaction<D>(
+        &self,
+        txn: &Transaction<'_, D>,
+        primary: &impl AsColumnFamilyRef,
+        index: &impl AsColumnFamilyRef,
+        key: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let key = key.as_ref();
+        self.check_cfs(primary, index)?;
+        let old = txn.get_for_update_cf(primary, key, true)?;
+        let (deleted, _) = self.changes(key, old.as_deref(), None);
+        for entry in deleted {
+            txn.delete_cf(index, entry)?;
+        }
+        txn.delete_cf(primary, key)
+    }
+
+    /// Returns the index entries whose indexed value is in `values`, sorted
+    /// by indexed value then key.
+    pub fn range<'a, T: ThreadMode, I: DBInner>(
+        &self,
+        db: &'a DBCommon<T, I>,
+        values: impl IterateBounds,
+    ) -> IndexEntries<'a, DBCommon<T, I>> {
+        let (lower, upper) = values.into_bounds();
+        let mut readopts = ReadOptions::default();
+        if let Some(lower) = lower {
+            readopts.set_iterate_lower_bound(encode_value(&lower));
+        }
+        if let Some(upper) = upper {
+            readopts.set_iterate_upper_bound(encode_value(&upper));
+        }
+        let iter = db.cfs.with_cf_internal(&self.index, |index| {
+            db.raw_iterator_cf_opt(&index, readopts)
+        });
+        match iter {
+            Some(iter) => IndexEntries::new(iter),
+            None => IndexEntries::failed(not_opened(&self.index)),
+        }
+    }
+
+    /// Returns the keys of the primary column family whose indexed values
+    /// include `value`, sorted.
+    pub fn keys<T: ThreadMode, I: DBInner>(
+        &self,
+        db: &DBCommon<T, I>,
+        value: impl AsRef<[u8]>,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let value = value.as_ref();
+        // the smallest value greater than `value`
+        let mut end = value.to_vec();
+        end.push(0);
+        self.range(db, value.to_vec()..end)
+            .map(|entry| entry.map(|entry| entry.key))
+            .collect()
+    }
+
+    /// Returns the index entries to delete and to add when the value of
+    /// `key` changes from `old` to `new`.
+    fn changes(
+        &self,
+        key: &[u8],
+        old: Option<&[u8]>,
+        new: Option<&[u8]>,
+    ) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        let entries = |value: Option<&[u8]>| -> BTreeSet<Vec<u8>> {
+            value.map_or_else(BTreeSet::new, |value| {
+                (self.extract)(key, value)
+                    .iter()
+                    .map(|indexed| index_key(indexed, key))
+                    .collect()
+            })
+        };
+        let (old, new) = (entries(old), entries(new));
+        (
+            old.difference(&new).cloned().collect(),
+            new.difference(&old).cloned().collect(),
+        )
+    }
+
+    fn with_cfs<T: ThreadMode, I: DBInner, R>(
+        &self,
+        db: &DBCommon<T, I>,
+        f: impl FnOnce(CfId, CfId) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        db.cfs
+            .with_cf_internal(&self.primary, |primary| {
+                db.cfs
+                    .with_cf_internal(&self.index, |index| f(primary, index))
+                    .unwrap_or_else(|| Err(not_opened(&self.index)))
+            })
+            .unwrap_or_else(|| Err(not_opened(&self.primary)))
+    }
+
+    fn check_cfs(
+        &self,
+        primary: &impl AsColumnFamilyRef,
+        index: &impl AsColumnFamilyRef,
+    ) -> Result<(), Error> {
+        for (cf, name) in [
+            (primary.inner(), &self.primary),
+            (index.inner(), &self.index),
+        ] {
+            let actual = column_family_name(cf);
+            if actual != *name {
+                return Err(Error::new(format!(
+                    "Invalid argument: expected the handle of column family {name}, \
+                     got the handle of column family {actual}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn not_opened(name: &str) -> Error {
+    Error::new(format!(
+        "Invalid argument: column family {name} is not opened"
+    ))
+}
+
+/// Encodes `value` so that the encoded values sort like the values and no
+/// encoded value is a prefix of another: the zero bytes are escaped as
+/// `0x00 0xFF` and the value is terminated by `0x00 0x01`.
+fn encode_value(value: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(value.len() + 2);
+    for &byte in value {
+        encoded.push(byte);
+        if byte == 0 {
+            encoded.push(0xFF);
+        }
+    }
+    encoded.extend_from_slice(&[0, 1]);
+    encoded
+}
+
+/// Returns the key of the index entry of `value` for the primary key `key`.
+fn index_key(value: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut index_key = encode_value(value);
+    index_key.extend_from_slice(key);
+    index_key
+}
+
+fn decode_index_key(index_key: &[u8]) -> Result<IndexEntry, Error> {
+    let mut value = Vec::new();
+    let mut i = 0;
+    while i + 1 < index_key.len() {
+        match (index_key[i], index_key[i + 1]) {
+            (0, 0xFF) => value.push(0),
+            (0, 1) => {
+                return Ok(IndexEntry {
+                    value,
+                    key: index_key[i + 2..].to_vec(),
+                })
+            }
+            (0, _) => break,
+            (byte, _) => {
+                value.push(byte);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    Err(Error::new(
+        "Corruption: invalid key in the column family of a secondary index".to_owned(),
+    ))
+}
+
+/// An iterator over the entries of a [`SecondaryIndex`], see
+/// [`SecondaryIndex::range`].
+pub struct IndexEntries<'a, D: DBAccess> {
+    iter: Option<DBRawIteratorWithThreadMode<'a, D>>,
+    error: Option<Error>,
+}
+
+impl<'a, D: DBAccess> IndexEntries<'a, D> {
+    fn new(mut iter: DBRawIteratorWithThreadMode<'a, D>) -> Self {
+        iter.seek_to_first();
+        Self {
+            iter: Some(iter),
+            error: None,
+        }
+    }
+
+    fn failed(error: Error) -> Self {
+        Self {
+            iter: None,
+            error: Some(error),
+        }
+    }
+}
+
+impl<'a, D: DBAccess> Iterator for IndexEntries<'a, D> {
+    type Item = Result<IndexEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        let iter = self.iter.as_mut()?;
+        let entry = match iter.key() {
+            Some(key) => decode_index_key(key),
+            None => {
+                let status = iter.status();
+                self.iter = None;
+                return status.err().map(Err);
+            }
+        };
+        iter.next();
+        // stops after the first error
+        if entry.is_err() {
+            self.iter = None;
+        }
+        Some(entry)
+    }
+}
+
+impl<'a, D: DBAccess> FusedIterator for IndexEntries<'a, D> {}
@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{db::MultiThreaded, ffi, Options};
+use crate::{
+    comparator::CompareFn, db::MultiThreaded, ffi, ffi_util::CStrLike, merge_operator::MergeFn,
+    Error, MemtableFactory, Options,
+};
 
 use std::sync::Arc;
+use std::time::Duration;
 
 /// The name of the default column family.
 ///
@@ -25,9 +29,12 @@ pub const DEFAULT_COLUMN_FAMILY_NAME: &str = "default";
 /// A descriptor for a RocksDB column family.
 ///
 /// A description of the column family, containing the name and `Options`.
+#[derive(Clone, Debug)]
 pub struct ColumnFamilyDescriptor {
     pub(crate) name: String,
     pub(crate) options: Options,
+    pub(crate) ttl: Option<Duration>,
+    pub(crate) lazy: bool,
 }
 
 impl ColumnFamilyDescriptor {
@@ -39,6 +46,19 @@ impl ColumnFamilyDescriptor {
         Self {
             name: name.into(),
             options,
+            ttl: None,
+            lazy: false,
+        }
+    }
+
+    /// Returns a builder of a descriptor of the column family `name`, with
+    /// default `Options`.
+    pub fn builder<S>(name: S) -> ColumnFamilyDescriptorBuilder
+    where
+        S: Into<String>,
+    {
+        ColumnFamilyDescriptorBuilder {
+            descriptor: Self::new(name, Options::default()),
         }
     }
 
@@ -46,6 +66,157 @@ impl ColumnFamilyDescriptor {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Returns the options of the column family.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Returns the options of the column family for modification.
+    pub fn options_mut(&mut self) -> &mut Options {
+        &mut self.options
+    }
+
+    /// Returns the time to live of the column family, if it overrides the
+    /// one the DB is opened with, see [`ColumnFamilyDescriptorBuilder::ttl`].
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Returns `true` if the column family is only opened if it exists, see
+    /// [`ColumnFamilyDescriptorBuilder::lazy`].
+    pub fn is_lazy(&self) -> bool {
+        self.lazy
+    }
+
+    /// Fails if the descriptor has a time to live or is lazy, which only
+    /// [`DBWithThreadMode`](crate::DBWithThreadMode) supports, when opening a
+    /// `db` of another type.
+    pub(crate) fn check_supported_by(&self, db: &str) -> Result<(), Error> {
+        let unsupported = if self.ttl.is_some() {
+            "has a time to live"
+        } else if self.lazy {
+            "is lazy"
+        } else {
+            return Ok(());
+        };
+        Err(Error::new(format!(
+            "Invalid argument: column family {} {unsupported}, which {db} does not support",
+            self.name
+        )))
+    }
+
+    /// Sets the memtable of the column family, see
+    /// [`Options::set_memtable_factory`].
+    pub fn with_memtable_factory(mut self, factory: MemtableFactory) -> Self {
+        self.options.set_memtable_factory(factory);
+        self
+    }
+}
+
+/// Builds a [`ColumnFamilyDescriptor`].
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{ColumnFamilyDescriptor, Options};
+/// use std::time::Duration;
+///
+/// let mut opts = Options::default();
+/// opts.set_write_buffer_size(4 << 20);
+/// let descriptor = ColumnFamilyDescriptor::builder("sessions")
+///     .options(opts)
+///     .ttl(Duration::from_secs(3600))
+///     .merge_operator_associative("concat", |_key, existing, operands| {
+///         let mut value = existing.map(<[u8]>::to_vec).unwrap_or_default();
+///         operands.iter().for_each(|op| value.extend_from_slice(op));
+///         Some(value)
+///     })
+///     .build();
+///
+/// assert_eq!(descriptor.name(), "sessions");
+/// assert_eq!(descriptor.ttl(), Some(Duration::from_secs(3600)));
+/// assert!(!descriptor.is_lazy());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ColumnFamilyDescriptorBuilder {
+    descriptor: ColumnFamilyDescriptor,
+}
+
+impl ColumnFamilyDescriptorBuilder {
+    /// Returns the descriptor.
+    pub fn build(self) -> ColumnFamilyDescriptor {
+        self.descriptor
+    }
+
+    /// Sets the options of the column family, replacing the comparator and
+    /// merge operator attached before.
+    pub fn options(mut self, options: Options) -> Self {
+        self.descriptor.options = options;
+        self
+    }
+
+    /// Sets the time to live of the entries of the column family, instead of
+    /// the one given to
+    /// [`open_cf_descriptors_with_ttl`](crate::DBWithThreadMode::open_cf_descriptors_with_ttl).
+    ///
+    /// Opening a descriptor with a time to live by any other means fails.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.descriptor.ttl = Some(ttl);
+        self
+    }
+
+    /// Only opens the column family if it already exists in the DB, instead
+    /// of failing or creating it when it is missing.
+    ///
+    /// RocksDB must open every existing column family, so a missing lazy
+    /// column family is created later, e.g. with
+    /// [`create_cf`](crate::DBWithThreadMode::create_cf). For the same
+    /// reason, an existing column family cannot be left out and opened on
+    /// first access, except in read-only and secondary mode where a subset
+    /// of the column families may be opened.
+    ///
+    /// Opening a lazy descriptor with a
+    /// [`TransactionDB`](crate::TransactionDB) or an
+    /// [`OptimisticTransactionDB`](crate::OptimisticTransactionDB) fails.
+    pub fn lazy(mut self, lazy: bool) -> Self {
+        self.descriptor.lazy = lazy;
+        self
+    }
+
+    /// Attaches a comparator to the column family, see
+    /// [`Options::set_comparator`].
+    pub fn comparator(mut self, name: impl CStrLike, compare_fn: Box<CompareFn>) -> Self {
+        self.descriptor.options.set_comparator(name, compare_fn);
+        self
+    }
+
+    /// Attaches an associative merge operator to the column family, see
+    /// [`Options::set_merge_operator_associative`].
+    pub fn merge_operator_associative<F: MergeFn + Clone>(
+        mut self,
+        name: impl CStrLike,
+        full_merge_fn: F,
+    ) -> Self {
+        self.descriptor
+            .options
+            .set_merge_operator_associative(name, full_merge_fn);
+        self
+    }
+
+    /// Attaches a merge operator to the column family, see
+    /// [`Options::set_merge_operator`].
+    pub fn merge_operator<F: MergeFn, PF: MergeFn>(
+        mut self,
+        name: impl CStrLike,
+        full_merge_fn: F,
+        partial_merge_fn: PF,
+    ) -> Self {
+        self.descriptor
+            .options
+            .set_merge_operator(name, full_merge_fn, partial_merge_fn);
+        self
+    }
 }
 
 /// An opaque type used to represent a column family. Returned from some functions, and used
@@ -59,6 +230,9 @@ pub struct ColumnFamily {
 /// single-threaded mode). `Clone`/`Copy` is safe because this lifetime is bound to DB like
 /// iterators/snapshots. On top of it, this is as cheap and small as `&ColumnFamily` because
 /// this only has a single pointer-wide field.
+///
+/// It is handed out as an `Arc<BoundColumnFamily>`, which is cheap to clone
+/// and can be shared between threads, e.g. with [`std::thread::scope`].
 pub struct BoundColumnFamily<'a> {
     pub(crate) inner: *mut ffi::rocksdb_column_family_handle_t,
     pub(crate) multi_threaded_cfs: std::marker::PhantomData<&'a MultiThreaded>,
@@ -80,6 +254,22 @@ impl UnboundColumnFamily {
     }
 }
 
+/// Returns the name of the column family of `handle`.
+pub(crate) fn column_family_name(handle: *mut ffi::rocksdb_column_family_handle_t) -> String {
+    unsafe {
+        let mut len: libc::size_t = 0;
+        let ptr = ffi::rocksdb_column_family_handle_get_name(handle, &mut len);
+        let name =
+            String::from_utf8_lossy(std::slice::from_raw_parts(ptr as *const u8, len)).into_owned();
+        ffi::rocksdb_free(ptr as *mut libc::c_void);
+        name
+    }
+}
+
+pub(crate) fn column_family_id(handle: *mut ffi::rocksdb_column_family_handle_t) -> u32 {
+    unsafe { ffi::rocksdb_column_family_handle_get_id(handle) }
+}
+
 fn destroy_handle(handle: *mut ffi::rocksdb_column_family_handle_t) {
     // SAFETY: This should be called only from various Drop::drop(), strictly keeping a 1-to-1
     // ownership to avoid double invocation to the rocksdb function with same handle.
@@ -145,7 +335,63 @@ impl<'a> AsColumnFamilyRef for Arc<BoundColumnFamily<'a>> {
     }
 }
 
+/// A `Copy` token for a column family handle, accepted by all the `_cf`
+/// methods like the handle it is taken from with [`ColumnFamily::id`] or
+/// [`BoundColumnFamily::id`].
+///
+/// The token borrows the handle, so passing it around costs no reference
+/// counting in hot paths, while the `Arc<BoundColumnFamily>` it is taken
+/// from keeps the column family alive.
+#[derive(Debug, Clone, Copy)]
+pub struct CfId<'a> {
+    inner: *mut ffi::rocksdb_column_family_handle_t,
+    handle: std::marker::PhantomData<&'a ()>,
+}
+
+impl ColumnFamily {
+    /// Returns a `Copy` token for this column family handle.
+    pub fn id(&self) -> CfId<'_> {
+        CfId {
+            inner: self.inner,
+            handle: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a> BoundColumnFamily<'a> {
+    /// Returns a `Copy` token for this column family handle.
+    pub fn id(&self) -> CfId<'_> {
+        CfId {
+            inner: self.inner,
+            handle: std::marker::PhantomData,
+        }
+    }
+}
+
+impl UnboundColumnFamily {
+    /// Returns a `Copy` token for this column family handle.
+    pub(crate) fn id(&self) -> CfId<'_> {
+        CfId {
+            inner: self.inner,
+            handle: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a> AsColumnFamilyRef for CfId<'a> {
+    fn inner(&self) -> *mut ffi::rocksdb_column_family_handle_t {
+        self.inner
+    }
+}
+
+// The methods of the C++ column family handle only read immutable state, so
+// handles can be shared between threads. This makes `&ColumnFamily` and
+// `Arc<BoundColumnFamily>` `Send` and `Sync`.
 unsafe impl Send for ColumnFamily {}
+unsafe impl Sync for ColumnFamily {}
 unsafe impl Send for UnboundColumnFamily {}
 unsafe impl Sync for UnboundColumnFamily {}
 unsafe impl<'a> Send for BoundColumnFamily<'a> {}
+unsafe impl<'a> Sync for BoundColumnFamily<'a> {}
+unsafe impl<'a> Send for CfId<'a> {}
+unsafe impl<'a> Sync for CfId<'a> {}
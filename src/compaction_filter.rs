@@ -14,6 +14,7 @@
 //
 
 use libc::{c_char, c_int, c_uchar, c_void, size_t};
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
 use std::slice;
 
@@ -22,6 +23,7 @@ use std::slice;
 /// This is returned by a compaction filter callback. Depending
 /// on the value, the object may be kept, removed, or changed
 /// in the database during a compaction.
+#[non_exhaustive]
 pub enum Decision {
     /// Keep the old value
     Keep,
@@ -29,6 +31,8 @@ pub enum Decision {
     Remove,
     /// Change the value for the key
     Change(&'static [u8]),
+    /// Change the value for the key to a value computed by the filter
+    ChangeValue(Vec<u8>),
 }
 
 /// CompactionFilter allows an application to modify/delete a key-value at
@@ -96,6 +100,13 @@ where
     }
 }
 
+thread_local! {
+    /// Holds the value of a `Decision::ChangeValue` until RocksDB has copied
+    /// it, which happens on the compaction thread right after the filter
+    /// returns.
+    static CHANGED_VALUE: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+}
+
 pub unsafe extern "C" fn destructor_callback<F>(raw_cb: *mut c_void)
 where
     F: CompactionFilter,
@@ -125,7 +136,7 @@ pub unsafe extern "C" fn filter_callback<F>(
 where
     F: CompactionFilter,
 {
-    use self::Decision::{Change, Keep, Remove};
+    use self::Decision::{Change, ChangeValue, Keep, Remove};
 
     let cb = &mut *(raw_cb as *mut F);
     let key = slice::from_raw_parts(raw_key as *const u8, key_length);
@@ -140,5 +151,15 @@ where
             *value_changed = 1_u8;
             0
         }
+        ChangeValue(newval) => {
+            CHANGED_VALUE.with(|changed| {
+                let mut changed = changed.borrow_mut();
+                *changed = newval;
+                *new_value = changed.as_ptr() as *mut c_char;
+                *new_value_length = changed.len() as size_t;
+            });
+            *value_changed = 1_u8;
+            0
+        }
     }
 }
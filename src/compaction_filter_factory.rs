@@ -1,9 +1,14 @@
-use std::ffi::CStr;
+use std::convert::TryInto;
+use std::ffi::{CStr, CString};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use libc::{self, c_char, c_void};
 
 use crate::{
-    compaction_filter::{self, CompactionFilter},
+    compaction_filter::{
+        self, CompactionFilter, CompactionFilterCallback, CompactionFilterFn, Decision,
+    },
     ffi,
 };
 
@@ -26,6 +31,209 @@ pub trait CompactionFilterFactory {
     fn name(&self) -> &CStr;
 }
 
+/// A compaction filter factory creating a filter from a closure for every
+/// compaction, see
+/// [Options::set_compaction_filter_factory_fn][set_compaction_filter_factory_fn].
+///
+///  [set_compaction_filter_factory_fn]: ../struct.Options.html#method.set_compaction_filter_factory_fn
+pub struct CompactionFilterFactoryCallback<F> {
+    pub name: CString,
+    pub create_fn: F,
+}
+
+impl<F, G> CompactionFilterFactory for CompactionFilterFactoryCallback<F>
+where
+    F: FnMut(CompactionFilterContext) -> G,
+    G: CompactionFilterFn + Send + 'static,
+{
+    type Filter = CompactionFilterCallback<G>;
+
+    fn create(&mut self, context: CompactionFilterContext) -> Self::Filter {
+        CompactionFilterCallback {
+            name: self.name.clone(),
+            filter_fn: (self.create_fn)(context),
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        self.name.as_c_str()
+    }
+}
+
+/// A compaction filter factory removing the entries whose value starts with
+/// a timestamp older than a time to live, to expire entries without opening
+/// the DB with a TTL.
+///
+/// The timestamp is the first 8 bytes of the value, in seconds since the
+/// Unix epoch as a big endian integer, shorter values are kept. The current
+/// time is read when a compaction starts.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{compaction_filter_factory::ValueTimestampTtl, Options};
+/// use std::time::Duration;
+///
+/// let mut opts = Options::default();
+/// // expires the entries after a day, once compacted down to level 2
+/// opts.set_compaction_filter_factory(
+///     ValueTimestampTtl::new(Duration::from_secs(24 * 3600)).with_min_level(2),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValueTimestampTtl {
+    ttl: Duration,
+    min_level: u32,
+}
+
+impl ValueTimestampTtl {
+    /// Creates a factory removing the entries written more than `ttl` ago.
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, min_level: 0 }
+    }
+
+    /// Only removes entries in compactions starting at `level` or deeper.
+    pub fn with_min_level(mut self, level: u32) -> Self {
+        self.min_level = level;
+        self
+    }
+}
+
+impl CompactionFilterFactory for ValueTimestampTtl {
+    type Filter = ValueTimestampTtlFilter;
+
+    fn create(&mut self, _context: CompactionFilterContext) -> Self::Filter {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        ValueTimestampTtlFilter {
+            expired_before: now.saturating_sub(self.ttl).as_secs(),
+            min_level: self.min_level,
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        static_name(b"ValueTimestampTtl\0")
+    }
+}
+
+/// The filter created by [`ValueTimestampTtl`] for a compaction.
+pub struct ValueTimestampTtlFilter {
+    expired_before: u64,
+    min_level: u32,
+}
+
+impl CompactionFilter for ValueTimestampTtlFilter {
+    fn filter(&mut self, level: u32, _key: &[u8], value: &[u8]) -> Decision {
+        let timestamp = match value.get(..8) {
+            Some(timestamp) if level >= self.min_level => {
+                u64::from_be_bytes(timestamp.try_into().unwrap())
+            }
+            _ => return Decision::Keep,
+        };
+        if timestamp < self.expired_before {
+            Decision::Remove
+        } else {
+            Decision::Keep
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        static_name(b"ValueTimestampTtl\0")
+    }
+}
+
+/// A compaction filter factory removing the entries whose key is before a
+/// boundary, e.g. to garbage collect a log truncated up to a sequence
+/// number.
+///
+/// The boundary can be moved forward while the DB is open through a clone
+/// of the factory, it is read when a compaction starts.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{compaction_filter_factory::RemoveKeysBefore, Options};
+///
+/// let truncation = RemoveKeysBefore::new(0_u64.to_be_bytes());
+/// let mut opts = Options::default();
+/// opts.set_compaction_filter_factory(truncation.clone());
+///
+/// // once the log up to entry 42 is not needed anymore
+/// truncation.set_boundary(42_u64.to_be_bytes());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RemoveKeysBefore {
+    boundary: Arc<RwLock<Vec<u8>>>,
+    min_level: u32,
+}
+
+impl RemoveKeysBefore {
+    /// Creates a factory removing the entries whose key is less than
+    /// `boundary`.
+    pub fn new(boundary: impl Into<Vec<u8>>) -> Self {
+        Self {
+            boundary: Arc::new(RwLock::new(boundary.into())),
+            min_level: 0,
+        }
+    }
+
+    /// Only removes entries in compactions starting at `level` or deeper.
+    pub fn with_min_level(mut self, level: u32) -> Self {
+        self.min_level = level;
+        self
+    }
+
+    /// Moves the boundary, for this factory and its clones.
+    pub fn set_boundary(&self, boundary: impl Into<Vec<u8>>) {
+        *self.boundary.write().unwrap() = boundary.into();
+    }
+
+    /// Returns the boundary.
+    pub fn boundary(&self) -> Vec<u8> {
+        self.boundary.read().unwrap().clone()
+    }
+}
+
+impl CompactionFilterFactory for RemoveKeysBefore {
+    type Filter = RemoveKeysBeforeFilter;
+
+    fn create(&mut self, _context: CompactionFilterContext) -> Self::Filter {
+        RemoveKeysBeforeFilter {
+            boundary: self.boundary(),
+            min_level: self.min_level,
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        static_name(b"RemoveKeysBefore\0")
+    }
+}
+
+/// The filter created by [`RemoveKeysBefore`] for a compaction.
+pub struct RemoveKeysBeforeFilter {
+    boundary: Vec<u8>,
+    min_level: u32,
+}
+
+impl CompactionFilter for RemoveKeysBeforeFilter {
+    fn filter(&mut self, level: u32, key: &[u8], _value: &[u8]) -> Decision {
+        if level >= self.min_level && key < self.boundary.as_slice() {
+            Decision::Remove
+        } else {
+            Decision::Keep
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        static_name(b"RemoveKeysBefore\0")
+    }
+}
+
+fn static_name(name: &'static [u8]) -> &'static CStr {
+    CStr::from_bytes_with_nul(name).unwrap()
+}
+
 pub unsafe extern "C" fn destructor_callback<F>(raw_self: *mut c_void)
 where
     F: CompactionFilterFactory,
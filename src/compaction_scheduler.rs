@@ -0,0 +1,370 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    db::LiveFile, manifest, periodic_thread::PeriodicThread, DBWithThreadMode, Error, ThreadMode,
+};
+
+/// Selects the table files a [`CompactionScheduler`] compacts.
+///
+/// A file is selected when it matches every criterion set on the policy,
+/// a policy without criteria selecting every file.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::CompactionPolicy;
+/// use std::time::Duration;
+///
+/// // Compacts the sessions older than a day away once they are deleted.
+/// let policy = CompactionPolicy::new()
+///     .key_prefix(b"session:".to_vec())
+///     .min_age(Duration::from_secs(24 * 3600))
+///     .min_tombstone_ratio(0.5);
+/// ```
+#[derive(Clone, Default)]
+pub struct CompactionPolicy {
+    column_family: Option<String>,
+    key_prefix: Option<Vec<u8>>,
+    min_age: Option<Duration>,
+    min_tombstone_ratio: Option<f64>,
+    filter: Option<Arc<dyn Fn(&LiveFile) -> bool + Send + Sync>>,
+}
+
+impl CompactionPolicy {
+    /// Creates a policy selecting every file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only selects the files of the column family `name`.
+    pub fn column_family(mut self, name: impl Into<String>) -> Self {
+        self.column_family = Some(name.into());
+        self
+    }
+
+    /// Only selects the files holding keys starting with `prefix`, and only
+    /// compacts the part of their key range within the prefix.
+    pub fn key_prefix(mut self, prefix: impl Into<Vec<u8>>) -> Self {
+        self.key_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Only selects the files written at least `age` ago.
+    ///
+    /// The age of a file is read from the creation time RocksDB records for
+    /// it in the MANIFEST, which compactions do not reset for the files they
+    /// move to another level. Files without a recorded creation time are not
+    /// selected.
+    pub fn min_age(mut self, age: Duration) -> Self {
+        self.min_age = Some(age);
+        self
+    }
+
+    /// Only selects the files in which at least `ratio` of the entries are
+    /// deletions, e.g. `0.5` for half of them.
+    pub fn min_tombstone_ratio(mut self, ratio: f64) -> Self {
+        self.min_tombstone_ratio = Some(ratio);
+        self
+    }
+
+    /// Only selects the files for which `filter` returns `true`.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&LiveFile) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Returns the range of `file` to compact, or `None` if the policy does
+    /// not select it.
+    #[allow(clippy::cast_precision_loss)]
+    fn select(&self, file: &LiveFile, age: Option<Duration>) -> Option<KeyRange> {
+        if let Some(ref name) = self.column_family {
+            if *name != file.column_family_name {
+                return None;
+            }
+        }
+        if let Some(min_age) = self.min_age {
+            if age? < min_age {
+                return None;
+            }
+        }
+        if let Some(ratio) = self.min_tombstone_ratio {
+            if file.num_entries == 0
+                || (file.num_deletions as f64) < ratio * file.num_entries as f64
+            {
+                return None;
+            }
+        }
+        if let Some(ref filter) = self.filter {
+            if !filter(file) {
+                return None;
+            }
+        }
+        let range = KeyRange {
+            start: file.start_key.clone(),
+            end: file.end_key.clone(),
+        };
+        match self.key_prefix {
+            Some(ref prefix) => range.within_prefix(prefix),
+            None => Some(range),
+        }
+    }
+}
+
+impl fmt::Debug for CompactionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactionPolicy")
+            .field("column_family", &self.column_family)
+            .field("key_prefix", &self.key_prefix)
+            .field("min_age", &self.min_age)
+            .field("min_tombstone_ratio", &self.min_tombstone_ratio)
+            .field("filter", &self.filter.is_some())
+            .finish()
+    }
+}
+
+/// A key range, `None` bounds being unbounded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyRange {
+    start: Option<Vec<u8>>,
+    end: Option<Vec<u8>>,
+}
+
+impl KeyRange {
+    /// Returns the part of the range holding keys starting with `prefix`.
+    fn within_prefix(self, prefix: &[u8]) -> Option<Self> {
+        // the smallest key greater than all the keys starting with the prefix
+        let successor = prefix_successor(prefix);
+        let start = match self.start {
+            Some(start) if start.as_slice() > prefix => start,
+            _ => prefix.to_vec(),
+        };
+        let end = match (self.end, successor) {
+            (Some(end), Some(successor)) => Some(end.min(successor)),
+            (end, None) => end,
+            (None, successor) => successor,
+        };
+        if end.as_ref().map_or(false, |end| *end < start) {
+            return None;
+        }
+        Some(Self {
+            start: Some(start),
+            end,
+        })
+    }
+
+    /// Returns `true` if `other`, starting after this range, overlaps it.
+    fn overlaps_next(&self, other: &Self) -> bool {
+        match (&self.end, &other.start) {
+            (Some(end), Some(start)) => start <= end,
+            _ => true,
+        }
+    }
+}
+
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+    while let Some(last) = successor.pop() {
+        if last < u8::MAX {
+            successor.push(last + 1);
+            return Some(successor);
+        }
+    }
+    None
+}
+
+/// Returns the creation times recorded in the MANIFEST of the DB in `path`,
+/// by file number.
+fn file_creation_times(path: &Path) -> Result<HashMap<u64, u64>, Error> {
+    Ok(manifest::describe(path)?
+        .column_families
+        .iter()
+        .flat_map(|cf| cf.levels.iter().flatten())
+        .filter_map(|file| Some((file.number, file.file_creation_time?)))
+        .collect())
+}
+
+/// Parses the number of a live file from its name, e.g. `/000012.sst`.
+fn file_number(name: &str) -> Option<u64> {
+    name.trim_start_matches('/')
+        .strip_suffix(".sst")?
+        .parse()
+        .ok()
+}
+
+/// A range compacted by a [`CompactionScheduler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledCompaction {
+    /// Name of the column family
+    pub column_family: String,
+    /// Smallest key of the range, `None` if unbounded
+    pub start: Option<Vec<u8>>,
+    /// Largest key of the range, `None` if unbounded
+    pub end: Option<Vec<u8>>,
+    /// Names of the files selected in the range
+    pub files: Vec<String>,
+}
+
+/// Compacts the key ranges of the table files selected by
+/// [`CompactionPolicy`]s, e.g. to reclaim the space of cold prefixes full of
+/// deletions without waiting for RocksDB to compact them.
+///
+/// The policies are applied by [`run_once`](Self::run_once), called on a
+/// thread of the application or periodically on a thread of the scheduler
+/// with [`spawn`](Self::spawn). The ranges selected by several files are
+/// compacted once.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{CompactionPolicy, CompactionScheduler, DBWithThreadMode, MultiThreaded};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let path = "_path_for_rocksdb_storage_compaction_scheduler";
+/// {
+///     let db = Arc::new(DBWithThreadMode::<MultiThreaded>::open_default(path).unwrap());
+///     let scheduler = CompactionScheduler::new(Arc::clone(&db))
+///         .add_policy(CompactionPolicy::new().min_tombstone_ratio(0.5));
+///     let thread = scheduler.spawn(Duration::from_secs(600)).unwrap();
+///     // ...
+///     assert!(thread.take_error().is_none());
+/// }
+/// let _ = DBWithThreadMode::<MultiThreaded>::destroy(&rocksdb::Options::default(), path);
+/// ```
+pub struct CompactionScheduler<T: ThreadMode> {
+    db: Arc<DBWithThreadMode<T>>,
+    policies: Vec<CompactionPolicy>,
+}
+
+impl<T: ThreadMode> CompactionScheduler<T> {
+    /// Creates a scheduler of `db` without policies.
+    pub fn new(db: Arc<DBWithThreadMode<T>>) -> Self {
+        Self {
+            db,
+            policies: Vec::new(),
+        }
+    }
+
+    /// Adds a policy selecting files to compact.
+    pub fn add_policy(mut self, policy: CompactionPolicy) -> Self {
+        self.policies.push(policy);
+        self
+    }
+
+    /// Compacts the ranges of the files currently selected by the policies,
+    /// and returns them.
+    pub fn run_once(&self) -> Result<Vec<ScheduledCompaction>, Error> {
+        let now = SystemTime::now();
+        let creation_times = if self.policies.iter().any(|policy| policy.min_age.is_some()) {
+            file_creation_times(self.db.path())?
+        } else {
+            HashMap::new()
+        };
+        let mut ranges: BTreeMap<String, Vec<(KeyRange, String)>> = BTreeMap::new();
+        for file in self.db.live_files_metadata()? {
+            let age = file_number(&file.name)
+                .and_then(|number| creation_times.get(&number))
+                .and_then(|&created| {
+                    now.duration_since(UNIX_EPOCH + Duration::from_secs(created))
+                        .ok()
+                });
+            for policy in &self.policies {
+                if let Some(range) = policy.select(&file, age) {
+                    ranges
+                        .entry(file.column_family_name.clone())
+                        .or_default()
+                        .push((range, file.name.clone()));
+                }
+            }
+        }
+
+        let mut compactions = Vec::new();
+        for (column_family, mut ranges) in ranges {
+            ranges.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start));
+            let mut merged: Vec<(KeyRange, Vec<String>)> = Vec::new();
+            for (range, file) in ranges {
+                match merged.last_mut() {
+                    Some((last, files)) if last.overlaps_next(&range) => {
+                        last.end = match (last.end.take(), range.end) {
+                            (Some(a), Some(b)) => Some(a.max(b)),
+                            _ => None,
+                        };
+                        if !files.contains(&file) {
+                            files.push(file);
+                        }
+                    }
+                    _ => merged.push((range, vec![file])),
+                }
+            }
+            compactions.extend(
+                merged
+                    .into_iter()
+                    .map(|(range, files)| ScheduledCompaction {
+                        column_family: column_family.clone(),
+                        start: range.start,
+                        end: range.end,
+                        files,
+                    }),
+            );
+        }
+
+        for compaction in &compactions {
+            let name = &compaction.column_family;
+            self.db
+                .cfs
+                .with_cf_internal(name, |cf| {
+                    self.db.compact_range_cf(
+                        &cf,
+                        compaction.start.as_deref(),
+                        compaction.end.as_deref(),
+                    );
+                })
+                .ok_or_else(|| {
+                    Error::new(format!(
+                        "Invalid argument: column family {name} is not opened"
+                    ))
+                })?;
+        }
+        Ok(compactions)
+    }
+}
+
+impl<T: ThreadMode + Send + 'static> CompactionScheduler<T> {
+    /// Spawns a thread calling [`run_once`](Self::run_once) every
+    /// `interval`, the first time right away.
+    pub fn spawn(self, interval: Duration) -> Result<CompactionSchedulerThread, Error> {
+        PeriodicThread::spawn(
+            "rocksdb-compaction-scheduler",
+            move || self.run_once().map(drop),
+            interval,
+        )
+        .map(CompactionSchedulerThread)
+    }
+}
+
+/// A background thread running a [`CompactionScheduler`], see
+/// [`CompactionScheduler::spawn`].
+///
+/// The thread is stopped when the handle is dropped, after the compactions
+/// it is running complete.
+pub struct CompactionSchedulerThread(PeriodicThread);
+
+impl CompactionSchedulerThread {
+    /// Returns the error of the last failed run, if any, and clears it. The
+    /// thread keeps running after an error.
+    pub fn take_error(&self) -> Option<Error> {
+        self.0.take_error()
+    }
+
+    /// Stops the thread and waits for it to finish.
+    pub fn stop(self) {
+        drop(self);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    db::DBInner, AsColumnFamilyRef, DBAccess, DBCommon, DBIteratorWithThreadMode, Error,
+    IteratorMode, ReadOptions, ThreadMode, Transaction, TransactionDB,
+};
+
+/// Reads and writes shared by [`DBWithThreadMode`](crate::DBWithThreadMode),
+/// [`TransactionDB`](crate::TransactionDB),
+/// [`OptimisticTransactionDB`](crate::OptimisticTransactionDB) and
+/// [`Transaction`](crate::Transaction), to write application code once and
+/// run it either directly on a database or within a transaction.
+///
+/// Writes use the default write options on databases, and are buffered
+/// until the commit in transactions.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{Database, Error, IteratorMode, TransactionDB, DB};
+///
+/// fn rename<D: Database>(db: &D, from: &[u8], to: &[u8]) -> Result<(), Error> {
+///     if let Some(value) = db.get(from)? {
+///         db.put(to, value)?;
+///         db.delete(from)?;
+///     }
+///     Ok(())
+/// }
+///
+/// let path = "_path_for_rocksdb_storage_database_trait";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     db.put(b"old", b"value").unwrap();
+///     rename(&db, b"old", b"new").unwrap();
+///     assert_eq!(db.iterator(IteratorMode::Start).count(), 1);
+/// }
+/// let _ = DB::destroy(&rocksdb::Options::default(), path);
+///
+/// let path = "_path_for_rocksdb_storage_database_trait_txn";
+/// {
+///     let db: TransactionDB = TransactionDB::open_default(path).unwrap();
+///     db.put(b"old", b"value").unwrap();
+///     let txn = db.transaction();
+///     rename(&txn, b"old", b"new").unwrap();
+///     txn.commit().unwrap();
+///     assert_eq!(db.get(b"new").unwrap(), Some(b"value".to_vec()));
+/// }
+/// let _ = DB::destroy(&rocksdb::Options::default(), path);
+/// ```
+pub trait Database: DBAccess {
+    fn put<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>;
+
+    fn put_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>;
+
+    fn merge<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>;
+
+    fn merge_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>;
+
+    fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error>;
+
+    fn delete_cf<K: AsRef<[u8]>>(&self, cf: &impl AsColumnFamilyRef, key: K) -> Result<(), Error>;
+
+    fn get<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt(key, &ReadOptions::default())
+    }
+
+    fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_cf_opt(cf, key, &ReadOptions::default())
+    }
+
+    fn iterator(&self, mode: IteratorMode) -> DBIteratorWithThreadMode<'_, Self>
+    where
+        Self: Sized,
+    {
+        DBIteratorWithThreadMode::new(self, ReadOptions::default(), mode)
+    }
+
+    fn iterator_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        mode: IteratorMode,
+    ) -> DBIteratorWithThreadMode<'_, Self>
+    where
+        Self: Sized,
+    {
+        DBIteratorWithThreadMode::new_cf(self, cf.inner(), ReadOptions::default(), mode)
+    }
+}
+
+impl<T: ThreadMode, D: DBInner> Database for DBCommon<T, D> {
+    fn put<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put(key, value)
+    }
+
+    fn put_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put_cf(cf, key, value)
+    }
+
+    fn merge<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge(key, value)
+    }
+
+    fn merge_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge_cf(cf, key, value)
+    }
+
+    fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error> {
+        self.delete(key)
+    }
+
+    fn delete_cf<K: AsRef<[u8]>>(&self, cf: &impl AsColumnFamilyRef, key: K) -> Result<(), Error> {
+        self.delete_cf(cf, key)
+    }
+}
+
+impl<T: ThreadMode> Database for TransactionDB<T> {
+    fn put<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put(key, value)
+    }
+
+    fn put_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put_cf(cf, key, value)
+    }
+
+    fn merge<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge(key, value)
+    }
+
+    fn merge_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge_cf(cf, key, value)
+    }
+
+    fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error> {
+        self.delete(key)
+    }
+
+    fn delete_cf<K: AsRef<[u8]>>(&self, cf: &impl AsColumnFamilyRef, key: K) -> Result<(), Error> {
+        self.delete_cf(cf, key)
+    }
+}
+
+impl<'db, DB> Database for Transaction<'db, DB> {
+    fn put<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put(key, value)
+    }
+
+    fn put_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.put_cf(cf, key, value)
+    }
+
+    fn merge<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge(key, value)
+    }
+
+    fn merge_cf<K, V>(&self, cf: &impl AsColumnFamilyRef, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge_cf(cf, key, value)
+    }
+
+    fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error> {
+        self.delete(key)
+    }
+
+    fn delete_cf<K: AsRef<[u8]>>(&self, cf: &impl AsColumnFamilyRef, key: K) -> Result<(), Error> {
+        self.delete_cf(cf, key)
+    }
+}
@@ -17,22 +17,38 @@ use crate::{
     column_family::AsColumnFamilyRef,
     column_family::BoundColumnFamily,
     column_family::UnboundColumnFamily,
+    column_family::{column_family_id, column_family_name, CfId},
+    db_lock::describe_lock_error,
     db_options::OptionsMustOutliveDB,
     ffi,
     ffi_util::{from_cstr, opt_bytes_to_ptr, raw_data, to_cpath, CStrLike},
-    ColumnFamily, ColumnFamilyDescriptor, CompactOptions, DBIteratorWithThreadMode,
-    DBPinnableSlice, DBRawIteratorWithThreadMode, DBWALIterator, Direction, Error, FlushOptions,
-    IngestExternalFileOptions, IteratorMode, Options, ReadOptions, SnapshotWithThreadMode,
-    WaitForCompactOptions, WriteBatch, WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
+    manifest,
+    open_handles::{HandleKind, OpenHandles},
+    options_file::{check_merge_operators, KeyOrder, OptionMismatch, OptionsFile},
+    perf::{
+        perf_stats_level, set_perf_stats, CacheUsageByRole, PerfContext, PerfMetric, PerfStatsLevel,
+    },
+    properties::{self, PropName},
+    wal_file::{list_wal_files, read_wal_from, WalPosition, WalReader},
+    BottommostLevelCompaction, CfStats, ColumnFamily, ColumnFamilyDescriptor,
+    ColumnFamilyOperators, CompactOptions, DBIteratorWithThreadMode, DBPinnableSlice,
+    DBRawIteratorWithThreadMode, DBWALIterator, Direction, Error, ErrorKind, FileChecksum,
+    FlushOptions, IngestExternalFileOptions, IterateBounds, IteratorMode, Options, ReadOptions,
+    ScanBuilder, ScopedReadOptions, SnapshotWithThreadMode, WaitForCompactOptions, WalFile,
+    WalFileType, WriteBatch, WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
 };
 
 use crate::ffi_util::CSlice;
 use libc::{self, c_char, c_int, c_uchar, c_void, size_t};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::TryInto;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::fs;
+use std::io::{BufRead, BufReader};
 use std::iter;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
 use std::path::Path;
 use std::path::PathBuf;
 use std::ptr;
@@ -40,7 +56,8 @@ use std::slice;
 use std::str;
 use std::sync::Arc;
 use std::sync::RwLock;
-use std::time::Duration;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Marker trait to specify single or multi threaded column family alternations for
 /// [`DBWithThreadMode<T>`]
@@ -52,7 +69,9 @@ use std::time::Duration;
 /// also has a minimum set of not-encapsulated internal methods between
 /// [`SingleThreaded`] and [`MultiThreaded`].  These methods aren't expected to be
 /// called and defined externally.
-pub trait ThreadMode {
+///
+/// This trait is sealed: its supertrait can't be named outside of this crate.
+pub trait ThreadMode: cf_access::CfAccess {
     /// Internal implementation for storing column family handles
     fn new_cf_map_internal(
         cf_map: BTreeMap<String, *mut ffi::rocksdb_column_family_handle_t>,
@@ -61,6 +80,22 @@ pub trait ThreadMode {
     fn drop_all_cfs_internal(&mut self);
 }
 
+pub(crate) mod cf_access {
+    use crate::CfId;
+
+    /// Crate-private access to the column families of a [`ThreadMode`],
+    /// sealing it.
+    ///
+    /// [`ThreadMode`]: super::ThreadMode
+    pub trait CfAccess {
+        /// Calls `f` with the handle of a column family looked up by name,
+        /// kept alive during the call
+        fn with_cf_internal<R>(&self, name: &str, f: impl FnOnce(CfId) -> R) -> Option<R>;
+        /// Lists the names of the column families
+        fn cf_names_internal(&self) -> Vec<String>;
+    }
+}
+
 /// Actual marker type for the marker trait `ThreadMode`, which holds
 /// a collection of column families without synchronization primitive, providing
 /// no overhead for the single-threaded column family alternations. The other
@@ -98,6 +133,16 @@ impl ThreadMode for SingleThreaded {
     }
 }
 
+impl cf_access::CfAccess for SingleThreaded {
+    fn with_cf_internal<R>(&self, name: &str, f: impl FnOnce(CfId) -> R) -> Option<R> {
+        self.cfs.get(name).map(|cf| f(cf.id()))
+    }
+
+    fn cf_names_internal(&self) -> Vec<String> {
+        self.cfs.keys().cloned().collect()
+    }
+}
+
 impl ThreadMode for MultiThreaded {
     fn new_cf_map_internal(
         cfs: BTreeMap<String, *mut ffi::rocksdb_column_family_handle_t>,
@@ -117,6 +162,17 @@ impl ThreadMode for MultiThreaded {
     }
 }
 
+impl cf_access::CfAccess for MultiThreaded {
+    fn with_cf_internal<R>(&self, name: &str, f: impl FnOnce(CfId) -> R) -> Option<R> {
+        let cf = self.cfs.read().unwrap().get(name).cloned();
+        cf.map(|cf| f(cf.id()))
+    }
+
+    fn cf_names_internal(&self) -> Vec<String> {
+        self.cfs.read().unwrap().keys().cloned().collect()
+    }
+}
+
 /// Get underlying `rocksdb_t`.
 pub trait DBInner {
     fn inner(&self) -> *mut ffi::rocksdb_t;
@@ -125,14 +181,35 @@ pub trait DBInner {
 /// A helper type to implement some common methods for [`DBWithThreadMode`]
 /// and [`OptimisticTransactionDB`].
 ///
+/// Dropping the DB closes it, unless iterators or snapshots created from it
+/// were leaked, e.g. with `mem::forget`: RocksDB would then close the DB under
+/// them, so the DB is leaked instead, keeping its memory, its open files and
+/// the `LOCK` file of its directory until the process exits. Use
+/// [`close`](Self::close) to detect it.
+///
 /// [`OptimisticTransactionDB`]: crate::OptimisticTransactionDB
 pub struct DBCommon<T: ThreadMode, D: DBInner> {
-    pub(crate) inner: D,
-    cfs: T, // Column families are held differently depending on thread mode
+    /// Closes the DB when dropped, unless handles created from it are left
+    pub(crate) inner: ManuallyDrop<D>,
+    pub(crate) cfs: T, // Column families are held differently depending on thread mode
     path: PathBuf,
+    role: DBRole,
+    handles: Arc<OpenHandles>,
     _outlive: Vec<OptionsMustOutliveDB>,
 }
 
+/// The way a DB instance was opened.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DBRole {
+    /// Opened for reads and writes
+    Primary,
+    /// Opened with one of the `open_*_for_read_only` methods
+    ReadOnly,
+    /// Opened with one of the `open_*_as_secondary` methods, following a
+    /// primary instance with [`DBCommon::try_catch_up_with_primary`]
+    Secondary,
+}
+
 /// Minimal set of DB-related methods, intended to be generic over
 /// `DBWithThreadMode<T>`. Mainly used internally
 pub trait DBAccess {
@@ -192,6 +269,30 @@ pub trait DBAccess {
         K: AsRef<[u8]>,
         I: IntoIterator<Item = (&'b W, K)>,
         W: AsColumnFamilyRef + 'b;
+
+    /// Returns `false` if the given key definitely doesn't exist. Databases
+    /// which cannot tell return `true`.
+    fn key_may_exist_opt<K: AsRef<[u8]>>(&self, _key: K, _readopts: &ReadOptions) -> bool {
+        true
+    }
+
+    /// Returns `false` if the given key definitely doesn't exist in the
+    /// given column family. Databases which cannot tell return `true`.
+    fn key_may_exist_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        _cf: &impl AsColumnFamilyRef,
+        _key: K,
+        _readopts: &ReadOptions,
+    ) -> bool {
+        true
+    }
+
+    /// Returns the counts of the iterators and snapshots of the DB which are
+    /// still alive, `None` if they are not counted.
+    #[doc(hidden)]
+    fn open_handles(&self) -> Option<&Arc<OpenHandles>> {
+        None
+    }
 }
 
 impl<T: ThreadMode, D: DBInner> DBAccess for DBCommon<T, D> {
@@ -273,6 +374,23 @@ impl<T: ThreadMode, D: DBInner> DBAccess for DBCommon<T, D> {
     {
         self.multi_get_cf_opt(keys_cf, readopts)
     }
+
+    fn key_may_exist_opt<K: AsRef<[u8]>>(&self, key: K, readopts: &ReadOptions) -> bool {
+        self.key_may_exist_opt(key, readopts)
+    }
+
+    fn key_may_exist_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> bool {
+        self.key_may_exist_cf_opt(cf, key, readopts)
+    }
+
+    fn open_handles(&self) -> Option<&Arc<OpenHandles>> {
+        Some(&self.handles)
+    }
 }
 
 pub struct DBWithThreadModeInner {
@@ -359,6 +477,11 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
     }
 
     /// Opens the database for read only with the specified options.
+    ///
+    /// Writes to the returned DB fail at runtime, open a [`ReadOnlyDB`]
+    /// instead to rule them out at compile time.
+    ///
+    /// [`ReadOnlyDB`]: crate::ReadOnlyDB
     pub fn open_for_read_only<P: AsRef<Path>>(
         opts: &Options,
         path: P,
@@ -368,6 +491,11 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
     }
 
     /// Opens the database as a secondary.
+    ///
+    /// Writes to the returned DB fail at runtime, open a [`SecondaryDB`]
+    /// instead to rule them out at compile time.
+    ///
+    /// [`SecondaryDB`]: crate::SecondaryDB
     pub fn open_as_secondary<P: AsRef<Path>>(
         opts: &Options,
         primary_path: P,
@@ -595,7 +723,25 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
         P: AsRef<Path>,
         I: IntoIterator<Item = ColumnFamilyDescriptor>,
     {
-        let cfs: Vec<_> = cfs.into_iter().collect();
+        let mut cfs: Vec<_> = cfs.into_iter().collect();
+        if cfs.iter().any(|cf| cf.lazy) {
+            // a DB which does not exist yet has no column family
+            let existing = if path.as_ref().join("CURRENT").exists() {
+                Self::list_cf(opts, &path)?
+            } else {
+                Vec::new()
+            };
+            cfs.retain(|cf| !cf.lazy || existing.contains(&cf.name));
+        }
+        if !matches!(access_type, AccessType::WithTTL { .. }) {
+            if let Some(cf) = cfs.iter().find(|cf| cf.ttl.is_some()) {
+                return Err(Error::new(format!(
+                    "Invalid argument: column family {} has a time to live, the DB must be \
+                     opened with open_cf_descriptors_with_ttl",
+                    cf.name
+                )));
+            }
+        }
         let outlive = iter::once(opts.outlive.clone())
             .chain(cfs.iter().map(|cf| cf.options.outlive.clone()))
             .collect();
@@ -608,19 +754,22 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
             )));
         }
 
+        check_merge_operators(path.as_ref(), opts, &cfs)?;
+
         let db: *mut ffi::rocksdb_t;
         let mut cf_map = BTreeMap::new();
 
         if cfs.is_empty() {
-            db = Self::open_raw(opts, &cpath, access_type)?;
+            db = Self::open_raw(opts, &cpath, access_type)
+                .map_err(|e| describe_lock_error(e, path.as_ref()))?;
         } else {
             let mut cfs_v = cfs;
             // Always open the default column family.
             if !cfs_v.iter().any(|cf| cf.name == DEFAULT_COLUMN_FAMILY_NAME) {
-                cfs_v.push(ColumnFamilyDescriptor {
-                    name: String::from(DEFAULT_COLUMN_FAMILY_NAME),
-                    options: Options::default(),
-                });
+                cfs_v.push(ColumnFamilyDescriptor::new(
+                    DEFAULT_COLUMN_FAMILY_NAME,
+                    Options::default(),
+                ));
             }
             // We need to store our CStrings in an intermediate vector
             // so that their pointers remain valid.
@@ -647,7 +796,8 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
                 &cfopts,
                 &mut cfhandles,
                 access_type,
-            )?;
+            )
+            .map_err(|e| describe_lock_error(e, path.as_ref()))?;
             for handle in &cfhandles {
                 if handle.is_null() {
                     return Err(Error::new(
@@ -666,9 +816,15 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
         }
 
         Ok(Self {
-            inner: DBWithThreadModeInner { inner: db },
+            inner: ManuallyDrop::new(DBWithThreadModeInner { inner: db }),
             path: path.as_ref().to_path_buf(),
             cfs: T::new_cf_map_internal(cf_map),
+            role: match access_type {
+                AccessType::ReadWrite | AccessType::WithTTL { .. } => DBRole::Primary,
+                AccessType::ReadOnly { .. } => DBRole::ReadOnly,
+                AccessType::Secondary { .. } => DBRole::Secondary,
+            },
+            handles: Arc::default(),
             _outlive: outlive,
         })
     }
@@ -750,7 +906,10 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
                     ))
                 }
                 AccessType::WithTTL { ttl } => {
-                    let ttls_v = vec![ttl.as_secs() as c_int; cfs_v.len()];
+                    let ttls_v: Vec<_> = cfs_v
+                        .iter()
+                        .map(|cf| cf.ttl.unwrap_or(ttl).as_secs() as c_int)
+                        .collect();
                     ffi_try!(ffi::rocksdb_open_column_families_with_ttl(
                         opts.inner,
                         cpath.as_ptr(),
@@ -816,6 +975,129 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
         self.write_opt(batch, &WriteOptions::default())
     }
 
+    /// Writes the batch like [`write_opt`](Self::write_opt) and returns the
+    /// sequence number assigned to its first operation, the following
+    /// operations having the following sequence numbers.
+    ///
+    /// Unlike calling [`latest_sequence_number`](Self::latest_sequence_number)
+    /// after the write, this is not affected by concurrent writes, so it can
+    /// be used to order the writes for downstream indexing or change
+    /// tracking. The write is visible to readers when this returns: RocksDB
+    /// has no C API to run a callback between sequencing and publishing it.
+    pub fn write_opt_seq(&self, batch: WriteBatch, writeopts: &WriteOptions) -> Result<u64, Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_write(
+                self.inner.inner(),
+                writeopts.inner,
+                batch.inner
+            ));
+        }
+        // the write stores the sequence number in the header of the batch
+        Ok(batch.sequence_number())
+    }
+
+    /// Writes the batch with default write options and returns the sequence
+    /// number assigned to its first operation, see
+    /// [`write_opt_seq`](Self::write_opt_seq).
+    pub fn write_seq(&self, batch: WriteBatch) -> Result<u64, Error> {
+        self.write_opt_seq(batch, &WriteOptions::default())
+    }
+
+    /// Inserts a value like `put_opt` and returns the sequence number
+    /// assigned to the write, see [`write_opt_seq`](Self::write_opt_seq).
+    pub fn put_opt_seq<K, V>(
+        &self,
+        key: K,
+        value: V,
+        writeopts: &WriteOptions,
+    ) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let mut batch = WriteBatch::default();
+        batch.put(key, value);
+        self.write_opt_seq(batch, writeopts)
+    }
+
+    /// Inserts a value in the given column family like `put_cf_opt` and
+    /// returns the sequence number assigned to the write.
+    pub fn put_cf_opt_seq<K, V>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        value: V,
+        writeopts: &WriteOptions,
+    ) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let mut batch = WriteBatch::default();
+        batch.put_cf(cf, key, value);
+        self.write_opt_seq(batch, writeopts)
+    }
+
+    /// Merges a value like `merge_opt` and returns the sequence number
+    /// assigned to the write.
+    pub fn merge_opt_seq<K, V>(
+        &self,
+        key: K,
+        value: V,
+        writeopts: &WriteOptions,
+    ) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let mut batch = WriteBatch::default();
+        batch.merge(key, value);
+        self.write_opt_seq(batch, writeopts)
+    }
+
+    /// Merges a value in the given column family like `merge_cf_opt` and
+    /// returns the sequence number assigned to the write.
+    pub fn merge_cf_opt_seq<K, V>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        value: V,
+        writeopts: &WriteOptions,
+    ) -> Result<u64, Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let mut batch = WriteBatch::default();
+        batch.merge_cf(cf, key, value);
+        self.write_opt_seq(batch, writeopts)
+    }
+
+    /// Deletes a key like `delete_opt` and returns the sequence number
+    /// assigned to the write.
+    pub fn delete_opt_seq<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        writeopts: &WriteOptions,
+    ) -> Result<u64, Error> {
+        let mut batch = WriteBatch::default();
+        batch.delete(key);
+        self.write_opt_seq(batch, writeopts)
+    }
+
+    /// Deletes a key in the given column family like `delete_cf_opt` and
+    /// returns the sequence number assigned to the write.
+    pub fn delete_cf_opt_seq<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        writeopts: &WriteOptions,
+    ) -> Result<u64, Error> {
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(cf, key);
+        self.write_opt_seq(batch, writeopts)
+    }
+
     pub fn write_without_wal(&self, batch: WriteBatch) -> Result<(), Error> {
         let mut wo = WriteOptions::new();
         wo.disable_wal(true);
@@ -827,9 +1109,11 @@ impl<T: ThreadMode> DBWithThreadMode<T> {
 impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
     pub(crate) fn new(inner: D, cfs: T, path: PathBuf, outlive: Vec<OptionsMustOutliveDB>) -> Self {
         Self {
-            inner,
+            inner: ManuallyDrop::new(inner),
             cfs,
             path,
+            role: DBRole::Primary,
+            handles: Arc::default(),
             _outlive: outlive,
         }
     }
@@ -874,6 +1158,22 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         self.path.as_path()
     }
 
+    /// Closes the DB, failing if iterators or snapshots created from it are
+    /// still alive.
+    ///
+    /// Iterators and snapshots borrow the DB, so they are dropped before it
+    /// unless they were leaked, e.g. with `mem::forget`, or moved out of
+    /// their borrow with unsafe code. Closing the DB under them would leave
+    /// them dangling, so the DB is returned with the error instead. Dropping
+    /// such a DB leaks it, see [`DBCommon`].
+    #[allow(clippy::result_large_err)]
+    pub fn close(self) -> Result<(), (Self, Error)> {
+        match self.handles.check_none_left() {
+            Ok(()) => Ok(()),
+            Err(err) => Err((self, err)),
+        }
+    }
+
     /// Flushes the WAL buffer. If `sync` is set to `true`, also syncs
     /// the data to disk.
     pub fn flush_wal(&self, sync: bool) -> Result<(), Error> {
@@ -937,6 +1237,24 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         Ok(())
     }
 
+    fn flush_and_sync_all_impl(
+        &self,
+        cfs: &[&impl AsColumnFamilyRef],
+        default_cf_opened: bool,
+    ) -> Result<u64, Error> {
+        let seqno = self.latest_sequence_number();
+        let mut flushopts = FlushOptions::default();
+        flushopts.set_wait(true);
+        if !default_cf_opened {
+            self.flush_opt(&flushopts)?;
+        }
+        if !cfs.is_empty() {
+            self.flush_cfs_opt(cfs, &flushopts)?;
+        }
+        self.flush_wal(true)?;
+        Ok(seqno)
+    }
+
     /// Flushes database memtables to SST files on the disk for a given column family using default
     /// options.
     pub fn flush_cf(&self, cf: &impl AsColumnFamilyRef) -> Result<(), Error> {
@@ -1003,12 +1321,15 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
 
         let key = key.as_ref();
         unsafe {
-            let val = ffi_try!(ffi::rocksdb_get_pinned(
-                self.inner.inner(),
-                readopts.inner,
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-            ));
+            let val = ffi_try!(
+                ffi::rocksdb_get_pinned(
+                    self.inner.inner(),
+                    readopts.inner,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                );
+                key = key
+            );
             if val.is_null() {
                 Ok(None)
             } else {
@@ -1043,13 +1364,16 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
 
         let key = key.as_ref();
         unsafe {
-            let val = ffi_try!(ffi::rocksdb_get_pinned_cf(
-                self.inner.inner(),
-                readopts.inner,
-                cf.inner(),
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-            ));
+            let val = ffi_try!(
+                ffi::rocksdb_get_pinned_cf(
+                    self.inner.inner(),
+                    readopts.inner,
+                    cf.inner(),
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                );
+                cf = cf.inner(), key = key
+            );
             if val.is_null() {
                 Ok(None)
             } else {
@@ -1241,6 +1565,98 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         }
     }
 
+    /// Return the values associated with the given keys and the specified column family,
+    /// pinned in the block cache or memtable instead of being copied.
+    ///
+    /// This is [`batched_multi_get_cf`] for keys in any order and of any type implementing
+    /// `AsRef<[u8]>`.
+    ///
+    /// [`batched_multi_get_cf`]: Self::batched_multi_get_cf
+    pub fn multi_get_pinned_cf<K, I>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        keys: I,
+    ) -> Vec<Result<Option<DBPinnableSlice>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.multi_get_pinned_cf_opt(cf, keys, &ReadOptions::default())
+    }
+
+    /// Return the values associated with the given keys and the specified column family using
+    /// read options, see [`multi_get_pinned_cf`](Self::multi_get_pinned_cf).
+    pub fn multi_get_pinned_cf_opt<K, I>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        keys: I,
+        readopts: &ReadOptions,
+    ) -> Vec<Result<Option<DBPinnableSlice>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let keys: Vec<K> = keys.into_iter().collect();
+        self.batched_multi_get_cf_opt(cf, keys.iter().map(AsRef::as_ref), false, readopts)
+    }
+
+    /// Return the values associated with the given keys and column families, where the keys
+    /// of each column family are read in one batch, see [`batched_multi_get_cf`].
+    ///
+    /// The values are returned in the order of `keys`, whichever column families they span.
+    ///
+    /// [`batched_multi_get_cf`]: Self::batched_multi_get_cf
+    pub fn batched_multi_get_cfs<'b, K, I, W>(
+        &self,
+        keys: I,
+    ) -> Vec<Result<Option<DBPinnableSlice>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'b W, K)>,
+        W: 'b + AsColumnFamilyRef,
+    {
+        self.batched_multi_get_cfs_opt(keys, &ReadOptions::default())
+    }
+
+    /// Return the values associated with the given keys and column families using read
+    /// options, see [`batched_multi_get_cfs`](Self::batched_multi_get_cfs).
+    pub fn batched_multi_get_cfs_opt<'b, K, I, W>(
+        &self,
+        keys: I,
+        readopts: &ReadOptions,
+    ) -> Vec<Result<Option<DBPinnableSlice>, Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'b W, K)>,
+        W: 'b + AsColumnFamilyRef,
+    {
+        // the positions of the keys of each column family, in order of first appearance
+        let mut groups: Vec<(&W, Vec<(usize, K)>)> = Vec::new();
+        let mut len = 0;
+        for (cf, key) in keys {
+            match groups.iter_mut().find(|(c, _)| c.inner() == cf.inner()) {
+                Some((_, group)) => group.push((len, key)),
+                None => groups.push((cf, vec![(len, key)])),
+            }
+            len += 1;
+        }
+
+        let mut results: Vec<Option<Result<Option<DBPinnableSlice>, Error>>> =
+            iter::repeat_with(|| None).take(len).collect();
+        for (cf, group) in groups {
+            let values = self.batched_multi_get_cf_opt(
+                cf,
+                group.iter().map(|(_, key)| key.as_ref()),
+                false,
+                readopts,
+            );
+            for ((idx, _), value) in group.iter().zip(values) {
+                results[*idx] = Some(value);
+            }
+        }
+        results.into_iter().flatten().collect()
+    }
+
     /// Returns `false` if the given key definitely doesn't exist in the database, otherwise returns
     /// `true`. This function uses default `ReadOptions`.
     pub fn key_may_exist<K: AsRef<[u8]>>(&self, key: K) -> bool {
@@ -1340,6 +1756,38 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         }
     }
 
+    /// Checks which of the given keys may exist, see [`key_may_exist`].
+    ///
+    /// Only the memtables, the block cache and the filters are consulted,
+    /// there is no disk read. A `false` means the key definitely does not
+    /// exist, a `true` that it has to be looked up to know, which is also
+    /// the answer when the blocks needed to decide are not cached.
+    ///
+    /// [`key_may_exist`]: Self::key_may_exist
+    pub fn keys_may_exist<K, I>(&self, keys: I) -> Vec<bool>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let readopts = ReadOptions::default();
+        keys.into_iter()
+            .map(|key| self.key_may_exist_opt(key, &readopts))
+            .collect()
+    }
+
+    /// Same as [`keys_may_exist`](Self::keys_may_exist) but for the given
+    /// column family.
+    pub fn keys_may_exist_cf<K, I>(&self, cf: &impl AsColumnFamilyRef, keys: I) -> Vec<bool>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let readopts = ReadOptions::default();
+        keys.into_iter()
+            .map(|key| self.key_may_exist_cf_opt(cf, key, &readopts))
+            .collect()
+    }
+
     fn create_inner_cf_handle(
         &self,
         name: impl CStrLike,
@@ -1359,6 +1807,61 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         })
     }
 
+    /// Creates column families and returns the handles of the ones created,
+    /// which are all of them unless the returned result is an error.
+    fn create_inner_cf_handles<N: AsRef<str>>(
+        &self,
+        names: &[N],
+        opts: &Options,
+    ) -> (
+        Vec<*mut ffi::rocksdb_column_family_handle_t>,
+        Result<(), Error>,
+    ) {
+        let c_names = match names
+            .iter()
+            .map(|name| CString::new(name.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(c_names) => c_names,
+            Err(err) => {
+                return (
+                    Vec::new(),
+                    Err(Error::new(format!(
+                        "Failed to convert path to CString when creating cf: {err}"
+                    ))),
+                )
+            }
+        };
+        let name_ptrs: Vec<_> = c_names.iter().map(|name| name.as_ptr()).collect();
+        let mut count: size_t = 0;
+        let mut err: *mut c_char = ptr::null_mut();
+        unsafe {
+            // the handles of the created column families are returned even if
+            // creating the following ones failed
+            let handles = ffi::rocksdb_create_column_families(
+                self.inner.inner(),
+                opts.inner,
+                name_ptrs.len() as c_int,
+                name_ptrs.as_ptr(),
+                &mut count,
+                &mut err,
+            );
+            let inners = if handles.is_null() {
+                Vec::new()
+            } else {
+                let inners = slice::from_raw_parts(handles, count).to_vec();
+                ffi::rocksdb_create_column_families_destroy(handles);
+                inners
+            };
+            let result = if err.is_null() {
+                Ok(())
+            } else {
+                Err(Error::new(crate::ffi_util::error_message(err)))
+            };
+            (inners, result)
+        }
+    }
+
     pub fn iterator<'a: 'b, 'b>(
         &'a self,
         mode: IteratorMode,
@@ -1477,11 +1980,203 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         DBRawIteratorWithThreadMode::new_cf(self, cf_handle.inner(), readopts)
     }
 
+    /// Opens an iterator with bounds borrowed for the lifetime of the
+    /// iterator, see [`ReadOptions::scoped_bounds`].
+    pub fn iterator_scoped<'a: 'b, 'b>(
+        &'a self,
+        mode: IteratorMode,
+        readopts: ScopedReadOptions<'b>,
+    ) -> DBIteratorWithThreadMode<'b, Self> {
+        DBIteratorWithThreadMode::new(self, readopts.into_inner(), mode)
+    }
+
+    /// Opens an iterator over the given column family with bounds borrowed
+    /// for the lifetime of the iterator, see [`ReadOptions::scoped_bounds`].
+    pub fn iterator_cf_scoped<'a: 'b, 'b>(
+        &'a self,
+        cf_handle: &impl AsColumnFamilyRef,
+        readopts: ScopedReadOptions<'b>,
+        mode: IteratorMode,
+    ) -> DBIteratorWithThreadMode<'b, Self> {
+        DBIteratorWithThreadMode::new_cf(self, cf_handle.inner(), readopts.into_inner(), mode)
+    }
+
+    /// Opens a raw iterator with bounds borrowed for the lifetime of the
+    /// iterator, see [`ReadOptions::scoped_bounds`].
+    pub fn raw_iterator_scoped<'a: 'b, 'b>(
+        &'a self,
+        readopts: ScopedReadOptions<'b>,
+    ) -> DBRawIteratorWithThreadMode<'b, Self> {
+        DBRawIteratorWithThreadMode::new(self, readopts.into_inner())
+    }
+
+    /// Opens a raw iterator over the given column family with bounds
+    /// borrowed for the lifetime of the iterator, see
+    /// [`ReadOptions::scoped_bounds`].
+    pub fn raw_iterator_cf_scoped<'a: 'b, 'b>(
+        &'a self,
+        cf_handle: &impl AsColumnFamilyRef,
+        readopts: ScopedReadOptions<'b>,
+    ) -> DBRawIteratorWithThreadMode<'b, Self> {
+        DBRawIteratorWithThreadMode::new_cf(self, cf_handle.inner(), readopts.into_inner())
+    }
+
     pub fn snapshot(&self) -> SnapshotWithThreadMode<Self> {
         SnapshotWithThreadMode::<Self>::new(self)
     }
 
-    pub fn put_opt<K, V>(&self, key: K, value: V, writeopts: &WriteOptions) -> Result<(), Error>
+    /// Returns a builder for an iterator over a range of keys, see
+    /// [`ScanBuilder`].
+    pub fn scan(&self) -> ScanBuilder<Self> {
+        ScanBuilder::new(self, None)
+    }
+
+    /// Same as [`scan`](Self::scan) but over the given column family.
+    pub fn scan_cf(&self, cf: &impl AsColumnFamilyRef) -> ScanBuilder<Self> {
+        ScanBuilder::new(self, Some(cf.inner()))
+    }
+
+    /// Reads every key of the given range with `fill_cache` enabled, to load
+    /// its blocks into the block cache, e.g. after a restart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{Options, PrefixRange, DB};
+    ///
+    /// let path = "_path_for_rocksdb_storage_warm_cache_range";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     db.put(b"user:1", b"value").unwrap();
+    ///     db.warm_cache_range(PrefixRange(&b"user:"[..])).unwrap();
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn warm_cache_range(&self, range: impl IterateBounds) -> Result<(), Error> {
+        Self::warm_cache_iter(self.raw_iterator_opt(Self::warm_cache_readopts(range)))
+    }
+
+    /// Same as [`warm_cache_range`](Self::warm_cache_range) but for the given
+    /// column family.
+    pub fn warm_cache_range_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        range: impl IterateBounds,
+    ) -> Result<(), Error> {
+        Self::warm_cache_iter(self.raw_iterator_cf_opt(cf, Self::warm_cache_readopts(range)))
+    }
+
+    fn warm_cache_readopts(range: impl IterateBounds) -> ReadOptions {
+        let mut readopts = ReadOptions::default();
+        readopts.fill_cache(true);
+        readopts.set_readahead_size(2 * 1024 * 1024);
+        readopts.set_iterate_range(range);
+        readopts
+    }
+
+    fn warm_cache_iter(mut iter: DBRawIteratorWithThreadMode<Self>) -> Result<(), Error> {
+        iter.seek_to_first();
+        while iter.valid() {
+            iter.next();
+        }
+        iter.status()
+    }
+
+    /// Reads every entry in `range` of the default column family verifying
+    /// the checksums of the blocks read, to audit the integrity of the data
+    /// online. See [`verify_range_checksum_cf`](Self::verify_range_checksum_cf).
+    pub fn verify_range_checksum(
+        &self,
+        range: impl IterateBounds,
+    ) -> Result<ChecksumReport, Error> {
+        self.verify_range_checksum_impl(DEFAULT_COLUMN_FAMILY_NAME, range, |readopts| {
+            self.raw_iterator_opt(readopts)
+        })
+    }
+
+    /// Reads every entry in `range` of the given column family verifying
+    /// the checksums of the blocks read, to audit the integrity of the data
+    /// online.
+    ///
+    /// A corrupt block stops the iteration: the scan resumes after the
+    /// largest key of the table file holding the block, the entries of the
+    /// file after the block are not verified. The scan stops at the first
+    /// corrupt block of a column family with a custom or reverse comparator,
+    /// as the key to resume from cannot be derived from the largest key of
+    /// the file. Blob files are not verified.
+    ///
+    /// Returns an error if the scan fails for another reason than a
+    /// corruption.
+    pub fn verify_range_checksum_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        range: impl IterateBounds,
+    ) -> Result<ChecksumReport, Error> {
+        self.verify_range_checksum_impl(&column_family_name(cf.inner()), range, |readopts| {
+            self.raw_iterator_cf_opt(cf, readopts)
+        })
+    }
+
+    fn verify_range_checksum_impl<'a>(
+        &'a self,
+        cf_name: &str,
+        range: impl IterateBounds,
+        iterator: impl Fn(ReadOptions) -> DBRawIteratorWithThreadMode<'a, Self>,
+    ) -> Result<ChecksumReport, Error> {
+        let (mut lower, upper) = range.into_bounds();
+        let mut report = ChecksumReport::default();
+        loop {
+            let mut readopts = ReadOptions::default();
+            readopts.set_verify_checksums(true);
+            readopts.fill_cache(false);
+            readopts.set_readahead_size(2 * 1024 * 1024);
+            if let Some(ref lower) = lower {
+                readopts.set_iterate_lower_bound(lower.as_slice());
+            }
+            if let Some(ref upper) = upper {
+                readopts.set_iterate_upper_bound(upper.as_slice());
+            }
+            let mut iter = iterator(readopts);
+            iter.seek_to_first();
+            while iter.valid() {
+                report.keys_verified += 1;
+                iter.next();
+            }
+            let err = match iter.status() {
+                Ok(()) => return Ok(report),
+                Err(err) if err.kind() == ErrorKind::Corruption => err,
+                Err(err) => return Err(err),
+            };
+            let block = CorruptBlock::from_error(err);
+            // the smallest key after the file holding the corrupt block, only
+            // known in the bytewise order
+            let bytewise = matches!(
+                KeyOrder::of_cf(self.path(), cf_name),
+                Ok(KeyOrder::Bytewise)
+            );
+            let resume = match block.file {
+                Some(ref path) if bytewise => self
+                    .live_files_metadata()?
+                    .into_iter()
+                    .find(|file| path.ends_with(&file.name))
+                    .and_then(|file| file.end_key)
+                    .map(|mut key| {
+                        key.push(0);
+                        key
+                    }),
+                _ => None,
+            };
+            report.corrupt_blocks.push(block);
+            match resume {
+                Some(resume) if lower.as_ref().map_or(true, |lower| resume > *lower) => {
+                    lower = Some(resume);
+                }
+                _ => return Ok(report),
+            }
+        }
+    }
+
+    pub fn put_opt<K, V>(&self, key: K, value: V, writeopts: &WriteOptions) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
         V: AsRef<[u8]>,
@@ -1490,14 +2185,17 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         let value = value.as_ref();
 
         unsafe {
-            ffi_try!(ffi::rocksdb_put(
-                self.inner.inner(),
-                writeopts.inner,
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-                value.as_ptr() as *const c_char,
-                value.len() as size_t,
-            ));
+            ffi_try!(
+                ffi::rocksdb_put(
+                    self.inner.inner(),
+                    writeopts.inner,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    value.as_ptr() as *const c_char,
+                    value.len() as size_t,
+                );
+                key = key
+            );
             Ok(())
         }
     }
@@ -1517,15 +2215,18 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         let value = value.as_ref();
 
         unsafe {
-            ffi_try!(ffi::rocksdb_put_cf(
-                self.inner.inner(),
-                writeopts.inner,
-                cf.inner(),
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-                value.as_ptr() as *const c_char,
-                value.len() as size_t,
-            ));
+            ffi_try!(
+                ffi::rocksdb_put_cf(
+                    self.inner.inner(),
+                    writeopts.inner,
+                    cf.inner(),
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    value.as_ptr() as *const c_char,
+                    value.len() as size_t,
+                );
+                cf = cf.inner(), key = key
+            );
             Ok(())
         }
     }
@@ -1539,14 +2240,17 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         let value = value.as_ref();
 
         unsafe {
-            ffi_try!(ffi::rocksdb_merge(
-                self.inner.inner(),
-                writeopts.inner,
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-                value.as_ptr() as *const c_char,
-                value.len() as size_t,
-            ));
+            ffi_try!(
+                ffi::rocksdb_merge(
+                    self.inner.inner(),
+                    writeopts.inner,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    value.as_ptr() as *const c_char,
+                    value.len() as size_t,
+                );
+                key = key
+            );
             Ok(())
         }
     }
@@ -1566,15 +2270,18 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         let value = value.as_ref();
 
         unsafe {
-            ffi_try!(ffi::rocksdb_merge_cf(
-                self.inner.inner(),
-                writeopts.inner,
-                cf.inner(),
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-                value.as_ptr() as *const c_char,
-                value.len() as size_t,
-            ));
+            ffi_try!(
+                ffi::rocksdb_merge_cf(
+                    self.inner.inner(),
+                    writeopts.inner,
+                    cf.inner(),
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    value.as_ptr() as *const c_char,
+                    value.len() as size_t,
+                );
+                cf = cf.inner(), key = key
+            );
             Ok(())
         }
     }
@@ -1587,12 +2294,15 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         let key = key.as_ref();
 
         unsafe {
-            ffi_try!(ffi::rocksdb_delete(
-                self.inner.inner(),
-                writeopts.inner,
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-            ));
+            ffi_try!(
+                ffi::rocksdb_delete(
+                    self.inner.inner(),
+                    writeopts.inner,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                );
+                key = key
+            );
             Ok(())
         }
     }
@@ -1606,13 +2316,16 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         let key = key.as_ref();
 
         unsafe {
-            ffi_try!(ffi::rocksdb_delete_cf(
-                self.inner.inner(),
-                writeopts.inner,
-                cf.inner(),
-                key.as_ptr() as *const c_char,
-                key.len() as size_t,
-            ));
+            ffi_try!(
+                ffi::rocksdb_delete_cf(
+                    self.inner.inner(),
+                    writeopts.inner,
+                    cf.inner(),
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                );
+                cf = cf.inner(), key = key
+            );
             Ok(())
         }
     }
@@ -1649,6 +2362,50 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         self.merge_cf_opt(cf, key.as_ref(), value.as_ref(), &WriteOptions::default())
     }
 
+    /// Adds `delta` to the counter stored at `key`, using the native
+    /// [`BuiltinMergeOperator::UInt64Add`] merge operator which must be set on
+    /// the DB. A missing counter counts as 0, and the sum wraps on overflow.
+    ///
+    /// Read the counter back with [`get_u64`](Self::get_u64).
+    ///
+    /// [`BuiltinMergeOperator::UInt64Add`]: crate::BuiltinMergeOperator::UInt64Add
+    pub fn increment_u64<K: AsRef<[u8]>>(&self, key: K, delta: u64) -> Result<(), Error> {
+        self.merge(key, delta.to_le_bytes())
+    }
+
+    /// Adds `delta` to the counter stored at `key` in the given column family,
+    /// see [`increment_u64`](Self::increment_u64).
+    pub fn increment_u64_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        delta: u64,
+    ) -> Result<(), Error> {
+        self.merge_cf(cf, key, delta.to_le_bytes())
+    }
+
+    /// Returns the counter stored at `key` by
+    /// [`increment_u64`](Self::increment_u64), or `None` if there is none.
+    ///
+    /// Returns an error if the value is not 8 bytes long.
+    pub fn get_u64<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<u64>, Error> {
+        self.get_pinned(key)?
+            .map(|value| decode_u64_counter(&value))
+            .transpose()
+    }
+
+    /// Returns the counter stored at `key` in the given column family, see
+    /// [`get_u64`](Self::get_u64).
+    pub fn get_u64_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+    ) -> Result<Option<u64>, Error> {
+        self.get_pinned_cf(cf, key)?
+            .map(|value| decode_u64_counter(&value))
+            .transpose()
+    }
+
     pub fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<(), Error> {
         self.delete_opt(key.as_ref(), &WriteOptions::default())
     }
@@ -1746,6 +2503,73 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         }
     }
 
+    /// Same as `compact_range_cf_opt`, and returns what the compaction did.
+    ///
+    /// The C API returns nothing from a manual compaction, so the result is
+    /// found by comparing the table files of the column family before and
+    /// after it. The compaction must be exclusive, see
+    /// [`CompactOptions::set_exclusive_manual_compaction`], so that no
+    /// background compaction changes the files meanwhile. The files flushed
+    /// to level 0 meanwhile are not reported.
+    pub fn compact_range_cf_opt_with_result<S: AsRef<[u8]>, E: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        start: Option<S>,
+        end: Option<E>,
+        opts: &CompactOptions,
+    ) -> Result<CompactionResult, Error> {
+        if !opts.get_exclusive_manual_compaction() {
+            return Err(Error::new(
+                "Invalid argument: the result of a manual compaction needs it to be exclusive"
+                    .to_owned(),
+            ));
+        }
+        let name = column_family_name(cf.inner());
+        let cf_files = || -> Result<Vec<LiveFile>, Error> {
+            Ok(self
+                .live_files_metadata()?
+                .into_iter()
+                .filter(|file| file.column_family_name == name)
+                .collect())
+        };
+        let before = cf_files()?;
+        let started = Instant::now();
+        self.compact_range_cf_opt(cf, start, end, opts);
+        let elapsed = started.elapsed();
+        let after = cf_files()?;
+
+        let input_files: Vec<LiveFile> = before
+            .iter()
+            .filter(|file| !after.iter().any(|f| f.name == file.name))
+            .cloned()
+            .collect();
+        let mut output_files = Vec::new();
+        let mut moved_files = Vec::new();
+        for file in after {
+            match before.iter().find(|f| f.name == file.name) {
+                Some(previous) => {
+                    if previous.level != file.level {
+                        moved_files.push(file);
+                    }
+                }
+                None => {
+                    // manual compactions do not write to level 0, flushes do
+                    if file.level > 0 {
+                        output_files.push(file);
+                    }
+                }
+            }
+        }
+        Ok(CompactionResult {
+            bytes_read: input_files.iter().map(|file| file.size as u64).sum(),
+            bytes_written: output_files.iter().map(|file| file.size as u64).sum(),
+            input_files,
+            output_files,
+            moved_files,
+            elapsed,
+        })
+    }
+
     /// Wait for all flush and compactions jobs to finish. Jobs to wait include the
     /// unscheduled (queued, but not scheduled yet).
     ///
@@ -1801,6 +2625,119 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         Ok(())
     }
 
+    /// Returns the maximum number of threads a compaction job can be split
+    /// into, see [`Options::set_max_subcompactions`].
+    ///
+    /// The value is read from the latest OPTIONS file, which RocksDB writes
+    /// when the DB is opened.
+    pub fn max_subcompactions(&self) -> Result<u32, Error> {
+        self.db_option("max_subcompactions")
+    }
+
+    /// Returns the maximum number of concurrent background flushes and
+    /// compactions, see [`Options::set_max_background_jobs`].
+    ///
+    /// The value is read from the latest OPTIONS file, see
+    /// [`max_subcompactions`](Self::max_subcompactions).
+    pub fn max_background_jobs(&self) -> Result<c_int, Error> {
+        self.db_option("max_background_jobs")
+    }
+
+    fn db_option<V: str::FromStr>(&self, key: &str) -> Result<V, Error> {
+        let file = OptionsFile::read_latest(&self.path)?
+            .ok_or_else(|| Error::new("Not found: the DB has no OPTIONS file".to_owned()))?;
+        let value = file
+            .get("DBOptions", key)
+            .ok_or_else(|| Error::new(format!("Not found: no option {key} in the OPTIONS file")))?;
+        value.parse().map_err(|_| {
+            Error::new(format!(
+                "Corruption: invalid option {key} in the OPTIONS file"
+            ))
+        })
+    }
+
+    /// Switches the default column family into bulk-load-friendly settings:
+    /// auto compactions are disabled, write buffers are enlarged and the
+    /// level 0 and pending compaction bytes write stall triggers are lifted.
+    ///
+    /// The current values of the changed options, as recorded in the latest
+    /// OPTIONS file of the DB, are returned so they can be restored with
+    /// [`finish_bulk_load`] once all data has been written.
+    ///
+    /// The memtable representation cannot be changed on a live DB. For the
+    /// fastest loads into an empty DB, open it with
+    /// [`Options::prepare_for_bulk_load`] and a vector memtable instead.
+    ///
+    /// [`finish_bulk_load`]: Self::finish_bulk_load
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DB, Options};
+    ///
+    /// let path = "_path_for_rocksdb_storage_bulk_load";
+    /// {
+    ///     let mut opts = Options::default();
+    ///     opts.create_if_missing(true);
+    ///     let db = DB::open(&opts, path).unwrap();
+    ///
+    ///     let settings = db.prepare_for_bulk_load().unwrap();
+    ///     for i in 0..1000_u32 {
+    ///         db.put(i.to_be_bytes(), b"value").unwrap();
+    ///     }
+    ///     db.finish_bulk_load(settings).unwrap();
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn prepare_for_bulk_load(&self) -> Result<BulkLoadSettings, Error> {
+        let original = self.bulk_load_settings(DEFAULT_COLUMN_FAMILY_NAME)?;
+        BulkLoadSettings::bulk_load().apply(|opts| self.set_options(opts))?;
+        Ok(original)
+    }
+
+    /// Same as [`prepare_for_bulk_load`](Self::prepare_for_bulk_load) but for
+    /// the given column family.
+    pub fn prepare_for_bulk_load_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+    ) -> Result<BulkLoadSettings, Error> {
+        let original = self.bulk_load_settings(&column_family_name(cf.inner()))?;
+        BulkLoadSettings::bulk_load().apply(|opts| self.set_options_cf(cf, opts))?;
+        Ok(original)
+    }
+
+    /// Reads the current values of the options changed for bulk loads from
+    /// the latest OPTIONS file, which RocksDB rewrites on each `SetOptions`.
+    fn bulk_load_settings(&self, cf: &str) -> Result<BulkLoadSettings, Error> {
+        let file = OptionsFile::read_latest(&self.path)?
+            .ok_or_else(|| Error::new("Not found: the DB has no OPTIONS file".to_owned()))?;
+        BulkLoadSettings::from_options_file(&file, cf)
+    }
+
+    /// Restores the settings changed by
+    /// [`prepare_for_bulk_load`](Self::prepare_for_bulk_load), flushes the
+    /// memtables and compacts the whole key range of the default column
+    /// family.
+    pub fn finish_bulk_load(&self, settings: BulkLoadSettings) -> Result<(), Error> {
+        settings.apply(|opts| self.set_options(opts))?;
+        self.flush()?;
+        self.compact_range::<&[u8], &[u8]>(None, None);
+        Ok(())
+    }
+
+    /// Same as [`finish_bulk_load`](Self::finish_bulk_load) but for the given
+    /// column family.
+    pub fn finish_bulk_load_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        settings: BulkLoadSettings,
+    ) -> Result<(), Error> {
+        settings.apply(|opts| self.set_options_cf(cf, opts))?;
+        self.flush_cf(cf)?;
+        self.compact_range_cf::<&[u8], &[u8]>(cf, None, None);
+        Ok(())
+    }
+
     /// Implementation for property_value et al methods.
     ///
     /// `name` is the name of the property.  It will be converted into a CString
@@ -1905,6 +2842,170 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         )
     }
 
+    fn background_job_info_impl(
+        int_property: impl Fn(&PropName) -> Result<Option<u64>, Error>,
+    ) -> Result<BackgroundJobInfo, Error> {
+        let value = |name: &PropName| int_property(name).map(Option::unwrap_or_default);
+        Ok(BackgroundJobInfo {
+            num_running_flushes: value(properties::NUM_RUNNING_FLUSHES)?,
+            num_running_compactions: value(properties::NUM_RUNNING_COMPACTIONS)?,
+            flush_pending: value(properties::MEM_TABLE_FLUSH_PENDING)? != 0,
+            compaction_pending: value(properties::COMPACTION_PENDING)? != 0,
+            num_immutable_mem_tables: value(properties::NUM_IMMUTABLE_MEM_TABLE)?,
+            estimate_pending_compaction_bytes: value(
+                properties::ESTIMATE_PENDING_COMPACTION_BYTES,
+            )?,
+            background_errors: value(properties::BACKGROUND_ERRORS)?,
+        })
+    }
+
+    /// Samples the properties describing the background flushes and
+    /// compactions of the default column family in one call.
+    ///
+    /// This gives an observable window into background work without
+    /// registering an event listener.
+    pub fn get_background_job_info(&self) -> Result<BackgroundJobInfo, Error> {
+        Self::background_job_info_impl(|name| self.property_int_value(name))
+    }
+
+    /// Samples the properties describing the background flushes and
+    /// compactions of the given column family in one call.
+    pub fn get_background_job_info_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+    ) -> Result<BackgroundJobInfo, Error> {
+        Self::background_job_info_impl(|name| self.property_int_value_cf(cf, name))
+    }
+
+    /// Returns the usage of the block cache of the default column family
+    /// broken down by role of the entries, e.g. how much of it holds filter,
+    /// index or data blocks.
+    ///
+    /// RocksDB scans the cache to collect these statistics, and serves them
+    /// again without scanning for a few seconds afterwards.
+    pub fn get_block_cache_usage_by_role(&self) -> Result<CacheUsageByRole, Error> {
+        self.property_value(properties::BLOCK_CACHE_ENTRY_STATS)?
+            .map_or_else(
+                || Ok(CacheUsageByRole::default()),
+                |value| CacheUsageByRole::parse(&value),
+            )
+    }
+
+    /// Returns the usage of the block cache of the given column family
+    /// broken down by role of the entries, see
+    /// [`get_block_cache_usage_by_role`](Self::get_block_cache_usage_by_role).
+    pub fn get_block_cache_usage_by_role_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+    ) -> Result<CacheUsageByRole, Error> {
+        self.property_value_cf(cf, properties::BLOCK_CACHE_ENTRY_STATS)?
+            .map_or_else(
+                || Ok(CacheUsageByRole::default()),
+                |value| CacheUsageByRole::parse(&value),
+            )
+    }
+
+    /// Returns the number of merge operands stored for the keys in
+    /// `[from, to)` which have not been merged yet by a compaction or by
+    /// [`max_successive_merges`](Options::set_max_successive_merges), i.e.
+    /// the merge operands a read of these keys has to apply.
+    ///
+    /// RocksDB has no property for this, so the range is scanned and the
+    /// operands applied are counted with the [`PerfContext`](crate::PerfContext)
+    /// of the current thread: the scan reads the whole range, with the perf
+    /// stats level of the thread set to
+    /// [`EnableCount`](crate::PerfStatsLevel::EnableCount) until it returns.
+    pub fn merge_operand_count<K: AsRef<[u8]>>(&self, from: K, to: K) -> Result<u64, Error> {
+        let readopts = Self::merge_operand_count_readopts(from, to);
+        Self::merge_operand_count_impl(self.raw_iterator_opt(readopts))
+    }
+
+    /// Returns the number of merge operands stored for the keys in
+    /// `[from, to)` of the given column family, see
+    /// [`merge_operand_count`](Self::merge_operand_count).
+    pub fn merge_operand_count_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        from: K,
+        to: K,
+    ) -> Result<u64, Error> {
+        let readopts = Self::merge_operand_count_readopts(from, to);
+        Self::merge_operand_count_impl(self.raw_iterator_cf_opt(cf, readopts))
+    }
+
+    fn merge_operand_count_readopts<K: AsRef<[u8]>>(from: K, to: K) -> ReadOptions {
+        let mut readopts = ReadOptions::default();
+        readopts.fill_cache(false);
+        readopts.set_total_order_seek(true);
+        readopts.set_iterate_lower_bound(from.as_ref());
+        readopts.set_iterate_upper_bound(to.as_ref());
+        readopts
+    }
+
+    fn merge_operand_count_impl(mut iter: DBRawIteratorWithThreadMode<Self>) -> Result<u64, Error> {
+        let level = perf_stats_level();
+        set_perf_stats(PerfStatsLevel::EnableCount);
+        let ctx = PerfContext::default();
+        let before = ctx.metric(PerfMetric::InternalMergeCount);
+        iter.seek_to_first();
+        while iter.valid() {
+            iter.next();
+        }
+        let count = ctx.metric(PerfMetric::InternalMergeCount) - before;
+        set_perf_stats(level);
+        iter.status()?;
+        Ok(count)
+    }
+
+    /// Returns the number of unreleased snapshots of the DB.
+    pub fn get_snapshot_count(&self) -> Result<u64, Error> {
+        Ok(self
+            .property_int_value(properties::NUM_SNAPSHOTS)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the sequence number of the oldest unreleased snapshot, or
+    /// `None` if there is no snapshot.
+    ///
+    /// Data older than this sequence number that was overwritten or deleted
+    /// cannot be garbage collected by compactions while the snapshot is held.
+    pub fn get_oldest_snapshot_sequence_number(&self) -> Result<Option<u64>, Error> {
+        Ok(self
+            .property_int_value(properties::OLDEST_SNAPSHOT_SEQUENCE)?
+            .filter(|seq| *seq != 0))
+    }
+
+    /// Returns how long the oldest unreleased snapshot has been held, or
+    /// `None` if there is no snapshot.
+    ///
+    /// The age has a resolution of one second. Checking it periodically
+    /// against a threshold helps finding snapshots that are never released.
+    pub fn get_oldest_snapshot_age(&self) -> Result<Option<Duration>, Error> {
+        let created = match self.property_int_value(properties::OLDEST_SNAPSHOT_TIME)? {
+            Some(time) if time != 0 => time,
+            _ => return Ok(None),
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| Error::new(format!("Failed to read the system time: {err}")))?;
+        Ok(Some(now.saturating_sub(Duration::from_secs(created))))
+    }
+
+    /// Returns the per-level compaction statistics and the write stall
+    /// counters of the given column family.
+    ///
+    /// The statistics are parsed from the `rocksdb.cfstats-no-file-histogram`
+    /// property. Note that retrieving this property also resets the interval
+    /// statistics RocksDB reports in the "Int" rows.
+    pub fn get_cf_stats(&self, cf: &impl AsColumnFamilyRef) -> Result<CfStats, Error> {
+        match self.property_value_cf(cf, properties::CFSTATS_NO_FILE_HISTOGRAM)? {
+            Some(value) => CfStats::parse(&value),
+            None => Err(Error::new(
+                "Could not get column family statistics".to_owned(),
+            )),
+        }
+    }
+
     /// The sequence number of the most recent transaction.
     pub fn latest_sequence_number(&self) -> u64 {
         unsafe { ffi::rocksdb_get_latest_sequence_number(self.inner.inner()) }
@@ -1920,7 +3021,7 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
     ///
     /// Calling `get_updates_since()` with a sequence number that is out of
     /// bounds will return an error.
-    pub fn get_updates_since(&self, seq_number: u64) -> Result<DBWALIterator, Error> {
+    pub fn get_updates_since(&self, seq_number: u64) -> Result<DBWALIterator<'_>, Error> {
         unsafe {
             // rocksdb_wal_readoptions_t does not appear to have any functions
             // for creating and destroying it; fortunately we can pass a nullptr
@@ -1934,10 +3035,60 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
             Ok(DBWALIterator {
                 inner: iter,
                 start_seq_number: seq_number,
+                column_families: None,
+                _handle: Some(self.handles.track(HandleKind::Iterator)),
+                _db: PhantomData,
             })
         }
     }
 
+    /// Iterate over the batches of write operations since a given sequence
+    /// which write to at least one of the given column families.
+    ///
+    /// Works like [`get_updates_since`](Self::get_updates_since), skipping the
+    /// batches which only write to other column families, e.g. to replicate
+    /// some column families of a DB without transferring the writes to the
+    /// others. The returned batches are not trimmed: a batch writing to a
+    /// requested column family is returned with all of its writes, whose
+    /// column families are returned by
+    /// [`WriteBatch::column_family_ids`](crate::WriteBatchWithTransaction::column_family_ids).
+    ///
+    /// The column families are given by name and must be opened.
+    pub fn get_updates_since_cf<I, N>(
+        &self,
+        seq_number: u64,
+        column_families: I,
+    ) -> Result<DBWALIterator<'_>, Error>
+    where
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let ids: BTreeSet<u32> = column_families
+            .into_iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.cfs
+                    .with_cf_internal(name, |cf| column_family_id(cf.inner()))
+                    .ok_or_else(|| {
+                        Error::new(format!(
+                            "Invalid argument: column family {name} is not opened"
+                        ))
+                    })
+            })
+            .collect::<Result<_, Error>>()?;
+        let mut iter = self.get_updates_since(seq_number)?;
+        iter.column_families = Some(ids);
+        Ok(iter)
+    }
+
+    /// Returns the id of the column family `cf`, as returned by
+    /// [`WriteBatch::column_family_ids`](crate::WriteBatchWithTransaction::column_family_ids).
+    ///
+    /// The id of the default column family is 0.
+    pub fn column_family_id(&self, cf: &impl AsColumnFamilyRef) -> u32 {
+        column_family_id(cf.inner())
+    }
+
     /// Tries to catch up with the primary by reading as much as possible from the
     /// log files.
     pub fn try_catch_up_with_primary(&self) -> Result<(), Error> {
@@ -1947,6 +3098,31 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         Ok(())
     }
 
+    /// Catches up with the primary until the writes up to the sequence number
+    /// `seq` are visible, e.g. to read the writes the primary reported with
+    /// [`latest_sequence_number`](Self::latest_sequence_number).
+    ///
+    /// Returns `false` if the writes are still not visible after `timeout`.
+    pub fn catch_up_to_sequence_number(&self, seq: u64, timeout: Duration) -> Result<bool, Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.try_catch_up_with_primary()?;
+            if self.latest_sequence_number() >= seq {
+                return Ok(true);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(false);
+            }
+            thread::sleep((deadline - now).min(Duration::from_millis(10)));
+        }
+    }
+
+    /// Returns the way the DB was opened.
+    pub fn role(&self) -> DBRole {
+        self.role
+    }
+
     /// Loads a list of external SST files created with SstFileWriter into the DB with default opts
     pub fn ingest_external_file<P: AsRef<Path>>(&self, paths: Vec<P>) -> Result<(), Error> {
         let opts = IngestExternalFileOptions::default();
@@ -2068,7 +3244,33 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
 
     /// Returns a list of all table files with their level, start key
     /// and end key
+    ///
+    /// The full checksums of the files are read from the MANIFEST, see
+    /// [`Options::enable_file_checksums`].
     pub fn live_files(&self) -> Result<Vec<LiveFile>, Error> {
+        let mut files = self.live_files_metadata()?;
+        let checksums: HashMap<u64, FileChecksum> = manifest::describe(&self.path)?
+            .column_families
+            .into_iter()
+            .flat_map(|cf| cf.levels.into_iter().flatten())
+            .filter_map(|file| Some((file.number, file.file_checksum?)))
+            .collect();
+        for file in &mut files {
+            let number = file
+                .name
+                .trim_start_matches('/')
+                .trim_end_matches(".sst")
+                .parse::<u64>();
+            if let Ok(number) = number {
+                file.file_checksum = checksums.get(&number).cloned();
+            }
+        }
+        Ok(files)
+    }
+
+    /// Returns the live files without their checksums, which saves reading
+    /// the MANIFEST.
+    pub(crate) fn live_files_metadata(&self) -> Result<Vec<LiveFile>, Error> {
         unsafe {
             let files = ffi::rocksdb_livefiles(self.inner.inner());
             if files.is_null() {
@@ -2103,6 +3305,7 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
                         end_key: largest_key,
                         num_entries: ffi::rocksdb_livefiles_entries(files, i),
                         num_deletions: ffi::rocksdb_livefiles_deletions(files, i),
+                        file_checksum: None,
                     });
                 }
 
@@ -2115,6 +3318,415 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         }
     }
 
+    /// Prevents file deletions, so the files of the DB can be copied by an
+    /// external tool without obsolete files disappearing in the meantime.
+    /// Compactions keep running, only the deletion of their input files is
+    /// delayed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{Options, DB};
+    ///
+    /// let path = "_path_for_rocksdb_storage_external_backup";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     db.put(b"key", b"value").unwrap();
+    ///     db.flush().unwrap();
+    ///
+    ///     db.disable_file_deletions().unwrap();
+    ///     let live_files = db.get_live_files().unwrap();
+    ///     for file in &live_files.files {
+    ///         // copy `file`, only the first `live_files.manifest_file_size`
+    ///         // bytes of the MANIFEST
+    ///     }
+    ///     db.enable_file_deletions(false).unwrap();
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn disable_file_deletions(&self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_disable_file_deletions(self.inner.inner()));
+        }
+        Ok(())
+    }
+
+    /// Allows file deletions again after [`disable_file_deletions`].
+    ///
+    /// Calls to `disable_file_deletions` are counted, and deletions are only
+    /// allowed again once `enable_file_deletions` has been called as many
+    /// times, unless `force` is set.
+    ///
+    /// [`disable_file_deletions`]: Self::disable_file_deletions
+    pub fn enable_file_deletions(&self, force: bool) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_enable_file_deletions(
+                self.inner.inner(),
+                c_uchar::from(force)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the files making up the current state of the DB, for backups
+    /// copying them with external tools. Call [`disable_file_deletions`]
+    /// first so the files are not deleted while they are copied.
+    ///
+    /// The SST files are the ones of the MANIFEST when it is read, so they
+    /// match its first [`manifest_file_size`] bytes even while flushes and
+    /// compactions run.
+    ///
+    /// The data still in the memtables is only in the WAL files, which are
+    /// not listed: flush the column families before, or copy the WAL files
+    /// as well. Blob files are not listed either.
+    ///
+    /// [`disable_file_deletions`]: Self::disable_file_deletions
+    /// [`manifest_file_size`]: LiveFileList::manifest_file_size
+    pub fn get_live_files(&self) -> Result<LiveFileList, Error> {
+        let manifest = manifest::describe(&self.path)?;
+        let mut files: Vec<String> = manifest
+            .column_families
+            .iter()
+            .flat_map(|cf| cf.levels.iter().flatten())
+            .map(|file| format!("/{}", file.name()))
+            .collect();
+
+        let latest_options = fs::read_dir(&self.path)
+            .map_err(|err| Error::new(format!("Failed to list the DB directory: {err}")))?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with("OPTIONS-") && !name.ends_with(".dbtmp"))
+            .max_by_key(|name| name["OPTIONS-".len()..].parse::<u64>().unwrap_or(0));
+
+        files.push("/CURRENT".to_owned());
+        files.push(format!("/{}", manifest.manifest_name));
+        if let Some(options) = latest_options {
+            files.push(format!("/{options}"));
+        }
+
+        Ok(LiveFileList {
+            files,
+            manifest_file_size: manifest.manifest_size,
+        })
+    }
+
+    /// Returns the space used by the files of the DB, by kind of file.
+    ///
+    /// The sizes are those of the files in the DB directory and its
+    /// `archive` directory, so files put elsewhere with
+    /// [`Options::set_db_paths`] or [`Options::set_wal_dir`] are not
+    /// accounted for.
+    ///
+    /// [`Options::set_db_paths`]: crate::Options::set_db_paths
+    /// [`Options::set_wal_dir`]: crate::Options::set_wal_dir
+    pub fn get_disk_usage(&self) -> Result<DiskUsage, Error> {
+        let live: HashSet<String> = self
+            .live_files_metadata()?
+            .into_iter()
+            .map(|file| file.name.trim_start_matches('/').to_owned())
+            .collect();
+        let mut usage = DiskUsage::default();
+        // the WAL files are only stat'ed, not read
+        if let Ok(entries) = fs::read_dir(self.path.join("archive")) {
+            usage.wal_bytes = entries
+                .filter_map(Result::ok)
+                .filter(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()
+                        .map_or(false, |name| name.ends_with(".log"))
+                })
+                .filter_map(|entry| entry.metadata().ok())
+                .map(|metadata| metadata.len())
+                .sum();
+        }
+
+        let entries = fs::read_dir(&self.path)
+            .map_err(|err| Error::new(format!("Failed to list the DB directory: {err}")))?;
+        for entry in entries.filter_map(Result::ok) {
+            let name = match entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            // files can be deleted while the directory is listed
+            let size = match entry.metadata() {
+                Ok(metadata) if metadata.is_file() => metadata.len(),
+                _ => continue,
+            };
+            if name.ends_with(".log") {
+                usage.wal_bytes += size;
+            } else if name.ends_with(".trash") {
+                usage.trash_bytes += size;
+            } else if name.ends_with(".sst") && live.contains(&name) {
+                usage.live_sst_bytes += size;
+            } else if name.ends_with(".sst") {
+                usage.obsolete_sst_bytes += size;
+            } else if name.ends_with(".blob") {
+                usage.blob_bytes += size;
+            } else {
+                usage.other_bytes += size;
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Returns the identity of the DB, a unique id generated when the DB is
+    /// created and kept across opens, read from its `IDENTITY` file.
+    ///
+    /// Checkpoints and restored backups of the DB share its identity.
+    pub fn get_db_identity(&self) -> Result<String, Error> {
+        let identity = fs::read_to_string(self.path.join("IDENTITY"))
+            .map_err(|err| Error::new(format!("Failed to read the DB identity: {err}")))?;
+        Ok(identity.trim_end().to_owned())
+    }
+
+    /// Returns the session id of the DB, a unique id generated each time the
+    /// DB is opened, which is also recorded in the properties of the SST
+    /// files written during the session.
+    ///
+    /// This is best effort: the C API does not expose the session id, which
+    /// is read from the header of the `LOG` file in the DB directory instead.
+    /// Returns `None` if there is no such file or its header has no session
+    /// id. The info log must be written there, which is the default: with
+    /// [`set_db_log_dir`](crate::Options::set_db_log_dir), the `LOG` file of
+    /// the directory, if any, belongs to a previous open and its session id
+    /// is returned.
+    pub fn get_db_session_id(&self) -> Result<Option<String>, Error> {
+        let log = match fs::File::open(self.path.join("LOG")) {
+            Ok(log) => log,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Error::new(format!("Failed to read the info log: {err}")));
+            }
+        };
+        // the id is in the first lines of the header, e.g.
+        // `2024/01/01-00:00:00.000000 1234 DB Session ID:  ZCXRA5V3P41SPVT8LAVF`,
+        // the rest of the file is not read
+        Ok(BufReader::new(log)
+            .lines()
+            .take(16)
+            .map_while(Result::ok)
+            .find_map(|line| {
+                let (_, id) = line.split_once("DB Session ID:")?;
+                Some(id.trim().to_owned())
+            }))
+    }
+
+    /// Compares the options a column family was created or last opened with
+    /// to `opts`, and returns the options which differ, e.g. to check that a
+    /// column family created by another process matches the expected schema.
+    ///
+    /// The options of the column family are read from the latest OPTIONS
+    /// file of the DB, see
+    /// [`column_family_operators_cf`](Self::column_family_operators_cf). Only
+    /// the options which can be read back from `opts` with getters are
+    /// compared, along with the name of the merge operator.
+    pub fn compare_cf_options(
+        &self,
+        name: &str,
+        opts: &Options,
+    ) -> Result<Vec<OptionMismatch>, Error> {
+        OptionsFile::read_latest(&self.path)?
+            .and_then(|file| file.compare_cf_options(name, opts))
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "Not found: no options of column family {name} in the OPTIONS file"
+                ))
+            })
+    }
+
+    /// Returns the names of the merge operator and compaction filters of the
+    /// default column family.
+    ///
+    /// See [`column_family_operators_cf`](Self::column_family_operators_cf).
+    pub fn column_family_operators(&self) -> Result<ColumnFamilyOperators, Error> {
+        self.column_family_operators_by_name(DEFAULT_COLUMN_FAMILY_NAME)
+    }
+
+    /// Returns the names of the merge operator and compaction filters of a
+    /// column family.
+    ///
+    /// The names are read from the latest OPTIONS file of the DB, which
+    /// RocksDB writes when the DB is opened in read-write mode and when
+    /// column families are created. A DB opened in read-only or secondary
+    /// mode returns the operators of the primary instance.
+    pub fn column_family_operators_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+    ) -> Result<ColumnFamilyOperators, Error> {
+        let name = self.get_column_family_metadata_cf(cf).name;
+        self.column_family_operators_by_name(&name)
+    }
+
+    fn column_family_operators_by_name(&self, cf: &str) -> Result<ColumnFamilyOperators, Error> {
+        OptionsFile::read_latest(&self.path)?
+            .and_then(|file| file.cf_operators(cf))
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "Not found: no options of column family {cf} in the OPTIONS file"
+                ))
+            })
+    }
+
+    /// Returns the WAL files of the DB, alive and archived, sorted by number.
+    ///
+    /// The files are looked up in the DB directory, so this does not find
+    /// them if the DB was opened with [`Options::set_wal_dir`]. Call
+    /// [`disable_file_deletions`] first to keep the files from being deleted
+    /// while they are read.
+    ///
+    /// The files compressed with [`Options::set_wal_compression_type`] are
+    /// listed, but their batches cannot be read: their
+    /// [`start_sequence`](WalFile::start_sequence) is 0, and reading them
+    /// with [`WalFile::read_batches`] or [`read_wal`](Self::read_wal) fails.
+    ///
+    /// [`Options::set_wal_dir`]: crate::Options::set_wal_dir
+    /// [`Options::set_wal_compression_type`]: crate::Options::set_wal_compression_type
+    /// [`disable_file_deletions`]: Self::disable_file_deletions
+    pub fn get_sorted_wal_files(&self) -> Result<Vec<WalFile>, Error> {
+        list_wal_files(&self.path)
+    }
+
+    /// Returns the WAL file currently being written to, if any.
+    ///
+    /// See [`get_sorted_wal_files`](Self::get_sorted_wal_files) for the
+    /// lookup of the files.
+    pub fn get_current_wal_file(&self) -> Result<Option<WalFile>, Error> {
+        Ok(self
+            .get_sorted_wal_files()?
+            .into_iter()
+            .filter(|file| file.file_type == WalFileType::Alive)
+            .last())
+    }
+
+    /// Returns a reader of the write batches in the WAL files, starting at
+    /// `position`.
+    ///
+    /// Unlike [`get_updates_since`](Self::get_updates_since), the reader
+    /// starts at a position rather than a sequence number, and yields
+    /// records borrowing the content of the files instead of write batches:
+    /// copying a batch is left to the caller. A position with a zero offset
+    /// starts at the first file numbered from it, e.g.
+    /// `WalPosition::default()` starts at the oldest file.
+    ///
+    /// See [`get_sorted_wal_files`](Self::get_sorted_wal_files) for the
+    /// lookup of the files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{WalPosition, DB};
+    ///
+    /// let path = "_path_for_rocksdb_storage_read_wal";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     db.put(b"k1", b"v1").unwrap();
+    ///     db.put(b"k2", b"v2").unwrap();
+    ///
+    ///     let mut reader = db.read_wal(WalPosition::default()).unwrap();
+    ///     let first = reader.next_record().unwrap().unwrap();
+    ///     assert_eq!(first.count(), 1);
+    ///     let resume_at = first.next_position();
+    ///
+    ///     let mut reader = db.read_wal(resume_at).unwrap();
+    ///     let second = reader.next_record().unwrap().unwrap();
+    ///     assert_eq!(second.sequence(), first.sequence() + 1);
+    ///     assert!(reader.next_record().is_none());
+    /// }
+    /// let _ = DB::destroy(&rocksdb::Options::default(), path);
+    /// ```
+    pub fn read_wal(&self, position: WalPosition) -> Result<WalReader, Error> {
+        read_wal_from(&self.path, position)
+    }
+
+    /// Suggests keys splitting the given column family into shards of about
+    /// `target_shard_size` bytes, e.g. to distribute the data of a DB.
+    ///
+    /// The keys are returned in ascending order, each one being the largest
+    /// key of a shard: the next shard starts right after it. The keys are
+    /// ordered by the comparator of the column family, which must be the
+    /// bytewise comparator or its reverse. The estimate is based on the key
+    /// ranges and sizes of the SST files, so data still in the memtables is
+    /// not accounted for, and overlapping files of level 0 make the shard
+    /// sizes less accurate.
+    pub fn suggest_split_keys(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        target_shard_size: u64,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let cf_name = self.get_column_family_metadata_cf(cf).name;
+        let order = KeyOrder::of_cf(self.path(), &cf_name)?;
+        let mut files: Vec<(Vec<u8>, usize)> = self
+            .live_files_metadata()?
+            .into_iter()
+            .filter(|file| file.column_family_name == cf_name)
+            .filter_map(|file| file.end_key.map(|end_key| (end_key, file.size)))
+            .collect();
+        files.sort_by(|(a, _), (b, _)| order.compare(a, b));
+
+        let last_key = match files.last() {
+            Some((key, _)) => key.clone(),
+            None => return Ok(Vec::new()),
+        };
+        let mut split_keys: Vec<Vec<u8>> = Vec::new();
+        let mut shard_size = 0_u64;
+        for (end_key, size) in files {
+            shard_size += size as u64;
+            // a split at the last key would leave an empty shard behind it
+            if shard_size >= target_shard_size
+                && end_key != last_key
+                && split_keys
+                    .last()
+                    .map_or(true, |split| order.compare(split, &end_key).is_lt())
+            {
+                split_keys.push(end_key);
+                shard_size = 0;
+            }
+        }
+        Ok(split_keys)
+    }
+
+    /// Estimates the tombstones of the given column family in `range`, from
+    /// the deletion counts recorded in the table properties of its SST
+    /// files, e.g. to decide when to compact a range of a queue whose reads
+    /// slow down as they skip the deleted entries.
+    ///
+    /// The files whose key range overlaps `range` are counted in full, so the
+    /// estimate grows with the part of the files outside of the range. The
+    /// deletions still in the memtables are not counted. The keys are
+    /// compared in the order of the comparator of the column family, which
+    /// must be the bytewise comparator or its reverse. Files can be
+    /// compacted on their own once their tombstones reach a threshold with
+    /// [`Options::add_compact_on_deletion_collector_factory`].
+    ///
+    /// [`Options::add_compact_on_deletion_collector_factory`]: crate::Options::add_compact_on_deletion_collector_factory
+    pub fn estimate_deletions_in_range(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        range: impl IterateBounds,
+    ) -> Result<DeletionEstimate, Error> {
+        let name = column_family_name(cf.inner());
+        let order = KeyOrder::of_cf(self.path(), &name)?;
+        let (lower, upper) = range.into_bounds();
+        let mut estimate = DeletionEstimate::default();
+        for file in self.live_files_metadata()? {
+            let overlaps = file.column_family_name == name
+                && match (&upper, &file.start_key) {
+                    (Some(upper), Some(start)) => order.compare(start, upper).is_lt(),
+                    _ => true,
+                }
+                && match (&lower, &file.end_key) {
+                    (Some(lower), Some(end)) => order.compare(end, lower).is_ge(),
+                    _ => true,
+                };
+            if overlaps {
+                estimate.file_count += 1;
+                estimate.num_entries += file.num_entries;
+                estimate.num_deletions += file.num_deletions;
+            }
+        }
+        Ok(estimate)
+    }
+
     /// Delete sst files whose keys are entirely in the given range.
     ///
     /// Could leave some keys in the range which are in files which are not
@@ -2160,6 +3772,59 @@ impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
         }
     }
 
+    /// Removes all entries in the range `["from", "to")` of a column family and
+    /// reclaims their space, e.g. to drop a partition of the key space.
+    ///
+    /// This runs, in order:
+    /// 1. [`delete_file_in_range_cf`], dropping the table files which only hold
+    ///    keys of the range without rewriting them,
+    /// 2. [`delete_range_cf`], deleting the entries left in other files and in
+    ///    the memtables,
+    /// 3. if `compact` is `true`, a manual compaction of the range including
+    ///    the bottommost level, which drops the deleted entries and the range
+    ///    tombstone from disk.
+    ///
+    /// The purge is not atomic: until the second step is done, readers can see
+    /// part of the range, including older versions of keys whose newer
+    /// versions or deletions were in the dropped files. Snapshots taken before
+    /// the purge might not see the range either.
+    ///
+    /// [`delete_file_in_range_cf`]: Self::delete_file_in_range_cf
+    /// [`delete_range_cf`]: Self::delete_range_cf
+    pub fn purge_range_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        from: K,
+        to: K,
+        compact: bool,
+    ) -> Result<(), Error> {
+        let from = from.as_ref();
+        let to = to.as_ref();
+
+        // DeleteFilesInRange includes its end, so stop at the last key of the
+        // range to keep the files holding `to`
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_lower_bound(from);
+        readopts.set_iterate_upper_bound(to);
+        let mut iter = self.raw_iterator_cf_opt(cf, readopts);
+        iter.seek_to_last();
+        iter.status()?;
+        let last = iter.key().map(<[u8]>::to_vec);
+        drop(iter);
+        if let Some(last) = last {
+            self.delete_file_in_range_cf(cf, from, &last)?;
+        }
+
+        self.delete_range_cf(cf, from, to)?;
+
+        if compact {
+            let mut opts = CompactOptions::default();
+            opts.set_bottommost_level_compaction(BottommostLevelCompaction::Force);
+            self.compact_range_cf_opt(cf, Some(from), Some(to), &opts);
+        }
+        Ok(())
+    }
+
     /// Request stopping background work, if wait is true wait until it's done.
     pub fn cancel_all_background_work(&self, wait: bool) {
         unsafe {
@@ -2196,6 +3861,25 @@ impl<I: DBInner> DBCommon<SingleThreaded, I> {
         Ok(())
     }
 
+    /// Creates column families with the given names and the same options,
+    /// in a single call to RocksDB.
+    ///
+    /// If creating one of them fails, the ones created before are kept.
+    pub fn create_cfs<N: AsRef<str>>(&mut self, names: &[N], opts: &Options) -> Result<(), Error> {
+        let (inners, result) = self.create_inner_cf_handles(names, opts);
+        for (name, inner) in names.iter().zip(inners) {
+            self.cfs
+                .cfs
+                .insert(name.as_ref().to_string(), ColumnFamily { inner });
+        }
+        result
+    }
+
+    /// Returns `true` if the column family with the given name is opened.
+    pub fn cf_exists(&self, name: &str) -> bool {
+        self.cfs.cfs.contains_key(name)
+    }
+
     /// Drops the column family with the given name
     pub fn drop_cf(&mut self, name: &str) -> Result<(), Error> {
         if let Some(cf) = self.cfs.cfs.remove(name) {
@@ -2209,6 +3893,58 @@ impl<I: DBInner> DBCommon<SingleThreaded, I> {
     pub fn cf_handle(&self, name: &str) -> Option<&ColumnFamily> {
         self.cfs.cfs.get(name)
     }
+
+    /// Flushes the memtables of all the column families and syncs the WAL,
+    /// returning a sequence number up to which all the writes are durable.
+    ///
+    /// This makes writes done with [`WriteOptions::no_wal`] durable, the
+    /// MANIFEST recording the flushed files being synced by RocksDB. The
+    /// column families are flushed atomically if
+    /// [`Options::set_atomic_flush`] is enabled, except for the default
+    /// column family when the DB was opened without naming column families.
+    ///
+    /// Writes done concurrently may be flushed too, but only the ones before
+    /// the returned sequence number are guaranteed to be.
+    pub fn flush_and_sync_all(&self) -> Result<u64, Error> {
+        let cfs: Vec<&ColumnFamily> = self.cfs.cfs.values().collect();
+        self.flush_and_sync_all_impl(&cfs, self.cf_exists(DEFAULT_COLUMN_FAMILY_NAME))
+    }
+
+    /// Returns the handles of all the column families opened or created
+    /// through this DB, keyed by name.
+    ///
+    /// This saves a `cf_handle` lookup (and unwrap) per column family right
+    /// after opening the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{ColumnFamilyDescriptor, DBWithThreadMode, Options, SingleThreaded};
+    ///
+    /// let path = "_path_for_rocksdb_storage_cf_handles";
+    /// {
+    ///     let mut opts = Options::default();
+    ///     opts.create_if_missing(true);
+    ///     opts.create_missing_column_families(true);
+    ///     let cfs = vec![
+    ///         ColumnFamilyDescriptor::new("cf1", Options::default()),
+    ///         ColumnFamilyDescriptor::new("cf2", Options::default()),
+    ///     ];
+    ///     let db = DBWithThreadMode::<SingleThreaded>::open_cf_descriptors(&opts, path, cfs)
+    ///         .unwrap();
+    ///     let handles = db.cf_handles();
+    ///     db.put_cf(handles["cf1"], b"k1", b"v1").unwrap();
+    ///     db.put_cf(handles["cf2"], b"k2", b"v2").unwrap();
+    /// }
+    /// let _ = DBWithThreadMode::<SingleThreaded>::destroy(&Options::default(), path);
+    /// ```
+    pub fn cf_handles(&self) -> BTreeMap<&str, &ColumnFamily> {
+        self.cfs
+            .cfs
+            .iter()
+            .map(|(name, cf)| (name.as_str(), cf))
+            .collect()
+    }
 }
 
 impl<I: DBInner> DBCommon<MultiThreaded, I> {
@@ -2222,6 +3958,27 @@ impl<I: DBInner> DBCommon<MultiThreaded, I> {
         Ok(())
     }
 
+    /// Creates column families with the given names and the same options,
+    /// in a single call to RocksDB.
+    ///
+    /// If creating one of them fails, the ones created before are kept.
+    pub fn create_cfs<N: AsRef<str>>(&self, names: &[N], opts: &Options) -> Result<(), Error> {
+        let (inners, result) = self.create_inner_cf_handles(names, opts);
+        let mut cfs = self.cfs.cfs.write().unwrap();
+        for (name, inner) in names.iter().zip(inners) {
+            cfs.insert(
+                name.as_ref().to_string(),
+                Arc::new(UnboundColumnFamily { inner }),
+            );
+        }
+        result
+    }
+
+    /// Returns `true` if the column family with the given name is opened.
+    pub fn cf_exists(&self, name: &str) -> bool {
+        self.cfs.cfs.read().unwrap().contains_key(name)
+    }
+
     /// Drops the column family with the given name by internally locking the inner column
     /// family map. This avoids needing `&mut self` reference
     pub fn drop_cf(&self, name: &str) -> Result<(), Error> {
@@ -2242,11 +3999,51 @@ impl<I: DBInner> DBCommon<MultiThreaded, I> {
             .cloned()
             .map(UnboundColumnFamily::bound_column_family)
     }
+
+    /// Flushes the memtables of all the column families and syncs the WAL,
+    /// returning a sequence number up to which all the writes are durable.
+    ///
+    /// This makes writes done with [`WriteOptions::no_wal`] durable, the
+    /// MANIFEST recording the flushed files being synced by RocksDB. The
+    /// column families are flushed atomically if
+    /// [`Options::set_atomic_flush`] is enabled, except for the default
+    /// column family when the DB was opened without naming column families.
+    ///
+    /// Writes done concurrently may be flushed too, but only the ones before
+    /// the returned sequence number are guaranteed to be.
+    pub fn flush_and_sync_all(&self) -> Result<u64, Error> {
+        let handles = self.cf_handles();
+        let cfs: Vec<&Arc<BoundColumnFamily>> = handles.values().collect();
+        self.flush_and_sync_all_impl(&cfs, handles.contains_key(DEFAULT_COLUMN_FAMILY_NAME))
+    }
+
+    /// Returns the handles of all the column families opened or created
+    /// through this DB, keyed by name.
+    ///
+    /// The returned handles are bound to the lifetime of the DB and can be
+    /// cached by the caller instead of looking each one up with `cf_handle`.
+    pub fn cf_handles(&self) -> BTreeMap<String, Arc<BoundColumnFamily>> {
+        self.cfs
+            .cfs
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, cf)| (name.clone(), cf.clone().bound_column_family()))
+            .collect()
+    }
 }
 
 impl<T: ThreadMode, I: DBInner> Drop for DBCommon<T, I> {
     fn drop(&mut self) {
         self.cfs.drop_all_cfs_internal();
+        if self.handles.check_none_left().is_err() {
+            // closing the DB would leave the leaked handles dangling, so the
+            // DB and the options it uses are leaked instead
+            mem::forget(mem::take(&mut self._outlive));
+            return;
+        }
+        // SAFETY: the DB is not used after it is closed
+        unsafe { ManuallyDrop::drop(&mut self.inner) };
     }
 }
 
@@ -2268,6 +4065,86 @@ pub struct ColumnFamilyMetaData {
     pub file_count: usize,
 }
 
+/// The result of a checksum verification scan, as returned by
+/// [`DBCommon::verify_range_checksum`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumReport {
+    /// Number of entries read
+    pub keys_verified: u64,
+    /// The corrupt blocks found
+    pub corrupt_blocks: Vec<CorruptBlock>,
+}
+
+impl ChecksumReport {
+    /// Returns `true` if no corrupt block was found.
+    pub fn is_ok(&self) -> bool {
+        self.corrupt_blocks.is_empty()
+    }
+}
+
+/// A block whose checksum does not match, see [`ChecksumReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptBlock {
+    /// Path of the table file holding the block, if RocksDB reported it
+    pub file: Option<String>,
+    /// Offset of the block in the file, if RocksDB reported it
+    pub offset: Option<u64>,
+    /// Size of the block, if RocksDB reported it
+    pub size: Option<u64>,
+    /// The corruption reported by RocksDB
+    pub error: Error,
+}
+
+impl CorruptBlock {
+    /// Parses the location of the block from an error like `Corruption:
+    /// block checksum mismatch: ... in /db/000012.sst offset 4096 size 4023`.
+    fn from_error(error: Error) -> Self {
+        let message = error.as_ref();
+        let location = message.rfind(" offset ").and_then(|offset| {
+            let file = message[..offset].rsplit_once(" in ")?.1.trim();
+            let mut fields = message[offset..].split_whitespace();
+            let offset = match (fields.next(), fields.next()) {
+                (Some("offset"), Some(offset)) => offset.parse().ok(),
+                _ => None,
+            };
+            let size = match (fields.next(), fields.next()) {
+                (Some("size"), Some(size)) => size.parse().ok(),
+                _ => None,
+            };
+            Some((file.to_owned(), offset, size))
+        });
+        let (file, offset, size) = match location {
+            Some((file, offset, size)) => (Some(file), offset, size),
+            None => (None, None, None),
+        };
+        Self {
+            file,
+            offset,
+            size,
+            error,
+        }
+    }
+}
+
+/// What a manual compaction did, as returned by
+/// [`DBCommon::compact_range_cf_opt_with_result`].
+#[derive(Debug, Clone)]
+pub struct CompactionResult {
+    /// The table files the compaction replaced
+    pub input_files: Vec<LiveFile>,
+    /// The table files the compaction wrote
+    pub output_files: Vec<LiveFile>,
+    /// The table files the compaction moved to another level without
+    /// rewriting them, with their new level
+    pub moved_files: Vec<LiveFile>,
+    /// Size of the input files in bytes
+    pub bytes_read: u64,
+    /// Size of the output files in bytes
+    pub bytes_written: u64,
+    /// Time the compaction took
+    pub elapsed: Duration,
+}
+
 /// The metadata that describes a SST file
 #[derive(Debug, Clone)]
 pub struct LiveFile {
@@ -2287,6 +4164,191 @@ pub struct LiveFile {
     pub num_entries: u64,
     /// Number of deletions/tomb key(s) in the file
     pub num_deletions: u64,
+    /// Full checksum of the file, if the DB was opened with
+    /// [`Options::enable_file_checksums`]
+    pub file_checksum: Option<FileChecksum>,
+}
+
+/// The files of a DB as returned by [`DBCommon::get_live_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveFileList {
+    /// Names of the SST files, the CURRENT file, the MANIFEST and the latest
+    /// OPTIONS file, relative to the DB directory and starting with `/`
+    pub files: Vec<String>,
+    /// Size of the valid part of the MANIFEST, the file may grow after it
+    /// was listed and only this many bytes of it should be copied
+    pub manifest_file_size: u64,
+}
+
+/// The space used by the files of a DB, as returned by
+/// [`DBCommon::get_disk_usage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Size of the SST files making up the current state of the DB
+    pub live_sst_bytes: u64,
+    /// Size of the SST files which are not used anymore and not deleted
+    /// yet, e.g. while file deletions are disabled or a snapshot of the
+    /// files is held
+    pub obsolete_sst_bytes: u64,
+    /// Size of the deleted files RocksDB renamed to `.trash` files to remove
+    /// them later, when file deletions are rate limited
+    pub trash_bytes: u64,
+    /// Size of the WAL files, alive and archived
+    pub wal_bytes: u64,
+    /// Size of the blob files
+    pub blob_bytes: u64,
+    /// Size of the other files, e.g. the MANIFEST, OPTIONS and LOG files
+    pub other_bytes: u64,
+}
+
+impl DiskUsage {
+    /// Returns the size of all the files.
+    pub fn total_bytes(&self) -> u64 {
+        self.live_sst_bytes
+            + self.obsolete_sst_bytes
+            + self.trash_bytes
+            + self.wal_bytes
+            + self.blob_bytes
+            + self.other_bytes
+    }
+}
+
+/// The tombstones of a range of keys, as estimated by
+/// [`DBCommon::estimate_deletions_in_range`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeletionEstimate {
+    /// Number of SST files overlapping the range
+    pub file_count: usize,
+    /// Number of entries in these files, tombstones included
+    pub num_entries: u64,
+    /// Number of tombstones in these files
+    pub num_deletions: u64,
+}
+
+impl DeletionEstimate {
+    /// Returns the share of the entries which are tombstones, 0 if there is
+    /// no entry.
+    pub fn deletion_ratio(&self) -> f64 {
+        if self.num_entries == 0 {
+            0.0
+        } else {
+            self.num_deletions as f64 / self.num_entries as f64
+        }
+    }
+}
+
+/// A snapshot of the background work of a column family, as returned by
+/// [`DBCommon::get_background_job_info`].
+///
+/// The running flush and compaction counters are DB-wide, the remaining
+/// fields describe the column family the snapshot was taken for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackgroundJobInfo {
+    /// Number of currently running flushes
+    pub num_running_flushes: u64,
+    /// Number of currently running compactions
+    pub num_running_compactions: u64,
+    /// Whether a memtable flush is pending
+    pub flush_pending: bool,
+    /// Whether at least one compaction is pending
+    pub compaction_pending: bool,
+    /// Number of immutable memtables that have not yet been flushed
+    pub num_immutable_mem_tables: u64,
+    /// Estimated number of bytes compaction needs to rewrite to get all
+    /// levels down to under target size
+    pub estimate_pending_compaction_bytes: u64,
+    /// Accumulated number of background errors
+    pub background_errors: u64,
+}
+
+/// The mutable options changed by [`DBCommon::prepare_for_bulk_load`].
+///
+/// Holds the values to restore with [`DBCommon::finish_bulk_load`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkLoadSettings {
+    disable_auto_compactions: bool,
+    write_buffer_size: usize,
+    max_write_buffer_number: c_int,
+    level0_file_num_compaction_trigger: c_int,
+    level0_slowdown_writes_trigger: c_int,
+    level0_stop_writes_trigger: c_int,
+    soft_pending_compaction_bytes_limit: usize,
+    hard_pending_compaction_bytes_limit: usize,
+}
+
+impl BulkLoadSettings {
+    fn from_options_file(file: &OptionsFile, cf: &str) -> Result<Self, Error> {
+        Ok(Self {
+            disable_auto_compactions: file.cf_option(cf, "disable_auto_compactions")?,
+            write_buffer_size: file.cf_option(cf, "write_buffer_size")?,
+            max_write_buffer_number: file.cf_option(cf, "max_write_buffer_number")?,
+            level0_file_num_compaction_trigger: file
+                .cf_option(cf, "level0_file_num_compaction_trigger")?,
+            level0_slowdown_writes_trigger: file.cf_option(cf, "level0_slowdown_writes_trigger")?,
+            level0_stop_writes_trigger: file.cf_option(cf, "level0_stop_writes_trigger")?,
+            soft_pending_compaction_bytes_limit: file
+                .cf_option(cf, "soft_pending_compaction_bytes_limit")?,
+            hard_pending_compaction_bytes_limit: file
+                .cf_option(cf, "hard_pending_compaction_bytes_limit")?,
+        })
+    }
+
+    /// The values `Options::PrepareForBulkLoad` uses for the mutable options.
+    fn bulk_load() -> Self {
+        Self {
+            disable_auto_compactions: true,
+            write_buffer_size: 256 * 1024 * 1024,
+            max_write_buffer_number: 6,
+            level0_file_num_compaction_trigger: 1 << 30,
+            level0_slowdown_writes_trigger: 1 << 30,
+            level0_stop_writes_trigger: 1 << 30,
+            // 0 disables the pending compaction bytes limits
+            soft_pending_compaction_bytes_limit: 0,
+            hard_pending_compaction_bytes_limit: 0,
+        }
+    }
+
+    fn apply(
+        &self,
+        set_options: impl FnOnce(&[(&str, &str)]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let values = [
+            (
+                "disable_auto_compactions",
+                self.disable_auto_compactions.to_string(),
+            ),
+            ("write_buffer_size", self.write_buffer_size.to_string()),
+            (
+                "max_write_buffer_number",
+                self.max_write_buffer_number.to_string(),
+            ),
+            (
+                "level0_file_num_compaction_trigger",
+                self.level0_file_num_compaction_trigger.to_string(),
+            ),
+            (
+                "level0_slowdown_writes_trigger",
+                self.level0_slowdown_writes_trigger.to_string(),
+            ),
+            (
+                "level0_stop_writes_trigger",
+                self.level0_stop_writes_trigger.to_string(),
+            ),
+            (
+                "soft_pending_compaction_bytes_limit",
+                self.soft_pending_compaction_bytes_limit.to_string(),
+            ),
+            (
+                "hard_pending_compaction_bytes_limit",
+                self.hard_pending_compaction_bytes_limit.to_string(),
+            ),
+        ];
+        let opts: Vec<(&str, &str)> = values
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        set_options(&opts)
+    }
 }
 
 fn convert_options(opts: &[(&str, &str)]) -> Result<Vec<(CString, CString)>, Error> {
@@ -2305,6 +4367,17 @@ fn convert_options(opts: &[(&str, &str)]) -> Result<Vec<(CString, CString)>, Err
         .collect()
 }
 
+/// Decodes a counter written with the `uint64add` merge operator.
+fn decode_u64_counter(value: &[u8]) -> Result<u64, Error> {
+    let bytes = value.try_into().map_err(|_| {
+        Error::new(format!(
+            "Corruption: a u64 counter is 8 bytes long, got {} bytes",
+            value.len()
+        ))
+    })?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
 pub(crate) fn convert_values(
     values: Vec<*mut c_char>,
     values_sizes: Vec<usize>,
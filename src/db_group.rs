@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{
+    perf::{get_memory_usage_stats, MemoryUsageStats},
+    Cache, Error, Options, DB,
+};
+
+/// A set of DBs opened with the same options, for processes managing many
+/// DBs side by side.
+///
+/// Every DB of the group is opened with a copy of the options the group was
+/// created with, so the env, block cache, row cache, write buffer manager
+/// and rate limiter set on them are shared by all DBs.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{Cache, DbGroup, Options, WriteBufferManager};
+///
+/// let cache = Cache::new_lru_cache(64 * 1024 * 1024);
+/// let manager = WriteBufferManager::new(64 * 1024 * 1024, false);
+///
+/// let mut opts = Options::default();
+/// opts.create_if_missing(true);
+/// opts.set_row_cache(&cache);
+/// opts.set_write_buffer_manager(&manager);
+/// opts.set_ratelimiter(16 * 1024 * 1024, 100 * 1000, 10);
+///
+/// let path = "_path_for_rocksdb_storage_db_group";
+/// {
+///     let mut group = DbGroup::new(opts);
+///     let tenant1 = group.open("tenant1", format!("{path}/tenant1")).unwrap();
+///     let tenant2 = group.open("tenant2", format!("{path}/tenant2")).unwrap();
+///     tenant1.put(b"key", b"value1").unwrap();
+///     tenant2.put(b"key", b"value2").unwrap();
+///
+///     group.flush_all().unwrap();
+///     let stats = group.memory_usage_stats(&[&cache]).unwrap();
+///     println!("memtables: {}", stats.mem_table_total);
+/// }
+/// let _ = std::fs::remove_dir_all(path);
+/// ```
+pub struct DbGroup {
+    opts: Options,
+    dbs: BTreeMap<String, Arc<DB>>,
+}
+
+impl DbGroup {
+    /// Creates an empty group whose DBs are opened with `opts`.
+    pub fn new(opts: Options) -> Self {
+        Self {
+            opts,
+            dbs: BTreeMap::new(),
+        }
+    }
+
+    /// Opens the DB at `path` and adds it to the group under `name`.
+    pub fn open<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<Arc<DB>, Error> {
+        if self.dbs.contains_key(name) {
+            return Err(Error::new(format!(
+                "A DB named {name} is already open in the group"
+            )));
+        }
+        let db = Arc::new(DB::open(&self.opts, path)?);
+        self.dbs.insert(name.to_owned(), Arc::clone(&db));
+        Ok(db)
+    }
+
+    /// Returns the DB added under `name`.
+    pub fn get(&self, name: &str) -> Option<Arc<DB>> {
+        self.dbs.get(name).map(Arc::clone)
+    }
+
+    /// Returns the names of the DBs in the group.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.dbs.keys().map(String::as_str)
+    }
+
+    /// Returns the number of DBs in the group.
+    pub fn len(&self) -> usize {
+        self.dbs.len()
+    }
+
+    /// Returns `true` if the group has no DBs.
+    pub fn is_empty(&self) -> bool {
+        self.dbs.is_empty()
+    }
+
+    /// Removes the DB added under `name` from the group. The DB is closed
+    /// once the last handle to it is dropped.
+    pub fn remove(&mut self, name: &str) -> Option<Arc<DB>> {
+        self.dbs.remove(name)
+    }
+
+    /// Flushes the memtables of all DBs. Every DB is flushed even if flushing
+    /// a previous one failed, the first error is returned.
+    pub fn flush_all(&self) -> Result<(), Error> {
+        self.try_for_each(DB::flush)
+    }
+
+    /// Flushes the WALs of all DBs, see [`DBCommon::flush_wal`].
+    ///
+    /// [`DBCommon::flush_wal`]: crate::DBCommon::flush_wal
+    pub fn flush_wal_all(&self, sync: bool) -> Result<(), Error> {
+        self.try_for_each(|db| db.flush_wal(sync))
+    }
+
+    /// Requests all DBs to stop their background work, see
+    /// [`DBCommon::cancel_all_background_work`].
+    ///
+    /// [`DBCommon::cancel_all_background_work`]: crate::DBCommon::cancel_all_background_work
+    pub fn cancel_background_work_all(&self, wait: bool) {
+        for db in self.dbs.values() {
+            db.cancel_all_background_work(wait);
+        }
+    }
+
+    /// Returns the memory usage of all DBs combined with the given caches.
+    pub fn memory_usage_stats(&self, caches: &[&Cache]) -> Result<MemoryUsageStats, Error> {
+        let dbs: Vec<&DB> = self.dbs.values().map(AsRef::as_ref).collect();
+        get_memory_usage_stats(Some(&dbs), Some(caches))
+    }
+
+    /// Stops the background work of the DBs which are not used outside of
+    /// the group and closes them.
+    ///
+    /// The DBs whose handles are still held elsewhere, e.g. from
+    /// [`get`](Self::get), are left untouched in the group and their names
+    /// are returned.
+    pub fn close_all(&mut self) -> Vec<String> {
+        let (unused, in_use): (BTreeMap<_, _>, BTreeMap<_, _>) = std::mem::take(&mut self.dbs)
+            .into_iter()
+            .partition(|(_, db)| Arc::strong_count(db) == 1);
+        for db in unused.values() {
+            db.cancel_all_background_work(true);
+        }
+        self.dbs = in_use;
+        self.dbs.keys().cloned().collect()
+    }
+
+    fn try_for_each(&self, f: impl Fn(&DB) -> Result<(), Error>) -> Result<(), Error> {
+        let mut result = Ok(());
+        for db in self.dbs.values() {
+            if let Err(err) = f(db) {
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+        result
+    }
+}
@@ -14,10 +14,12 @@
 
 use crate::{
     db::{DBAccess, DB},
-    ffi, Error, ReadOptions, WriteBatch,
+    ffi,
+    open_handles::{HandleGuard, HandleKind},
+    AsColumnFamilyRef, Error, ReadOptions, WriteBatch,
 };
 use libc::{c_char, c_uchar, size_t};
-use std::{marker::PhantomData, slice};
+use std::{collections::BTreeSet, marker::PhantomData, slice};
 
 /// A type alias to keep compatibility. See [`DBRawIteratorWithThreadMode`] for details
 pub type DBRawIterator<'a> = DBRawIteratorWithThreadMode<'a, DB>;
@@ -83,13 +85,69 @@ pub struct DBRawIteratorWithThreadMode<'a, D: DBAccess> {
     /// point to vectors we own.  See issue #660.
     _readopts: ReadOptions,
 
+    /// Counts the iterator as alive in the DB it was created from
+    _handle: Option<HandleGuard>,
+
     db: PhantomData<&'a D>,
 }
 
+/// Where a [`DBRawIteratorWithThreadMode`] stands, as returned by
+/// [`state`](DBRawIteratorWithThreadMode::state).
+#[derive(Debug)]
+pub enum IteratorState {
+    /// The iterator is positioned on an entry.
+    Valid,
+    /// The iterator went past the last entry of its range in the direction
+    /// it was moving, either the end of the column family or one of the
+    /// bounds set with [`ReadOptions::set_iterate_upper_bound`] and
+    /// [`ReadOptions::set_iterate_lower_bound`], or was never positioned.
+    Exhausted,
+    /// The iterator failed. The [kind](Error::kind) of the error tells e.g.
+    /// an I/O error ([`ErrorKind::IOError`]) from a dropped column family
+    /// ([`ErrorKind::ColumnFamilyDropped`]).
+    ///
+    /// [`ErrorKind::IOError`]: crate::ErrorKind::IOError
+    /// [`ErrorKind::ColumnFamilyDropped`]: crate::ErrorKind::ColumnFamilyDropped
+    Failed(Error),
+}
+
+impl IteratorState {
+    /// Returns `true` if the iterator is positioned on an entry.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+
+    /// Turns the state into a `Result`, with `Ok(true)` if the iterator is
+    /// positioned on an entry and `Ok(false)` if it is exhausted. This fits
+    /// scan loops which must stop on errors:
+    ///
+    /// ```
+    /// use rocksdb::{Error, DB};
+    ///
+    /// fn count_keys(db: &DB) -> Result<usize, Error> {
+    ///     let mut iter = db.raw_iterator();
+    ///     let mut count = 0;
+    ///     iter.seek_to_first();
+    ///     while iter.state().into_result()? {
+    ///         count += 1;
+    ///         iter.next();
+    ///     }
+    ///     Ok(count)
+    /// }
+    /// ```
+    pub fn into_result(self) -> Result<bool, Error> {
+        match self {
+            Self::Valid => Ok(true),
+            Self::Exhausted => Ok(false),
+            Self::Failed(err) => Err(err),
+        }
+    }
+}
+
 impl<'a, D: DBAccess> DBRawIteratorWithThreadMode<'a, D> {
     pub(crate) fn new(db: &D, readopts: ReadOptions) -> Self {
         let inner = unsafe { db.create_iterator(&readopts) };
-        Self::from_inner(inner, readopts)
+        Self::from_inner(db, inner, readopts)
     }
 
     pub(crate) fn new_cf(
@@ -98,10 +156,10 @@ impl<'a, D: DBAccess> DBRawIteratorWithThreadMode<'a, D> {
         readopts: ReadOptions,
     ) -> Self {
         let inner = unsafe { db.create_iterator_cf(cf_handle, &readopts) };
-        Self::from_inner(inner, readopts)
+        Self::from_inner(db, inner, readopts)
     }
 
-    fn from_inner(inner: *mut ffi::rocksdb_iterator_t, readopts: ReadOptions) -> Self {
+    fn from_inner(db: &D, inner: *mut ffi::rocksdb_iterator_t, readopts: ReadOptions) -> Self {
         // This unwrap will never fail since rocksdb_create_iterator and
         // rocksdb_create_iterator_cf functions always return non-null. They
         // use new and deference the result so any nulls would end up with SIGSEGV
@@ -110,6 +168,9 @@ impl<'a, D: DBAccess> DBRawIteratorWithThreadMode<'a, D> {
         Self {
             inner,
             _readopts: readopts,
+            _handle: db
+                .open_handles()
+                .map(|handles| handles.track(HandleKind::Iterator)),
             db: PhantomData,
         }
     }
@@ -136,6 +197,19 @@ impl<'a, D: DBAccess> DBRawIteratorWithThreadMode<'a, D> {
         Ok(())
     }
 
+    /// Returns whether the iterator is positioned on an entry, exhausted or
+    /// failed, which [`valid`](DBRawIteratorWithThreadMode::valid) alone does
+    /// not tell apart.
+    pub fn state(&self) -> IteratorState {
+        if self.valid() {
+            return IteratorState::Valid;
+        }
+        match self.status() {
+            Ok(()) => IteratorState::Exhausted,
+            Err(err) => IteratorState::Failed(err),
+        }
+    }
+
     /// Seeks to the first key in the database.
     ///
     /// # Examples
@@ -412,7 +486,8 @@ pub struct DBIteratorWithThreadMode<'a, D: DBAccess> {
     done: bool,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Forward,
     Reverse,
@@ -454,6 +529,21 @@ impl<'a, D: DBAccess> DBIteratorWithThreadMode<'a, D> {
         rv
     }
 
+    /// Turns the iterator into a [`Cursor`], which tells where to resume the
+    /// iteration with a [`ResumeToken`].
+    pub fn into_cursor(self) -> Cursor<'a, D> {
+        let position = if self.done {
+            None
+        } else {
+            self.raw.key().map(|key| (key.to_vec(), false))
+        };
+        Cursor {
+            iter: self,
+            position,
+            errored: false,
+        }
+    }
+
     pub fn set_mode(&mut self, mode: IteratorMode) {
         self.done = false;
         self.direction = match mode {
@@ -505,6 +595,175 @@ impl<'a, D: DBAccess> Into<DBRawIteratorWithThreadMode<'a, D>> for DBIteratorWit
     }
 }
 
+/// An iterator remembering where it stands, to resume the iteration later
+/// with another iterator, e.g. for the next page of a paginated API,
+/// without keeping the iterator alive in between.
+///
+/// Created by [`DBIteratorWithThreadMode::into_cursor`].
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{Cursor, IteratorMode, DB};
+///
+/// let path = "_path_for_rocksdb_storage_cursor";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     for key in [b"a", b"b", b"c"] {
+///         db.put(key, b"").unwrap();
+///     }
+///
+///     let mut page = db.iterator(IteratorMode::Start).into_cursor();
+///     assert_eq!(&*page.next().unwrap().unwrap().0, b"a");
+///     let token = page.resume_token();
+///     drop(page);
+///
+///     let page = Cursor::resume(&db, &token);
+///     let keys: Vec<_> = page.map(|item| item.unwrap().0).collect();
+///     assert_eq!(keys, [&b"b"[..], b"c"].map(Box::from));
+/// }
+/// let _ = DB::destroy(&rocksdb::Options::default(), path);
+/// ```
+pub struct Cursor<'a, D: DBAccess> {
+    iter: DBIteratorWithThreadMode<'a, D>,
+    /// The key to resume from and whether it was already returned, `None`
+    /// once the iteration is over.
+    position: Option<(Vec<u8>, bool)>,
+    /// Whether the iteration stopped on an error, keeping the position of
+    /// the last entry returned to resume from.
+    errored: bool,
+}
+
+impl<'a, D: DBAccess> Cursor<'a, D> {
+    /// Resumes the iteration of the default column family of `db` where
+    /// `token` tells.
+    pub fn resume(db: &'a D, token: &ResumeToken) -> Self {
+        Self::resume_impl(token, |readopts, mode| {
+            DBIteratorWithThreadMode::new(db, readopts, mode)
+        })
+    }
+
+    /// Resumes the iteration of the column family `cf` of `db` where `token`
+    /// tells.
+    pub fn resume_cf(db: &'a D, cf: &impl AsColumnFamilyRef, token: &ResumeToken) -> Self {
+        Self::resume_impl(token, |readopts, mode| {
+            DBIteratorWithThreadMode::new_cf(db, cf.inner(), readopts, mode)
+        })
+    }
+
+    fn resume_impl(
+        token: &ResumeToken,
+        iterator: impl FnOnce(ReadOptions, IteratorMode) -> DBIteratorWithThreadMode<'a, D>,
+    ) -> Self {
+        let mut readopts = ReadOptions::default();
+        if let Some(ref lower) = token.lower_bound {
+            readopts.set_iterate_lower_bound(lower.as_slice());
+        }
+        if let Some(ref upper) = token.upper_bound {
+            readopts.set_iterate_upper_bound(upper.as_slice());
+        }
+        let (key, returned) = match token.position {
+            Some((ref key, returned)) => (key.as_slice(), returned),
+            None => {
+                let mut iter = iterator(readopts, IteratorMode::Start);
+                iter.done = true;
+                return iter.into_cursor();
+            }
+        };
+        let mut iter = iterator(readopts, IteratorMode::From(key, token.direction));
+        if returned && iter.raw.key() == Some(key) {
+            match token.direction {
+                Direction::Forward => iter.raw.next(),
+                Direction::Reverse => iter.raw.prev(),
+            }
+        }
+        iter.into_cursor()
+    }
+
+    /// Returns a token to resume the iteration after the last entry
+    /// returned, with [`resume`](Self::resume).
+    ///
+    /// The token records the bounds set on the read options of the iterator,
+    /// except the ones set with
+    /// [`ReadOptions::scoped_bounds`](crate::ReadOptions::scoped_bounds).
+    /// Other read options are not recorded.
+    pub fn resume_token(&self) -> ResumeToken {
+        let (lower, upper) = self.iter.raw._readopts.iterate_bounds();
+        ResumeToken {
+            position: self.position.clone(),
+            direction: self.iter.direction,
+            lower_bound: lower.map(<[u8]>::to_vec),
+            upper_bound: upper.map(<[u8]>::to_vec),
+        }
+    }
+}
+
+impl<'a, D: DBAccess> Iterator for Cursor<'a, D> {
+    type Item = Result<KVBytes, Error>;
+
+    fn next(&mut self) -> Option<Result<KVBytes, Error>> {
+        if self.errored {
+            return None;
+        }
+        let item = self.iter.next();
+        match item {
+            Some(Ok((ref key, _))) => self.position = Some((key.to_vec(), true)),
+            // the iteration can be resumed after an error
+            Some(Err(_)) => self.errored = true,
+            None => self.position = None,
+        }
+        item
+    }
+}
+
+impl<'a, D: DBAccess> std::iter::FusedIterator for Cursor<'a, D> {}
+
+/// Where to resume the iteration of a [`Cursor`], see
+/// [`Cursor::resume_token`].
+///
+/// The token does not borrow the DB, it can be kept between requests or,
+/// with the `serde1` feature, serialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResumeToken {
+    position: Option<(Vec<u8>, bool)>,
+    direction: Direction,
+    lower_bound: Option<Vec<u8>>,
+    upper_bound: Option<Vec<u8>>,
+}
+
+impl ResumeToken {
+    /// Returns the last key returned by the cursor, `None` if it returned
+    /// none or its iteration is over.
+    pub fn last_key(&self) -> Option<&[u8]> {
+        match self.position {
+            Some((ref key, true)) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the iteration is over, a cursor resumed from the
+    /// token returning no entry.
+    pub fn is_finished(&self) -> bool {
+        self.position.is_none()
+    }
+
+    /// Returns the direction of the iteration.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns the lower bound of the iteration, if any.
+    pub fn lower_bound(&self) -> Option<&[u8]> {
+        self.lower_bound.as_deref()
+    }
+
+    /// Returns the upper bound of the iteration, if any.
+    pub fn upper_bound(&self) -> Option<&[u8]> {
+        self.upper_bound.as_deref()
+    }
+}
+
 /// Iterates the batches of writes since a given sequence number.
 ///
 /// `DBWALIterator` is returned by `DB::get_updates_since()` and will return the
@@ -515,12 +774,23 @@ impl<'a, D: DBAccess> Into<DBRawIteratorWithThreadMode<'a, D>> for DBIteratorWit
 /// The iterator item type is a tuple of (`u64`, `WriteBatch`) where the first
 /// value is the sequence number of the associated write batch.
 ///
-pub struct DBWALIterator {
+/// The iterator returned by `DB::get_updates_since_cf()` skips the batches
+/// which do not write to the given column families.
+///
+/// To read the WAL from a position in its files, or without allocating a
+/// write batch for every record, see [`DBCommon::read_wal`](crate::DBCommon::read_wal).
+pub struct DBWALIterator<'a> {
     pub(crate) inner: *mut ffi::rocksdb_wal_iterator_t,
     pub(crate) start_seq_number: u64,
+    /// Ids of the column families whose batches are returned, `None` for all
+    pub(crate) column_families: Option<BTreeSet<u32>>,
+    /// Counts the iterator as alive in the DB it was created from
+    pub(crate) _handle: Option<HandleGuard>,
+    /// Borrows the DB, which must outlive the iterator
+    pub(crate) _db: PhantomData<&'a ()>,
 }
 
-impl DBWALIterator {
+impl DBWALIterator<'_> {
     /// Returns `true` if the iterator is valid. An iterator is invalidated when
     /// it reaches the end of its defined range, or when it encounters an error.
     ///
@@ -541,12 +811,10 @@ impl DBWALIterator {
         }
         Ok(())
     }
-}
 
-impl Iterator for DBWALIterator {
-    type Item = Result<(u64, WriteBatch), Error>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Returns the next batch after the start sequence number, whatever the
+    /// column families it writes to.
+    fn next_batch(&mut self) -> Option<Result<(u64, WriteBatch), Error>> {
         if !self.valid() {
             return None;
         }
@@ -587,7 +855,29 @@ impl Iterator for DBWALIterator {
     }
 }
 
-impl Drop for DBWALIterator {
+impl Iterator for DBWALIterator<'_> {
+    type Item = Result<(u64, WriteBatch), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (seq, batch) = match self.next_batch()? {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e)),
+            };
+            // skips the batches not writing to the requested column families
+            if let Some(ref column_families) = self.column_families {
+                match batch.column_family_ids() {
+                    Ok(ids) if !ids.iter().any(|id| column_families.contains(id)) => continue,
+                    Ok(_) => {}
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            return Some(Ok((seq, batch)));
+        }
+    }
+}
+
+impl Drop for DBWALIterator<'_> {
     fn drop(&mut self) {
         unsafe {
             ffi::rocksdb_wal_iter_destroy(self.inner);
@@ -0,0 +1,115 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{DBWithThreadMode, Error, ThreadMode};
+
+impl<T: ThreadMode> DBWithThreadMode<T> {
+    /// Returns `true` if the DB at `path` is opened in read-write mode, by
+    /// this process or another one.
+    ///
+    /// RocksDB locks the `LOCK` file of a DB opened in read-write mode, and
+    /// has no option to skip the lock: a DB opened by another process can be
+    /// read concurrently by opening it with
+    /// [`open_for_read_only`](Self::open_for_read_only) or
+    /// [`open_as_secondary`](Self::open_as_secondary), which do not lock it.
+    ///
+    /// The locks are read from `/proc/locks`, so this is only supported on
+    /// Linux, and returns a `Not implemented` error elsewhere.
+    pub fn is_locked<P: AsRef<Path>>(path: P) -> Result<bool, Error> {
+        if cfg!(target_os = "linux") {
+            Ok(lock_holder(&path.as_ref().join("LOCK")).is_some())
+        } else {
+            Err(Error::new(
+                "Not implemented: locks can only be probed on Linux".to_owned(),
+            ))
+        }
+    }
+
+    /// Deletes the `LOCK` file of the DB at `path`, so that it can be
+    /// opened while the process holding its lock is still running, e.g.
+    /// when it hangs or is on another host sharing the file system.
+    ///
+    /// The lock of a process which exited is released with it: a `LOCK`
+    /// file left over by a crash does not prevent opening the DB, and does
+    /// not need to be deleted.
+    ///
+    /// Returns a `Resource busy` error if the DB is opened by this process,
+    /// as RocksDB also keeps track of the DBs a process opened.
+    ///
+    /// # Warning
+    ///
+    /// The process holding the lock keeps it on the deleted file, and keeps
+    /// writing the DB if it is still running. Make sure it does not before
+    /// opening the DB, otherwise the DB is corrupted by two processes
+    /// writing it concurrently.
+    pub fn force_unlock_risking_corruption<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+        let lock_file = path.as_ref().join("LOCK");
+        if lock_holder(&lock_file) == Some(Some(std::process::id())) {
+            return Err(Error::new(format!(
+                "Resource busy: the DB at {} is opened by this process, close it instead",
+                path.as_ref().display()
+            )));
+        }
+        match fs::remove_file(&lock_file) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(Error::new(format!(
+                "IO error: failed to delete {}: {e}",
+                lock_file.display()
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Adds the process holding the lock of the DB at `path` to an error
+/// returned when opening it, if it failed to lock it.
+pub(crate) fn describe_lock_error(error: Error, path: &Path) -> Error {
+    // e.g. `IO error: While lock file: <path>/LOCK: Resource temporarily
+    // unavailable`
+    if !error.as_ref().contains("LOCK: ") {
+        return error;
+    }
+    let holder = match lock_holder(&path.join("LOCK")) {
+        Some(Some(pid)) if pid == std::process::id() => "this process".to_owned(),
+        Some(Some(pid)) => format!("process {pid}"),
+        _ => return error,
+    };
+    error.with_detail(&format!(
+        ", the DB is opened by {holder}: close it, or open the DB read-only or as a \
+         secondary instance to read it concurrently"
+    ))
+}
+
+/// Returns `Some` if the lock file of a DB is locked, with the id of the
+/// process holding it if it is known.
+///
+/// The lock is looked up in `/proc/locks` rather than tested with `fcntl`:
+/// closing any descriptor of the file would release the lock RocksDB holds
+/// if the DB is opened by this process.
+#[cfg(target_os = "linux")]
+pub(crate) fn lock_holder(lock_file: &Path) -> Option<Option<u32>> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(lock_file).ok()?;
+    let dev = metadata.dev();
+    let (major, minor) = (
+        ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff),
+        (dev & 0xff) | ((dev >> 12) & !0xff),
+    );
+    let file_id = format!("{major:02x}:{minor:02x}:{}", metadata.ino());
+
+    // lines look like `1: POSIX  ADVISORY  WRITE 1234 08:01:5678 0 EOF`
+    let locks = fs::read_to_string("/proc/locks").ok()?;
+    locks.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, _, _, _, pid, id, ..] if *id == file_id => Some(pid.parse().ok()),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn lock_holder(_lock_file: &Path) -> Option<Option<u32>> {
+    None
+}
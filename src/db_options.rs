@@ -12,25 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::marker::PhantomData;
 use std::path::Path;
 use std::ptr::{null_mut, NonNull};
 use std::slice;
 use std::sync::Arc;
+use std::time::Duration;
 
 use libc::{self, c_char, c_double, c_int, c_uchar, c_uint, c_void, size_t};
 
 use crate::{
     compaction_filter::{self, CompactionFilterCallback, CompactionFilterFn},
-    compaction_filter_factory::{self, CompactionFilterFactory},
+    compaction_filter_factory::{
+        self, CompactionFilterContext, CompactionFilterFactory, CompactionFilterFactoryCallback,
+    },
     comparator::{self, ComparatorCallback, CompareFn},
     db::DBAccess,
     env::Env,
     ffi,
-    ffi_util::{from_cstr, to_cpath, CStrLike},
+    ffi_util::{error_message, from_cstr, to_cpath, CStrLike},
     merge_operator::{
         self, full_merge_callback, partial_merge_callback, MergeFn, MergeOperatorCallback,
     },
+    options_file,
     slice_transform::SliceTransform,
     ColumnFamilyDescriptor, Error, SnapshotWithThreadMode,
 };
@@ -57,6 +63,19 @@ impl Cache {
         Cache(Arc::new(CacheWrapper { inner }))
     }
 
+    /// Creates an LRU cache with capacity in bytes, which fails to insert
+    /// entries once it is full instead of exceeding its capacity.
+    ///
+    /// Reads needing a block which cannot be inserted fail with an
+    /// `Incomplete` error. The limit is fixed when the cache is created.
+    pub fn new_lru_cache_with_strict_capacity_limit(capacity: size_t) -> Cache {
+        let inner = NonNull::new(unsafe {
+            ffi::rocksdb_cache_create_lru_with_strict_capacity_limit(capacity)
+        })
+        .unwrap();
+        Cache(Arc::new(CacheWrapper { inner }))
+    }
+
     /// Creates a HyperClockCache with capacity in bytes.
     ///
     /// `estimated_entry_charge` is an important tuning parameter. The optimal
@@ -96,18 +115,143 @@ impl Cache {
         unsafe { ffi::rocksdb_cache_get_pinned_usage(self.0.inner.as_ptr()) }
     }
 
+    /// Returns the cache capacity in bytes.
+    pub fn get_capacity(&self) -> usize {
+        unsafe { ffi::rocksdb_cache_get_capacity(self.0.inner.as_ptr()) }
+    }
+
     /// Sets cache capacity in bytes.
     pub fn set_capacity(&mut self, capacity: size_t) {
         unsafe {
             ffi::rocksdb_cache_set_capacity(self.0.inner.as_ptr(), capacity);
         }
     }
+
+    /// Returns `true` if both handles refer to the same cache.
+    pub fn ptr_eq(this: &Cache, other: &Cache) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+pub(crate) struct WriteBufferManagerWrapper {
+    pub(crate) inner: NonNull<ffi::rocksdb_write_buffer_manager_t>,
+}
+
+impl Drop for WriteBufferManagerWrapper {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_write_buffer_manager_destroy(self.inner.as_ptr());
+        }
+    }
+}
+
+/// Limits the total memory used by the memtables of all column families and
+/// DBs it is shared with.
+#[derive(Clone)]
+pub struct WriteBufferManager(pub(crate) Arc<WriteBufferManagerWrapper>);
+
+impl WriteBufferManager {
+    /// Creates a write buffer manager limiting the memtables to `buffer_size`
+    /// bytes. Memtables are flushed once the limit is reached, and if
+    /// `allow_stall` is set, writes are stalled until the memory usage drops
+    /// below the limit again.
+    pub fn new(buffer_size: size_t, allow_stall: bool) -> WriteBufferManager {
+        let inner = NonNull::new(unsafe {
+            ffi::rocksdb_write_buffer_manager_create(buffer_size, allow_stall)
+        })
+        .unwrap();
+        WriteBufferManager(Arc::new(WriteBufferManagerWrapper { inner }))
+    }
+
+    /// Returns the memory used by the memtables in bytes.
+    pub fn get_usage(&self) -> usize {
+        unsafe { ffi::rocksdb_write_buffer_manager_memory_usage(self.0.inner.as_ptr()) }
+    }
+
+    /// Returns the memory limit in bytes.
+    pub fn get_buffer_size(&self) -> usize {
+        unsafe { ffi::rocksdb_write_buffer_manager_buffer_size(self.0.inner.as_ptr()) }
+    }
+}
+
+pub(crate) struct RateLimiterWrapper {
+    pub(crate) inner: NonNull<ffi::rocksdb_ratelimiter_t>,
+}
+
+impl Drop for RateLimiterWrapper {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_ratelimiter_destroy(self.inner.as_ptr());
+        }
+    }
+}
+
+/// Limits the rate at which flushes and compactions write to the device,
+/// shared by the DBs it is set on with [`Options::set_rate_limiter`].
+///
+/// Flushes have a higher priority than compactions. A limiter is refilled
+/// with `rate_bytes_per_sec * refill_period_us / 1_000_000` bytes every
+/// `refill_period_us` microseconds, and `fairness` sets how often the low
+/// priority requests are served first, once every `fairness` refills, to
+/// avoid starving them.
+#[derive(Clone)]
+pub struct RateLimiter(pub(crate) Arc<RateLimiterWrapper>);
+
+impl RateLimiter {
+    /// Creates a rate limiter allowing `rate_bytes_per_sec` bytes per second.
+    pub fn new(rate_bytes_per_sec: i64, refill_period_us: i64, fairness: i32) -> RateLimiter {
+        let inner = NonNull::new(unsafe {
+            ffi::rocksdb_ratelimiter_create(rate_bytes_per_sec, refill_period_us, fairness)
+        })
+        .unwrap();
+        RateLimiter(Arc::new(RateLimiterWrapper { inner }))
+    }
+
+    /// Creates a rate limiter adapting its rate to the demand, between
+    /// `rate_bytes_per_sec / 20` and `rate_bytes_per_sec` bytes per second.
+    ///
+    /// The rate is raised when the limiter is often drained and lowered when
+    /// it is rarely drained, so `rate_bytes_per_sec` can be set to the
+    /// capability of the device without throttling the background writes
+    /// below what the workload needs most of the time.
+    pub fn auto_tuned(
+        rate_bytes_per_sec: i64,
+        refill_period_us: i64,
+        fairness: i32,
+    ) -> RateLimiter {
+        let inner = NonNull::new(unsafe {
+            ffi::rocksdb_ratelimiter_create_auto_tuned(
+                rate_bytes_per_sec,
+                refill_period_us,
+                fairness,
+            )
+        })
+        .unwrap();
+        RateLimiter(Arc::new(RateLimiterWrapper { inner }))
+    }
+
+    /// Returns `true` if both handles refer to the same rate limiter.
+    pub fn ptr_eq(this: &RateLimiter, other: &RateLimiter) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+/// The merge operator set on an [`Options`], checked when opening a DB
+/// against the one its column families were created with.
+#[derive(Clone)]
+pub(crate) enum MergeOperatorId {
+    /// A merge operator implemented in Rust, with its name
+    Named(String),
+    /// A merge operator of RocksDB, or one loaded from an options file
+    Unchecked,
 }
 
 #[derive(Default)]
 pub(crate) struct OptionsMustOutliveDB {
     env: Option<Env>,
     row_cache: Option<Cache>,
+    write_buffer_manager: Option<WriteBufferManager>,
+    rate_limiter: Option<RateLimiter>,
     block_based: Option<BlockBasedOptionsMustOutliveDB>,
 }
 
@@ -116,6 +260,11 @@ impl OptionsMustOutliveDB {
         Self {
             env: self.env.as_ref().map(Env::clone),
             row_cache: self.row_cache.as_ref().map(Cache::clone),
+            write_buffer_manager: self
+                .write_buffer_manager
+                .as_ref()
+                .map(WriteBufferManager::clone),
+            rate_limiter: self.rate_limiter.as_ref().map(RateLimiter::clone),
             block_based: self
                 .block_based
                 .as_ref()
@@ -172,6 +321,12 @@ impl BlockBasedOptionsMustOutliveDB {
 pub struct Options {
     pub(crate) inner: *mut ffi::rocksdb_options_t,
     pub(crate) outlive: OptionsMustOutliveDB,
+    pub(crate) merge_operator: Option<MergeOperatorId>,
+    /// Set with `set_max_bytes_for_level_multiplier_additional`, which the C
+    /// API cannot read back
+    level_multiplier_additional: Vec<i32>,
+    /// Set with `set_verify_merge_operators`
+    pub(crate) verify_merge_operators: bool,
 }
 
 /// Optionally disable WAL or sync for this write.
@@ -231,12 +386,21 @@ pub struct FlushOptions {
 pub struct BlockBasedOptions {
     pub(crate) inner: *mut ffi::rocksdb_block_based_table_options_t,
     outlive: BlockBasedOptionsMustOutliveDB,
+    format_version: i32,
 }
 
+/// Options for reads and iterators.
+///
+/// Cloning copies every setting, including the snapshot and the iterator
+/// bounds. To build the same options for many requests, see
+/// [`ReadOptionsBuilder`].
 pub struct ReadOptions {
     pub(crate) inner: *mut ffi::rocksdb_readoptions_t,
     iterate_upper_bound: Option<Vec<u8>>,
     iterate_lower_bound: Option<Vec<u8>>,
+    // recorded for `Clone` since the C API has no getter for them
+    snapshot: *const ffi::rocksdb_snapshot_t,
+    auto_readahead_size: bool,
 }
 
 /// Configuration of cuckoo-based storage.
@@ -311,10 +475,27 @@ impl Clone for Options {
         Self {
             inner,
             outlive: self.outlive.clone(),
+            merge_operator: self.merge_operator.clone(),
+            level_multiplier_additional: self.level_multiplier_additional.clone(),
+            verify_merge_operators: self.verify_merge_operators,
         }
     }
 }
 
+/// Prints the options which can be read back, named like in OPTIONS files.
+impl fmt::Debug for Options {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Options");
+        for (name, value) in options_file::db_option_values(self)
+            .iter()
+            .chain(&options_file::cf_option_values(self))
+        {
+            debug.field(name, &format_args!("{value}"));
+        }
+        debug.finish()
+    }
+}
+
 impl Drop for BlockBasedOptions {
     fn drop(&mut self) {
         unsafe {
@@ -393,6 +574,20 @@ impl BlockBasedOptions {
     ///
     /// Use partitioned full filters for each SST file. This option is
     /// incompatible with block-based filters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{BlockBasedIndexType, BlockBasedOptions};
+    ///
+    /// let mut opts = BlockBasedOptions::default();
+    /// opts.set_ribbon_filter(10.0);
+    /// opts.set_index_type(BlockBasedIndexType::TwoLevelIndexSearch);
+    /// opts.set_partition_filters(true);
+    /// opts.set_metadata_block_size(4096);
+    /// opts.set_cache_index_and_filter_blocks(true);
+    /// opts.set_pin_top_level_index_and_filter(true);
+    /// ```
     pub fn set_partition_filters(&mut self, size: bool) {
         unsafe {
             ffi::rocksdb_block_based_options_set_partition_filters(self.inner, c_uchar::from(size));
@@ -559,11 +754,18 @@ impl BlockBasedOptions {
     ///
     /// Default: 5.
     pub fn set_format_version(&mut self, version: i32) {
+        self.format_version = version;
         unsafe {
             ffi::rocksdb_block_based_options_set_format_version(self.inner, version);
         }
     }
 
+    /// Returns the format version set with
+    /// [`set_format_version`](Self::set_format_version).
+    pub fn get_format_version(&self) -> i32 {
+        self.format_version
+    }
+
     /// Number of keys between restart points for delta encoding of keys.
     /// This parameter can be changed dynamically. Most clients should
     /// leave this parameter alone. The minimum value allowed is 1. Any smaller
@@ -629,6 +831,12 @@ impl BlockBasedOptions {
 
     /// If false, place only prefixes in the filter, not whole keys.
     ///
+    /// Prefixes are only added if a prefix extractor is set with
+    /// [`Options::set_prefix_extractor`], so disabling whole key filtering
+    /// without one leaves the filter empty. Prefix filters help prefix seeks
+    /// and point lookups alike, so disabling whole key filtering makes filters
+    /// smaller for workloads dominated by prefix seeks.
+    ///
     /// Defaults to true.
     pub fn set_whole_key_filtering(&mut self, v: bool) {
         unsafe {
@@ -669,6 +877,97 @@ impl BlockBasedOptions {
             );
         }
     }
+
+    /// Use delta encoding to compress keys in blocks.
+    ///
+    /// [`ReadOptions::set_pin_data`] requires this option to be disabled.
+    ///
+    /// Default: true
+    pub fn set_use_delta_encoding(&mut self, v: bool) {
+        unsafe {
+            ffi::rocksdb_block_based_options_set_use_delta_encoding(self.inner, c_uchar::from(v));
+        }
+    }
+
+    /// Returns options keeping the memory used by the table readers low, for
+    /// DBs whose indexes and filters do not fit in memory.
+    ///
+    /// Indexes and filters are partitioned and stored in the block cache,
+    /// which bounds their memory usage, only their top level being pinned so
+    /// that a lookup reads at most one partition of each. Filters are Ribbon
+    /// filters, about 30% smaller than Bloom filters of the same accuracy,
+    /// and data blocks are 16 KiB to shrink the indexes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{BlockBasedOptions, Options};
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_block_based_table_factory(&BlockBasedOptions::preset_low_memory());
+    /// ```
+    pub fn preset_low_memory() -> Self {
+        let mut opts = Self::default();
+        opts.set_block_size(16 * 1024);
+        opts.set_ribbon_filter(10.0);
+        opts.set_optimize_filters_for_memory(true);
+        opts.set_index_type(BlockBasedIndexType::TwoLevelIndexSearch);
+        opts.set_partition_filters(true);
+        opts.set_metadata_block_size(4096);
+        opts.set_cache_index_and_filter_blocks(true);
+        opts.set_pin_top_level_index_and_filter(true);
+        opts
+    }
+
+    /// Returns options for point lookups served from `cache`.
+    ///
+    /// Filters are full Bloom filters of 10 bits per key on whole keys,
+    /// skipping about 99% of the files not holding a key, and data blocks
+    /// have a hash index to find a key without a binary search. Indexes and
+    /// filters are stored in `cache`, those of level 0 being pinned since
+    /// each lookup reads all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{BlockBasedOptions, Cache, Options};
+    ///
+    /// let cache = Cache::new_lru_cache(64 * 1024 * 1024);
+    /// let mut opts = Options::default();
+    /// opts.set_block_based_table_factory(&BlockBasedOptions::preset_point_lookup(&cache));
+    /// ```
+    pub fn preset_point_lookup(cache: &Cache) -> Self {
+        let mut opts = Self::default();
+        opts.set_block_cache(cache);
+        opts.set_bloom_filter(10.0, false);
+        opts.set_whole_key_filtering(true);
+        opts.set_data_block_index_type(DataBlockIndexType::BinaryAndHash);
+        opts.set_data_block_hash_ratio(0.75);
+        opts.set_cache_index_and_filter_blocks(true);
+        opts.set_pin_l0_filter_and_index_blocks_in_cache(true);
+        opts
+    }
+
+    /// Returns options for range scans.
+    ///
+    /// Data blocks are 64 KiB so that a scan reads fewer and larger blocks.
+    /// No filter is built since scans cannot use whole key filters, which
+    /// saves their memory, and the indexes are kept by the table readers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{BlockBasedOptions, Options};
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_block_based_table_factory(&BlockBasedOptions::preset_range_scan());
+    /// ```
+    pub fn preset_range_scan() -> Self {
+        let mut opts = Self::default();
+        opts.set_block_size(64 * 1024);
+        opts.set_index_type(BlockBasedIndexType::BinarySearch);
+        opts
+    }
 }
 
 impl Default for BlockBasedOptions {
@@ -682,6 +981,7 @@ impl Default for BlockBasedOptions {
         Self {
             inner: block_opts,
             outlive: BlockBasedOptionsMustOutliveDB::default(),
+            format_version: 5,
         }
     }
 }
@@ -764,6 +1064,19 @@ pub enum LogLevel {
     Header,
 }
 
+impl LogLevel {
+    fn from_raw(value: c_int) -> Self {
+        match value {
+            0 => Self::Debug,
+            1 => Self::Info,
+            2 => Self::Warn,
+            3 => Self::Error,
+            4 => Self::Fatal,
+            _ => Self::Header,
+        }
+    }
+}
+
 impl Options {
     /// Constructs the DBOptions and ColumnFamilyDescriptors by loading the
     /// latest RocksDB options file stored in the specified rocksdb database.
@@ -793,6 +1106,9 @@ impl Options {
         let options = Options {
             inner: db_options,
             outlive: OptionsMustOutliveDB::default(),
+            merge_operator: Some(MergeOperatorId::Unchecked),
+            level_multiplier_additional: Vec::new(),
+            verify_merge_operators: false,
         };
         let column_families = unsafe {
             Options::read_column_descriptors(
@@ -821,10 +1137,13 @@ impl Options {
                 .map(|ptr| Options {
                     inner: *ptr,
                     outlive: OptionsMustOutliveDB::default(),
+                    merge_operator: Some(MergeOperatorId::Unchecked),
+                    level_multiplier_additional: Vec::new(),
+                    verify_merge_operators: false,
                 });
         let column_descriptors = column_family_names_iter
             .zip(column_family_options_iter)
-            .map(|(name, options)| ColumnFamilyDescriptor { name, options })
+            .map(|(name, options)| ColumnFamilyDescriptor::new(name, options))
             .collect::<Vec<_>>();
         // free pointers
         slice::from_raw_parts(column_family_names, num_column_families)
@@ -975,6 +1294,77 @@ impl Options {
         }
     }
 
+    /// Enables full file checksums for every SST file written by the DB,
+    /// using the given checksum generator. The checksums are stored in the
+    /// MANIFEST and are used by backups and external file ingestion to
+    /// validate the copied files. They are reported by
+    /// [`DBCommon::live_files`](crate::DBCommon::live_files).
+    ///
+    /// RocksDB only ships a CRC32c generator: [`ChecksumGenType::XxHash64`]
+    /// returns a `Not implemented` error, XXH64 checksums can be computed
+    /// with [`ChecksumGenType::checksum_file`] instead.
+    ///
+    /// Has to be set before the database is opened.
+    ///
+    /// Default: disabled
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{ChecksumGenType, Options};
+    ///
+    /// let mut opts = Options::default();
+    /// opts.enable_file_checksums(ChecksumGenType::Crc32c).unwrap();
+    /// ```
+    pub fn enable_file_checksums(&mut self, gen_type: ChecksumGenType) -> Result<(), Error> {
+        let factory = gen_type.factory_name().ok_or_else(|| {
+            Error::new(format!(
+                "Not implemented: RocksDB has no {} file checksum generator",
+                gen_type.func_name()
+            ))
+        })?;
+        self.apply_options_string(&format!("file_checksum_gen_factory={factory}"))
+    }
+
+    /// Sets the number of bytes of the checksum protecting each entry of the
+    /// memtables, which must be 0 (no protection), 1, 2, 4 or 8.
+    ///
+    /// Entries are verified when they are read from the memtables or flushed,
+    /// so memory corruption is reported as a `Corruption` error instead of
+    /// being persisted.
+    ///
+    /// Default: 0
+    pub fn set_memtable_protection_bytes_per_key(&mut self, bytes: u32) -> Result<(), Error> {
+        self.apply_options_string(&format!("memtable_protection_bytes_per_key={bytes}"))
+    }
+
+    /// Replaces the options by a copy with the options of `opts_str` applied,
+    /// for options the C API only exposes through option strings.
+    fn apply_options_string(&mut self, opts_str: &str) -> Result<(), Error> {
+        let opts_str = CString::new(opts_str).map_err(|err| {
+            Error::new(format!(
+                "Failed to convert options string to CString: {err}"
+            ))
+        })?;
+        unsafe {
+            let new_inner = ffi::rocksdb_options_create();
+            let mut err: *mut c_char = null_mut();
+            ffi::rocksdb_get_options_from_string(
+                self.inner,
+                opts_str.as_ptr(),
+                new_inner,
+                &mut err,
+            );
+            if !err.is_null() {
+                ffi::rocksdb_options_destroy(new_inner);
+                return Err(Error::new(error_message(err)));
+            }
+            ffi::rocksdb_options_destroy(self.inner);
+            self.inner = new_inner;
+        }
+        Ok(())
+    }
+
     /// A list of paths where SST files can be put into, with its target size.
     /// Newer data is placed into paths specified earlier in the vector while
     /// older data gradually moves to paths specified later in the vector.
@@ -1258,6 +1648,84 @@ impl Options {
         }
     }
 
+    /// Limits the amount of data buffered in memory while sampling SST file
+    /// data for dictionary compression. Once the limit is reached the buffered
+    /// data is used to finalize the dictionary and the remaining blocks are
+    /// compressed as they are written.
+    ///
+    /// Only takes effect when `max_dict_bytes` has been set using
+    /// [`set_compression_options`](#method.set_compression_options).
+    ///
+    /// Default: 0 (unlimited)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{Options, DBCompressionType};
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_compression_type(DBCompressionType::Zstd);
+    /// opts.set_compression_options(-14, 3, 0, 16 * 1024);
+    /// opts.set_compression_options_max_dict_buffer_bytes(64 * 1024 * 1024);
+    /// ```
+    pub fn set_compression_options_max_dict_buffer_bytes(&mut self, value: u64) {
+        unsafe {
+            ffi::rocksdb_options_set_compression_options_max_dict_buffer_bytes(self.inner, value);
+        }
+    }
+
+    /// Same as [`set_compression_options_max_dict_buffer_bytes`](#method.set_compression_options_max_dict_buffer_bytes)
+    /// but only affects the bottom-most compression which is set using
+    /// [`set_bottommost_compression_type`](#method.set_bottommost_compression_type) method.
+    ///
+    /// Default: 0 (unlimited)
+    pub fn set_bottommost_compression_options_max_dict_buffer_bytes(
+        &mut self,
+        value: u64,
+        enabled: bool,
+    ) {
+        unsafe {
+            ffi::rocksdb_options_set_bottommost_compression_options_max_dict_buffer_bytes(
+                self.inner,
+                value,
+                c_uchar::from(enabled),
+            );
+        }
+    }
+
+    /// If true, the samples collected for the compression dictionary are
+    /// passed through zstd's dictionary trainer. Otherwise zstd's
+    /// `ZDICT_finalizeDictionary` is used to build a dictionary from the raw
+    /// samples, which is faster but may yield a lower compression ratio.
+    ///
+    /// Default: true
+    pub fn set_compression_options_use_zstd_dict_trainer(&mut self, v: bool) {
+        unsafe {
+            ffi::rocksdb_options_set_compression_options_use_zstd_dict_trainer(
+                self.inner,
+                c_uchar::from(v),
+            );
+        }
+    }
+
+    /// Same as [`set_compression_options_use_zstd_dict_trainer`](#method.set_compression_options_use_zstd_dict_trainer)
+    /// but only affects the bottom-most compression.
+    ///
+    /// Default: true
+    pub fn set_bottommost_compression_options_use_zstd_dict_trainer(
+        &mut self,
+        v: bool,
+        enabled: bool,
+    ) {
+        unsafe {
+            ffi::rocksdb_options_set_bottommost_compression_options_use_zstd_dict_trainer(
+                self.inner,
+                c_uchar::from(v),
+                c_uchar::from(enabled),
+            );
+        }
+    }
+
     /// If non-zero, we perform bigger reads when doing compaction. If you're
     /// running RocksDB on spinning disks, you should set this to at least 2MB.
     /// That way RocksDB's compaction is doing sequential instead of random reads.
@@ -1272,8 +1740,10 @@ impl Options {
     /// Allow RocksDB to pick dynamic base of bytes for levels.
     /// With this feature turned on, RocksDB will automatically adjust max bytes for each level.
     /// The goal of this feature is to have lower bound on size amplification.
+    /// See [`describe_levels_for_size`](Self::describe_levels_for_size) for the
+    /// resulting capacities.
     ///
-    /// Default: false.
+    /// Default: true.
     pub fn set_level_compaction_dynamic_level_bytes(&mut self, v: bool) {
         unsafe {
             ffi::rocksdb_options_set_level_compaction_dynamic_level_bytes(
@@ -1337,8 +1807,10 @@ impl Options {
         name: impl CStrLike,
         full_merge_fn: F,
     ) {
+        let name = name.into_c_string().unwrap();
+        self.merge_operator = Some(MergeOperatorId::Named(name.to_string_lossy().into_owned()));
         let cb = Box::new(MergeOperatorCallback {
-            name: name.into_c_string().unwrap(),
+            name,
             full_merge_fn: full_merge_fn.clone(),
             partial_merge_fn: full_merge_fn,
         });
@@ -1362,8 +1834,10 @@ impl Options {
         full_merge_fn: F,
         partial_merge_fn: PF,
     ) {
+        let name = name.into_c_string().unwrap();
+        self.merge_operator = Some(MergeOperatorId::Named(name.to_string_lossy().into_owned()));
         let cb = Box::new(MergeOperatorCallback {
-            name: name.into_c_string().unwrap(),
+            name,
             full_merge_fn,
             partial_merge_fn,
         });
@@ -1381,6 +1855,60 @@ impl Options {
         }
     }
 
+    /// Sets one of the merge operators shipped with RocksDB. These run
+    /// natively, so merging does not call back into Rust for every operand.
+    ///
+    /// Replaces a merge operator set by [`set_merge_operator`] before.
+    ///
+    /// Fails if the delimiter of [`BuiltinMergeOperator::StringAppend`]
+    /// contains braces, which cannot be escaped in an options string.
+    ///
+    /// [`set_merge_operator`]: Self::set_merge_operator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{BuiltinMergeOperator, Options, DB};
+    ///
+    /// let path = "_path_for_rocksdb_storage_builtin_merge_operator";
+    /// {
+    ///     let mut opts = Options::default();
+    ///     opts.create_if_missing(true);
+    ///     opts.set_builtin_merge_operator(BuiltinMergeOperator::UInt64Add)
+    ///         .unwrap();
+    ///     let db = DB::open(&opts, path).unwrap();
+    ///
+    ///     db.merge(b"counter", 1_u64.to_le_bytes()).unwrap();
+    ///     db.merge(b"counter", 2_u64.to_le_bytes()).unwrap();
+    ///     assert_eq!(db.get(b"counter").unwrap().unwrap(), 3_u64.to_le_bytes());
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn set_builtin_merge_operator(
+        &mut self,
+        merge_operator: BuiltinMergeOperator,
+    ) -> Result<(), Error> {
+        self.apply_options_string(&format!("merge_operator={}", merge_operator.to_options()?))?;
+        self.merge_operator = Some(MergeOperatorId::Unchecked);
+        Ok(())
+    }
+
+    /// Checks, when opening a DB with these options, that the column families
+    /// created with a merge operator are opened with a merge operator of the
+    /// same name, as merging values with another operator silently produces
+    /// wrong values. Opening the DB fails with an `InvalidArgument` error
+    /// otherwise.
+    ///
+    /// The names are read from the latest OPTIONS file of the DB. Operators
+    /// shipped with RocksDB, and options loaded with
+    /// [`load_latest`](Self::load_latest), are only checked to be set, as
+    /// RocksDB records the built-in operators under their class names.
+    ///
+    /// Default: `false`
+    pub fn set_verify_merge_operators(&mut self, verify: bool) {
+        self.verify_merge_operators = verify;
+    }
+
     #[deprecated(
         since = "0.5.0",
         note = "add_merge_operator has been renamed to set_merge_operator"
@@ -1445,6 +1973,45 @@ impl Options {
         }
     }
 
+    /// Sets a compaction filter factory calling `create_fn` on each compaction
+    /// run to create the filter closure of the run, e.g. to capture the
+    /// current time for a TTL filter.
+    ///
+    /// See [`set_compaction_filter_factory`](Self::set_compaction_filter_factory).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{CompactionDecision, Options};
+    /// use std::convert::TryInto;
+    /// use std::time::{SystemTime, UNIX_EPOCH};
+    ///
+    /// let mut opts = Options::default();
+    /// // values start with the expiration time as big endian seconds
+    /// opts.set_compaction_filter_factory_fn("ttl", |_context| {
+    ///     let now = SystemTime::now()
+    ///         .duration_since(UNIX_EPOCH)
+    ///         .unwrap()
+    ///         .as_secs();
+    ///     move |_level: u32, _key: &[u8], value: &[u8]| match value.get(..8) {
+    ///         Some(expiration) if u64::from_be_bytes(expiration.try_into().unwrap()) < now => {
+    ///             CompactionDecision::Remove
+    ///         }
+    ///         _ => CompactionDecision::Keep,
+    ///     }
+    /// });
+    /// ```
+    pub fn set_compaction_filter_factory_fn<F, G>(&mut self, name: impl CStrLike, create_fn: F)
+    where
+        F: FnMut(CompactionFilterContext) -> G + Send + 'static,
+        G: CompactionFilterFn + Send + 'static,
+    {
+        self.set_compaction_filter_factory(CompactionFilterFactoryCallback {
+            name: name.into_c_string().unwrap(),
+            create_fn,
+        });
+    }
+
     /// Sets the comparator used to define the order of keys in the table.
     /// Default: a comparator that uses lexicographic byte-wise ordering
     ///
@@ -1468,6 +2035,16 @@ impl Options {
         }
     }
 
+    /// Sets the transform extracting the prefix of keys, used by prefix seeks
+    /// (see [`ReadOptions::set_prefix_same_as_start`]) and by the prefix bloom
+    /// filters of memtables ([`set_memtable_prefix_bloom_ratio`]) and table
+    /// files ([`BlockBasedOptions::set_whole_key_filtering`]).
+    ///
+    /// Keys outside of the domain of the transform are not added to prefix
+    /// filters, and a prefix seek on such a key behaves like a total order
+    /// seek.
+    ///
+    /// [`set_memtable_prefix_bloom_ratio`]: Self::set_memtable_prefix_bloom_ratio
     pub fn set_prefix_extractor(&mut self, prefix_extractor: SliceTransform) {
         unsafe {
             ffi::rocksdb_options_set_prefix_extractor(self.inner, prefix_extractor.inner);
@@ -1507,6 +2084,12 @@ impl Options {
     /// process will still get automatically delete on every compaction,
     /// regardless of this setting.
     ///
+    /// The deletions cannot be rate limited: the C API does not expose the
+    /// `SstFileManager` of RocksDB, which also tracks the space used by the
+    /// SST files, available instead with the
+    /// [`TOTAL_SST_FILES_SIZE`](crate::properties::TOTAL_SST_FILES_SIZE)
+    /// property.
+    ///
     /// Default: 6 hours
     pub fn set_delete_obsolete_files_period_micros(&mut self, micros: u64) {
         unsafe {
@@ -2193,6 +2776,10 @@ impl Options {
     /// concurrently perform a compaction job by breaking it into multiple,
     /// smaller ones that are run simultaneously.
     ///
+    /// Compactions always run in the process of the DB: the C API does not
+    /// expose the `CompactionService` of RocksDB, which ships them to remote
+    /// workers.
+    ///
     /// Default: 1 (i.e. no subcompactions)
     pub fn set_max_subcompactions(&mut self, num: u32) {
         unsafe {
@@ -2326,6 +2913,11 @@ impl Options {
     /// ensure that there are never more than max_successive_merges merge
     /// operations in the memtable.
     ///
+    /// This bounds the merge operands a read has to apply from the memtable
+    /// for counter-like workloads; the operands left in SST files until they
+    /// are compacted can be counted with
+    /// [`DB::merge_operand_count`](crate::DBCommon::merge_operand_count).
+    ///
     /// Default: 0 (disabled)
     pub fn set_max_successive_merges(&mut self, num: usize) {
         unsafe {
@@ -2381,6 +2973,7 @@ impl Options {
     ///
     /// Dynamically changeable through SetOptions() API
     pub fn set_max_bytes_for_level_multiplier_additional(&mut self, level_values: &[i32]) {
+        self.level_multiplier_additional = level_values.to_vec();
         let count = level_values.len();
         unsafe {
             ffi::rocksdb_options_set_max_bytes_for_level_multiplier_additional(
@@ -2467,6 +3060,12 @@ impl Options {
     /// See official [wiki](https://github.com/facebook/rocksdb/wiki/MemTable) for more information.
     /// Defaults to using a skiplist.
     ///
+    /// Opening a DB fails if one of these memtables is used while
+    /// [`set_allow_concurrent_memtable_write`] is enabled, which is the
+    /// default, as only the skip list supports concurrent writes.
+    ///
+    /// [`set_allow_concurrent_memtable_write`]: Self::set_allow_concurrent_memtable_write
+    ///
     /// # Examples
     ///
     /// ```
@@ -2504,6 +3103,52 @@ impl Options {
         };
     }
 
+    /// Uses a skip list memtable, the default, scanning forward up to
+    /// `lookahead` entries from the last position before searching from the
+    /// top of the list, which speeds up sequential inserts and seeks.
+    ///
+    /// Default: 0, which disables the lookahead
+    pub fn set_skip_list_memtable(&mut self, lookahead: usize) -> Result<(), Error> {
+        // the C API has no setter for the skip list factory
+        self.apply_options_string(&format!(
+            "memtable_factory={{id=SkipListFactory;lookahead={lookahead}}}"
+        ))
+    }
+
+    /// Uses a hash table of prefixes pointing to sorted linked lists as
+    /// memtable, like [`MemtableFactory::HashLinkList`], with all the
+    /// parameters of the factory. Requires a prefix extractor, and
+    /// [`set_allow_concurrent_memtable_write`] to be disabled.
+    ///
+    /// - `huge_page_tlb_size`: size of the huge pages allocating the
+    ///   memtable, 0 to not use huge pages
+    /// - `bucket_entries_logging_threshold`: number of entries in a bucket
+    ///   above which the bucket is logged when it is flushed, if
+    ///   `if_log_bucket_dist_when_flash` is set
+    /// - `threshold_use_skiplist`: number of entries in a bucket above which
+    ///   its linked list is converted to a skip list
+    ///
+    /// RocksDB defaults to 0, 4096, `true` and 256 respectively.
+    ///
+    /// [`set_allow_concurrent_memtable_write`]: Self::set_allow_concurrent_memtable_write
+    pub fn set_hash_link_list_memtable(
+        &mut self,
+        bucket_count: usize,
+        huge_page_tlb_size: usize,
+        bucket_entries_logging_threshold: i32,
+        if_log_bucket_dist_when_flash: bool,
+        threshold_use_skiplist: u32,
+    ) -> Result<(), Error> {
+        // the C API only takes the bucket count
+        self.apply_options_string(&format!(
+            "memtable_factory={{id=HashLinkListRepFactory;bucket_count={bucket_count};\
+             huge_page_size={huge_page_tlb_size};\
+             logging_threshold={bucket_entries_logging_threshold};\
+             log_when_flash={if_log_bucket_dist_when_flash};\
+             threshold={threshold_use_skiplist}}}"
+        ))
+    }
+
     pub fn set_block_based_table_factory(&mut self, factory: &BlockBasedOptions) {
         unsafe {
             ffi::rocksdb_options_set_block_based_table_factory(self.inner, factory.inner);
@@ -2511,6 +3156,53 @@ impl Options {
         self.outlive.block_based = Some(factory.outlive.clone());
     }
 
+    /// Sets the options of the block-based table factory which the C API
+    /// has no setters for, over the factory set with
+    /// [`set_block_based_table_factory`](Self::set_block_based_table_factory).
+    /// Setting the factory again afterwards resets them.
+    ///
+    /// - `block_align`: if true, data blocks are aligned on the lesser of the
+    ///   page size and the block size, which saves reads spanning two pages
+    ///   with direct IO. Requires compression to be disabled, otherwise
+    ///   opening the DB fails.
+    /// - `index_shortening`: how the keys separating the index entries of the
+    ///   data blocks are shortened
+    /// - `read_amp_bytes_per_bit`: if not zero, a bitmap of
+    ///   `block_size / read_amp_bytes_per_bit` bits is kept for each data
+    ///   block to track the bytes actually read, reported by the
+    ///   `rocksdb.read.amp.estimate.useful.bytes` and
+    ///   `rocksdb.read.amp.total.read.bytes` statistics. The value is rounded
+    ///   down to a power of 2.
+    ///
+    /// RocksDB defaults to `false`, `IndexShorteningMode::ShortenSeparators`
+    /// and 0 respectively.
+    pub fn set_block_based_table_layout(
+        &mut self,
+        block_align: bool,
+        index_shortening: IndexShorteningMode,
+        read_amp_bytes_per_bit: u32,
+    ) -> Result<(), Error> {
+        // the nested options are applied over the current table factory
+        self.apply_options_string(&format!(
+            "block_based_table_factory={{block_align={block_align};\
+             index_shortening={};\
+             read_amp_bytes_per_bit={read_amp_bytes_per_bit}}}",
+            index_shortening.option_name()
+        ))
+    }
+
+    /// Returns the block cache set with [`BlockBasedOptions::set_block_cache`]
+    /// on the block-based table factory of these options.
+    ///
+    /// Returns `None` if no block cache was set, in which case RocksDB uses an
+    /// internal cache of its own for each column family.
+    pub fn get_block_cache(&self) -> Option<Cache> {
+        self.outlive
+            .block_based
+            .as_ref()
+            .and_then(|block_based| block_based.block_cache.clone())
+    }
+
     /// Sets the table factory to a CuckooTableFactory (the default table
     /// factory is a block-based table factory that provides a default
     /// implementation of TableBuilder and TableReader with default
@@ -2644,29 +3336,14 @@ impl Options {
         }
     }
 
-    pub fn enable_statistics(&mut self) {
-        unsafe {
-            ffi::rocksdb_options_enable_statistics(self.inner);
-        }
-    }
-
-    pub fn get_statistics(&self) -> Option<String> {
-        unsafe {
-            let value = ffi::rocksdb_options_statistics_get_string(self.inner);
-            if value.is_null() {
-                return None;
-            }
-
-            // Must have valid UTF-8 format.
-            let s = CStr::from_ptr(value).to_str().unwrap().to_owned();
-            ffi::rocksdb_free(value as *mut c_void);
-            Some(s)
-        }
-    }
-
-    /// If not zero, dump `rocksdb.stats` to LOG every `stats_dump_period_sec`.
+    /// Enables best-efforts recovery: when opening the DB, recover to the
+    /// most recent consistent point-in-time state whose files all exist,
+    /// instead of failing when SST files referenced by the MANIFEST are
+    /// missing. The WAL is not replayed in this mode.
     ///
-    /// Default: `600` (10 mins)
+    /// Useful to open a DB that lost files, e.g. after an incomplete copy.
+    ///
+    /// Default: false
     ///
     /// # Examples
     ///
@@ -2674,7 +3351,78 @@ impl Options {
     /// use rocksdb::Options;
     ///
     /// let mut opts = Options::default();
-    /// opts.set_stats_dump_period_sec(300);
+    /// opts.set_best_efforts_recovery(true).unwrap();
+    /// ```
+    pub fn set_best_efforts_recovery(&mut self, enabled: bool) -> Result<(), Error> {
+        self.apply_options_string(&format!("best_efforts_recovery={enabled}"))
+    }
+
+    /// Sets how many times RocksDB tries to resume automatically from a
+    /// retryable background error, e.g. an I/O error while flushing, before
+    /// it gives up and the DB stays in read-only mode until it is reopened.
+    ///
+    /// 0 disables the automatic resume.
+    ///
+    /// Default: `i32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::Options;
+    /// use std::time::Duration;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_max_bgerror_resume_count(10).unwrap();
+    /// opts.set_bgerror_resume_retry_interval(Duration::from_secs(5)).unwrap();
+    /// ```
+    pub fn set_max_bgerror_resume_count(&mut self, count: i32) -> Result<(), Error> {
+        self.apply_options_string(&format!("max_bgerror_resume_count={count}"))
+    }
+
+    /// Sets the interval between two attempts to resume automatically from a
+    /// background error, see [`set_max_bgerror_resume_count`].
+    ///
+    /// [`set_max_bgerror_resume_count`]: Self::set_max_bgerror_resume_count
+    ///
+    /// Default: 1 second
+    pub fn set_bgerror_resume_retry_interval(&mut self, interval: Duration) -> Result<(), Error> {
+        self.apply_options_string(&format!(
+            "bgerror_resume_retry_interval={}",
+            interval.as_micros()
+        ))
+    }
+
+    pub fn enable_statistics(&mut self) {
+        unsafe {
+            ffi::rocksdb_options_enable_statistics(self.inner);
+        }
+    }
+
+    pub fn get_statistics(&self) -> Option<String> {
+        unsafe {
+            let value = ffi::rocksdb_options_statistics_get_string(self.inner);
+            if value.is_null() {
+                return None;
+            }
+
+            // Must have valid UTF-8 format.
+            let s = CStr::from_ptr(value).to_str().unwrap().to_owned();
+            ffi::rocksdb_free(value as *mut c_void);
+            Some(s)
+        }
+    }
+
+    /// If not zero, dump `rocksdb.stats` to LOG every `stats_dump_period_sec`.
+    ///
+    /// Default: `600` (10 mins)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_stats_dump_period_sec(300);
     /// ```
     pub fn set_stats_dump_period_sec(&mut self, period: c_uint) {
         unsafe {
@@ -2700,6 +3448,28 @@ impl Options {
         }
     }
 
+    /// Sets the maximum size in bytes of the statistics history kept in
+    /// memory, sampled every
+    /// [`stats_persist_period_sec`](Self::set_stats_persist_period_sec).
+    ///
+    /// Default: `1MB`
+    pub fn set_stats_history_buffer_size(&mut self, size: usize) -> Result<(), Error> {
+        self.apply_options_string(&format!("stats_history_buffer_size={size}"))
+    }
+
+    /// If true, statistics sampled every
+    /// [`stats_persist_period_sec`](Self::set_stats_persist_period_sec) are
+    /// written to the [`STATS_HISTORY_COLUMN_FAMILY_NAME`](crate::STATS_HISTORY_COLUMN_FAMILY_NAME)
+    /// column family instead of being kept in memory, and can be read with
+    /// [`get_stats_history`](crate::DBCommon::get_stats_history).
+    ///
+    /// Requires [`enable_statistics`](Self::enable_statistics).
+    ///
+    /// Default: `false`
+    pub fn set_persist_stats_to_disk(&mut self, enabled: bool) -> Result<(), Error> {
+        self.apply_options_string(&format!("persist_stats_to_disk={enabled}"))
+    }
+
     /// When set to true, reading SST files will opt out of the filesystem's
     /// readahead. Setting this to false may improve sequential iteration
     /// performance.
@@ -2746,6 +3516,11 @@ impl Options {
     /// creates a prefix bloom filter for each memtable with the size of
     /// `write_buffer_size * memtable_prefix_bloom_ratio` (capped at 0.25).
     ///
+    /// The filter holds the prefixes of the keys, and their whole keys too if
+    /// [`set_memtable_whole_key_filtering`] is enabled. Without a prefix
+    /// extractor and without whole key filtering, no filter is created and
+    /// the ratio has no effect.
+    ///
     /// Default: `0`
     ///
     /// # Examples
@@ -2758,6 +3533,8 @@ impl Options {
     /// opts.set_prefix_extractor(transform);
     /// opts.set_memtable_prefix_bloom_ratio(0.2);
     /// ```
+    ///
+    /// [`set_memtable_whole_key_filtering`]: Self::set_memtable_whole_key_filtering
     pub fn set_memtable_prefix_bloom_ratio(&mut self, ratio: f64) {
         unsafe {
             ffi::rocksdb_options_set_memtable_prefix_bloom_size_ratio(self.inner, ratio);
@@ -2812,6 +3589,9 @@ impl Options {
     /// 4. If both are not 0, WAL files will be checked every 10 min and both
     ///    checks will be performed with ttl being first.
     ///
+    /// Archiving WAL files disables
+    /// [`set_recycle_log_file_num`](Self::set_recycle_log_file_num).
+    ///
     /// Default: 0
     pub fn set_wal_ttl_seconds(&mut self, secs: u64) {
         unsafe {
@@ -2961,6 +3741,35 @@ impl Options {
         self.outlive.row_cache = Some(cache.clone());
     }
 
+    /// Sets a write buffer manager to limit the memory used by memtables. The
+    /// same manager can be set on the options of several DBs to cap their
+    /// combined memtable memory.
+    ///
+    /// Takes precedence over [`set_db_write_buffer_size`].
+    ///
+    /// [`set_db_write_buffer_size`]: Self::set_db_write_buffer_size
+    ///
+    /// Default: null (disabled)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{Options, WriteBufferManager};
+    ///
+    /// let manager = WriteBufferManager::new(64 * 1024 * 1024, false);
+    /// let mut opts = Options::default();
+    /// opts.set_write_buffer_manager(&manager);
+    /// ```
+    pub fn set_write_buffer_manager(&mut self, write_buffer_manager: &WriteBufferManager) {
+        unsafe {
+            ffi::rocksdb_options_set_write_buffer_manager(
+                self.inner,
+                write_buffer_manager.0.inner.as_ptr(),
+            );
+        }
+        self.outlive.write_buffer_manager = Some(write_buffer_manager.clone());
+    }
+
     /// Use to control write rate of flush and compaction. Flush has higher
     /// priority than compaction.
     /// If rate limiter is enabled, bytes_per_sync is set to 1MB by default.
@@ -2990,6 +3799,29 @@ impl Options {
         }
     }
 
+    /// Sets the rate limiter of flushes and compactions, which can be shared
+    /// with other DBs to limit their total write rate.
+    ///
+    /// Replaces the rate limiter set by [`set_ratelimiter`](Self::set_ratelimiter).
+    ///
+    /// Default: disable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{Options, RateLimiter};
+    ///
+    /// let limiter = RateLimiter::auto_tuned(256 * 1024 * 1024, 100 * 1000, 10);
+    /// let mut options = Options::default();
+    /// options.set_rate_limiter(&limiter);
+    /// ```
+    pub fn set_rate_limiter(&mut self, rate_limiter: &RateLimiter) {
+        unsafe {
+            ffi::rocksdb_options_set_ratelimiter(self.inner, rate_limiter.0.inner.as_ptr());
+        }
+        self.outlive.rate_limiter = Some(rate_limiter.clone());
+    }
+
     /// Sets the maximal size of the info log file.
     ///
     /// If the log file is larger than `max_log_file_size`, a new info log file
@@ -3031,6 +3863,12 @@ impl Options {
     /// because the blocks are already allocated and fdatasync does not need to
     /// update the inode after each write.
     ///
+    /// Recycling is disabled when WAL files are archived, see
+    /// [`set_wal_ttl_seconds`](Self::set_wal_ttl_seconds) and
+    /// [`set_wal_size_limit_mb`](Self::set_wal_size_limit_mb), or when
+    /// [`DBRecoveryMode::TolerateCorruptedTailRecords`] or
+    /// [`DBRecoveryMode::AbsoluteConsistency`] is used.
+    ///
     /// Default: 0
     ///
     /// # Examples
@@ -3092,6 +3930,9 @@ impl Options {
     /// if memtable_prefix_bloom_size_ratio is not 0. Enabling whole key filtering
     /// can potentially reduce CPU usage for point-look-ups.
     ///
+    /// This does not require a prefix extractor: without one, the memtable
+    /// filter only holds whole keys and does not help prefix seeks.
+    ///
     /// Default: false (disable)
     ///
     /// Dynamically changeable through SetOptions() API
@@ -3205,15 +4046,30 @@ impl Options {
         }
     }
 
-    // A factory of a table property collector that marks an SST
-    // file as need-compaction when it observe at least "D" deletion
-    // entries in any "N" consecutive entries, or the ratio of tombstone
-    // entries >= deletion_ratio.
-    //
-    // `window_size`: is the sliding window size "N"
-    // `num_dels_trigger`: is the deletion trigger "D"
-    // `deletion_ratio`: if <= 0 or > 1, disable triggering compaction based on
-    // deletion ratio.
+    /// Adds a factory of a table property collector that marks an SST
+    /// file as need-compaction when it observes at least "D" deletion
+    /// entries in any "N" consecutive entries, or the ratio of tombstone
+    /// entries >= deletion_ratio.
+    ///
+    /// Files marked this way are picked up by the background compactions,
+    /// so key ranges with a heavy tombstone density (e.g. queue-like
+    /// workloads) get compacted promptly instead of slowing down scans.
+    ///
+    /// `window_size`: is the sliding window size "N"
+    /// `num_dels_trigger`: is the deletion trigger "D"
+    /// `deletion_ratio`: if <= 0 or > 1, disable triggering compaction based on
+    /// deletion ratio.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// // 100 tombstones in any 1000 consecutive entries, or 30% tombstones
+    /// // overall, mark the file for compaction.
+    /// opts.add_compact_on_deletion_collector_factory(1000, 100, 0.3);
+    /// ```
     pub fn add_compact_on_deletion_collector_factory(
         &mut self,
         window_size: size_t,
@@ -3231,6 +4087,638 @@ impl Options {
     }
 }
 
+/// Getters of the options set on `Options`.
+impl Options {
+    /// Returns the value set with [`create_if_missing`](Self::create_if_missing).
+    pub fn get_create_if_missing(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_create_if_missing(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`create_missing_column_families`](Self::create_missing_column_families).
+    pub fn get_create_missing_column_families(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_create_missing_column_families(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_error_if_exists`](Self::set_error_if_exists).
+    pub fn get_error_if_exists(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_error_if_exists(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_paranoid_checks`](Self::set_paranoid_checks).
+    pub fn get_paranoid_checks(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_paranoid_checks(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_log_level`](Self::set_log_level).
+    pub fn get_log_level(&self) -> LogLevel {
+        LogLevel::from_raw(unsafe { ffi::rocksdb_options_get_info_log_level(self.inner) })
+    }
+
+    /// Returns the value set with [`set_compression_type`](Self::set_compression_type).
+    ///
+    /// Returns `None` if the value is not one of [`DBCompressionType`], e.g. when
+    /// the options were loaded from an options file.
+    pub fn get_compression_type(&self) -> Option<DBCompressionType> {
+        DBCompressionType::from_raw(unsafe { ffi::rocksdb_options_get_compression(self.inner) })
+    }
+
+    /// Returns the value set with
+    /// [`set_bottommost_compression_type`](Self::set_bottommost_compression_type).
+    ///
+    /// Returns `None` if no bottommost compression type is set, in which case
+    /// the bottommost level uses the [compression type](Self::get_compression_type).
+    pub fn get_bottommost_compression_type(&self) -> Option<DBCompressionType> {
+        DBCompressionType::from_raw(unsafe {
+            ffi::rocksdb_options_get_bottommost_compression(self.inner)
+        })
+    }
+
+    /// Returns the value set with [`set_wal_compression_type`](Self::set_wal_compression_type).
+    ///
+    /// Returns `None` if the value is not one of [`DBCompressionType`], e.g. when
+    /// the options were loaded from an options file.
+    pub fn get_wal_compression_type(&self) -> Option<DBCompressionType> {
+        DBCompressionType::from_raw(unsafe { ffi::rocksdb_options_get_wal_compression(self.inner) })
+    }
+
+    /// Returns the value set with
+    /// [`set_compression_options_parallel_threads`](Self::set_compression_options_parallel_threads).
+    pub fn get_compression_options_parallel_threads(&self) -> i32 {
+        unsafe { ffi::rocksdb_options_get_compression_options_parallel_threads(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_compression_options_max_dict_buffer_bytes`](Self::set_compression_options_max_dict_buffer_bytes).
+    pub fn get_compression_options_max_dict_buffer_bytes(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_compression_options_max_dict_buffer_bytes(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_compression_options_use_zstd_dict_trainer`](Self::set_compression_options_use_zstd_dict_trainer).
+    pub fn get_compression_options_use_zstd_dict_trainer(&self) -> bool {
+        unsafe {
+            ffi::rocksdb_options_get_compression_options_use_zstd_dict_trainer(self.inner) != 0
+        }
+    }
+
+    /// Returns the value set with [`set_zstd_max_train_bytes`](Self::set_zstd_max_train_bytes).
+    pub fn get_zstd_max_train_bytes(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_compression_options_zstd_max_train_bytes(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_compaction_readahead_size`](Self::set_compaction_readahead_size).
+    pub fn get_compaction_readahead_size(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_compaction_readahead_size(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_level_compaction_dynamic_level_bytes`](Self::set_level_compaction_dynamic_level_bytes).
+    pub fn get_level_compaction_dynamic_level_bytes(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_level_compaction_dynamic_level_bytes(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_periodic_compaction_seconds`](Self::set_periodic_compaction_seconds).
+    pub fn get_periodic_compaction_seconds(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_periodic_compaction_seconds(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_optimize_filters_for_hits`](Self::set_optimize_filters_for_hits).
+    pub fn get_optimize_filters_for_hits(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_optimize_filters_for_hits(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_delete_obsolete_files_period_micros`](Self::set_delete_obsolete_files_period_micros).
+    pub fn get_delete_obsolete_files_period_micros(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_delete_obsolete_files_period_micros(self.inner) }
+    }
+
+    /// Returns the value set with [`set_max_open_files`](Self::set_max_open_files).
+    pub fn get_max_open_files(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_max_open_files(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_file_opening_threads`](Self::set_max_file_opening_threads).
+    pub fn get_max_file_opening_threads(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_max_file_opening_threads(self.inner) }
+    }
+
+    /// Returns the value set with [`set_use_fsync`](Self::set_use_fsync).
+    pub fn get_use_fsync(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_use_fsync(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_bytes_per_sync`](Self::set_bytes_per_sync).
+    pub fn get_bytes_per_sync(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_bytes_per_sync(self.inner) }
+    }
+
+    /// Returns the value set with [`set_wal_bytes_per_sync`](Self::set_wal_bytes_per_sync).
+    pub fn get_wal_bytes_per_sync(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_wal_bytes_per_sync(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_writable_file_max_buffer_size`](Self::set_writable_file_max_buffer_size).
+    pub fn get_writable_file_max_buffer_size(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_writable_file_max_buffer_size(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_allow_concurrent_memtable_write`](Self::set_allow_concurrent_memtable_write).
+    pub fn get_allow_concurrent_memtable_write(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_allow_concurrent_memtable_write(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_enable_write_thread_adaptive_yield`](Self::set_enable_write_thread_adaptive_yield).
+    pub fn get_enable_write_thread_adaptive_yield(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_enable_write_thread_adaptive_yield(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_sequential_skip_in_iterations`](Self::set_max_sequential_skip_in_iterations).
+    pub fn get_max_sequential_skip_in_iterations(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_max_sequential_skip_in_iterations(self.inner) }
+    }
+
+    /// Returns the value set with [`set_use_direct_reads`](Self::set_use_direct_reads).
+    pub fn get_use_direct_reads(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_use_direct_reads(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_use_direct_io_for_flush_and_compaction`](Self::set_use_direct_io_for_flush_and_compaction).
+    pub fn get_use_direct_io_for_flush_and_compaction(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_use_direct_io_for_flush_and_compaction(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_is_fd_close_on_exec`](Self::set_is_fd_close_on_exec).
+    pub fn get_is_fd_close_on_exec(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_is_fd_close_on_exec(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_table_cache_num_shard_bits`](Self::set_table_cache_num_shard_bits).
+    pub fn get_table_cache_num_shard_bits(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_table_cache_numshardbits(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_target_file_size_multiplier`](Self::set_target_file_size_multiplier).
+    pub fn get_target_file_size_multiplier(&self) -> i32 {
+        unsafe { ffi::rocksdb_options_get_target_file_size_multiplier(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_write_buffer_number`](Self::set_max_write_buffer_number).
+    pub fn get_max_write_buffer_number(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_max_write_buffer_number(self.inner) }
+    }
+
+    /// Returns the value set with [`set_write_buffer_size`](Self::set_write_buffer_size).
+    pub fn get_write_buffer_size(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_write_buffer_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_db_write_buffer_size`](Self::set_db_write_buffer_size).
+    pub fn get_db_write_buffer_size(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_db_write_buffer_size(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_bytes_for_level_base`](Self::set_max_bytes_for_level_base).
+    pub fn get_max_bytes_for_level_base(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_max_bytes_for_level_base(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_bytes_for_level_multiplier`](Self::set_max_bytes_for_level_multiplier).
+    pub fn get_max_bytes_for_level_multiplier(&self) -> f64 {
+        unsafe { ffi::rocksdb_options_get_max_bytes_for_level_multiplier(self.inner) }
+    }
+
+    /// Returns the values set with
+    /// [`set_max_bytes_for_level_multiplier_additional`](Self::set_max_bytes_for_level_multiplier_additional),
+    /// empty if none were set, in which case every level uses 1.
+    ///
+    /// The C API cannot read them back, so the values of options loaded from
+    /// an options file are not returned.
+    pub fn get_max_bytes_for_level_multiplier_additional(&self) -> &[i32] {
+        &self.level_multiplier_additional
+    }
+
+    /// Predicts the capacity and the target file size of each level of a
+    /// new column family, from the level sizing options of leveled
+    /// compaction.
+    ///
+    /// With [`set_level_compaction_dynamic_level_bytes`], the capacities
+    /// depend on the size of the last level, which is empty in a new column
+    /// family: the files of level 0 are compacted straight into the last
+    /// level. Use [`describe_levels_for_size`] to predict the capacities
+    /// once the data has grown.
+    ///
+    /// [`set_level_compaction_dynamic_level_bytes`]: Self::set_level_compaction_dynamic_level_bytes
+    /// [`describe_levels_for_size`]: Self::describe_levels_for_size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_level_compaction_dynamic_level_bytes(false);
+    /// opts.set_max_bytes_for_level_base(256 << 20);
+    /// opts.set_max_bytes_for_level_multiplier(10.0);
+    /// opts.set_target_file_size_base(64 << 20);
+    /// opts.set_target_file_size_multiplier(2);
+    ///
+    /// let levels = opts.describe_levels();
+    /// assert_eq!(levels[0].max_bytes, None);
+    /// assert_eq!(levels[1].max_bytes, Some(256 << 20));
+    /// assert_eq!(levels[2].max_bytes, Some(2560 << 20));
+    /// assert_eq!(levels[3].target_file_size, 256 << 20);
+    /// ```
+    pub fn describe_levels(&self) -> Vec<LevelDescription> {
+        self.describe_levels_for_size(0)
+    }
+
+    /// Predicts the capacity and the target file size of each level, see
+    /// [`describe_levels`](Self::describe_levels), for a column family whose
+    /// last level holds `last_level_size` bytes.
+    ///
+    /// The size only matters with
+    /// [`set_level_compaction_dynamic_level_bytes`](Self::set_level_compaction_dynamic_level_bytes):
+    /// the capacity of each level is then the one of the next level divided
+    /// by [`max_bytes_for_level_multiplier`](Self::set_max_bytes_for_level_multiplier),
+    /// and level 0 is compacted into the first level whose capacity does not
+    /// exceed [`max_bytes_for_level_base`](Self::set_max_bytes_for_level_base).
+    /// The levels above it are not used. This approximates the choice RocksDB
+    /// makes from the actual sizes of all the levels.
+    pub fn describe_levels_for_size(&self, last_level_size: u64) -> Vec<LevelDescription> {
+        let num_levels = self.get_num_levels().max(1) as usize;
+        let level_base = self.get_max_bytes_for_level_base();
+        let multiplier = self.get_max_bytes_for_level_multiplier();
+        let file_base = self.get_target_file_size_base();
+        let file_multiplier = self.get_target_file_size_multiplier().max(1) as u64;
+        // the target file size of the level at `distance` from the level
+        // 0 is compacted into, as MutableCFOptions::RefreshDerivedOptions
+        let file_size = |distance: usize| {
+            let exponent = distance.saturating_sub(1).min(u32::MAX as usize) as u32;
+            file_base.saturating_mul(file_multiplier.saturating_pow(exponent))
+        };
+        let scale = |size: u64, factor: f64| (size as f64 * factor).min(u64::MAX as f64) as u64;
+
+        if !self.get_level_compaction_dynamic_level_bytes() {
+            let mut max_bytes = level_base;
+            return (0..num_levels)
+                .map(|level| {
+                    if level > 1 {
+                        let additional = self
+                            .level_multiplier_additional
+                            .get(level - 1)
+                            .copied()
+                            .unwrap_or(1);
+                        max_bytes = scale(scale(max_bytes, multiplier), f64::from(additional));
+                    }
+                    LevelDescription {
+                        level,
+                        max_bytes: (level > 0).then(|| max_bytes),
+                        target_file_size: file_size(level),
+                    }
+                })
+                .collect();
+        }
+
+        let mut base_level = num_levels - 1;
+        let mut level_size = last_level_size;
+        while base_level > 1 && level_size > level_base {
+            base_level -= 1;
+            level_size = scale(level_size, 1.0 / multiplier);
+        }
+        (0..num_levels)
+            .map(|level| {
+                let used = level >= base_level && level > 0 && last_level_size > 0;
+                if used && level > base_level {
+                    level_size = scale(level_size, multiplier);
+                }
+                let distance = if level < base_level {
+                    level
+                } else {
+                    level - base_level
+                };
+                LevelDescription {
+                    level,
+                    max_bytes: used.then(|| level_size.max(level_base)),
+                    target_file_size: file_size(distance),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the value set with [`set_max_manifest_file_size`](Self::set_max_manifest_file_size).
+    pub fn get_max_manifest_file_size(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_max_manifest_file_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_target_file_size_base`](Self::set_target_file_size_base).
+    pub fn get_target_file_size_base(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_target_file_size_base(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_min_write_buffer_number_to_merge`](Self::set_min_write_buffer_number_to_merge).
+    pub fn get_min_write_buffer_number_to_merge(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_min_write_buffer_number_to_merge(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_level_zero_file_num_compaction_trigger`](Self::set_level_zero_file_num_compaction_trigger).
+    pub fn get_level_zero_file_num_compaction_trigger(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_level0_file_num_compaction_trigger(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_level_zero_slowdown_writes_trigger`](Self::set_level_zero_slowdown_writes_trigger).
+    pub fn get_level_zero_slowdown_writes_trigger(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_level0_slowdown_writes_trigger(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_level_zero_stop_writes_trigger`](Self::set_level_zero_stop_writes_trigger).
+    pub fn get_level_zero_stop_writes_trigger(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_level0_stop_writes_trigger(self.inner) }
+    }
+
+    /// Returns the value set with [`set_compaction_style`](Self::set_compaction_style).
+    ///
+    /// Returns `None` if the value is not one of [`DBCompactionStyle`], e.g. when
+    /// the options were loaded from an options file.
+    pub fn get_compaction_style(&self) -> Option<DBCompactionStyle> {
+        DBCompactionStyle::from_raw(unsafe {
+            ffi::rocksdb_options_get_compaction_style(self.inner)
+        })
+    }
+
+    /// Returns the value set with [`set_unordered_write`](Self::set_unordered_write).
+    pub fn get_unordered_write(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_unordered_write(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_max_subcompactions`](Self::set_max_subcompactions).
+    pub fn get_max_subcompactions(&self) -> u32 {
+        unsafe { ffi::rocksdb_options_get_max_subcompactions(self.inner) }
+    }
+
+    /// Returns the value set with [`set_max_background_jobs`](Self::set_max_background_jobs).
+    pub fn get_max_background_jobs(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_max_background_jobs(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_background_compactions`](Self::set_max_background_compactions).
+    pub fn get_max_background_compactions(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_max_background_compactions(self.inner) }
+    }
+
+    /// Returns the value set with [`set_max_background_flushes`](Self::set_max_background_flushes).
+    pub fn get_max_background_flushes(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_max_background_flushes(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_disable_auto_compactions`](Self::set_disable_auto_compactions).
+    pub fn get_disable_auto_compactions(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_disable_auto_compactions(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_memtable_huge_page_size`](Self::set_memtable_huge_page_size).
+    pub fn get_memtable_huge_page_size(&self) -> size_t {
+        unsafe { ffi::rocksdb_options_get_memtable_huge_page_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_max_successive_merges`](Self::set_max_successive_merges).
+    pub fn get_max_successive_merges(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_max_successive_merges(self.inner) }
+    }
+
+    /// Returns the value set with [`set_bloom_locality`](Self::set_bloom_locality).
+    pub fn get_bloom_locality(&self) -> u32 {
+        unsafe { ffi::rocksdb_options_get_bloom_locality(self.inner) }
+    }
+
+    /// Returns the value set with [`set_inplace_update_support`](Self::set_inplace_update_support).
+    pub fn get_inplace_update_support(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_inplace_update_support(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_inplace_update_locks`](Self::set_inplace_update_locks).
+    pub fn get_inplace_update_locks(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_inplace_update_num_locks(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_skip_checking_sst_file_sizes_on_db_open`](Self::set_skip_checking_sst_file_sizes_on_db_open).
+    pub fn get_skip_checking_sst_file_sizes_on_db_open(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_skip_checking_sst_file_sizes_on_db_open(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_write_buffer_size_to_maintain`](Self::set_max_write_buffer_size_to_maintain).
+    pub fn get_max_write_buffer_size_to_maintain(&self) -> i64 {
+        unsafe { ffi::rocksdb_options_get_max_write_buffer_size_to_maintain(self.inner) }
+    }
+
+    /// Returns the value set with [`set_enable_pipelined_write`](Self::set_enable_pipelined_write).
+    pub fn get_enable_pipelined_write(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_enable_pipelined_write(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_report_bg_io_stats`](Self::set_report_bg_io_stats).
+    pub fn get_report_bg_io_stats(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_report_bg_io_stats(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_max_total_wal_size`](Self::set_max_total_wal_size).
+    pub fn get_max_total_wal_size(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_max_total_wal_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_wal_recovery_mode`](Self::set_wal_recovery_mode).
+    pub fn get_wal_recovery_mode(&self) -> DBRecoveryMode {
+        DBRecoveryMode::from_raw(unsafe { ffi::rocksdb_options_get_wal_recovery_mode(self.inner) })
+    }
+
+    /// Returns the value set with [`set_stats_dump_period_sec`](Self::set_stats_dump_period_sec).
+    pub fn get_stats_dump_period_sec(&self) -> c_uint {
+        unsafe { ffi::rocksdb_options_get_stats_dump_period_sec(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_stats_persist_period_sec`](Self::set_stats_persist_period_sec).
+    pub fn get_stats_persist_period_sec(&self) -> c_uint {
+        unsafe { ffi::rocksdb_options_get_stats_persist_period_sec(self.inner) }
+    }
+
+    /// Returns the value set with [`set_advise_random_on_open`](Self::set_advise_random_on_open).
+    pub fn get_advise_random_on_open(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_advise_random_on_open(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_use_adaptive_mutex`](Self::set_use_adaptive_mutex).
+    pub fn get_use_adaptive_mutex(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_use_adaptive_mutex(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_num_levels`](Self::set_num_levels).
+    pub fn get_num_levels(&self) -> c_int {
+        unsafe { ffi::rocksdb_options_get_num_levels(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_memtable_prefix_bloom_ratio`](Self::set_memtable_prefix_bloom_ratio).
+    pub fn get_memtable_prefix_bloom_ratio(&self) -> f64 {
+        unsafe { ffi::rocksdb_options_get_memtable_prefix_bloom_size_ratio(self.inner) }
+    }
+
+    /// Returns the value set with [`set_max_compaction_bytes`](Self::set_max_compaction_bytes).
+    pub fn get_max_compaction_bytes(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_max_compaction_bytes(self.inner) }
+    }
+
+    /// Returns the value set with [`set_wal_ttl_seconds`](Self::set_wal_ttl_seconds).
+    pub fn get_wal_ttl_seconds(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_WAL_ttl_seconds(self.inner) }
+    }
+
+    /// Returns the value set with [`set_wal_size_limit_mb`](Self::set_wal_size_limit_mb).
+    pub fn get_wal_size_limit_mb(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_WAL_size_limit_MB(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_manifest_preallocation_size`](Self::set_manifest_preallocation_size).
+    pub fn get_manifest_preallocation_size(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_manifest_preallocation_size(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_skip_stats_update_on_db_open`](Self::set_skip_stats_update_on_db_open).
+    pub fn get_skip_stats_update_on_db_open(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_skip_stats_update_on_db_open(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_keep_log_file_num`](Self::set_keep_log_file_num).
+    pub fn get_keep_log_file_num(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_keep_log_file_num(self.inner) }
+    }
+
+    /// Returns the value set with [`set_allow_mmap_writes`](Self::set_allow_mmap_writes).
+    pub fn get_allow_mmap_writes(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_allow_mmap_writes(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_allow_mmap_reads`](Self::set_allow_mmap_reads).
+    pub fn get_allow_mmap_reads(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_allow_mmap_reads(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_manual_wal_flush`](Self::set_manual_wal_flush).
+    pub fn get_manual_wal_flush(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_manual_wal_flush(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_atomic_flush`](Self::set_atomic_flush).
+    pub fn get_atomic_flush(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_atomic_flush(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_max_log_file_size`](Self::set_max_log_file_size).
+    pub fn get_max_log_file_size(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_max_log_file_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_log_file_time_to_roll`](Self::set_log_file_time_to_roll).
+    pub fn get_log_file_time_to_roll(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_log_file_time_to_roll(self.inner) }
+    }
+
+    /// Returns the value set with [`set_recycle_log_file_num`](Self::set_recycle_log_file_num).
+    pub fn get_recycle_log_file_num(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_recycle_log_file_num(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_soft_pending_compaction_bytes_limit`](Self::set_soft_pending_compaction_bytes_limit).
+    pub fn get_soft_pending_compaction_bytes_limit(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_soft_pending_compaction_bytes_limit(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_hard_pending_compaction_bytes_limit`](Self::set_hard_pending_compaction_bytes_limit).
+    pub fn get_hard_pending_compaction_bytes_limit(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_hard_pending_compaction_bytes_limit(self.inner) }
+    }
+
+    /// Returns the value set with [`set_arena_block_size`](Self::set_arena_block_size).
+    pub fn get_arena_block_size(&self) -> usize {
+        unsafe { ffi::rocksdb_options_get_arena_block_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_enable_blob_files`](Self::set_enable_blob_files).
+    pub fn get_enable_blob_files(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_enable_blob_files(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_min_blob_size`](Self::set_min_blob_size).
+    pub fn get_min_blob_size(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_min_blob_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_blob_file_size`](Self::set_blob_file_size).
+    pub fn get_blob_file_size(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_blob_file_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_blob_compression_type`](Self::set_blob_compression_type).
+    ///
+    /// Returns `None` if the value is not one of [`DBCompressionType`], e.g. when
+    /// the options were loaded from an options file.
+    pub fn get_blob_compression_type(&self) -> Option<DBCompressionType> {
+        DBCompressionType::from_raw(unsafe {
+            ffi::rocksdb_options_get_blob_compression_type(self.inner)
+        })
+    }
+
+    /// Returns the value set with [`set_enable_blob_gc`](Self::set_enable_blob_gc).
+    pub fn get_enable_blob_gc(&self) -> bool {
+        unsafe { ffi::rocksdb_options_get_enable_blob_gc(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_blob_gc_age_cutoff`](Self::set_blob_gc_age_cutoff).
+    pub fn get_blob_gc_age_cutoff(&self) -> c_double {
+        unsafe { ffi::rocksdb_options_get_blob_gc_age_cutoff(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_blob_gc_force_threshold`](Self::set_blob_gc_force_threshold).
+    pub fn get_blob_gc_force_threshold(&self) -> c_double {
+        unsafe { ffi::rocksdb_options_get_blob_gc_force_threshold(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_blob_compaction_readahead_size`](Self::set_blob_compaction_readahead_size).
+    pub fn get_blob_compaction_readahead_size(&self) -> u64 {
+        unsafe { ffi::rocksdb_options_get_blob_compaction_readahead_size(self.inner) }
+    }
+}
+
 impl Default for Options {
     fn default() -> Self {
         unsafe {
@@ -3240,6 +4728,9 @@ impl Default for Options {
             Self {
                 inner: opts,
                 outlive: OptionsMustOutliveDB::default(),
+                merge_operator: None,
+                level_multiplier_additional: Vec::new(),
+                verify_merge_operators: false,
             }
         }
     }
@@ -3267,6 +4758,27 @@ impl FlushOptions {
             ffi::rocksdb_flushoptions_set_wait(self.inner, c_uchar::from(wait));
         }
     }
+
+    /// Returns the value set with [`set_wait`](Self::set_wait).
+    pub fn get_wait(&self) -> bool {
+        unsafe { ffi::rocksdb_flushoptions_get_wait(self.inner) != 0 }
+    }
+}
+
+impl Clone for FlushOptions {
+    fn clone(&self) -> Self {
+        let mut opts = Self::default();
+        opts.set_wait(self.get_wait());
+        opts
+    }
+}
+
+impl fmt::Debug for FlushOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlushOptions")
+            .field("wait", &self.get_wait())
+            .finish()
+    }
 }
 
 impl Default for FlushOptions {
@@ -3286,6 +4798,23 @@ impl WriteOptions {
         WriteOptions::default()
     }
 
+    /// Returns write options skipping the WAL, for bulk ingestion which can
+    /// be replayed after a crash. The writes are only durable once flushed,
+    /// e.g. with [`flush_and_sync_all`](crate::DBCommon::flush_and_sync_all).
+    pub fn no_wal() -> WriteOptions {
+        let mut opts = WriteOptions::default();
+        opts.disable_wal(true);
+        opts
+    }
+
+    /// Returns write options syncing the WAL before each write returns, so
+    /// that acknowledged writes survive a machine crash.
+    pub fn synced() -> WriteOptions {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(true);
+        opts
+    }
+
     /// Sets the sync mode. If true, the write will be flushed
     /// from the operating system buffer cache before the write is considered complete.
     /// If this flag is true, writes will be slower.
@@ -3360,6 +4889,38 @@ impl WriteOptions {
             );
         }
     }
+
+    /// Returns the value set with [`set_sync`](Self::set_sync).
+    pub fn get_sync(&self) -> bool {
+        unsafe { ffi::rocksdb_writeoptions_get_sync(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`disable_wal`](Self::disable_wal).
+    pub fn get_disable_wal(&self) -> bool {
+        unsafe { ffi::rocksdb_writeoptions_get_disable_WAL(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_ignore_missing_column_families`](Self::set_ignore_missing_column_families).
+    pub fn get_ignore_missing_column_families(&self) -> bool {
+        unsafe { ffi::rocksdb_writeoptions_get_ignore_missing_column_families(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_no_slowdown`](Self::set_no_slowdown).
+    pub fn get_no_slowdown(&self) -> bool {
+        unsafe { ffi::rocksdb_writeoptions_get_no_slowdown(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_low_pri`](Self::set_low_pri).
+    pub fn get_low_pri(&self) -> bool {
+        unsafe { ffi::rocksdb_writeoptions_get_low_pri(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_memtable_insert_hint_per_batch`](Self::set_memtable_insert_hint_per_batch).
+    pub fn get_memtable_insert_hint_per_batch(&self) -> bool {
+        unsafe { ffi::rocksdb_writeoptions_get_memtable_insert_hint_per_batch(self.inner) != 0 }
+    }
 }
 
 impl Default for WriteOptions {
@@ -3374,6 +4935,38 @@ impl Default for WriteOptions {
     }
 }
 
+impl Clone for WriteOptions {
+    fn clone(&self) -> Self {
+        let mut opts = Self::default();
+        opts.set_sync(self.get_sync());
+        opts.disable_wal(self.get_disable_wal());
+        opts.set_ignore_missing_column_families(self.get_ignore_missing_column_families());
+        opts.set_no_slowdown(self.get_no_slowdown());
+        opts.set_low_pri(self.get_low_pri());
+        opts.set_memtable_insert_hint_per_batch(self.get_memtable_insert_hint_per_batch());
+        opts
+    }
+}
+
+impl fmt::Debug for WriteOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteOptions")
+            .field("sync", &self.get_sync())
+            .field("disable_wal", &self.get_disable_wal())
+            .field(
+                "ignore_missing_column_families",
+                &self.get_ignore_missing_column_families(),
+            )
+            .field("no_slowdown", &self.get_no_slowdown())
+            .field("low_pri", &self.get_low_pri())
+            .field(
+                "memtable_insert_hint_per_batch",
+                &self.get_memtable_insert_hint_per_batch(),
+            )
+            .finish()
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
@@ -3384,6 +4977,16 @@ pub enum ReadTier {
     BlockCache,
 }
 
+impl ReadTier {
+    fn from_raw(value: c_int) -> Option<Self> {
+        match value {
+            0 => Some(Self::All),
+            1 => Some(Self::BlockCache),
+            _ => None,
+        }
+    }
+}
+
 impl ReadOptions {
     // TODO add snapshot setting here
     // TODO add snapshot wrapper structs with proper destructors;
@@ -3404,6 +5007,7 @@ impl ReadOptions {
     /// The snapshot must belong to the DB that is being read and must
     /// not have been released.
     pub fn set_snapshot<D: DBAccess>(&mut self, snapshot: &SnapshotWithThreadMode<D>) {
+        self.snapshot = snapshot.inner;
         unsafe {
             ffi::rocksdb_readoptions_set_snapshot(self.inner, snapshot.inner);
         }
@@ -3420,6 +5024,15 @@ impl ReadOptions {
         self.set_upper_bound_impl(Some(key.into()));
     }
 
+    /// Returns the lower and upper bounds owned by the options, the bounds
+    /// set with [`scoped_bounds`](Self::scoped_bounds) are not included.
+    pub(crate) fn iterate_bounds(&self) -> (Option<&[u8]>, Option<&[u8]>) {
+        (
+            self.iterate_lower_bound.as_deref(),
+            self.iterate_upper_bound.as_deref(),
+        )
+    }
+
     /// Sets lower and upper bounds based on the provided range.  This is
     /// similar to setting lower and upper bounds separately except that it also
     /// allows either bound to be reset.
@@ -3461,6 +5074,59 @@ impl ReadOptions {
         self.set_upper_bound_impl(upper);
     }
 
+    /// Sets iterator bounds borrowed from `lower` and `upper` instead of
+    /// copied, for the iterators opened with the returned options, e.g. by
+    /// [`iterator_scoped`](crate::DBCommon::iterator_scoped).
+    ///
+    /// This saves copying the bounds when opening many short-lived
+    /// iterators, the iterators cannot outlive the borrowed bounds. Bounds
+    /// previously set on these options are replaced, `None` leaving the
+    /// corresponding side unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{IteratorMode, ReadOptions, DB};
+    ///
+    /// let path = "_path_for_rocksdb_storage_scoped_bounds";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     db.put(b"user:1", b"a").unwrap();
+    ///     db.put(b"user:2", b"b").unwrap();
+    ///     db.put(b"video:1", b"c").unwrap();
+    ///
+    ///     let lower: &[u8] = b"user:";
+    ///     let upper = b"user;".to_vec();
+    ///     let readopts = ReadOptions::default().scoped_bounds(Some(lower), Some(&upper));
+    ///     assert_eq!(db.iterator_scoped(IteratorMode::Start, readopts).count(), 2);
+    /// }
+    /// let _ = DB::destroy(&rocksdb::Options::default(), path);
+    /// ```
+    pub fn scoped_bounds<'b>(
+        mut self,
+        lower: Option<&'b [u8]>,
+        upper: Option<&'b [u8]>,
+    ) -> ScopedReadOptions<'b> {
+        fn raw_parts(bound: Option<&[u8]>) -> (*const c_char, size_t) {
+            bound.map_or((std::ptr::null(), 0), |bound| {
+                (bound.as_ptr() as *const c_char, bound.len())
+            })
+        }
+        let (lower_ptr, lower_len) = raw_parts(lower);
+        let (upper_ptr, upper_len) = raw_parts(upper);
+        unsafe {
+            ffi::rocksdb_readoptions_set_iterate_lower_bound(self.inner, lower_ptr, lower_len);
+            ffi::rocksdb_readoptions_set_iterate_upper_bound(self.inner, upper_ptr, upper_len);
+        }
+        // the owned bounds are not referenced anymore
+        self.iterate_lower_bound = None;
+        self.iterate_upper_bound = None;
+        ScopedReadOptions {
+            readopts: self,
+            bounds: PhantomData,
+        }
+    }
+
     fn set_lower_bound_impl(&mut self, bound: Option<Vec<u8>>) {
         let (ptr, len) = if let Some(ref bound) = bound {
             (bound.as_ptr() as *const c_char, bound.len())
@@ -3593,6 +5259,7 @@ impl ReadOptions {
     ///
     /// Default: `false`
     pub fn set_auto_readahead_size(&mut self, v: bool) {
+        self.auto_readahead_size = v;
         unsafe {
             ffi::rocksdb_readoptions_set_auto_readahead_size(self.inner, c_uchar::from(v));
         }
@@ -3631,6 +5298,73 @@ impl ReadOptions {
             ffi::rocksdb_readoptions_set_async_io(self.inner, c_uchar::from(v));
         }
     }
+
+    /// Returns the value set with [`fill_cache`](Self::fill_cache).
+    pub fn get_fill_cache(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_fill_cache(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_read_tier`](Self::set_read_tier).
+    ///
+    /// Returns `None` if the value is not one of [`ReadTier`], e.g. when
+    /// the options were loaded from an options file.
+    pub fn get_read_tier(&self) -> Option<ReadTier> {
+        ReadTier::from_raw(unsafe { ffi::rocksdb_readoptions_get_read_tier(self.inner) })
+    }
+
+    /// Returns the value set with [`set_prefix_same_as_start`](Self::set_prefix_same_as_start).
+    pub fn get_prefix_same_as_start(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_prefix_same_as_start(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_total_order_seek`](Self::set_total_order_seek).
+    pub fn get_total_order_seek(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_total_order_seek(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_skippable_internal_keys`](Self::set_max_skippable_internal_keys).
+    pub fn get_max_skippable_internal_keys(&self) -> u64 {
+        unsafe { ffi::rocksdb_readoptions_get_max_skippable_internal_keys(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_background_purge_on_iterator_cleanup`](Self::set_background_purge_on_iterator_cleanup).
+    pub fn get_background_purge_on_iterator_cleanup(&self) -> bool {
+        unsafe {
+            ffi::rocksdb_readoptions_get_background_purge_on_iterator_cleanup(self.inner) != 0
+        }
+    }
+
+    /// Returns the value set with [`set_ignore_range_deletions`](Self::set_ignore_range_deletions).
+    pub fn get_ignore_range_deletions(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_ignore_range_deletions(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_verify_checksums`](Self::set_verify_checksums).
+    pub fn get_verify_checksums(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_verify_checksums(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_readahead_size`](Self::set_readahead_size).
+    pub fn get_readahead_size(&self) -> usize {
+        unsafe { ffi::rocksdb_readoptions_get_readahead_size(self.inner) }
+    }
+
+    /// Returns the value set with [`set_tailing`](Self::set_tailing).
+    pub fn get_tailing(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_tailing(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_pin_data`](Self::set_pin_data).
+    pub fn get_pin_data(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_pin_data(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_async_io`](Self::set_async_io).
+    pub fn get_async_io(&self) -> bool {
+        unsafe { ffi::rocksdb_readoptions_get_async_io(self.inner) != 0 }
+    }
 }
 
 impl Default for ReadOptions {
@@ -3640,8 +5374,216 @@ impl Default for ReadOptions {
                 inner: ffi::rocksdb_readoptions_create(),
                 iterate_upper_bound: None,
                 iterate_lower_bound: None,
+                snapshot: std::ptr::null(),
+                auto_readahead_size: false,
+            }
+        }
+    }
+}
+
+impl Clone for ReadOptions {
+    fn clone(&self) -> Self {
+        let mut opts = Self::default();
+        opts.fill_cache(self.get_fill_cache());
+        if let Some(tier) = self.get_read_tier() {
+            opts.set_read_tier(tier);
+        }
+        opts.set_prefix_same_as_start(self.get_prefix_same_as_start());
+        opts.set_total_order_seek(self.get_total_order_seek());
+        opts.set_max_skippable_internal_keys(self.get_max_skippable_internal_keys());
+        opts.set_background_purge_on_iterator_cleanup(
+            self.get_background_purge_on_iterator_cleanup(),
+        );
+        opts.set_ignore_range_deletions(self.get_ignore_range_deletions());
+        opts.set_verify_checksums(self.get_verify_checksums());
+        opts.set_readahead_size(self.get_readahead_size());
+        opts.set_auto_readahead_size(self.auto_readahead_size);
+        opts.set_tailing(self.get_tailing());
+        opts.set_pin_data(self.get_pin_data());
+        opts.set_async_io(self.get_async_io());
+        opts.set_lower_bound_impl(self.iterate_lower_bound.clone());
+        opts.set_upper_bound_impl(self.iterate_upper_bound.clone());
+        if !self.snapshot.is_null() {
+            opts.snapshot = self.snapshot;
+            unsafe {
+                ffi::rocksdb_readoptions_set_snapshot(opts.inner, self.snapshot);
             }
         }
+        opts
+    }
+}
+
+impl fmt::Debug for ReadOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadOptions")
+            .field("fill_cache", &self.get_fill_cache())
+            .field("read_tier", &self.get_read_tier())
+            .field("iterate_lower_bound", &self.iterate_lower_bound)
+            .field("iterate_upper_bound", &self.iterate_upper_bound)
+            .field("snapshot", &!self.snapshot.is_null())
+            .field("prefix_same_as_start", &self.get_prefix_same_as_start())
+            .field("total_order_seek", &self.get_total_order_seek())
+            .field(
+                "max_skippable_internal_keys",
+                &self.get_max_skippable_internal_keys(),
+            )
+            .field(
+                "background_purge_on_iterator_cleanup",
+                &self.get_background_purge_on_iterator_cleanup(),
+            )
+            .field("ignore_range_deletions", &self.get_ignore_range_deletions())
+            .field("verify_checksums", &self.get_verify_checksums())
+            .field("readahead_size", &self.get_readahead_size())
+            .field("auto_readahead_size", &self.auto_readahead_size)
+            .field("tailing", &self.get_tailing())
+            .field("pin_data", &self.get_pin_data())
+            .field("async_io", &self.get_async_io())
+            .finish()
+    }
+}
+
+/// [`ReadOptions`] with iterator bounds borrowed for the lifetime `'b`,
+/// created by [`ReadOptions::scoped_bounds`].
+///
+/// The options dereference to [`ReadOptions`] to read the other settings.
+pub struct ScopedReadOptions<'b> {
+    readopts: ReadOptions,
+    bounds: PhantomData<&'b [u8]>,
+}
+
+impl<'b> ScopedReadOptions<'b> {
+    /// Returns the options, whose bounds must outlive the iterators created
+    /// with them.
+    pub(crate) fn into_inner(self) -> ReadOptions {
+        self.readopts
+    }
+}
+
+impl<'b> std::ops::Deref for ScopedReadOptions<'b> {
+    type Target = ReadOptions;
+
+    fn deref(&self) -> &ReadOptions {
+        &self.readopts
+    }
+}
+
+/// Builds [`ReadOptions`] once to create them cheaply for every request.
+///
+/// The builder can be shared between threads, [`build`](Self::build) clones
+/// the options it holds.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::ReadOptionsBuilder;
+///
+/// let scan_options = ReadOptionsBuilder::new()
+///     .fill_cache(false)
+///     .readahead_size(2 << 20)
+///     .iterate_range(b"user:".to_vec()..b"user;".to_vec());
+///
+/// let readopts = scan_options.build();
+/// assert!(!readopts.get_fill_cache());
+/// assert_eq!(readopts.get_readahead_size(), 2 << 20);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReadOptionsBuilder {
+    opts: ReadOptions,
+}
+
+impl ReadOptionsBuilder {
+    /// Creates a builder starting from the default read options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates new read options with the settings of the builder.
+    pub fn build(&self) -> ReadOptions {
+        self.opts.clone()
+    }
+
+    /// See [`ReadOptions::fill_cache`].
+    pub fn fill_cache(mut self, v: bool) -> Self {
+        self.opts.fill_cache(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_iterate_range`].
+    pub fn iterate_range(mut self, range: impl crate::IterateBounds) -> Self {
+        self.opts.set_iterate_range(range);
+        self
+    }
+
+    /// See [`ReadOptions::set_read_tier`].
+    pub fn read_tier(mut self, tier: ReadTier) -> Self {
+        self.opts.set_read_tier(tier);
+        self
+    }
+
+    /// See [`ReadOptions::set_prefix_same_as_start`].
+    pub fn prefix_same_as_start(mut self, v: bool) -> Self {
+        self.opts.set_prefix_same_as_start(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_total_order_seek`].
+    pub fn total_order_seek(mut self, v: bool) -> Self {
+        self.opts.set_total_order_seek(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_max_skippable_internal_keys`].
+    pub fn max_skippable_internal_keys(mut self, num: u64) -> Self {
+        self.opts.set_max_skippable_internal_keys(num);
+        self
+    }
+
+    /// See [`ReadOptions::set_background_purge_on_iterator_cleanup`].
+    pub fn background_purge_on_iterator_cleanup(mut self, v: bool) -> Self {
+        self.opts.set_background_purge_on_iterator_cleanup(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_ignore_range_deletions`].
+    pub fn ignore_range_deletions(mut self, v: bool) -> Self {
+        self.opts.set_ignore_range_deletions(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_verify_checksums`].
+    pub fn verify_checksums(mut self, v: bool) -> Self {
+        self.opts.set_verify_checksums(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_readahead_size`].
+    pub fn readahead_size(mut self, v: usize) -> Self {
+        self.opts.set_readahead_size(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_auto_readahead_size`].
+    pub fn auto_readahead_size(mut self, v: bool) -> Self {
+        self.opts.set_auto_readahead_size(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_tailing`].
+    pub fn tailing(mut self, v: bool) -> Self {
+        self.opts.set_tailing(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_pin_data`].
+    pub fn pin_data(mut self, v: bool) -> Self {
+        self.opts.set_pin_data(v);
+        self
+    }
+
+    /// See [`ReadOptions::set_async_io`].
+    pub fn async_io(mut self, v: bool) -> Self {
+        self.opts.set_async_io(v);
+        self
     }
 }
 
@@ -3737,6 +5679,31 @@ pub enum DataBlockIndexType {
     BinaryAndHash = 1,
 }
 
+/// Used by [`Options::set_block_based_table_layout`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum IndexShorteningMode {
+    /// Use the full keys as separators.
+    NoShortening,
+    /// Shorten the separators between data blocks, but keep the last key of
+    /// the file. This is the default.
+    ShortenSeparators,
+    /// Also replace the last key of the file by a shorter successor, which
+    /// makes the index smaller but lets seeks past the last key read the
+    /// file.
+    ShortenSeparatorsAndSuccessor,
+}
+
+impl IndexShorteningMode {
+    fn option_name(self) -> &'static str {
+        match self {
+            Self::NoShortening => "kNoShortening",
+            Self::ShortenSeparators => "kShortenSeparators",
+            Self::ShortenSeparatorsAndSuccessor => "kShortenSeparatorsAndSuccessor",
+        }
+    }
+}
+
 /// Defines the underlying memtable implementation.
 /// See official [wiki](https://github.com/facebook/rocksdb/wiki/MemTable) for more information.
 pub enum MemtableFactory {
@@ -3760,6 +5727,71 @@ pub enum ChecksumType {
     XXH3 = 4, // Supported since RocksDB 6.27
 }
 
+/// Used by [`Options::enable_file_checksums`] and
+/// [`ChecksumGenType::checksum_file`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChecksumGenType {
+    /// Full file CRC32c checksum, the generator shipped with RocksDB.
+    Crc32c,
+    /// Full file XXH64 checksum, only computed by
+    /// [`ChecksumGenType::checksum_file`].
+    XxHash64,
+}
+
+impl ChecksumGenType {
+    fn factory_name(self) -> Option<&'static str> {
+        match self {
+            ChecksumGenType::Crc32c => Some("FileChecksumGenCrc32cFactory"),
+            ChecksumGenType::XxHash64 => None,
+        }
+    }
+}
+
+/// Used by [`Options::set_builtin_merge_operator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum BuiltinMergeOperator {
+    /// Adds up 64-bit unsigned integers encoded as 8 little endian bytes,
+    /// e.g. with [`u64::to_le_bytes`]. Operands of another size are logged
+    /// and treated as 0.
+    UInt64Add,
+    /// Keeps the lexicographically largest of the existing value and the
+    /// operands.
+    Max,
+    /// Appends the operands to the existing value, separated by the given
+    /// delimiter, which must not contain `{` or `}`.
+    StringAppend(String),
+    /// Merges comma separated lists of sorted integers into one sorted list.
+    SortList,
+    /// XORs the operands into the existing value.
+    BytesXor,
+    /// Replaces the existing value with the latest operand, like a put.
+    Put,
+}
+
+impl BuiltinMergeOperator {
+    fn to_options(&self) -> Result<String, Error> {
+        Ok(match self {
+            BuiltinMergeOperator::UInt64Add => "uint64add".to_owned(),
+            BuiltinMergeOperator::Max => "max".to_owned(),
+            // the braces keep delimiters like `;` from ending the option, but
+            // a brace in the delimiter would end or nest the value
+            BuiltinMergeOperator::StringAppend(delimiter) => {
+                if delimiter.contains(|c| c == '{' || c == '}') {
+                    return Err(Error::new(format!(
+                        "Invalid argument: the delimiter {delimiter:?} contains a brace"
+                    )));
+                }
+                format!("{{id=stringappend;delimiter={{{delimiter}}}}}")
+            }
+            BuiltinMergeOperator::SortList => "sortlist".to_owned(),
+            BuiltinMergeOperator::BytesXor => "bytesxor".to_owned(),
+            BuiltinMergeOperator::Put => "put".to_owned(),
+        })
+    }
+}
+
 /// Used in [`PlainTableFactoryOptions`].
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum KeyEncodingType {
@@ -3788,6 +5820,7 @@ impl Default for KeyEncodingType {
 ///  encoding_type: KeyEncodingType::Plain
 ///  full_scan_mode: false
 ///  store_index_in_file: false
+#[derive(Debug, Clone)]
 pub struct PlainTableFactoryOptions {
     pub user_key_length: u32,
     pub bloom_bits_per_key: i32,
@@ -3811,6 +5844,23 @@ pub enum DBCompressionType {
     Zstd = ffi::rocksdb_zstd_compression as isize,
 }
 
+impl DBCompressionType {
+    fn from_raw(value: c_int) -> Option<Self> {
+        [
+            Self::None,
+            Self::Snappy,
+            Self::Zlib,
+            Self::Bz2,
+            Self::Lz4,
+            Self::Lz4hc,
+            Self::Zstd,
+        ]
+        .iter()
+        .copied()
+        .find(|t| *t as c_int == value)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub enum DBCompactionStyle {
@@ -3819,6 +5869,28 @@ pub enum DBCompactionStyle {
     Fifo = ffi::rocksdb_fifo_compaction as isize,
 }
 
+impl DBCompactionStyle {
+    fn from_raw(value: c_int) -> Option<Self> {
+        [Self::Level, Self::Universal, Self::Fifo]
+            .iter()
+            .copied()
+            .find(|style| *style as c_int == value)
+    }
+}
+
+/// The capacity of a level, as predicted by [`Options::describe_levels`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LevelDescription {
+    /// Number of the level
+    pub level: usize,
+    /// Size above which the level is compacted into the next one, `None` for
+    /// level 0, which is compacted by number of files, and for the levels
+    /// which are not used
+    pub max_bytes: Option<u64>,
+    /// Size of the files compactions write to the level
+    pub target_file_size: u64,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub enum DBRecoveryMode {
@@ -3828,6 +5900,22 @@ pub enum DBRecoveryMode {
     SkipAnyCorruptedRecord = ffi::rocksdb_skip_any_corrupted_records_recovery as isize,
 }
 
+impl DBRecoveryMode {
+    fn from_raw(value: c_int) -> Self {
+        // these are all the recovery modes of RocksDB
+        [
+            Self::TolerateCorruptedTailRecords,
+            Self::AbsoluteConsistency,
+            Self::PointInTime,
+            Self::SkipAnyCorruptedRecord,
+        ]
+        .iter()
+        .copied()
+        .find(|mode| *mode as c_int == value)
+        .unwrap_or(Self::PointInTime)
+    }
+}
+
 /// File access pattern once a compaction has started
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
@@ -3875,6 +5963,28 @@ impl FifoCompactOptions {
             ffi::rocksdb_fifo_compaction_options_set_max_table_files_size(self.inner, nbytes);
         }
     }
+
+    /// Returns the value set with
+    /// [`set_max_table_files_size`](Self::set_max_table_files_size).
+    pub fn get_max_table_files_size(&self) -> u64 {
+        unsafe { ffi::rocksdb_fifo_compaction_options_get_max_table_files_size(self.inner) }
+    }
+}
+
+impl Clone for FifoCompactOptions {
+    fn clone(&self) -> Self {
+        let mut opts = Self::default();
+        opts.set_max_table_files_size(self.get_max_table_files_size());
+        opts
+    }
+}
+
+impl fmt::Debug for FifoCompactOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FifoCompactOptions")
+            .field("max_table_files_size", &self.get_max_table_files_size())
+            .finish()
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -3884,6 +5994,16 @@ pub enum UniversalCompactionStopStyle {
     Total = ffi::rocksdb_total_size_compaction_stop_style as isize,
 }
 
+impl UniversalCompactionStopStyle {
+    fn from_raw(value: c_int) -> Option<Self> {
+        match value {
+            v if v == Self::Similar as c_int => Some(Self::Similar),
+            v if v == Self::Total as c_int => Some(Self::Total),
+            _ => None,
+        }
+    }
+}
+
 pub struct UniversalCompactOptions {
     pub(crate) inner: *mut ffi::rocksdb_universal_compaction_options_t,
 }
@@ -3991,6 +6111,77 @@ impl UniversalCompactOptions {
             ffi::rocksdb_universal_compaction_options_set_stop_style(self.inner, style as c_int);
         }
     }
+
+    /// Returns the value set with [`set_size_ratio`](Self::set_size_ratio).
+    pub fn get_size_ratio(&self) -> c_int {
+        unsafe { ffi::rocksdb_universal_compaction_options_get_size_ratio(self.inner) }
+    }
+
+    /// Returns the value set with [`set_min_merge_width`](Self::set_min_merge_width).
+    pub fn get_min_merge_width(&self) -> c_int {
+        unsafe { ffi::rocksdb_universal_compaction_options_get_min_merge_width(self.inner) }
+    }
+
+    /// Returns the value set with [`set_max_merge_width`](Self::set_max_merge_width).
+    pub fn get_max_merge_width(&self) -> c_int {
+        unsafe { ffi::rocksdb_universal_compaction_options_get_max_merge_width(self.inner) }
+    }
+
+    /// Returns the value set with
+    /// [`set_max_size_amplification_percent`](Self::set_max_size_amplification_percent).
+    pub fn get_max_size_amplification_percent(&self) -> c_int {
+        unsafe {
+            ffi::rocksdb_universal_compaction_options_get_max_size_amplification_percent(self.inner)
+        }
+    }
+
+    /// Returns the value set with
+    /// [`set_compression_size_percent`](Self::set_compression_size_percent).
+    pub fn get_compression_size_percent(&self) -> c_int {
+        unsafe {
+            ffi::rocksdb_universal_compaction_options_get_compression_size_percent(self.inner)
+        }
+    }
+
+    /// Returns the value set with [`set_stop_style`](Self::set_stop_style).
+    pub fn get_stop_style(&self) -> Option<UniversalCompactionStopStyle> {
+        let style = unsafe { ffi::rocksdb_universal_compaction_options_get_stop_style(self.inner) };
+        UniversalCompactionStopStyle::from_raw(style)
+    }
+}
+
+impl Clone for UniversalCompactOptions {
+    fn clone(&self) -> Self {
+        let mut opts = Self::default();
+        opts.set_size_ratio(self.get_size_ratio());
+        opts.set_min_merge_width(self.get_min_merge_width());
+        opts.set_max_merge_width(self.get_max_merge_width());
+        opts.set_max_size_amplification_percent(self.get_max_size_amplification_percent());
+        opts.set_compression_size_percent(self.get_compression_size_percent());
+        if let Some(style) = self.get_stop_style() {
+            opts.set_stop_style(style);
+        }
+        opts
+    }
+}
+
+impl fmt::Debug for UniversalCompactOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UniversalCompactOptions")
+            .field("size_ratio", &self.get_size_ratio())
+            .field("min_merge_width", &self.get_min_merge_width())
+            .field("max_merge_width", &self.get_max_merge_width())
+            .field(
+                "max_size_amplification_percent",
+                &self.get_max_size_amplification_percent(),
+            )
+            .field(
+                "compression_size_percent",
+                &self.get_compression_size_percent(),
+            )
+            .field("stop_style", &self.get_stop_style())
+            .finish()
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -4009,6 +6200,18 @@ pub enum BottommostLevelCompaction {
     ForceOptimized,
 }
 
+impl BottommostLevelCompaction {
+    fn from_raw(value: c_uchar) -> Option<Self> {
+        match value {
+            0 => Some(Self::Skip),
+            1 => Some(Self::IfHaveCompactionFilter),
+            2 => Some(Self::Force),
+            3 => Some(Self::ForceOptimized),
+            _ => None,
+        }
+    }
+}
+
 pub struct CompactOptions {
     pub(crate) inner: *mut ffi::rocksdb_compactoptions_t,
 }
@@ -4067,6 +6270,60 @@ impl CompactOptions {
             ffi::rocksdb_compactoptions_set_target_level(self.inner, lvl);
         }
     }
+
+    /// Returns the value set with
+    /// [`set_exclusive_manual_compaction`](Self::set_exclusive_manual_compaction).
+    pub fn get_exclusive_manual_compaction(&self) -> bool {
+        unsafe { ffi::rocksdb_compactoptions_get_exclusive_manual_compaction(self.inner) != 0 }
+    }
+
+    /// Returns the value set with
+    /// [`set_bottommost_level_compaction`](Self::set_bottommost_level_compaction).
+    pub fn get_bottommost_level_compaction(&self) -> Option<BottommostLevelCompaction> {
+        let lvl =
+            unsafe { ffi::rocksdb_compactoptions_get_bottommost_level_compaction(self.inner) };
+        BottommostLevelCompaction::from_raw(lvl)
+    }
+
+    /// Returns the value set with [`set_change_level`](Self::set_change_level).
+    pub fn get_change_level(&self) -> bool {
+        unsafe { ffi::rocksdb_compactoptions_get_change_level(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_target_level`](Self::set_target_level).
+    pub fn get_target_level(&self) -> c_int {
+        unsafe { ffi::rocksdb_compactoptions_get_target_level(self.inner) }
+    }
+}
+
+impl Clone for CompactOptions {
+    fn clone(&self) -> Self {
+        let mut opts = Self::default();
+        opts.set_exclusive_manual_compaction(self.get_exclusive_manual_compaction());
+        if let Some(lvl) = self.get_bottommost_level_compaction() {
+            opts.set_bottommost_level_compaction(lvl);
+        }
+        opts.set_change_level(self.get_change_level());
+        opts.set_target_level(self.get_target_level());
+        opts
+    }
+}
+
+impl fmt::Debug for CompactOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactOptions")
+            .field(
+                "exclusive_manual_compaction",
+                &self.get_exclusive_manual_compaction(),
+            )
+            .field(
+                "bottommost_level_compaction",
+                &self.get_bottommost_level_compaction(),
+            )
+            .field("change_level", &self.get_change_level())
+            .field("target_level", &self.get_target_level())
+            .finish()
+    }
 }
 
 pub struct WaitForCompactOptions {
@@ -4126,6 +6383,42 @@ impl WaitForCompactOptions {
             ffi::rocksdb_wait_for_compact_options_set_timeout(self.inner, microseconds);
         }
     }
+
+    /// Returns the value set with [`set_abort_on_pause`](Self::set_abort_on_pause).
+    pub fn get_abort_on_pause(&self) -> bool {
+        unsafe { ffi::rocksdb_wait_for_compact_options_get_abort_on_pause(self.inner) != 0 }
+    }
+
+    /// Returns the value set with [`set_flush`](Self::set_flush).
+    pub fn get_flush(&self) -> bool {
+        unsafe { ffi::rocksdb_wait_for_compact_options_get_flush(self.inner) != 0 }
+    }
+
+    /// Returns the timeout in microseconds set with
+    /// [`set_timeout`](Self::set_timeout).
+    pub fn get_timeout(&self) -> u64 {
+        unsafe { ffi::rocksdb_wait_for_compact_options_get_timeout(self.inner) }
+    }
+}
+
+impl Clone for WaitForCompactOptions {
+    fn clone(&self) -> Self {
+        let mut opts = Self::default();
+        opts.set_abort_on_pause(self.get_abort_on_pause());
+        opts.set_flush(self.get_flush());
+        opts.set_timeout(self.get_timeout());
+        opts
+    }
+}
+
+impl fmt::Debug for WaitForCompactOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitForCompactOptions")
+            .field("abort_on_pause", &self.get_abort_on_pause())
+            .field("flush", &self.get_flush())
+            .field("timeout", &self.get_timeout())
+            .finish()
+    }
 }
 
 /// Represents a path where sst files can be put into
@@ -85,6 +85,30 @@ impl Env {
         }
     }
 
+    /// Returns the number of background worker threads of the default, `LOW`
+    /// priority thread pool.
+    pub fn get_background_threads(&self) -> c_int {
+        unsafe { ffi::rocksdb_env_get_background_threads(self.0.inner) }
+    }
+
+    /// Returns the size of the high priority thread pool, which runs the
+    /// memtable flushes.
+    pub fn get_high_priority_background_threads(&self) -> c_int {
+        unsafe { ffi::rocksdb_env_get_high_priority_background_threads(self.0.inner) }
+    }
+
+    /// Returns the size of the low priority thread pool, which runs the
+    /// compactions.
+    pub fn get_low_priority_background_threads(&self) -> c_int {
+        unsafe { ffi::rocksdb_env_get_low_priority_background_threads(self.0.inner) }
+    }
+
+    /// Returns the size of the bottom priority thread pool, which runs the
+    /// compactions into the bottommost level when it has any threads.
+    pub fn get_bottom_priority_background_threads(&self) -> c_int {
+        unsafe { ffi::rocksdb_env_get_bottom_priority_background_threads(self.0.inner) }
+    }
+
     /// Wait for all threads started by StartThread to terminate.
     pub fn join_all_threads(&mut self) {
         unsafe {
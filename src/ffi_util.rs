@@ -61,20 +61,36 @@ pub(crate) fn to_cpath<P: AsRef<Path>>(path: P) -> Result<CString, Error> {
 
 macro_rules! ffi_try {
     ( $($function:ident)::*() ) => {
-        ffi_try_impl!($($function)::*())
+        ffi_try_impl!($($function)::*(); None, None)
     };
 
     ( $($function:ident)::*( $arg1:expr $(, $arg:expr)* $(,)? ) ) => {
-        ffi_try_impl!($($function)::*($arg1 $(, $arg)* ,))
+        ffi_try_impl!($($function)::*($arg1 $(, $arg)* ,); None, None)
+    };
+
+    // single-key operations, whose errors record the key length and the
+    // column family with the `rich-errors` feature
+    ( $($function:ident)::*( $arg1:expr $(, $arg:expr)* $(,)? ); key = $key:expr ) => {
+        ffi_try_impl!($($function)::*($arg1 $(, $arg)* ,); None, Some($key.len()))
+    };
+
+    (
+        $($function:ident)::*( $arg1:expr $(, $arg:expr)* $(,)? );
+        cf = $cf:expr, key = $key:expr
+    ) => {
+        ffi_try_impl!($($function)::*($arg1 $(, $arg)* ,); Some($cf), Some($key.len()))
     };
 }
 
 macro_rules! ffi_try_impl {
-    ( $($function:ident)::*( $($arg:expr,)*) ) => {{
+    ( $($function:ident)::*( $($arg:expr,)*); $cf:expr, $key_len:expr ) => {{
         let mut err: *mut ::libc::c_char = ::std::ptr::null_mut();
         let result = $($function)::*($($arg,)* &mut err);
         if !err.is_null() {
-            return Err(Error::new($crate::ffi_util::error_message(err)));
+            let error = Error::new($crate::ffi_util::error_message(err));
+            #[cfg(feature = "rich-errors")]
+            let error = error.with_context(stringify!($($function)::*), $cf, $key_len);
+            return Err(error);
         }
         result
     }};
@@ -0,0 +1,207 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::{wal_file::crc32c_extend, ChecksumGenType, Error};
+
+/// Name RocksDB records for the checksums of its CRC32c generator.
+pub(crate) const CRC32C_FUNC_NAME: &str = "FileChecksumCrc32c";
+/// Name given to XXH64 checksums, which RocksDB has no generator for.
+pub(crate) const XXH64_FUNC_NAME: &str = "FileChecksumXxh64";
+
+/// The full checksum of a file, as recorded in the MANIFEST for the SST
+/// files of a DB with [`Options::enable_file_checksums`], or in the metadata
+/// of a backup.
+///
+/// [`Options::enable_file_checksums`]: crate::Options::enable_file_checksums
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileChecksum {
+    /// Raw bytes of the checksum, big endian for the checksums computed by
+    /// [`ChecksumGenType::checksum_file`]
+    pub value: Vec<u8>,
+    /// Name of the checksum function, e.g. `FileChecksumCrc32c`
+    pub func_name: String,
+}
+
+impl FileChecksum {
+    pub(crate) fn crc32c(crc: u32) -> Self {
+        Self {
+            value: crc.to_be_bytes().to_vec(),
+            func_name: CRC32C_FUNC_NAME.to_owned(),
+        }
+    }
+}
+
+impl ChecksumGenType {
+    /// Returns the name of the checksum function, as found in
+    /// [`FileChecksum::func_name`].
+    pub fn func_name(self) -> &'static str {
+        match self {
+            ChecksumGenType::Crc32c => CRC32C_FUNC_NAME,
+            ChecksumGenType::XxHash64 => XXH64_FUNC_NAME,
+        }
+    }
+
+    /// Computes the checksum of a file, reading it in chunks.
+    ///
+    /// CRC32c checksums are computed like RocksDB's generator does, so they
+    /// can be compared with the checksums of [`LiveFile`](crate::LiveFile).
+    pub fn checksum_file<P: AsRef<Path>>(self, path: P) -> Result<FileChecksum, Error> {
+        let value = match self {
+            ChecksumGenType::Crc32c => {
+                let mut crc = 0;
+                for_each_chunk(path.as_ref(), |chunk| crc = crc32c_extend(crc, chunk))?;
+                crc.to_be_bytes().to_vec()
+            }
+            ChecksumGenType::XxHash64 => {
+                let mut hasher = Xxh64::new();
+                for_each_chunk(path.as_ref(), |chunk| hasher.update(chunk))?;
+                hasher.finish().to_be_bytes().to_vec()
+            }
+        };
+        Ok(FileChecksum {
+            value,
+            func_name: self.func_name().to_owned(),
+        })
+    }
+}
+
+fn for_each_chunk(path: &Path, mut f: impl FnMut(&[u8])) -> Result<(), Error> {
+    let read_error = |err| {
+        Error::new(format!(
+            "IO error: failed to read {}: {err}",
+            path.display()
+        ))
+    };
+    let mut file = File::open(path).map_err(read_error)?;
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(len) => f(&buffer[..len]),
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(read_error(err)),
+        }
+    }
+}
+
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+/// Streaming XXH64 with a seed of 0, see
+/// https://github.com/Cyan4973/xxHash/blob/dev/doc/xxhash_spec.md
+struct Xxh64 {
+    accumulators: [u64; 4],
+    buffer: [u8; 32],
+    buffered: usize,
+    total_len: u64,
+}
+
+impl Xxh64 {
+    fn new() -> Self {
+        Self {
+            accumulators: [
+                PRIME64_1.wrapping_add(PRIME64_2),
+                PRIME64_2,
+                0,
+                PRIME64_1.wrapping_neg(),
+            ],
+            buffer: [0; 32],
+            buffered: 0,
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        if self.buffered > 0 {
+            let len = data.len().min(32 - self.buffered);
+            self.buffer[self.buffered..self.buffered + len].copy_from_slice(&data[..len]);
+            self.buffered += len;
+            data = &data[len..];
+            if self.buffered < 32 {
+                return;
+            }
+            let stripe = self.buffer;
+            self.consume(&stripe);
+            self.buffered = 0;
+        }
+        let mut stripes = data.chunks_exact(32);
+        for stripe in &mut stripes {
+            self.consume(stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    fn consume(&mut self, stripe: &[u8]) {
+        for (accumulator, lane) in self.accumulators.iter_mut().zip(stripe.chunks_exact(8)) {
+            *accumulator = xxh64_round(*accumulator, read_u64(lane));
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut hash = if self.total_len >= 32 {
+            let [v1, v2, v3, v4] = self.accumulators;
+            let mut hash = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            for accumulator in self.accumulators {
+                hash = (hash ^ xxh64_round(0, accumulator))
+                    .wrapping_mul(PRIME64_1)
+                    .wrapping_add(PRIME64_4);
+            }
+            hash
+        } else {
+            PRIME64_5
+        };
+        hash = hash.wrapping_add(self.total_len);
+
+        let mut rest = &self.buffer[..self.buffered];
+        while rest.len() >= 8 {
+            hash = (hash ^ xxh64_round(0, read_u64(rest)))
+                .rotate_left(27)
+                .wrapping_mul(PRIME64_1)
+                .wrapping_add(PRIME64_4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            let lane = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+            hash = (hash ^ u64::from(lane).wrapping_mul(PRIME64_1))
+                .rotate_left(23)
+                .wrapping_mul(PRIME64_2)
+                .wrapping_add(PRIME64_3);
+            rest = &rest[4..];
+        }
+        for &byte in rest {
+            hash = (hash ^ u64::from(byte).wrapping_mul(PRIME64_5))
+                .rotate_left(11)
+                .wrapping_mul(PRIME64_1);
+        }
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(PRIME64_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(PRIME64_3);
+        hash ^ (hash >> 32)
+    }
+}
+
+fn xxh64_round(accumulator: u64, lane: u64) -> u64 {
+    accumulator
+        .wrapping_add(lane.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut lane = [0; 8];
+    lane.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(lane)
+}
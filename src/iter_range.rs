@@ -1,3 +1,5 @@
+use crate::{db::DBAccess, ffi, DBIteratorWithThreadMode, IteratorMode, ReadOptions};
+
 /// A range which can be set as iterate bounds on [`crate::ReadOptions`].
 ///
 /// See [`crate::ReadOptions::set_iterate_range`] for documentation and
@@ -90,3 +92,135 @@ fn next_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
         Some(next)
     }
 }
+
+/// Builder for iterators over a range of keys, created with
+/// [`DBCommon::scan`] or [`DBCommon::scan_cf`].
+///
+/// The bounds set by [`from`], [`to`] and [`prefix`] are combined, the
+/// iterator only returns keys within all of them.
+///
+/// [`DBCommon::scan`]: crate::DBCommon::scan
+/// [`DBCommon::scan_cf`]: crate::DBCommon::scan_cf
+/// [`from`]: Self::from
+/// [`to`]: Self::to
+/// [`prefix`]: Self::prefix
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{Options, DB};
+///
+/// let path = "_path_for_rocksdb_storage_scan";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     db.put(b"user:1", b"alice").unwrap();
+///     db.put(b"user:2", b"bob").unwrap();
+///     db.put(b"user:3", b"carol").unwrap();
+///
+///     let keys: Vec<_> = db
+///         .scan()
+///         .prefix("user:")
+///         .to("user:3")
+///         .reverse()
+///         .fill_cache(false)
+///         .build()
+///         .map(|item| item.unwrap().0)
+///         .collect();
+///     assert_eq!(
+///         keys,
+///         vec![
+///             b"user:2".to_vec().into_boxed_slice(),
+///             b"user:1".to_vec().into_boxed_slice(),
+///         ]
+///     );
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct ScanBuilder<'a, D: DBAccess> {
+    db: &'a D,
+    cf: Option<*mut ffi::rocksdb_column_family_handle_t>,
+    lower_bound: Option<Vec<u8>>,
+    upper_bound: Option<Vec<u8>>,
+    prefix: Option<Vec<u8>>,
+    reverse: bool,
+    fill_cache: bool,
+}
+
+impl<'a, D: DBAccess> ScanBuilder<'a, D> {
+    pub(crate) fn new(db: &'a D, cf: Option<*mut ffi::rocksdb_column_family_handle_t>) -> Self {
+        Self {
+            db,
+            cf,
+            lower_bound: None,
+            upper_bound: None,
+            prefix: None,
+            reverse: false,
+            fill_cache: true,
+        }
+    }
+
+    /// Only returns keys greater than or equal to `key`.
+    pub fn from<K: Into<Vec<u8>>>(mut self, key: K) -> Self {
+        self.lower_bound = Some(key.into());
+        self
+    }
+
+    /// Only returns keys less than `key`.
+    pub fn to<K: Into<Vec<u8>>>(mut self, key: K) -> Self {
+        self.upper_bound = Some(key.into());
+        self
+    }
+
+    /// Only returns keys starting with `prefix`.
+    pub fn prefix<K: Into<Vec<u8>>>(mut self, prefix: K) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Returns the keys in descending order.
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Whether the blocks read by the iterator are put into the block cache.
+    /// Disable it for large one-off scans which would evict hot data.
+    ///
+    /// Default: true
+    pub fn fill_cache(mut self, fill_cache: bool) -> Self {
+        self.fill_cache = fill_cache;
+        self
+    }
+
+    /// Creates the iterator.
+    pub fn build(self) -> DBIteratorWithThreadMode<'a, D> {
+        let (mut lower_bound, mut upper_bound) = (self.lower_bound, self.upper_bound);
+        if let Some(prefix) = self.prefix {
+            let (prefix_lower, prefix_upper) = PrefixRange(prefix).into_bounds();
+            lower_bound = lower_bound.max(prefix_lower);
+            upper_bound = match (upper_bound, prefix_upper) {
+                (Some(upper), Some(prefix_upper)) => Some(upper.min(prefix_upper)),
+                (upper, prefix_upper) => upper.or(prefix_upper),
+            };
+        }
+
+        let mut readopts = ReadOptions::default();
+        readopts.fill_cache(self.fill_cache);
+        if let Some(lower_bound) = lower_bound {
+            readopts.set_iterate_lower_bound(lower_bound);
+        }
+        if let Some(upper_bound) = upper_bound {
+            readopts.set_iterate_upper_bound(upper_bound);
+        }
+
+        let mode = if self.reverse {
+            IteratorMode::End
+        } else {
+            IteratorMode::Start
+        };
+        match self.cf {
+            Some(cf) => DBIteratorWithThreadMode::new_cf(self.db, cf, readopts, mode),
+            None => DBIteratorWithThreadMode::new(self.db, readopts, mode),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Order-preserving encoding of keys.
+//!
+//! With the default bytewise comparator, keys are ordered by their bytes.
+//! The encodings of this module make keys built from integers, floats and
+//! byte strings sort like the values they hold:
+//!
+//! * unsigned integers are stored big endian,
+//! * signed integers are stored big endian with the sign bit flipped, so
+//!   negative values sort before positive ones,
+//! * floats are stored with the sign bit flipped for positive values and all
+//!   bits flipped for negative values, so they sort in numeric order, with
+//!   `-0.0` before `0.0` and NaNs at both ends depending on their sign,
+//! * byte strings and strings have their zero bytes escaped and are
+//!   terminated, so a component can be followed by other ones without
+//!   breaking the order. A length prefix would sort shorter components first
+//!   whatever their content.
+//!
+//! Components pushed in descending order have all their bytes inverted, so
+//! they sort in reverse.
+//!
+//! # Examples
+//!
+//! ```
+//! use rocksdb::keys::KeyEncoder;
+//!
+//! // keys of a (user, timestamp) index, with the most recent entries first
+//! let key = |user: &str, timestamp: i64| {
+//!     let mut encoder = KeyEncoder::new();
+//!     encoder.push(user).push_desc(timestamp);
+//!     encoder.into_bytes()
+//! };
+//! assert!(key("alice", 10) < key("alice", -5));
+//! assert!(key("alice", -5) < key("bob", 20));
+//!
+//! let bytes = key("alice", 10);
+//! let mut decoder = rocksdb::keys::KeyDecoder::new(&bytes);
+//! assert_eq!(decoder.read::<String>().unwrap(), "alice");
+//! assert_eq!(decoder.read_desc::<i64>().unwrap(), 10);
+//! assert!(decoder.is_empty());
+//! ```
+
+use std::convert::TryInto;
+
+use crate::Error;
+
+const ESCAPE: u8 = 0x00;
+const ESCAPED_ZERO: u8 = 0xff;
+const TERMINATOR: u8 = 0x01;
+
+/// A value that can be encoded as a key component.
+pub trait EncodeKey {
+    /// Appends the encoding of the value to `buf`, with all bytes xored with
+    /// `mask` (`0x00` for ascending order, `0xff` for descending order).
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8);
+}
+
+/// A value that can be decoded from a key component.
+pub trait DecodeKey: Sized {
+    /// Decodes a value from the start of `data`, whose bytes were xored with
+    /// `mask`, and returns it with the number of bytes read.
+    fn decode_key(data: &[u8], mask: u8) -> Result<(Self, usize), Error>;
+}
+
+impl<T: EncodeKey + ?Sized> EncodeKey for &T {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        (**self).encode_key(buf, mask);
+    }
+}
+
+fn encode_fixed(buf: &mut Vec<u8>, bytes: [u8; 8], mask: u8) {
+    buf.extend(bytes.iter().map(|b| b ^ mask));
+}
+
+fn decode_fixed(data: &[u8], mask: u8) -> Result<[u8; 8], Error> {
+    let bytes: [u8; 8] = data
+        .get(..8)
+        .ok_or_else(|| Error::new("Invalid key: truncated integer component".to_owned()))?
+        .try_into()
+        .unwrap();
+    Ok(bytes.map(|b| b ^ mask))
+}
+
+impl EncodeKey for u64 {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        encode_fixed(buf, encode_u64(*self), mask);
+    }
+}
+
+impl DecodeKey for u64 {
+    fn decode_key(data: &[u8], mask: u8) -> Result<(Self, usize), Error> {
+        Ok((u64::from_be_bytes(decode_fixed(data, mask)?), 8))
+    }
+}
+
+impl EncodeKey for i64 {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        encode_fixed(buf, encode_i64(*self), mask);
+    }
+}
+
+impl DecodeKey for i64 {
+    fn decode_key(data: &[u8], mask: u8) -> Result<(Self, usize), Error> {
+        Ok((decode_i64(decode_fixed(data, mask)?), 8))
+    }
+}
+
+impl EncodeKey for f64 {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        encode_fixed(buf, encode_f64(*self), mask);
+    }
+}
+
+impl DecodeKey for f64 {
+    fn decode_key(data: &[u8], mask: u8) -> Result<(Self, usize), Error> {
+        Ok((decode_f64(decode_fixed(data, mask)?), 8))
+    }
+}
+
+impl EncodeKey for [u8] {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        for b in self {
+            if *b == ESCAPE {
+                buf.push(ESCAPE ^ mask);
+                buf.push(ESCAPED_ZERO ^ mask);
+            } else {
+                buf.push(b ^ mask);
+            }
+        }
+        buf.push(ESCAPE ^ mask);
+        buf.push(TERMINATOR ^ mask);
+    }
+}
+
+impl EncodeKey for Vec<u8> {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        self.as_slice().encode_key(buf, mask);
+    }
+}
+
+impl DecodeKey for Vec<u8> {
+    fn decode_key(data: &[u8], mask: u8) -> Result<(Self, usize), Error> {
+        let mut value = Vec::new();
+        let mut bytes = data.iter().map(|b| b ^ mask).enumerate();
+        while let Some((_, b)) = bytes.next() {
+            if b != ESCAPE {
+                value.push(b);
+                continue;
+            }
+            match bytes.next() {
+                Some((_, ESCAPED_ZERO)) => value.push(ESCAPE),
+                Some((i, TERMINATOR)) => return Ok((value, i + 1)),
+                _ => break,
+            }
+        }
+        Err(Error::new(
+            "Invalid key: unterminated byte string component".to_owned(),
+        ))
+    }
+}
+
+impl EncodeKey for str {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        self.as_bytes().encode_key(buf, mask);
+    }
+}
+
+impl EncodeKey for String {
+    fn encode_key(&self, buf: &mut Vec<u8>, mask: u8) {
+        self.as_bytes().encode_key(buf, mask);
+    }
+}
+
+impl DecodeKey for String {
+    fn decode_key(data: &[u8], mask: u8) -> Result<(Self, usize), Error> {
+        let (bytes, len) = Vec::<u8>::decode_key(data, mask)?;
+        let value =
+            String::from_utf8(bytes).map_err(|err| Error::new(format!("Invalid key: {err}")))?;
+        Ok((value, len))
+    }
+}
+
+/// Encodes an unsigned integer so keys sort in numeric order.
+pub fn encode_u64(value: u64) -> [u8; 8] {
+    value.to_be_bytes()
+}
+
+/// Decodes an unsigned integer encoded with [`encode_u64`].
+pub fn decode_u64(bytes: [u8; 8]) -> u64 {
+    u64::from_be_bytes(bytes)
+}
+
+/// Encodes a signed integer so keys sort in numeric order.
+pub fn encode_i64(value: i64) -> [u8; 8] {
+    ((value as u64) ^ (1 << 63)).to_be_bytes()
+}
+
+/// Decodes a signed integer encoded with [`encode_i64`].
+pub fn decode_i64(bytes: [u8; 8]) -> i64 {
+    (u64::from_be_bytes(bytes) ^ (1 << 63)) as i64
+}
+
+/// Encodes a float so keys sort in numeric order.
+pub fn encode_f64(value: f64) -> [u8; 8] {
+    let bits = value.to_bits();
+    let bits = if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits ^ (1 << 63)
+    };
+    bits.to_be_bytes()
+}
+
+/// Decodes a float encoded with [`encode_f64`].
+pub fn decode_f64(bytes: [u8; 8]) -> f64 {
+    let bits = u64::from_be_bytes(bytes);
+    let bits = if bits >> 63 == 1 {
+        bits ^ (1 << 63)
+    } else {
+        !bits
+    };
+    f64::from_bits(bits)
+}
+
+/// Builds a key from several components, see the [module](self) docs.
+#[derive(Debug, Clone, Default)]
+pub struct KeyEncoder {
+    buf: Vec<u8>,
+}
+
+impl KeyEncoder {
+    /// Creates an empty key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a component sorting in ascending order.
+    pub fn push<T: EncodeKey>(&mut self, value: T) -> &mut Self {
+        value.encode_key(&mut self.buf, 0x00);
+        self
+    }
+
+    /// Appends a component sorting in descending order.
+    pub fn push_desc<T: EncodeKey>(&mut self, value: T) -> &mut Self {
+        value.encode_key(&mut self.buf, 0xff);
+        self
+    }
+
+    /// Returns the key built so far, e.g. to use it as a prefix.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the key.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reads the components of a key built with [`KeyEncoder`], in the order they
+/// were pushed.
+#[derive(Debug, Clone)]
+pub struct KeyDecoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> KeyDecoder<'a> {
+    /// Creates a decoder reading `key` from its start.
+    pub fn new(key: &'a [u8]) -> Self {
+        Self { data: key }
+    }
+
+    /// Reads a component pushed with [`KeyEncoder::push`].
+    pub fn read<T: DecodeKey>(&mut self) -> Result<T, Error> {
+        self.read_masked(0x00)
+    }
+
+    /// Reads a component pushed with [`KeyEncoder::push_desc`].
+    pub fn read_desc<T: DecodeKey>(&mut self) -> Result<T, Error> {
+        self.read_masked(0xff)
+    }
+
+    /// Returns the bytes not read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns `true` if all components have been read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn read_masked<T: DecodeKey>(&mut self, mask: u8) -> Result<T, Error> {
+        let (value, len) = T::decode_key(self.data, mask)?;
+        self.data = &self.data[len..];
+        Ok(value)
+    }
+}
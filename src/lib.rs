@@ -76,63 +76,108 @@
 mod ffi_util;
 
 pub mod backup;
+mod catch_up;
+mod cf_stats;
 pub mod checkpoint;
+mod coding;
+#[cfg(feature = "collections")]
+pub mod collections;
 mod column_family;
 pub mod compaction_filter;
 pub mod compaction_filter_factory;
+mod compaction_scheduler;
 mod comparator;
+mod database;
 mod db;
+mod db_group;
 mod db_iterator;
+mod db_lock;
 mod db_options;
 mod db_pinnable_slice;
 mod env;
+mod file_checksum;
 mod iter_range;
+pub mod keys;
+pub mod manifest;
 pub mod merge_operator;
+mod open_handles;
+mod open_report;
+mod options_file;
 pub mod perf;
+mod periodic_thread;
+mod preflight;
 mod prop_name;
 pub mod properties;
+mod read_only_db;
+#[cfg(feature = "replication")]
+mod replication;
 mod slice_transform;
 mod snapshot;
+mod snapshot_diff;
 mod sst_file_writer;
+mod stats_history;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 mod transactions;
+mod wal_file;
 mod write_batch;
+mod write_stall;
 
 pub use crate::{
+    catch_up::CatchUpThread,
+    cf_stats::{CfLevelStats, CfStats},
     column_family::{
-        AsColumnFamilyRef, BoundColumnFamily, ColumnFamily, ColumnFamilyDescriptor,
-        ColumnFamilyRef, DEFAULT_COLUMN_FAMILY_NAME,
+        AsColumnFamilyRef, BoundColumnFamily, CfId, ColumnFamily, ColumnFamilyDescriptor,
+        ColumnFamilyDescriptorBuilder, ColumnFamilyRef, DEFAULT_COLUMN_FAMILY_NAME,
     },
     compaction_filter::Decision as CompactionDecision,
+    compaction_scheduler::{
+        CompactionPolicy, CompactionScheduler, CompactionSchedulerThread, ScheduledCompaction,
+    },
+    database::Database,
     db::{
-        DBAccess, DBCommon, DBWithThreadMode, LiveFile, MultiThreaded, SingleThreaded, ThreadMode,
-        DB,
+        BackgroundJobInfo, BulkLoadSettings, ChecksumReport, CompactionResult, CorruptBlock,
+        DBAccess, DBCommon, DBRole, DBWithThreadMode, DeletionEstimate, DiskUsage, LiveFile,
+        LiveFileList, MultiThreaded, SingleThreaded, ThreadMode, DB,
     },
+    db_group::DbGroup,
     db_iterator::{
-        DBIterator, DBIteratorWithThreadMode, DBRawIterator, DBRawIteratorWithThreadMode,
-        DBWALIterator, Direction, IteratorMode,
+        Cursor, DBIterator, DBIteratorWithThreadMode, DBRawIterator, DBRawIteratorWithThreadMode,
+        DBWALIterator, Direction, IteratorMode, IteratorState, ResumeToken,
     },
     db_options::{
-        BlockBasedIndexType, BlockBasedOptions, BottommostLevelCompaction, Cache, ChecksumType,
-        CompactOptions, CuckooTableOptions, DBCompactionStyle, DBCompressionType, DBPath,
-        DBRecoveryMode, DataBlockIndexType, FifoCompactOptions, FlushOptions,
-        IngestExternalFileOptions, KeyEncodingType, LogLevel, MemtableFactory, Options,
-        PlainTableFactoryOptions, ReadOptions, ReadTier, UniversalCompactOptions,
-        UniversalCompactionStopStyle, WaitForCompactOptions, WriteOptions,
+        BlockBasedIndexType, BlockBasedOptions, BottommostLevelCompaction, BuiltinMergeOperator,
+        Cache, ChecksumGenType, ChecksumType, CompactOptions, CuckooTableOptions,
+        DBCompactionStyle, DBCompressionType, DBPath, DBRecoveryMode, DataBlockIndexType,
+        FifoCompactOptions, FlushOptions, IndexShorteningMode, IngestExternalFileOptions,
+        KeyEncodingType, LevelDescription, LogLevel, MemtableFactory, Options,
+        PlainTableFactoryOptions, RateLimiter, ReadOptions, ReadOptionsBuilder, ReadTier,
+        ScopedReadOptions, UniversalCompactOptions, UniversalCompactionStopStyle,
+        WaitForCompactOptions, WriteBufferManager, WriteOptions,
     },
     db_pinnable_slice::DBPinnableSlice,
     env::Env,
     ffi_util::CStrLike,
-    iter_range::{IterateBounds, PrefixRange},
+    file_checksum::FileChecksum,
+    iter_range::{IterateBounds, PrefixRange, ScanBuilder},
     merge_operator::MergeOperands,
+    open_report::OpenReport,
+    options_file::{ColumnFamilyOperators, OptionMismatch},
     perf::{PerfContext, PerfMetric, PerfStatsLevel},
+    preflight::{Diagnostic, DiagnosticKind, DiagnosticSeverity},
+    read_only_db::{ReadOnlyDB, SecondaryDB},
     slice_transform::SliceTransform,
-    snapshot::{Snapshot, SnapshotWithThreadMode},
+    snapshot::{SharedSnapshot, Snapshot, SnapshotWithThreadMode},
+    snapshot_diff::{KeyChange, SnapshotDiff},
     sst_file_writer::SstFileWriter,
+    stats_history::{StatsHistoryEntry, STATS_HISTORY_COLUMN_FAMILY_NAME},
     transactions::{
-        OptimisticTransactionDB, OptimisticTransactionOptions, Transaction, TransactionDB,
-        TransactionDBOptions, TransactionOptions,
+        OptimisticTransactionDB, OptimisticTransactionOptions, RetryOptions, Transaction,
+        TransactionDB, TransactionDBOptions, TransactionForUpdateIterator, TransactionOptions,
     },
-    write_batch::{WriteBatch, WriteBatchIterator, WriteBatchWithTransaction},
+    wal_file::{WalFile, WalFileType, WalPosition, WalReader, WalRecord},
+    write_batch::{BatchOp, WriteBatch, WriteBatchIterator, WriteBatchWithTransaction},
+    write_stall::{WriteStallCause, WriteStallCondition, WriteStallInfo, WriteStallReason},
 };
 
 use librocksdb_sys as ffi;
@@ -141,7 +186,7 @@ use std::error;
 use std::fmt;
 
 /// RocksDB error kind.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ErrorKind {
     NotFound,
     Corruption,
@@ -161,25 +206,9 @@ pub enum ErrorKind {
     Unknown,
 }
 
-/// A simple wrapper round a string, used for errors reported from
-/// ffi calls.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Error {
-    message: String,
-}
-
-impl Error {
-    fn new(message: String) -> Error {
-        Error { message }
-    }
-
-    pub fn into_string(self) -> String {
-        self.into()
-    }
-
-    /// Parse corresponding [`ErrorKind`] from error message.
-    pub fn kind(&self) -> ErrorKind {
-        match self.message.split(':').next().unwrap_or("") {
+impl ErrorKind {
+    fn from_message(message: &str) -> ErrorKind {
+        match message.split(':').next().unwrap_or("") {
             "NotFound" => ErrorKind::NotFound,
             "Corruption" => ErrorKind::Corruption,
             "Not implemented" => ErrorKind::NotSupported,
@@ -200,6 +229,209 @@ impl Error {
     }
 }
 
+/// RocksDB error sub code, giving the cause of some errors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorSubCode {
+    None,
+    MutexTimeout,
+    LockTimeout,
+    LockLimit,
+    NoSpace,
+    Deadlock,
+    StaleFile,
+    MemoryLimit,
+    SpaceLimit,
+    PathNotFound,
+    MergeOperandsInsufficientCapacity,
+    ManualCompactionPaused,
+    Overwritten,
+    TxnNotPrepared,
+    IOFenced,
+    MergeOperatorFailed,
+}
+
+impl ErrorSubCode {
+    /// The messages RocksDB prints for the sub codes, right after the
+    /// message of the error kind.
+    const MESSAGES: [(&'static str, ErrorSubCode); 15] = [
+        ("Timeout Acquiring Mutex", ErrorSubCode::MutexTimeout),
+        ("Timeout waiting to lock key", ErrorSubCode::LockTimeout),
+        (
+            "Failed to acquire lock due to max_num_locks limit",
+            ErrorSubCode::LockLimit,
+        ),
+        ("No space left on device", ErrorSubCode::NoSpace),
+        ("Deadlock", ErrorSubCode::Deadlock),
+        ("Stale file handle", ErrorSubCode::StaleFile),
+        ("Memory limit reached", ErrorSubCode::MemoryLimit),
+        ("Space limit reached", ErrorSubCode::SpaceLimit),
+        ("No such file or directory", ErrorSubCode::PathNotFound),
+        (
+            "Insufficient capacity for merge operands",
+            ErrorSubCode::MergeOperandsInsufficientCapacity,
+        ),
+        (
+            "Manual compaction paused",
+            ErrorSubCode::ManualCompactionPaused,
+        ),
+        (" (overwritten)", ErrorSubCode::Overwritten),
+        ("Txn not prepared", ErrorSubCode::TxnNotPrepared),
+        ("IO fenced off", ErrorSubCode::IOFenced),
+        ("Merge operator failed", ErrorSubCode::MergeOperatorFailed),
+    ];
+
+    fn from_message(message: &str, kind: ErrorKind) -> ErrorSubCode {
+        if kind == ErrorKind::Unknown {
+            return ErrorSubCode::None;
+        }
+        let detail = match message.find(": ") {
+            Some(idx) => &message[idx + 2..],
+            None => return ErrorSubCode::None,
+        };
+        ErrorSubCode::MESSAGES
+            .iter()
+            .find(|(prefix, _)| detail.starts_with(prefix))
+            .map_or(ErrorSubCode::None, |(_, sub_code)| *sub_code)
+    }
+}
+
+/// The operation which returned an [`Error`], recorded with the
+/// `rich-errors` feature.
+#[cfg(feature = "rich-errors")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    op: &'static str,
+    column_family: Option<String>,
+    key_len: Option<usize>,
+}
+
+#[cfg(feature = "rich-errors")]
+impl ErrorContext {
+    /// Returns the name of the RocksDB C API function which failed, without
+    /// its `rocksdb_` prefix, e.g. `"put_cf"`.
+    pub fn op(&self) -> &str {
+        self.op
+    }
+
+    /// Returns the name of the column family the operation was applied to,
+    /// for single-key operations on a column family.
+    pub fn column_family(&self) -> Option<&str> {
+        self.column_family.as_deref()
+    }
+
+    /// Returns the length of the key of single-key operations.
+    pub fn key_len(&self) -> Option<usize> {
+        self.key_len
+    }
+}
+
+#[cfg(feature = "rich-errors")]
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.op)?;
+        match (&self.column_family, self.key_len) {
+            (Some(cf), Some(len)) => write!(f, " (column family {cf}, key of {len} bytes)"),
+            (Some(cf), None) => write!(f, " (column family {cf})"),
+            (None, Some(len)) => write!(f, " (key of {len} bytes)"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// A wrapper round the message of errors reported from ffi calls, along
+/// with the kind and sub code parsed from it.
+///
+/// The C API reports errors as strings only, so the severity of background
+/// errors and the retryable flag of I/O errors are not available.
+///
+/// With the `rich-errors` feature, errors returned by RocksDB also record
+/// the operation which failed, see [`Error::context`]. It prefixes the
+/// `Display` output, while [`AsRef<str>`] and [`into_string`](Self::into_string)
+/// keep returning the message of RocksDB as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    kind: ErrorKind,
+    sub_code: ErrorSubCode,
+    #[cfg(feature = "rich-errors")]
+    context: Option<Box<ErrorContext>>,
+}
+
+impl Error {
+    fn new(message: String) -> Error {
+        let kind = ErrorKind::from_message(&message);
+        let sub_code = ErrorSubCode::from_message(&message, kind);
+        Error {
+            message,
+            kind,
+            sub_code,
+            #[cfg(feature = "rich-errors")]
+            context: None,
+        }
+    }
+
+    /// Records the context of an error returned by the C function
+    /// `function`, as formatted by `stringify!`.
+    #[cfg(feature = "rich-errors")]
+    fn with_context(
+        mut self,
+        function: &'static str,
+        cf: Option<*mut ffi::rocksdb_column_family_handle_t>,
+        key_len: Option<usize>,
+    ) -> Error {
+        let name = function.rsplit(':').next().unwrap_or(function).trim();
+        let column_family = cf.map(column_family::column_family_name);
+        self.context = Some(Box::new(ErrorContext {
+            op: name.strip_prefix("rocksdb_").unwrap_or(name),
+            column_family,
+            key_len,
+        }));
+        self
+    }
+
+    /// Returns the operation which returned the error, if it was returned by
+    /// RocksDB.
+    #[cfg(feature = "rich-errors")]
+    pub fn context(&self) -> Option<&ErrorContext> {
+        self.context.as_deref()
+    }
+
+    /// Appends `detail` to the message, keeping the kind of the error.
+    fn with_detail(mut self, detail: &str) -> Error {
+        self.message.push_str(detail);
+        self
+    }
+
+    pub fn into_string(self) -> String {
+        self.into()
+    }
+
+    /// Returns the [`ErrorKind`] parsed from the error message.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the [`ErrorSubCode`] parsed from the error message.
+    pub fn sub_code(&self) -> ErrorSubCode {
+        self.sub_code
+    }
+
+    /// Returns `true` if the operation may succeed when retried as is: the
+    /// error is caused by contention with other writers or transactions,
+    /// e.g. a lock timeout, a deadlock or a write conflict.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::Busy | ErrorKind::TryAgain | ErrorKind::TimedOut
+        )
+    }
+
+    /// Returns `true` if the error reports corrupted or missing data.
+    pub fn is_data_loss(&self) -> bool {
+        self.kind == ErrorKind::Corruption
+    }
+}
+
 impl AsRef<str> for Error {
     fn as_ref(&self) -> &str {
         &self.message
@@ -220,6 +452,10 @@ impl error::Error for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        #[cfg(feature = "rich-errors")]
+        if let Some(context) = &self.context {
+            write!(formatter, "{context}: ")?;
+        }
         self.message.fmt(formatter)
     }
 }
@@ -0,0 +1,473 @@
+//! Inspection of the MANIFEST of a DB.
+//!
+//! The MANIFEST is the log of the version edits of a DB: each edit adds or
+//! removes SST files, column families, or moves the counters of the DB
+//! forward. Replaying the edits gives the current version of the DB, i.e.
+//! the files each column family is made of, which is what
+//! [`describe`] returns, like `ldb manifest_dump` does, to debug the disk
+//! usage or the shape of the LSM trees without external tools.
+//!
+//! # Examples
+//!
+//! ```
+//! use rocksdb::{manifest, Options, DB};
+//!
+//! let path = "_path_for_rocksdb_storage_manifest_describe";
+//! {
+//!     let db = DB::open_default(path).unwrap();
+//!     db.put(b"key", b"value").unwrap();
+//!     db.flush().unwrap();
+//!
+//!     let manifest = manifest::describe(path).unwrap();
+//!     let default = &manifest.column_families[0];
+//!     assert_eq!(default.name, "default");
+//!     assert_eq!(default.levels[0].len(), 1);
+//! }
+//! let _ = DB::destroy(&Options::default(), path);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{coding, wal_file, Error, FileChecksum};
+
+/// The current version of a DB as recorded by its MANIFEST, see [`describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDescription {
+    /// Name of the MANIFEST named by the `CURRENT` file
+    pub manifest_name: String,
+    /// Size of the MANIFEST in bytes
+    pub manifest_size: u64,
+    /// Number of the version edits read from the MANIFEST
+    pub edit_count: usize,
+    /// Number of the next file created by the DB, shared by the SST, WAL,
+    /// MANIFEST and OPTIONS files
+    pub next_file_number: u64,
+    /// Last sequence number recorded by the MANIFEST, the writes which are
+    /// only in the WAL files can be more recent
+    pub last_sequence: u64,
+    /// Column families of the DB, ordered by ID
+    pub column_families: Vec<ManifestColumnFamily>,
+}
+
+/// A column family of the current version of a DB, see [`describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestColumnFamily {
+    /// ID of the column family, 0 for the default one
+    pub id: u32,
+    /// Name of the column family
+    pub name: String,
+    /// Name of the comparator the column family was created with
+    pub comparator: Option<String>,
+    /// Number of the oldest WAL file holding writes of the column family
+    /// which are not flushed yet
+    pub log_number: u64,
+    /// SST files of the column family by level, up to the last level
+    /// holding files, each level ordered by file number
+    pub levels: Vec<Vec<ManifestFile>>,
+}
+
+impl ManifestColumnFamily {
+    /// Returns the number of SST files of the column family.
+    pub fn file_count(&self) -> usize {
+        self.levels.iter().map(Vec::len).sum()
+    }
+
+    /// Returns the size of the SST files of the column family.
+    pub fn total_size(&self) -> u64 {
+        self.levels.iter().flatten().map(|file| file.size).sum()
+    }
+}
+
+/// An SST file of the current version of a DB, see [`describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestFile {
+    /// Number of the file
+    pub number: u64,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// Smallest user key in the file
+    pub smallest_key: Vec<u8>,
+    /// Largest user key in the file
+    pub largest_key: Vec<u8>,
+    /// Smallest sequence number in the file
+    pub smallest_sequence: u64,
+    /// Largest sequence number in the file
+    pub largest_sequence: u64,
+    /// Full checksum of the file, recorded when the DB was opened with
+    /// [`Options::enable_file_checksums`](crate::Options::enable_file_checksums)
+    pub file_checksum: Option<FileChecksum>,
+    /// Time the file was created, in seconds since the Unix epoch, `None`
+    /// if it was not recorded, e.g. for files ingested from older versions
+    pub file_creation_time: Option<u64>,
+}
+
+impl ManifestFile {
+    /// Returns the name of the file, relative to the DB directory or the
+    /// path set with [`Options::set_db_paths`](crate::Options::set_db_paths)
+    /// it was written to.
+    pub fn name(&self) -> String {
+        format!("{:06}.sst", self.number)
+    }
+}
+
+/// Reads the MANIFEST of the DB at `path` and returns its current version:
+/// the SST files of each column family by level, the size of the MANIFEST
+/// and the counters it records.
+///
+/// The C API does not expose the dump facilities of RocksDB, so the
+/// MANIFEST is decoded and its version edits are replayed here, as
+/// `ldb manifest_dump` does. The DB can be open while it is read: a version
+/// edit being written is ignored, like an atomic group whose edits are not
+/// all written yet. Blob files are not reported.
+pub fn describe<P: AsRef<Path>>(path: P) -> Result<ManifestDescription, Error> {
+    let path = path.as_ref();
+    let current = fs::read_to_string(path.join("CURRENT")).map_err(|err| {
+        Error::new(format!(
+            "IO error: failed to read the CURRENT file of {}: {err}",
+            path.display()
+        ))
+    })?;
+    let manifest_name = current.trim_end().to_owned();
+    let number = manifest_name
+        .strip_prefix("MANIFEST-")
+        .and_then(|number| number.parse().ok())
+        .ok_or_else(|| {
+            Error::new(format!(
+                "Corruption: the CURRENT file of {} names {manifest_name:?} which is not a MANIFEST",
+                path.display()
+            ))
+        })?;
+    let data = fs::read(path.join(&manifest_name))
+        .map_err(|err| Error::new(format!("IO error: failed to read {manifest_name}: {err}")))?;
+
+    let mut version = Version::new();
+    let mut atomic_group = Vec::new();
+    let mut edit_count = 0;
+    for record in wal_file::read_records(&data, number)? {
+        let edit = VersionEdit::decode(&record)?;
+        edit_count += 1;
+        match edit.remaining_in_atomic_group {
+            // the edits of an atomic group are applied once all are read
+            Some(remaining) => {
+                atomic_group.push(edit);
+                if remaining == 0 {
+                    for edit in atomic_group.drain(..) {
+                        version.apply(edit)?;
+                    }
+                }
+            }
+            None if !atomic_group.is_empty() => {
+                return Err(corrupted_manifest("incomplete atomic group"))
+            }
+            None => version.apply(edit)?,
+        }
+    }
+
+    Ok(ManifestDescription {
+        manifest_name,
+        manifest_size: data.len() as u64,
+        edit_count,
+        next_file_number: version.next_file_number,
+        last_sequence: version.last_sequence,
+        column_families: version
+            .column_families
+            .into_iter()
+            .map(|(id, cf)| cf.describe(id))
+            .collect(),
+    })
+}
+
+/// The state of the DB built by replaying the version edits.
+struct Version {
+    next_file_number: u64,
+    last_sequence: u64,
+    column_families: BTreeMap<u32, ColumnFamilyState>,
+}
+
+struct ColumnFamilyState {
+    name: String,
+    comparator: Option<String>,
+    log_number: u64,
+    /// Files by number with their level
+    files: BTreeMap<u64, (usize, ManifestFile)>,
+}
+
+impl Version {
+    fn new() -> Self {
+        let mut column_families = BTreeMap::new();
+        column_families.insert(0, ColumnFamilyState::new("default".to_owned()));
+        Self {
+            next_file_number: 0,
+            last_sequence: 0,
+            column_families,
+        }
+    }
+
+    fn apply(&mut self, edit: VersionEdit) -> Result<(), Error> {
+        if let Some(number) = edit.next_file_number {
+            self.next_file_number = number;
+        }
+        if let Some(sequence) = edit.last_sequence {
+            self.last_sequence = sequence;
+        }
+        if let Some(name) = edit.added_column_family {
+            self.column_families
+                .insert(edit.column_family, ColumnFamilyState::new(name));
+        }
+        if edit.dropped_column_family {
+            self.column_families.remove(&edit.column_family);
+            return Ok(());
+        }
+
+        let cf = self
+            .column_families
+            .get_mut(&edit.column_family)
+            .ok_or_else(|| {
+                corrupted_manifest(&format!(
+                    "edit of the unknown column family {}",
+                    edit.column_family
+                ))
+            })?;
+        if let Some(comparator) = edit.comparator {
+            cf.comparator = Some(comparator);
+        }
+        if let Some(number) = edit.log_number {
+            cf.log_number = number;
+        }
+        for number in edit.deleted_files {
+            cf.files.remove(&number);
+        }
+        for (level, file) in edit.new_files {
+            cf.files.insert(file.number, (level, file));
+        }
+        Ok(())
+    }
+}
+
+impl ColumnFamilyState {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            comparator: None,
+            log_number: 0,
+            files: BTreeMap::new(),
+        }
+    }
+
+    fn describe(self, id: u32) -> ManifestColumnFamily {
+        let mut levels: Vec<Vec<ManifestFile>> = Vec::new();
+        for (level, file) in self.files.into_values() {
+            if levels.len() <= level {
+                levels.resize_with(level + 1, Vec::new);
+            }
+            levels[level].push(file);
+        }
+        ManifestColumnFamily {
+            id,
+            name: self.name,
+            comparator: self.comparator,
+            log_number: self.log_number,
+            levels,
+        }
+    }
+}
+
+// tags of the fields of a version edit, see db/version_edit.h
+const COMPARATOR: u32 = 1;
+const LOG_NUMBER: u32 = 2;
+const NEXT_FILE_NUMBER: u32 = 3;
+const LAST_SEQUENCE: u32 = 4;
+const COMPACT_CURSOR: u32 = 5;
+const DELETED_FILE: u32 = 6;
+const NEW_FILE: u32 = 7;
+const PREV_LOG_NUMBER: u32 = 9;
+const MIN_LOG_NUMBER_TO_KEEP: u32 = 10;
+const NEW_FILE2: u32 = 100;
+const NEW_FILE3: u32 = 102;
+const NEW_FILE4: u32 = 103;
+const COLUMN_FAMILY: u32 = 200;
+const COLUMN_FAMILY_ADD: u32 = 201;
+const COLUMN_FAMILY_DROP: u32 = 202;
+const MAX_COLUMN_FAMILY: u32 = 203;
+const IN_ATOMIC_GROUP: u32 = 300;
+const BLOB_FILE_ADDITION: u32 = 400;
+const BLOB_FILE_GARBAGE: u32 = 401;
+/// Fields with this bit set are length prefixed and can be skipped
+const TAG_SAFE_IGNORE_MASK: u32 = 1 << 13;
+/// Ends the custom fields of new files and blob files
+const CUSTOM_FIELDS_END: u32 = 1;
+const FILE_CREATION_TIME: u32 = 6;
+const FILE_CHECKSUM: u32 = 7;
+const FILE_CHECKSUM_FUNC_NAME: u32 = 8;
+const UNKNOWN_FILE_CHECKSUM_FUNC_NAME: &str = "Unknown";
+
+/// The fields of a version edit needed to replay it.
+#[derive(Default)]
+struct VersionEdit {
+    column_family: u32,
+    comparator: Option<String>,
+    log_number: Option<u64>,
+    next_file_number: Option<u64>,
+    last_sequence: Option<u64>,
+    deleted_files: Vec<u64>,
+    new_files: Vec<(usize, ManifestFile)>,
+    added_column_family: Option<String>,
+    dropped_column_family: bool,
+    remaining_in_atomic_group: Option<u32>,
+}
+
+impl VersionEdit {
+    fn decode(mut input: &[u8]) -> Result<Self, Error> {
+        let input = &mut input;
+        let mut edit = Self::default();
+        while !input.is_empty() {
+            let tag = read_varint32(input)?;
+            match tag {
+                COMPARATOR => edit.comparator = Some(read_string(input)?),
+                LOG_NUMBER => edit.log_number = Some(read_varint64(input)?),
+                NEXT_FILE_NUMBER => edit.next_file_number = Some(read_varint64(input)?),
+                LAST_SEQUENCE => edit.last_sequence = Some(read_varint64(input)?),
+                PREV_LOG_NUMBER | MIN_LOG_NUMBER_TO_KEEP => {
+                    read_varint64(input)?;
+                }
+                COMPACT_CURSOR => {
+                    read_varint32(input)?;
+                    read_slice(input)?;
+                }
+                DELETED_FILE => {
+                    read_varint32(input)?;
+                    edit.deleted_files.push(read_varint64(input)?);
+                }
+                NEW_FILE | NEW_FILE2 | NEW_FILE3 | NEW_FILE4 => {
+                    let level = read_varint32(input)? as usize;
+                    let number = read_varint64(input)?;
+                    if tag == NEW_FILE3 {
+                        // path ID
+                        read_varint32(input)?;
+                    }
+                    let size = read_varint64(input)?;
+                    let smallest_key = read_user_key(input)?;
+                    let largest_key = read_user_key(input)?;
+                    let (smallest_sequence, largest_sequence) = if tag == NEW_FILE {
+                        (0, 0)
+                    } else {
+                        (read_varint64(input)?, read_varint64(input)?)
+                    };
+                    let (file_checksum, file_creation_time) = if tag == NEW_FILE4 {
+                        read_new_file_custom_fields(input)?
+                    } else {
+                        (None, None)
+                    };
+                    edit.new_files.push((
+                        level,
+                        ManifestFile {
+                            number,
+                            size,
+                            smallest_key,
+                            largest_key,
+                            smallest_sequence,
+                            largest_sequence,
+                            file_checksum,
+                            file_creation_time,
+                        },
+                    ));
+                }
+                COLUMN_FAMILY => edit.column_family = read_varint32(input)?,
+                COLUMN_FAMILY_ADD => edit.added_column_family = Some(read_string(input)?),
+                COLUMN_FAMILY_DROP => edit.dropped_column_family = true,
+                MAX_COLUMN_FAMILY => {
+                    read_varint32(input)?;
+                }
+                IN_ATOMIC_GROUP => edit.remaining_in_atomic_group = Some(read_varint32(input)?),
+                BLOB_FILE_ADDITION | BLOB_FILE_GARBAGE => {
+                    // number, blob count and blob bytes
+                    for _ in 0..3 {
+                        read_varint64(input)?;
+                    }
+                    if tag == BLOB_FILE_ADDITION {
+                        // checksum method and value
+                        read_slice(input)?;
+                        read_slice(input)?;
+                    }
+                    skip_custom_fields(input)?;
+                }
+                tag if tag & TAG_SAFE_IGNORE_MASK != 0 => {
+                    read_slice(input)?;
+                }
+                tag => return Err(corrupted_manifest(&format!("unknown tag {tag}"))),
+            }
+        }
+        Ok(edit)
+    }
+}
+
+/// Reads the custom fields of a new file and returns its checksum and its
+/// creation time, if recorded.
+fn read_new_file_custom_fields(
+    input: &mut &[u8],
+) -> Result<(Option<FileChecksum>, Option<u64>), Error> {
+    let mut value = Vec::new();
+    let mut func_name = String::new();
+    let mut file_creation_time = None;
+    loop {
+        match read_varint32(input)? {
+            CUSTOM_FIELDS_END => break,
+            FILE_CREATION_TIME => {
+                let mut field = read_slice(input)?;
+                // 0 when the creation time is unknown
+                file_creation_time = Some(read_varint64(&mut field)?).filter(|&time| time != 0);
+            }
+            FILE_CHECKSUM => value = read_slice(input)?.to_vec(),
+            FILE_CHECKSUM_FUNC_NAME => func_name = read_string(input)?,
+            _ => {
+                read_slice(input)?;
+            }
+        }
+    }
+    // files written without a checksum generator are recorded with an
+    // empty checksum and an unknown function
+    let file_checksum = if func_name.is_empty() || func_name == UNKNOWN_FILE_CHECKSUM_FUNC_NAME {
+        None
+    } else {
+        Some(FileChecksum { value, func_name })
+    };
+    Ok((file_checksum, file_creation_time))
+}
+
+fn skip_custom_fields(input: &mut &[u8]) -> Result<(), Error> {
+    while read_varint32(input)? != CUSTOM_FIELDS_END {
+        read_slice(input)?;
+    }
+    Ok(())
+}
+
+fn read_varint64(input: &mut &[u8]) -> Result<u64, Error> {
+    coding::read_varint64(input).ok_or_else(|| corrupted_manifest("invalid varint"))
+}
+
+fn read_varint32(input: &mut &[u8]) -> Result<u32, Error> {
+    coding::read_varint32(input).ok_or_else(|| corrupted_manifest("invalid varint"))
+}
+
+fn read_slice<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    coding::read_length_prefixed_slice(input).ok_or_else(|| corrupted_manifest("truncated field"))
+}
+
+fn read_string(input: &mut &[u8]) -> Result<String, Error> {
+    Ok(String::from_utf8_lossy(read_slice(input)?).into_owned())
+}
+
+/// Reads an internal key and strips its sequence number and type.
+fn read_user_key(input: &mut &[u8]) -> Result<Vec<u8>, Error> {
+    let key = read_slice(input)?;
+    key.len()
+        .checked_sub(8)
+        .map(|len| key[..len].to_vec())
+        .ok_or_else(|| corrupted_manifest("internal key too short"))
+}
+
+fn corrupted_manifest(reason: &str) -> Error {
+    Error::new(format!("Corruption: invalid MANIFEST: {reason}"))
+}
@@ -57,10 +57,15 @@
 
 use libc::{self, c_char, c_int, c_void, size_t};
 use std::ffi::CString;
-use std::mem;
+use std::iter::FusedIterator;
 use std::ptr;
 use std::slice;
 
+/// A merge function, called with the key, its existing value if any, and the
+/// operands to merge into it.
+///
+/// The existing value and the operands are borrowed from the buffers of
+/// RocksDB without being copied, and are valid for the duration of the call.
 pub trait MergeFn:
     Fn(&[u8], Option<&[u8]>, &MergeOperands) -> Option<Vec<u8>> + Send + Sync + 'static
 {
@@ -192,62 +197,82 @@ impl MergeOperands {
         self.num_operands == 0
     }
 
+    /// Returns an iterator over the operands, from the oldest to the newest.
     pub fn iter(&self) -> MergeOperandsIter {
         MergeOperandsIter {
             operands: self,
-            cursor: 0,
+            front: 0,
+            back: self.num_operands,
         }
     }
 
+    /// Returns the newest operand, i.e. the last one merged, if any.
+    pub fn operands_back(&self) -> Option<&[u8]> {
+        self.num_operands
+            .checked_sub(1)
+            .and_then(|index| self.get_operand(index))
+    }
+
     fn get_operand(&self, index: usize) -> Option<&[u8]> {
         if index >= self.num_operands {
             None
         } else {
+            // the operands are borrowed from RocksDB, which keeps them alive
+            // until the merge callback returns
             unsafe {
-                let base = self.operands_list as usize;
-                let base_len = self.operands_list_len as usize;
-                let spacing = mem::size_of::<*const *const u8>();
-                let spacing_len = mem::size_of::<*const size_t>();
-                let len_ptr = (base_len + (spacing_len * index)) as *const size_t;
-                let len = *len_ptr;
-                let ptr = base + (spacing * index);
-                Some(slice::from_raw_parts(
-                    *(ptr as *const *const u8) as *const u8,
-                    len,
-                ))
+                let ptr = *self.operands_list.add(index);
+                let len = *self.operands_list_len.add(index);
+                Some(slice::from_raw_parts(ptr as *const u8, len))
             }
         }
     }
 }
 
+/// An iterator over the operands of a merge, from the oldest to the newest,
+/// see [`MergeOperands::iter`].
 pub struct MergeOperandsIter<'a> {
     operands: &'a MergeOperands,
-    cursor: usize,
+    front: usize,
+    back: usize,
 }
 
 impl<'a> Iterator for MergeOperandsIter<'a> {
     type Item = &'a [u8];
 
     fn next(&mut self) -> Option<Self::Item> {
-        let operand = self.operands.get_operand(self.cursor)?;
-        self.cursor += 1;
+        if self.front == self.back {
+            return None;
+        }
+        let operand = self.operands.get_operand(self.front)?;
+        self.front += 1;
         Some(operand)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.operands.num_operands - self.cursor;
+        let remaining = self.back - self.front;
         (remaining, Some(remaining))
     }
 }
 
+impl<'a> DoubleEndedIterator for MergeOperandsIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.operands.get_operand(self.back)
+    }
+}
+
+impl<'a> ExactSizeIterator for MergeOperandsIter<'a> {}
+
+impl<'a> FusedIterator for MergeOperandsIter<'a> {}
+
 impl<'a> IntoIterator for &'a MergeOperands {
     type Item = &'a [u8];
     type IntoIter = MergeOperandsIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        Self::IntoIter {
-            operands: self,
-            cursor: 0,
-        }
+        self.iter()
     }
 }
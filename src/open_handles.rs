@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::Error;
+
+/// Kinds of handles which must not outlive the DB they were created from.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum HandleKind {
+    Iterator,
+    Snapshot,
+}
+
+/// Counts the iterators and snapshots of a DB which are still alive.
+///
+/// The handles borrow the DB, so they cannot outlive it in safe code unless
+/// they are leaked, e.g. with `mem::forget`, or moved out of the borrow with
+/// unsafe code. RocksDB would then close the DB under them: the counts make
+/// [`close`](crate::DBCommon::close) fail instead, and dropping the DB leak
+/// it.
+#[derive(Debug, Default)]
+pub struct OpenHandles {
+    iterators: AtomicUsize,
+    snapshots: AtomicUsize,
+}
+
+impl OpenHandles {
+    /// Counts a new handle until the returned guard is dropped.
+    pub(crate) fn track(self: &Arc<Self>, kind: HandleKind) -> HandleGuard {
+        self.counter(kind).fetch_add(1, Ordering::Relaxed);
+        HandleGuard {
+            handles: Arc::clone(self),
+            kind,
+        }
+    }
+
+    fn counter(&self, kind: HandleKind) -> &AtomicUsize {
+        match kind {
+            HandleKind::Iterator => &self.iterators,
+            HandleKind::Snapshot => &self.snapshots,
+        }
+    }
+
+    /// Returns an error naming the handles still alive, if any.
+    pub(crate) fn check_none_left(&self) -> Result<(), Error> {
+        let iterators = self.iterators.load(Ordering::Acquire);
+        let snapshots = self.snapshots.load(Ordering::Acquire);
+        if iterators == 0 && snapshots == 0 {
+            return Ok(());
+        }
+        Err(Error::new(format!(
+            "Resource busy: the DB cannot be closed while {iterators} iterator(s) and \
+             {snapshots} snapshot(s) created from it are alive, drop them before the DB: \
+             they were leaked or moved out of their borrow of the DB"
+        )))
+    }
+}
+
+/// Keeps a handle counted by [`OpenHandles`] while it is alive.
+pub(crate) struct HandleGuard {
+    handles: Arc<OpenHandles>,
+    kind: HandleKind,
+}
+
+impl Drop for HandleGuard {
+    fn drop(&mut self) {
+        self.handles
+            .counter(self.kind)
+            .fetch_sub(1, Ordering::Release);
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{
+    manifest,
+    wal_file::{list_wal_files, read_wal_from},
+    ColumnFamilyDescriptor, DBWithThreadMode, Error, Options, ThreadMode, WalFileType, WalPosition,
+    DEFAULT_COLUMN_FAMILY_NAME,
+};
+
+/// Diagnostics of the opening of a DB, returned by
+/// [`DBWithThreadMode::open_with_report`].
+///
+/// The durations of the recovery phases are best effort: the C API does not
+/// report them, so they are taken from the timestamps of the messages
+/// RocksDB writes to its info log while opening the DB. They are `None` if
+/// the `LOG` file of the DB directory was not written by this open, e.g.
+/// with [`Options::set_db_log_dir`], or if the messages are missing, e.g.
+/// because the info log level is above `Info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenReport {
+    /// Time spent opening the DB
+    pub total: Duration,
+    /// Time spent reading the MANIFEST to recover the files of the column
+    /// families
+    pub manifest_load: Option<Duration>,
+    /// Time spent replaying the WAL files, including the flushes of the
+    /// recovered memtables made during recovery
+    pub wal_recovery: Option<Duration>,
+    /// Numbers of the WAL files replayed, found in the DB directory before
+    /// opening the DB, empty unless the WAL files are inspected
+    pub wal_files_replayed: Vec<u64>,
+    /// Number of write batches read from the replayed WAL files, `None`
+    /// unless the WAL files are inspected, or if they could not be read,
+    /// e.g. because they are compressed, in which case no WAL file is
+    /// reported as replayed
+    pub wal_records_replayed: Option<u64>,
+    /// Names of the column families opened
+    pub column_families: Vec<String>,
+}
+
+impl<T: ThreadMode> DBWithThreadMode<T> {
+    /// Opens the database with the specified options, see [`open`](Self::open),
+    /// and reports where the time was spent, e.g. to investigate a slow start
+    /// after a crash.
+    ///
+    /// With `inspect_wal`, the MANIFEST and the WAL files to replay are read
+    /// once before opening the DB to report the WAL files and count their
+    /// write batches, which slows down the opening of a DB with large WAL
+    /// files.
+    pub fn open_with_report<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        inspect_wal: bool,
+    ) -> Result<(Self, OpenReport), Error> {
+        Self::open_with_report_internal(path.as_ref(), inspect_wal, |path| Self::open(opts, path))
+    }
+
+    /// Opens the database with the given database options and column family
+    /// descriptors, see [`open_cf_descriptors`](Self::open_cf_descriptors),
+    /// and reports where the time was spent.
+    ///
+    /// See [`open_with_report`](Self::open_with_report).
+    pub fn open_cf_descriptors_with_report<P, I>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+        inspect_wal: bool,
+    ) -> Result<(Self, OpenReport), Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        Self::open_with_report_internal(path.as_ref(), inspect_wal, |path| {
+            Self::open_cf_descriptors(opts, path, cfs)
+        })
+    }
+
+    fn open_with_report_internal(
+        path: &Path,
+        inspect_wal: bool,
+        open: impl FnOnce(&Path) -> Result<Self, Error>,
+    ) -> Result<(Self, OpenReport), Error> {
+        let (wal_files_replayed, wal_records_replayed) = if inspect_wal {
+            match wal_to_replay(path) {
+                Ok((files, records)) => (files, Some(records)),
+                Err(_) => (Vec::new(), None),
+            }
+        } else {
+            (Vec::new(), None)
+        };
+
+        let opened_at = SystemTime::now();
+        let start = Instant::now();
+        let db = open(path)?;
+        let total = start.elapsed();
+
+        let log = read_log_since(path, opened_at).unwrap_or_default();
+        let lines: Vec<&str> = log.lines().collect();
+        let mut column_families = db.cfs.cf_names_internal();
+        if !column_families
+            .iter()
+            .any(|cf| cf == DEFAULT_COLUMN_FAMILY_NAME)
+        {
+            column_families.insert(0, DEFAULT_COLUMN_FAMILY_NAME.to_owned());
+        }
+        let report = OpenReport {
+            total,
+            manifest_load: manifest_load(&lines),
+            wal_recovery: wal_recovery(&lines),
+            wal_files_replayed,
+            wal_records_replayed,
+            column_families,
+        };
+        Ok((db, report))
+    }
+}
+
+/// Returns the numbers of the WAL files which opening the DB in `path` will
+/// replay, and the number of write batches they hold.
+fn wal_to_replay(path: &Path) -> Result<(Vec<u64>, u64), Error> {
+    if !path.join("CURRENT").exists() {
+        return Ok((Vec::new(), 0));
+    }
+    // the writes of each column family older than its log number are
+    // already flushed
+    let min_log_number = manifest::describe(path)?
+        .column_families
+        .iter()
+        .map(|cf| cf.log_number)
+        .min()
+        .unwrap_or_default();
+    let files: Vec<u64> = list_wal_files(path)?
+        .into_iter()
+        .filter(|file| file.file_type == WalFileType::Alive && file.number >= min_log_number)
+        .map(|file| file.number)
+        .collect();
+    let mut records = 0;
+    if let Some(&first) = files.first() {
+        let mut reader = read_wal_from(
+            path,
+            WalPosition {
+                file_number: first,
+                offset: 0,
+            },
+        )?;
+        while let Some(record) = reader.next_record() {
+            if files.contains(&record?.position().file_number) {
+                records += 1;
+            }
+        }
+    }
+    Ok((files, records))
+}
+
+/// Reads the `LOG` file of the DB in `path` if it was written since
+/// `opened_at`, and not by a previous open.
+fn read_log_since(path: &Path, opened_at: SystemTime) -> Option<String> {
+    let log = path.join("LOG");
+    let modified = fs::metadata(&log).ok()?.modified().ok()?;
+    // file times can lag behind the system clock by a few ticks
+    if modified + Duration::from_secs(1) < opened_at {
+        return None;
+    }
+    fs::read_to_string(log).ok()
+}
+
+/// Returns the time between the messages RocksDB logs before and after
+/// reading the MANIFEST.
+fn manifest_load(lines: &[&str]) -> Option<Duration> {
+    let start = lines
+        .iter()
+        .find(|line| line.contains("Recovering from manifest file:"))?;
+    let end = lines
+        .iter()
+        .find(|line| line.contains("Recovered from manifest file:"))?;
+    elapsed(start, end)
+}
+
+/// Returns the time between the message RocksDB logs before replaying the
+/// first WAL file and the first message logged once all of them are
+/// replayed and the recovered memtables flushed.
+fn wal_recovery(lines: &[&str]) -> Option<Duration> {
+    let is_recovery = |line: &&str| {
+        line.contains("Recovering log #") || line.contains("WriteLevel0TableForRecovery")
+    };
+    let first = lines.iter().position(is_recovery)?;
+    let last = lines.iter().rposition(is_recovery)?;
+    let end = lines[last + 1..]
+        .iter()
+        .find(|line| log_timestamp(line).is_some())?;
+    elapsed(lines[first], end)
+}
+
+fn elapsed(start: &str, end: &str) -> Option<Duration> {
+    let micros = log_timestamp(end)? - log_timestamp(start)?;
+    Some(Duration::from_micros(micros.max(0) as u64))
+}
+
+/// Parses the timestamp starting a line of the info log, e.g.
+/// `2024/01/01-00:00:00.000000`, into microseconds since the Unix epoch in
+/// the local time zone of the DB.
+fn log_timestamp(line: &str) -> Option<i64> {
+    let stamp = line.get(..26)?;
+    let field = |from: usize, to: usize| -> Option<i64> {
+        let digits = stamp.get(from..to)?;
+        if digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    };
+    let (year, month, day) = (field(0, 4)?, field(5, 7)?, field(8, 10)?);
+    let (hour, minute, second) = (field(11, 13)?, field(14, 16)?, field(17, 19)?);
+    let micros = field(20, 26)?;
+
+    // days since 1970-01-01, see
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some((((days * 24 + hour) * 60 + minute) * 60 + second) * 1_000_000 + micros)
+}
@@ -0,0 +1,540 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{
+    db_options::MergeOperatorId, ColumnFamilyDescriptor, DBCompactionStyle, DBCompressionType,
+    Error, Options, DEFAULT_COLUMN_FAMILY_NAME,
+};
+
+/// Names of the operators a column family is configured with, as recorded in
+/// the options file of the DB.
+///
+/// The names are the ones given when setting the operators, e.g. to
+/// [`Options::set_merge_operator`], or the class names of the operators
+/// shipped with RocksDB.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnFamilyOperators {
+    pub merge_operator: Option<String>,
+    pub compaction_filter: Option<String>,
+    pub compaction_filter_factory: Option<String>,
+}
+
+impl ColumnFamilyOperators {
+    fn from_section(section: &BTreeMap<String, String>) -> Self {
+        let operator = |key: &str| section.get(key).and_then(|value| operator_name(value));
+        Self {
+            merge_operator: operator("merge_operator"),
+            compaction_filter: operator("compaction_filter"),
+            compaction_filter_factory: operator("compaction_filter_factory"),
+        }
+    }
+}
+
+/// An option of a column family which differs from the options it is
+/// compared with, as returned by
+/// [`DBCommon::compare_cf_options`](crate::DBCommon::compare_cf_options).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionMismatch {
+    /// Name of the option in the OPTIONS file, e.g. `write_buffer_size`
+    pub name: &'static str,
+    /// Value of the option for the column family
+    pub existing: String,
+    /// Value of the option in the compared options
+    pub supplied: String,
+}
+
+/// The content of an OPTIONS file, made of sections like
+/// `[CFOptions "default"]` holding `key=value` lines.
+pub(crate) struct OptionsFile {
+    sections: Vec<(String, BTreeMap<String, String>)>,
+}
+
+impl OptionsFile {
+    /// Reads the latest OPTIONS file of the DB in `dir`, written when the DB
+    /// was last opened in read-write mode or its column families changed.
+    ///
+    /// Returns `None` if the DB has no OPTIONS file, e.g. because it does not
+    /// exist yet.
+    pub(crate) fn read_latest(dir: &Path) -> Result<Option<Self>, Error> {
+        let latest = match latest_options_file(dir) {
+            Some(latest) => latest,
+            None => return Ok(None),
+        };
+        let content = fs::read_to_string(&latest)
+            .map_err(|err| Error::new(format!("Failed to read {}: {err}", latest.display())))?;
+
+        let mut sections = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') && line.ends_with(']') {
+                sections.push((line[1..line.len() - 1].to_owned(), BTreeMap::new()));
+            } else if let (Some((key, value)), Some((_, section))) =
+                (line.split_once('='), sections.last_mut())
+            {
+                section.insert(key.trim().to_owned(), value.trim().to_owned());
+            }
+        }
+        Ok(Some(Self { sections }))
+    }
+
+    fn section(&self, name: &str) -> Option<&BTreeMap<String, String>> {
+        self.sections
+            .iter()
+            .find(|(section, _)| section == name)
+            .map(|(_, section)| section)
+    }
+
+    /// Returns the value of `key` in the first section named `name`.
+    pub(crate) fn get(&self, name: &str, key: &str) -> Option<&str> {
+        self.section(name)?.get(key).map(String::as_str)
+    }
+
+    /// Parses the value of the option `key` of the column family `cf`.
+    pub(crate) fn cf_option<V: FromStr>(&self, cf: &str, key: &str) -> Result<V, Error> {
+        let value = self
+            .get(&format!("CFOptions \"{cf}\""), key)
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "Not found: no option {key} of column family {cf} in the OPTIONS file"
+                ))
+            })?;
+        value.parse().map_err(|_| {
+            Error::new(format!(
+                "Corruption: invalid option {key} of column family {cf} in the OPTIONS file"
+            ))
+        })
+    }
+
+    /// Compares the options of the column family `cf` with `opts`, or
+    /// returns `None` if the file has no options for it.
+    pub(crate) fn compare_cf_options(
+        &self,
+        cf: &str,
+        opts: &Options,
+    ) -> Option<Vec<OptionMismatch>> {
+        let section = self.section(&format!("CFOptions \"{cf}\""))?;
+
+        let mut supplied = cf_option_values(opts);
+        if let Some(MergeOperatorId::Named(name)) = &opts.merge_operator {
+            supplied.push(("merge_operator", name.clone()));
+        } else if opts.merge_operator.is_none() {
+            supplied.push(("merge_operator", "nullptr".to_owned()));
+        }
+
+        let mismatches = supplied
+            .into_iter()
+            .filter_map(|(name, supplied)| {
+                let existing = section.get(name)?;
+                let same = if name == "merge_operator" {
+                    operator_name(existing) == operator_name(&supplied)
+                } else {
+                    same_value(existing, &supplied)
+                };
+                if same {
+                    None
+                } else {
+                    Some(OptionMismatch {
+                        name,
+                        existing: existing.clone(),
+                        supplied,
+                    })
+                }
+            })
+            .collect();
+        Some(mismatches)
+    }
+
+    /// Returns the order of the keys of the column family `cf`, failing if it
+    /// has a custom comparator, whose order cannot be reproduced here.
+    pub(crate) fn key_order(&self, cf: &str) -> Result<KeyOrder, Error> {
+        let comparator: String = self.cf_option(cf, "comparator")?;
+        match operator_name(&comparator).as_deref() {
+            Some("leveldb.BytewiseComparator") => Ok(KeyOrder::Bytewise),
+            Some("rocksdb.ReverseBytewiseComparator") => Ok(KeyOrder::ReverseBytewise),
+            _ => Err(Error::new(format!(
+                "Not supported: the keys of column family {cf} are ordered by the custom \
+                 comparator {comparator}"
+            ))),
+        }
+    }
+
+    /// Returns the operators of the column family `cf`, or `None` if the file
+    /// has no options for it.
+    pub(crate) fn cf_operators(&self, cf: &str) -> Option<ColumnFamilyOperators> {
+        self.section(&format!("CFOptions \"{cf}\""))
+            .map(ColumnFamilyOperators::from_section)
+    }
+}
+
+/// The order of the keys of a column family, for the comparators shipped
+/// with RocksDB whose order is known without calling them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeyOrder {
+    Bytewise,
+    ReverseBytewise,
+}
+
+impl KeyOrder {
+    /// Returns the order of the keys of the column family `cf` of the DB in
+    /// `dir`, from its latest OPTIONS file, see [`OptionsFile::key_order`].
+    pub(crate) fn of_cf(dir: &Path, cf: &str) -> Result<Self, Error> {
+        OptionsFile::read_latest(dir)?
+            .ok_or_else(|| Error::new("Not found: the DB has no OPTIONS file".to_owned()))?
+            .key_order(cf)
+    }
+
+    /// Compares two keys in this order.
+    pub(crate) fn compare(self, a: &[u8], b: &[u8]) -> Ordering {
+        match self {
+            KeyOrder::Bytewise => a.cmp(b),
+            KeyOrder::ReverseBytewise => b.cmp(a),
+        }
+    }
+}
+
+fn latest_options_file(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let number: u64 = name.strip_prefix("OPTIONS-")?.parse().ok()?;
+            Some((number, entry.path()))
+        })
+        .max_by_key(|(number, _)| *number)
+        .map(|(_, path)| path)
+}
+
+/// Returns the values of the DB options which can be read back from `opts`,
+/// named and formatted like in OPTIONS files.
+pub(crate) fn db_option_values(opts: &Options) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "create_if_missing",
+            opts.get_create_if_missing().to_string(),
+        ),
+        (
+            "create_missing_column_families",
+            opts.get_create_missing_column_families().to_string(),
+        ),
+        ("error_if_exists", opts.get_error_if_exists().to_string()),
+        ("paranoid_checks", opts.get_paranoid_checks().to_string()),
+        ("max_open_files", opts.get_max_open_files().to_string()),
+        (
+            "max_file_opening_threads",
+            opts.get_max_file_opening_threads().to_string(),
+        ),
+        (
+            "max_total_wal_size",
+            opts.get_max_total_wal_size().to_string(),
+        ),
+        ("use_fsync", opts.get_use_fsync().to_string()),
+        (
+            "delete_obsolete_files_period_micros",
+            opts.get_delete_obsolete_files_period_micros().to_string(),
+        ),
+        (
+            "max_background_jobs",
+            opts.get_max_background_jobs().to_string(),
+        ),
+        (
+            "max_subcompactions",
+            opts.get_max_subcompactions().to_string(),
+        ),
+        (
+            "max_manifest_file_size",
+            opts.get_max_manifest_file_size().to_string(),
+        ),
+        (
+            "stats_dump_period_sec",
+            opts.get_stats_dump_period_sec().to_string(),
+        ),
+        (
+            "stats_persist_period_sec",
+            opts.get_stats_persist_period_sec().to_string(),
+        ),
+        ("use_direct_reads", opts.get_use_direct_reads().to_string()),
+        (
+            "use_direct_io_for_flush_and_compaction",
+            opts.get_use_direct_io_for_flush_and_compaction()
+                .to_string(),
+        ),
+        (
+            "db_write_buffer_size",
+            opts.get_db_write_buffer_size().to_string(),
+        ),
+        ("bytes_per_sync", opts.get_bytes_per_sync().to_string()),
+        (
+            "wal_bytes_per_sync",
+            opts.get_wal_bytes_per_sync().to_string(),
+        ),
+        (
+            "allow_concurrent_memtable_write",
+            opts.get_allow_concurrent_memtable_write().to_string(),
+        ),
+        (
+            "enable_pipelined_write",
+            opts.get_enable_pipelined_write().to_string(),
+        ),
+        ("unordered_write", opts.get_unordered_write().to_string()),
+    ]
+}
+
+/// Returns the values of the column family options which can be read back
+/// from `opts`, named and formatted like in OPTIONS files.
+pub(crate) fn cf_option_values(opts: &Options) -> Vec<(&'static str, String)> {
+    let mut values = vec![
+        (
+            "write_buffer_size",
+            opts.get_write_buffer_size().to_string(),
+        ),
+        (
+            "max_write_buffer_number",
+            opts.get_max_write_buffer_number().to_string(),
+        ),
+        (
+            "min_write_buffer_number_to_merge",
+            opts.get_min_write_buffer_number_to_merge().to_string(),
+        ),
+        (
+            "max_write_buffer_size_to_maintain",
+            opts.get_max_write_buffer_size_to_maintain().to_string(),
+        ),
+        ("num_levels", opts.get_num_levels().to_string()),
+        (
+            "target_file_size_base",
+            opts.get_target_file_size_base().to_string(),
+        ),
+        (
+            "target_file_size_multiplier",
+            opts.get_target_file_size_multiplier().to_string(),
+        ),
+        (
+            "max_bytes_for_level_base",
+            opts.get_max_bytes_for_level_base().to_string(),
+        ),
+        (
+            "max_bytes_for_level_multiplier",
+            opts.get_max_bytes_for_level_multiplier().to_string(),
+        ),
+        (
+            "level_compaction_dynamic_level_bytes",
+            opts.get_level_compaction_dynamic_level_bytes().to_string(),
+        ),
+        (
+            "level0_file_num_compaction_trigger",
+            opts.get_level_zero_file_num_compaction_trigger()
+                .to_string(),
+        ),
+        (
+            "level0_slowdown_writes_trigger",
+            opts.get_level_zero_slowdown_writes_trigger().to_string(),
+        ),
+        (
+            "level0_stop_writes_trigger",
+            opts.get_level_zero_stop_writes_trigger().to_string(),
+        ),
+        (
+            "max_compaction_bytes",
+            opts.get_max_compaction_bytes().to_string(),
+        ),
+        (
+            "soft_pending_compaction_bytes_limit",
+            opts.get_soft_pending_compaction_bytes_limit().to_string(),
+        ),
+        (
+            "hard_pending_compaction_bytes_limit",
+            opts.get_hard_pending_compaction_bytes_limit().to_string(),
+        ),
+        (
+            "disable_auto_compactions",
+            opts.get_disable_auto_compactions().to_string(),
+        ),
+        (
+            "periodic_compaction_seconds",
+            opts.get_periodic_compaction_seconds().to_string(),
+        ),
+        (
+            "max_successive_merges",
+            opts.get_max_successive_merges().to_string(),
+        ),
+        ("bloom_locality", opts.get_bloom_locality().to_string()),
+        (
+            "inplace_update_support",
+            opts.get_inplace_update_support().to_string(),
+        ),
+        (
+            "inplace_update_num_locks",
+            opts.get_inplace_update_locks().to_string(),
+        ),
+        (
+            "memtable_prefix_bloom_size_ratio",
+            opts.get_memtable_prefix_bloom_ratio().to_string(),
+        ),
+        (
+            "memtable_huge_page_size",
+            opts.get_memtable_huge_page_size().to_string(),
+        ),
+        ("arena_block_size", opts.get_arena_block_size().to_string()),
+        (
+            "optimize_filters_for_hits",
+            opts.get_optimize_filters_for_hits().to_string(),
+        ),
+        (
+            "max_sequential_skip_in_iterations",
+            opts.get_max_sequential_skip_in_iterations().to_string(),
+        ),
+        (
+            "enable_blob_files",
+            opts.get_enable_blob_files().to_string(),
+        ),
+        ("min_blob_size", opts.get_min_blob_size().to_string()),
+        ("blob_file_size", opts.get_blob_file_size().to_string()),
+        (
+            "enable_blob_garbage_collection",
+            opts.get_enable_blob_gc().to_string(),
+        ),
+        (
+            "blob_garbage_collection_age_cutoff",
+            opts.get_blob_gc_age_cutoff().to_string(),
+        ),
+        (
+            "blob_garbage_collection_force_threshold",
+            opts.get_blob_gc_force_threshold().to_string(),
+        ),
+        (
+            "blob_compaction_readahead_size",
+            opts.get_blob_compaction_readahead_size().to_string(),
+        ),
+        (
+            "bottommost_compression",
+            opts.get_bottommost_compression_type()
+                .map_or("kDisableCompressionOption", compression_name)
+                .to_owned(),
+        ),
+    ];
+    if let Some(compression) = opts.get_compression_type() {
+        values.push(("compression", compression_name(compression).to_owned()));
+    }
+    if let Some(compression) = opts.get_blob_compression_type() {
+        values.push((
+            "blob_compression_type",
+            compression_name(compression).to_owned(),
+        ));
+    }
+    if let Some(style) = opts.get_compaction_style() {
+        let name = match style {
+            DBCompactionStyle::Level => "kCompactionStyleLevel",
+            DBCompactionStyle::Universal => "kCompactionStyleUniversal",
+            DBCompactionStyle::Fifo => "kCompactionStyleFIFO",
+        };
+        values.push(("compaction_style", name.to_owned()));
+    }
+    // defaults RocksDB replaces with values computed when opening the DB
+    values.retain(|(name, value)| {
+        !matches!(
+            (*name, value.as_str()),
+            ("arena_block_size", "0")
+                | ("max_compaction_bytes", "0")
+                | ("periodic_compaction_seconds", "18446744073709551614")
+        )
+    });
+    values
+}
+
+fn compression_name(compression: DBCompressionType) -> &'static str {
+    match compression {
+        DBCompressionType::None => "kNoCompression",
+        DBCompressionType::Snappy => "kSnappyCompression",
+        DBCompressionType::Zlib => "kZlibCompression",
+        DBCompressionType::Bz2 => "kBZip2Compression",
+        DBCompressionType::Lz4 => "kLZ4Compression",
+        DBCompressionType::Lz4hc => "kLZ4HCCompression",
+        DBCompressionType::Zstd => "kZSTD",
+    }
+}
+
+/// Compares two option values, which RocksDB may format differently than
+/// Rust, e.g. `0.000000` and `0` for floats.
+fn same_value(existing: &str, supplied: &str) -> bool {
+    if existing == supplied {
+        return true;
+    }
+    match (existing.parse::<f64>(), supplied.parse::<f64>()) {
+        (Ok(existing), Ok(supplied)) => {
+            (existing - supplied).abs() <= f64::EPSILON * existing.abs()
+        }
+        _ => false,
+    }
+}
+
+/// Extracts the name of an operator from its serialized form, which is
+/// either `nullptr`, its name, or `{id=name;...}` if it has options.
+fn operator_name(value: &str) -> Option<String> {
+    let name = match value.strip_prefix('{') {
+        Some(options) => options
+            .split(|c| c == ';' || c == '}')
+            .find_map(|option| option.trim().strip_prefix("id="))?,
+        None => value,
+    };
+    if name.is_empty() || name == "nullptr" {
+        None
+    } else {
+        Some(name.to_owned())
+    }
+}
+
+/// Checks that the column families of the DB in `dir` which were created with
+/// a merge operator are opened with the same one, if enabled with
+/// [`Options::set_verify_merge_operators`]: merging values with another
+/// operator would silently produce wrong values.
+///
+/// Operators shipped with RocksDB are only checked to be set, as RocksDB
+/// records them under their class names. The default column family is only
+/// checked if it is given, or if no column family is.
+pub(crate) fn check_merge_operators(
+    dir: &Path,
+    opts: &Options,
+    cfs: &[ColumnFamilyDescriptor],
+) -> Result<(), Error> {
+    if !opts.verify_merge_operators {
+        return Ok(());
+    }
+    let file = match OptionsFile::read_latest(dir)? {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+    // the options of the DB apply to the default column family if no column
+    // family is given
+    let cfs: Vec<(&str, &Options)> = if cfs.is_empty() {
+        vec![(DEFAULT_COLUMN_FAMILY_NAME, opts)]
+    } else {
+        cfs.iter()
+            .map(|cf| (cf.name.as_str(), &cf.options))
+            .collect()
+    };
+    for (cf, opts) in cfs {
+        let recorded = match file.cf_operators(cf).and_then(|ops| ops.merge_operator) {
+            Some(recorded) => recorded,
+            None => continue,
+        };
+        match &opts.merge_operator {
+            None => {
+                return Err(Error::new(format!(
+                    "Invalid argument: column family {cf} was created with merge operator \
+                     {recorded} but is opened without a merge operator"
+                )))
+            }
+            Some(MergeOperatorId::Named(name)) if *name != recorded => {
+                return Err(Error::new(format!(
+                    "Invalid argument: column family {cf} was created with merge operator \
+                     {recorded} but is opened with merge operator {name}"
+                )))
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
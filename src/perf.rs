@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
 use libc::{c_int, c_uchar, c_void};
 
-use crate::{db::DBInner, ffi, ffi_util::from_cstr, Cache, Error, DB};
+use crate::{db::DBInner, ffi, ffi_util::from_cstr, Cache, Error, Options, DB};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -111,11 +115,24 @@ pub enum PerfMetric {
     TotalMetricCount = 68,
 }
 
+thread_local! {
+    /// Level last set with `set_perf_stats` on this thread, which the C API
+    /// cannot read back. RocksDB starts every thread with `EnableCount`.
+    static PERF_STATS_LEVEL: Cell<PerfStatsLevel> = Cell::new(PerfStatsLevel::EnableCount);
+}
+
 /// Sets the perf stats level for current thread.
 pub fn set_perf_stats(lvl: PerfStatsLevel) {
     unsafe {
         ffi::rocksdb_set_perf_level(lvl as c_int);
     }
+    PERF_STATS_LEVEL.with(|level| level.set(lvl));
+}
+
+/// Returns the perf stats level of the current thread, as set with
+/// [`set_perf_stats`].
+pub(crate) fn perf_stats_level() -> PerfStatsLevel {
+    PERF_STATS_LEVEL.with(Cell::get)
 }
 
 /// Thread local context for gathering performance counter efficiently
@@ -166,6 +183,201 @@ impl PerfContext {
     }
 }
 
+/// Counters of the filters of the SST files, e.g. bloom filters, to
+/// validate the bits per key and the prefix extractor of a column family.
+///
+/// A lookup is checked against the filter of each SST file which may hold
+/// the key: the filter is useful when it excludes the key, sparing a read
+/// of the file, and positive otherwise. A positive check is a false positive
+/// when the key is not in the file after all.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct FilterEffectiveness {
+    /// Number of whole key checks which excluded the key
+    pub useful: u64,
+    /// Number of whole key checks which did not exclude the key
+    pub full_positive: u64,
+    /// Number of the positive whole key checks which found the key in the
+    /// file, `None` when not counted
+    pub full_true_positive: Option<u64>,
+    /// Number of prefix checks, e.g. by the seeks of iterators
+    pub prefix_checked: u64,
+    /// Number of prefix checks which excluded the prefix
+    pub prefix_useful: u64,
+}
+
+impl FilterEffectiveness {
+    /// Reads the counters of the filters of all column families from the
+    /// statistics of a DB, `None` if
+    /// [`enable_statistics`](Options::enable_statistics) was not called on
+    /// the options the DB was opened with.
+    ///
+    /// RocksDB keeps statistics per DB, use a [`FilterStatsRecorder`] to
+    /// tell the column families apart.
+    pub fn from_statistics(opts: &Options) -> Option<Self> {
+        let stats = opts.get_statistics()?;
+        let ticker = |name: &str| {
+            stats
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(" COUNT : "))
+                .and_then(|count| count.trim().parse().ok())
+                .unwrap_or_default()
+        };
+        Some(Self {
+            useful: ticker("rocksdb.bloom.filter.useful"),
+            full_positive: ticker("rocksdb.bloom.filter.full.positive"),
+            full_true_positive: Some(ticker("rocksdb.bloom.filter.full.true.positive")),
+            prefix_checked: ticker("rocksdb.bloom.filter.prefix.checked"),
+            prefix_useful: ticker("rocksdb.bloom.filter.prefix.useful"),
+        })
+    }
+
+    /// Reads the counters of the filters of the SST files from a perf
+    /// context, which counts the checks of the calling thread while the
+    /// perf level is at least [`PerfStatsLevel::EnableCount`].
+    ///
+    /// The perf context does not tell whole key checks from prefix checks,
+    /// they are all counted as whole key checks, and does not count true
+    /// positives.
+    pub fn from_perf_context(ctx: &PerfContext) -> Self {
+        Self {
+            useful: ctx.metric(PerfMetric::BloomSstMissCount),
+            full_positive: ctx.metric(PerfMetric::BloomSstHitCount),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the number of whole key checks.
+    pub fn checked(&self) -> u64 {
+        self.useful + self.full_positive
+    }
+
+    /// Returns the share of the whole key checks which excluded the key,
+    /// `None` if there was no check.
+    pub fn useful_ratio(&self) -> Option<f64> {
+        ratio(self.useful, self.checked())
+    }
+
+    /// Returns the share of the lookups of keys missing from a file which
+    /// the filter did not exclude, to compare with the false positive rate
+    /// expected from the bits per key, `None` if the true positives are not
+    /// counted or no missing key was looked up.
+    pub fn false_positive_rate(&self) -> Option<f64> {
+        let false_positive = self.full_positive.saturating_sub(self.full_true_positive?);
+        ratio(false_positive, false_positive + self.useful)
+    }
+
+    /// Returns the share of the prefix checks which excluded the prefix,
+    /// `None` if there was no check.
+    pub fn prefix_useful_ratio(&self) -> Option<f64> {
+        ratio(self.prefix_useful, self.prefix_checked)
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.useful += other.useful;
+        self.full_positive += other.full_positive;
+        self.full_true_positive = match (self.full_true_positive, other.full_true_positive) {
+            (Some(count), Some(other)) => Some(count + other),
+            _ => None,
+        };
+        self.prefix_checked += other.prefix_checked;
+        self.prefix_useful += other.prefix_useful;
+    }
+
+    fn since(&self, start: &Self) -> Self {
+        Self {
+            useful: self.useful.saturating_sub(start.useful),
+            full_positive: self.full_positive.saturating_sub(start.full_positive),
+            full_true_positive: None,
+            prefix_checked: self.prefix_checked.saturating_sub(start.prefix_checked),
+            prefix_useful: self.prefix_useful.saturating_sub(start.prefix_useful),
+        }
+    }
+}
+
+fn ratio(count: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| count as f64 / total as f64)
+}
+
+/// Aggregates the [`FilterEffectiveness`] of the reads of each column
+/// family.
+///
+/// RocksDB keeps statistics per DB, so the checks of the filters of each
+/// column family are counted by wrapping its reads with
+/// [`record`](Self::record), which measures them with the perf context of
+/// the reading thread. The perf level of the thread must be at least
+/// [`PerfStatsLevel::EnableCount`], see [`set_perf_stats`].
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::perf::{set_perf_stats, FilterStatsRecorder, PerfStatsLevel};
+/// use rocksdb::{BlockBasedOptions, Options, DB};
+///
+/// let path = "_path_for_rocksdb_storage_filter_stats_recorder";
+/// {
+///     let mut block_opts = BlockBasedOptions::default();
+///     block_opts.set_bloom_filter(10.0, false);
+///     let mut opts = Options::default();
+///     opts.create_if_missing(true);
+///     opts.set_block_based_table_factory(&block_opts);
+///
+///     let db = DB::open(&opts, path).unwrap();
+///     db.put(b"key", b"value").unwrap();
+///     db.flush().unwrap();
+///
+///     set_perf_stats(PerfStatsLevel::EnableCount);
+///     let recorder = FilterStatsRecorder::new();
+///     for key in [&b"key"[..], b"missing"] {
+///         recorder.record("default", || db.get(key)).unwrap();
+///     }
+///     assert_eq!(recorder.get("default").checked(), 2);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+#[derive(Debug, Default)]
+pub struct FilterStatsRecorder {
+    stats: Mutex<BTreeMap<String, FilterEffectiveness>>,
+}
+
+impl FilterStatsRecorder {
+    /// Creates a recorder without any check recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `read`, which should only read the column family `cf`, and adds
+    /// the checks of the filters it made to the ones of `cf`.
+    pub fn record<R>(&self, cf: &str, read: impl FnOnce() -> R) -> R {
+        let ctx = PerfContext::default();
+        let start = FilterEffectiveness::from_perf_context(&ctx);
+        let result = read();
+        let checks = FilterEffectiveness::from_perf_context(&ctx).since(&start);
+        self.stats
+            .lock()
+            .unwrap()
+            .entry(cf.to_owned())
+            .or_default()
+            .add(&checks);
+        result
+    }
+
+    /// Returns the checks recorded for the column family `cf`.
+    pub fn get(&self, cf: &str) -> FilterEffectiveness {
+        let stats = self.stats.lock().unwrap();
+        stats.get(cf).copied().unwrap_or_default()
+    }
+
+    /// Returns the checks recorded for each column family, by name.
+    pub fn snapshot(&self) -> BTreeMap<String, FilterEffectiveness> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Clears the checks recorded.
+    pub fn reset(&self) {
+        self.stats.lock().unwrap().clear();
+    }
+}
+
 /// Memory usage stats
 pub struct MemoryUsageStats {
     /// Approximate memory usage of all the mem-tables
@@ -283,3 +495,239 @@ pub fn get_memory_usage_stats(
         cache_total: mu.approximate_cache_total(),
     })
 }
+
+/// Usage of a block cache and the column families sharing it
+pub struct CacheUsage {
+    /// The cache
+    pub cache: Cache,
+    /// Names of the column families using the cache
+    pub column_families: Vec<String>,
+    /// Memory usage of the cache in bytes
+    pub usage: usize,
+    /// Memory usage of the entries pinned in the cache in bytes
+    pub pinned_usage: usize,
+}
+
+impl CacheUsage {
+    /// Returns `true` if the cache is shared by several column families.
+    pub fn is_shared(&self) -> bool {
+        self.column_families.len() > 1
+    }
+}
+
+/// Block cache usage of a set of column families, grouped by the cache they
+/// use.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{perf::CacheUsageReport, BlockBasedOptions, Cache, Options};
+///
+/// let shared = Cache::new_lru_cache(64 * 1024 * 1024);
+/// let mut block_opts = BlockBasedOptions::default();
+/// block_opts.set_block_cache(&shared);
+///
+/// let mut hot = Options::default();
+/// hot.set_block_based_table_factory(&block_opts);
+/// let mut warm = Options::default();
+/// warm.set_block_based_table_factory(&block_opts);
+/// let cold = Options::default();
+///
+/// let report = CacheUsageReport::new([("hot", &hot), ("warm", &warm), ("cold", &cold)]);
+/// assert_eq!(report.caches.len(), 1);
+/// assert!(report.caches[0].is_shared());
+/// assert_eq!(report.internal_cache_column_families, vec!["cold"]);
+/// ```
+pub struct CacheUsageReport {
+    /// The block caches, in order of the first column family using them
+    pub caches: Vec<CacheUsage>,
+    /// Names of the column families without a block cache set, which use an
+    /// internal cache of their own
+    pub internal_cache_column_families: Vec<String>,
+}
+
+impl CacheUsageReport {
+    /// Groups the column families by the block cache set on their options,
+    /// see [`Options::get_block_cache`], and reads the usage of the caches.
+    pub fn new<'a, I>(column_families: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a Options)>,
+    {
+        let mut caches: Vec<CacheUsage> = Vec::new();
+        let mut internal_cache_column_families = Vec::new();
+        for (name, opts) in column_families {
+            let cache = match opts.get_block_cache() {
+                Some(cache) => cache,
+                None => {
+                    internal_cache_column_families.push(name.to_owned());
+                    continue;
+                }
+            };
+            match caches
+                .iter_mut()
+                .find(|usage| Cache::ptr_eq(&usage.cache, &cache))
+            {
+                Some(usage) => usage.column_families.push(name.to_owned()),
+                None => caches.push(CacheUsage {
+                    usage: cache.get_usage(),
+                    pinned_usage: cache.get_pinned_usage(),
+                    cache,
+                    column_families: vec![name.to_owned()],
+                }),
+            }
+        }
+        Self {
+            caches,
+            internal_cache_column_families,
+        }
+    }
+}
+
+/// Role of the entries of a block cache, as tracked by RocksDB
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum CacheEntryRole {
+    /// Data blocks of the tables
+    DataBlock,
+    /// Filter blocks, full or partitioned
+    FilterBlock,
+    /// Top-level index of the partitioned filters
+    FilterMetaBlock,
+    /// Block-based filters of the older table formats
+    DeprecatedFilterBlock,
+    /// Index blocks, full or partitioned
+    IndexBlock,
+    /// Other kinds of blocks, e.g. the compression dictionary
+    OtherBlock,
+    /// Memory charged for the write buffers
+    WriteBuffer,
+    /// Memory charged for building compression dictionaries
+    CompressionDictionaryBuildingBuffer,
+    /// Memory charged for building filters
+    FilterConstruction,
+    /// Memory charged for the table readers
+    BlockBasedTableReader,
+    /// Memory charged for the file metadata
+    FileMetadata,
+    /// Blob values
+    BlobValue,
+    /// Memory charged for the blob cache
+    BlobCache,
+    /// Other entries
+    Misc,
+}
+
+impl CacheEntryRole {
+    /// Returns the role named `name` as in the statistics of RocksDB, e.g.
+    /// `"DataBlock"`.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "DataBlock" => Self::DataBlock,
+            "FilterBlock" => Self::FilterBlock,
+            "FilterMetaBlock" => Self::FilterMetaBlock,
+            "DeprecatedFilterBlock" => Self::DeprecatedFilterBlock,
+            "IndexBlock" => Self::IndexBlock,
+            "OtherBlock" => Self::OtherBlock,
+            "WriteBuffer" => Self::WriteBuffer,
+            "CompressionDictionaryBuildingBuffer" => Self::CompressionDictionaryBuildingBuffer,
+            "FilterConstruction" => Self::FilterConstruction,
+            "BlockBasedTableReader" => Self::BlockBasedTableReader,
+            "FileMetadata" => Self::FileMetadata,
+            "BlobValue" => Self::BlobValue,
+            "BlobCache" => Self::BlobCache,
+            "Misc" => Self::Misc,
+            _ => return None,
+        })
+    }
+}
+
+/// Usage of a block cache by the entries of a role
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct CacheRoleUsage {
+    /// Number of entries
+    pub count: u64,
+    /// Memory charged for the entries in bytes
+    pub bytes: u64,
+}
+
+/// Usage of a block cache broken down by role of the entries, see
+/// [`get_block_cache_usage_by_role`](crate::DBCommon::get_block_cache_usage_by_role).
+///
+/// RocksDB reports the sizes rounded to two decimals of the largest unit,
+/// e.g. `1.25 MB`, they are approximate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheUsageByRole {
+    /// Capacity of the cache in bytes
+    pub capacity: u64,
+    /// Memory usage of the cache in bytes
+    pub usage: u64,
+    /// Usage of the roles having entries in the cache
+    pub roles: BTreeMap<CacheEntryRole, CacheRoleUsage>,
+}
+
+impl CacheUsageByRole {
+    /// Returns the usage of the entries of `role`, zero if the cache has none.
+    pub fn get(&self, role: CacheEntryRole) -> CacheRoleUsage {
+        self.roles.get(&role).copied().unwrap_or_default()
+    }
+
+    /// Parses the value of the
+    /// [`BLOCK_CACHE_ENTRY_STATS`](crate::properties::BLOCK_CACHE_ENTRY_STATS)
+    /// property, e.g.
+    ///
+    /// ```text
+    /// Block cache LRUCache@0x5600bb634770#7 capacity: 8.00 MB seed: 1 usage: 3.12 KB ...
+    /// Block cache entry stats(count,size,portion): DataBlock(1,0.69 KB,0.0084%) Misc(1,0.00 KB,0%)
+    /// ```
+    pub(crate) fn parse(value: &str) -> Result<Self, Error> {
+        let invalid = || Error::new(format!("Invalid block cache entry stats: {value}"));
+        let mut stats = Self::default();
+        let mut lines = value.lines();
+        let header: Vec<&str> = lines.next().unwrap_or_default().split(' ').collect();
+        for (i, field) in header.iter().enumerate() {
+            let total = match *field {
+                "capacity:" => &mut stats.capacity,
+                "usage:" => &mut stats.usage,
+                _ => continue,
+            };
+            *total = match header.get(i + 1..i + 3) {
+                Some([size, unit]) => parse_human_bytes(size, unit).ok_or_else(invalid)?,
+                _ => return Err(invalid()),
+            };
+        }
+        let entries = lines
+            .find_map(|line| line.strip_prefix("Block cache entry stats(count,size,portion):"))
+            .unwrap_or_default();
+        for entry in entries.split(')').map(str::trim).filter(|e| !e.is_empty()) {
+            let (name, fields) = entry.split_once('(').ok_or_else(invalid)?;
+            let mut fields = fields.split(',');
+            let count = fields.next().and_then(|count| count.parse().ok());
+            let bytes = fields
+                .next()
+                .and_then(|size| size.split_once(' '))
+                .and_then(|(size, unit)| parse_human_bytes(size, unit));
+            let usage = match (count, bytes) {
+                (Some(count), Some(bytes)) => CacheRoleUsage { count, bytes },
+                _ => return Err(invalid()),
+            };
+            // roles added by later versions of RocksDB
+            if let Some(role) = CacheEntryRole::from_name(name) {
+                stats.roles.insert(role, usage);
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Converts a size printed by RocksDB, e.g. `1.25 MB`, to bytes.
+fn parse_human_bytes(size: &str, unit: &str) -> Option<u64> {
+    let exponent = match unit {
+        "KB" => 1,
+        "MB" => 2,
+        "GB" => 3,
+        "TB" => 4,
+        _ => return None,
+    };
+    let size: f64 = size.parse().ok()?;
+    Some((size * 1024f64.powi(exponent)).round() as u64)
+}
@@ -0,0 +1,69 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::Error;
+
+struct PeriodicState {
+    stopped: Mutex<bool>,
+    condvar: Condvar,
+    last_error: Mutex<Option<Error>>,
+}
+
+/// A background thread running a task periodically until it is dropped.
+pub(crate) struct PeriodicThread {
+    state: Arc<PeriodicState>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PeriodicThread {
+    /// Spawns a thread named `name` calling `task` every `interval`.
+    pub(crate) fn spawn<F>(name: &str, task: F, interval: Duration) -> Result<Self, Error>
+    where
+        F: Fn() -> Result<(), Error> + Send + 'static,
+    {
+        let state = Arc::new(PeriodicState {
+            stopped: Mutex::new(false),
+            condvar: Condvar::new(),
+            last_error: Mutex::new(None),
+        });
+        let thread_state = Arc::clone(&state);
+        let thread = thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || {
+                let mut stopped = thread_state.stopped.lock().unwrap();
+                while !*stopped {
+                    if let Err(err) = task() {
+                        *thread_state.last_error.lock().unwrap() = Some(err);
+                    }
+                    stopped = thread_state
+                        .condvar
+                        .wait_timeout(stopped, interval)
+                        .unwrap()
+                        .0;
+                }
+            })
+            .map_err(|err| Error::new(format!("Failed to spawn the {name} thread: {err}")))?;
+
+        Ok(Self {
+            state,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the error of the last failed run of the task, if any, and
+    /// clears it.
+    pub(crate) fn take_error(&self) -> Option<Error> {
+        self.state.last_error.lock().unwrap().take()
+    }
+}
+
+impl Drop for PeriodicThread {
+    fn drop(&mut self) {
+        *self.state.stopped.lock().unwrap() = true;
+        self.state.condvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
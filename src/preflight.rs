@@ -0,0 +1,225 @@
+use std::fs;
+use std::path::Path;
+
+use crate::{
+    db_lock::lock_holder, ffi, options_file::OptionsFile, DBWithThreadMode, Options, ThreadMode,
+    DEFAULT_COLUMN_FAMILY_NAME,
+};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    /// Opening the DB will fail.
+    Error,
+    /// Opening the DB may fail or behave unexpectedly.
+    Warning,
+}
+
+/// A problem found by [`DBWithThreadMode::preflight`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// There is no DB at the path and `create_if_missing` is disabled.
+    DbMissing,
+    /// There is a DB at the path and `error_if_exists` is enabled.
+    DbExists,
+    /// The path exists but is not a directory.
+    NotADirectory,
+    /// The directory of the DB is not writable.
+    ReadOnly,
+    /// The DB is already opened by a process, given if it is known.
+    Locked { pid: Option<u32> },
+    /// Column families to open which do not exist, while
+    /// `create_missing_column_families` is disabled.
+    MissingColumnFamilies(Vec<String>),
+    /// Column families of the DB which would not be opened. RocksDB requires
+    /// all column families to be opened in read-write mode.
+    UnopenedColumnFamilies(Vec<String>),
+    /// The DB was last opened by a newer version of RocksDB than the one
+    /// linked, see [`librocksdb_sys::ROCKSDB_VERSION`], and may use features
+    /// this version does not support.
+    NewerVersion { version: String },
+}
+
+/// A problem which prevents opening a DB, or may cause trouble when doing so.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    pub kind: DiagnosticKind,
+    /// Description of the problem and how to solve it
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(kind: DiagnosticKind, message: String) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Error,
+            kind,
+            message,
+        }
+    }
+
+    fn warning(kind: DiagnosticKind, message: String) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Warning,
+            kind,
+            message,
+        }
+    }
+}
+
+impl<T: ThreadMode> DBWithThreadMode<T> {
+    /// Checks whether the DB at `path` can be opened with [`open`](Self::open),
+    /// and returns the problems found. An empty list does not guarantee that
+    /// opening succeeds, but the problems RocksDB reports with a terse error
+    /// message are described here with their cause.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DiagnosticSeverity, Options, DB};
+    ///
+    /// let path = "_path_for_rocksdb_storage_preflight";
+    /// let mut opts = Options::default();
+    /// opts.create_if_missing(true);
+    /// for diagnostic in DB::preflight(&opts, path) {
+    ///     if diagnostic.severity == DiagnosticSeverity::Error {
+    ///         panic!("cannot open {path}: {}", diagnostic.message);
+    ///     }
+    /// }
+    /// let db = DB::open(&opts, path).unwrap();
+    /// # drop(db);
+    /// # let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn preflight<P: AsRef<Path>>(opts: &Options, path: P) -> Vec<Diagnostic> {
+        Self::preflight_cf(opts, path, [DEFAULT_COLUMN_FAMILY_NAME])
+    }
+
+    /// Checks whether the DB at `path` can be opened with the given column
+    /// families, see [`preflight`](Self::preflight).
+    pub fn preflight_cf<P, I, N>(opts: &Options, path: P, cfs: I) -> Vec<Diagnostic>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let path = path.as_ref();
+        let mut diagnostics = Vec::new();
+
+        match fs::metadata(path) {
+            Ok(metadata) if !metadata.is_dir() => {
+                diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::NotADirectory,
+                    format!("{} is not a directory", path.display()),
+                ));
+                return diagnostics;
+            }
+            Ok(metadata) if metadata.permissions().readonly() => {
+                diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ReadOnly,
+                    format!(
+                        "{} is read-only, open the DB with open_for_read_only instead",
+                        path.display()
+                    ),
+                ));
+            }
+            _ => {}
+        }
+
+        if !path.join("CURRENT").exists() {
+            if !opts.get_create_if_missing() {
+                diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::DbMissing,
+                    format!(
+                        "there is no DB at {}, enable create_if_missing to create it",
+                        path.display()
+                    ),
+                ));
+            }
+            return diagnostics;
+        }
+
+        if opts.get_error_if_exists() {
+            diagnostics.push(Diagnostic::error(
+                DiagnosticKind::DbExists,
+                format!(
+                    "there is already a DB at {} and error_if_exists is enabled",
+                    path.display()
+                ),
+            ));
+        }
+
+        if let Some(pid) = lock_holder(&path.join("LOCK")) {
+            let holder = match pid {
+                Some(pid) if pid == std::process::id() => "this process".to_owned(),
+                Some(pid) => format!("process {pid}"),
+                None => "another process".to_owned(),
+            };
+            diagnostics.push(Diagnostic::error(
+                DiagnosticKind::Locked { pid },
+                format!(
+                    "the DB at {} is opened by {holder}, close it or open a secondary instance",
+                    path.display()
+                ),
+            ));
+        }
+
+        if let Ok(existing) = Self::list_cf(opts, path) {
+            let requested: Vec<String> = cfs.into_iter().map(|cf| cf.as_ref().to_owned()).collect();
+            let missing: Vec<String> = requested
+                .iter()
+                .filter(|cf| !existing.contains(cf) && cf.as_str() != DEFAULT_COLUMN_FAMILY_NAME)
+                .cloned()
+                .collect();
+            if !missing.is_empty() && !opts.get_create_missing_column_families() {
+                diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::MissingColumnFamilies(missing.clone()),
+                    format!(
+                        "column families {missing:?} do not exist, enable \
+                         create_missing_column_families to create them"
+                    ),
+                ));
+            }
+            let unopened: Vec<String> = existing
+                .into_iter()
+                .filter(|cf| !requested.contains(cf) && cf != DEFAULT_COLUMN_FAMILY_NAME)
+                .collect();
+            if !unopened.is_empty() {
+                diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::UnopenedColumnFamilies(unopened.clone()),
+                    format!(
+                        "the DB has column families {unopened:?} which are not opened, \
+                         list them with DB::list_cf and open them all"
+                    ),
+                ));
+            }
+        }
+
+        if let Some(version) = written_by_version(path) {
+            let parse = |version: &str| -> Vec<u32> {
+                version.split('.').filter_map(|n| n.parse().ok()).collect()
+            };
+            if parse(&version) > parse(ffi::ROCKSDB_VERSION) {
+                diagnostics.push(Diagnostic::warning(
+                    DiagnosticKind::NewerVersion {
+                        version: version.clone(),
+                    },
+                    format!(
+                        "the DB was last opened by RocksDB {version}, newer than the linked {}",
+                        ffi::ROCKSDB_VERSION
+                    ),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Returns the RocksDB version recorded in the latest OPTIONS file of the DB.
+fn written_by_version(path: &Path) -> Option<String> {
+    OptionsFile::read_latest(path)
+        .ok()??
+        .get("Version", "rocksdb_version")
+        .map(str::to_owned)
+}
@@ -39,7 +39,7 @@ pub const SSTABLES: &PropName = property!("sstables");
 /// "rocksdb.cfstats" - Both of "rocksdb.cfstats-no-file-histogram" and
 /// "rocksdb.cf-file-histogram" together. See below for description
 /// of the two.
-pub const CFSTATS: &PropName = property!("CFSTATS");
+pub const CFSTATS: &PropName = property!("cfstats");
 
 /// "rocksdb.cfstats-no-file-histogram" - returns a multi-line string with
 /// general column family stats per-level over db's lifetime ("`L<n>`"),
@@ -141,6 +141,10 @@ pub const NUM_SNAPSHOTS: &PropName = property!("num-snapshots");
 /// timestamp of oldest unreleased snapshot.
 pub const OLDEST_SNAPSHOT_TIME: &PropName = property!("oldest-snapshot-time");
 
+/// "rocksdb.oldest-snapshot-sequence" - returns number representing the
+/// sequence number of oldest unreleased snapshot.
+pub const OLDEST_SNAPSHOT_SEQUENCE: &PropName = property!("oldest-snapshot-sequence");
+
 /// "rocksdb.num-live-versions" - returns number of live versions. `Version`
 /// is an internal data structure. See version_set.h for details. More
 /// live versions often mean more SST files are held from being deleted,
@@ -221,6 +225,10 @@ pub const BLOCK_CACHE_USAGE: &PropName = property!("block-cache-usage");
 /// entries being pinned.
 pub const BLOCK_CACHE_PINNED_USAGE: &PropName = property!("block-cache-pinned-usage");
 
+/// "rocksdb.block-cache-entry-stats" - returns a multi-line string with
+/// the usage of the block cache broken down by role of the entries.
+pub const BLOCK_CACHE_ENTRY_STATS: &PropName = property!("block-cache-entry-stats");
+
 /// "rocksdb.options-statistics" - returns multi-line string
 /// of options.statistics
 pub const OPTIONS_STATISTICS: &PropName = property!("options-statistics");
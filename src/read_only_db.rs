@@ -0,0 +1,551 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    db::DBAccess, ffi, ffi_util::CStrLike, open_handles::OpenHandles, AsColumnFamilyRef,
+    CatchUpThread, ColumnFamily, ColumnFamilyDescriptor, DBIteratorWithThreadMode, DBPinnableSlice,
+    DBRawIteratorWithThreadMode, Error, IteratorMode, LiveFile, Options, ReadOptions,
+    SnapshotWithThreadMode, DB,
+};
+
+/// Defines methods forwarding to the methods of the same name of the wrapped
+/// `db`. Generics and where clauses are given in brackets.
+macro_rules! forward_to_db {
+    ($(
+        $(#[doc = $doc:expr])*
+        $vis:vis fn $name:ident[$($generics:tt)*](&self $(, $arg:ident: $arg_ty:ty)* $(,)?) -> $ret:ty
+        $([where $($bounds:tt)*])?;
+    )*) => {
+        $(
+            $(#[doc = $doc])*
+            $vis fn $name<$($generics)*>(&self $(, $arg: $arg_ty)*) -> $ret
+            $(where $($bounds)*)?
+            {
+                self.db.$name($($arg),*)
+            }
+        )*
+    };
+}
+
+/// A DB opened for read only, which only has the read methods of [`DB`].
+///
+/// Writing to a DB opened with [`DB::open_for_read_only`] fails at runtime;
+/// with this type, it does not compile. Open it with [`ReadOnlyDB::open`] or
+/// [`DB::open_checkpoint_read_only`].
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{checkpoint::Checkpoint, Options, DB};
+///
+/// let path = "_path_for_rocksdb_storage_read_only_db";
+/// let checkpoint_path = "_path_for_rocksdb_storage_read_only_db_checkpoint";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     db.put(b"key", b"value").unwrap();
+///     Checkpoint::new(&db).unwrap().create_checkpoint(checkpoint_path).unwrap();
+///
+///     let opts = Options::default();
+///     let checkpoint = DB::open_checkpoint_read_only(&opts, checkpoint_path).unwrap();
+///     assert_eq!(checkpoint.get(b"key").unwrap().unwrap(), b"value");
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// let _ = DB::destroy(&Options::default(), checkpoint_path);
+/// ```
+#[derive(Debug)]
+pub struct ReadOnlyDB {
+    db: DB,
+}
+
+impl DB {
+    /// Opens a checkpoint created with [`Checkpoint`](crate::checkpoint::Checkpoint)
+    /// for read only, with all its column families.
+    ///
+    /// Returns an error without opening the checkpoint if it is incomplete,
+    /// i.e. its `CURRENT` file or the MANIFEST it names is missing. The WAL
+    /// files of the checkpoint, if any, are replayed in memory so that the
+    /// writes they hold are visible.
+    ///
+    /// `opts` is used for the column families too, so it must have the merge
+    /// operator and comparator the checkpoint was written with.
+    pub fn open_checkpoint_read_only<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+    ) -> Result<ReadOnlyDB, Error> {
+        let path = path.as_ref();
+        check_checkpoint(path)?;
+        let cfs = DB::list_cf(opts, path)?
+            .into_iter()
+            .map(|name| ColumnFamilyDescriptor::new(name, opts.clone()));
+        ReadOnlyDB::open_cf_descriptors(opts, path, cfs, false)
+    }
+}
+
+/// Checks that the `CURRENT` file of a checkpoint names a MANIFEST which
+/// exists.
+fn check_checkpoint(path: &Path) -> Result<(), Error> {
+    let current = match fs::read_to_string(path.join("CURRENT")) {
+        Ok(current) => current,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::new(format!(
+                "Invalid argument: there is no checkpoint at {}, it has no CURRENT file",
+                path.display()
+            )));
+        }
+        Err(err) => {
+            return Err(Error::new(format!(
+                "IO error: failed to read the CURRENT file of {}: {err}",
+                path.display()
+            )));
+        }
+    };
+    let manifest = current.trim_end();
+    if !manifest.starts_with("MANIFEST-") || !path.join(manifest).is_file() {
+        return Err(Error::new(format!(
+            "Corruption: the checkpoint at {} is incomplete, its CURRENT file names \
+             {manifest:?} which does not exist",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+impl ReadOnlyDB {
+    /// Opens the database for read only, see [`DB::open_for_read_only`].
+    pub fn open<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        error_if_log_file_exist: bool,
+    ) -> Result<Self, Error> {
+        Self::open_cf(opts, path, None::<&str>, error_if_log_file_exist)
+    }
+
+    /// Opens the database for read only with the given column family names,
+    /// see [`DB::open_cf_for_read_only`].
+    pub fn open_cf<P, I, N>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+        error_if_log_file_exist: bool,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let db = DB::open_cf_for_read_only(opts, path, cfs, error_if_log_file_exist)?;
+        Ok(Self { db })
+    }
+
+    /// Opens the database for read only with the given column family
+    /// descriptors, see [`DB::open_cf_descriptors_read_only`].
+    pub fn open_cf_descriptors<P, I>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+        error_if_log_file_exist: bool,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        let db = DB::open_cf_descriptors_read_only(opts, path, cfs, error_if_log_file_exist)?;
+        Ok(Self { db })
+    }
+
+    forward_to_db! {
+        /// Returns the path of the DB.
+        pub fn path[](&self) -> &Path;
+
+        /// Returns the underlying column family handle.
+        pub fn cf_handle[](&self, name: &str) -> Option<&ColumnFamily>;
+
+        /// Returns the handles of all the opened column families, by name.
+        pub fn cf_handles[](&self) -> BTreeMap<&str, &ColumnFamily>;
+
+        /// Returns the bytes associated with a key value, see [`DB::get`].
+        pub fn get[K: AsRef<[u8]>](&self, key: K) -> Result<Option<Vec<u8>>, Error>;
+
+        /// Returns the bytes associated with a key value with read options.
+        pub fn get_opt[K: AsRef<[u8]>](
+            &self,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<Vec<u8>>, Error>;
+
+        /// Returns the bytes associated with a key value and column family.
+        pub fn get_cf[K: AsRef<[u8]>](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            key: K,
+        ) -> Result<Option<Vec<u8>>, Error>;
+
+        /// Returns the bytes associated with a key value, column family and
+        /// read options.
+        pub fn get_cf_opt[K: AsRef<[u8]>](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<Vec<u8>>, Error>;
+
+        /// Returns the value associated with a key without copying it, see
+        /// [`DB::get_pinned`].
+        pub fn get_pinned[K: AsRef<[u8]>](&self, key: K) -> Result<Option<DBPinnableSlice>, Error>;
+
+        /// Returns the value associated with a key without copying it, with
+        /// read options.
+        pub fn get_pinned_opt[K: AsRef<[u8]>](
+            &self,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<DBPinnableSlice>, Error>;
+
+        /// Returns the value associated with a key and column family without
+        /// copying it.
+        pub fn get_pinned_cf[K: AsRef<[u8]>](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            key: K,
+        ) -> Result<Option<DBPinnableSlice>, Error>;
+
+        /// Returns the value associated with a key and column family without
+        /// copying it, with read options.
+        pub fn get_pinned_cf_opt[K: AsRef<[u8]>](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<DBPinnableSlice>, Error>;
+
+        /// Returns the values associated with the given keys.
+        pub fn multi_get[K, I](&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+        [where
+            K: AsRef<[u8]>,
+            I: IntoIterator<Item = K>,];
+
+        /// Returns the values associated with the given keys, with read
+        /// options.
+        pub fn multi_get_opt[K, I](
+            &self,
+            keys: I,
+            readopts: &ReadOptions,
+        ) -> Vec<Result<Option<Vec<u8>>, Error>>
+        [where
+            K: AsRef<[u8]>,
+            I: IntoIterator<Item = K>,];
+
+        /// Returns the values associated with the given keys and column
+        /// families.
+        pub fn multi_get_cf['b, K, I, W](&self, keys: I) -> Vec<Result<Option<Vec<u8>>, Error>>
+        [where
+            K: AsRef<[u8]>,
+            I: IntoIterator<Item = (&'b W, K)>,
+            W: 'b + AsColumnFamilyRef,];
+
+        /// Returns the values associated with the given keys and column
+        /// families, with read options.
+        pub fn multi_get_cf_opt['b, K, I, W](
+            &self,
+            keys: I,
+            readopts: &ReadOptions,
+        ) -> Vec<Result<Option<Vec<u8>>, Error>>
+        [where
+            K: AsRef<[u8]>,
+            I: IntoIterator<Item = (&'b W, K)>,
+            W: 'b + AsColumnFamilyRef,];
+
+        /// Returns `false` if the given key definitely doesn't exist in the
+        /// database, otherwise returns `true`.
+        pub fn key_may_exist[K: AsRef<[u8]>](&self, key: K) -> bool;
+
+        /// Returns `false` if the given key definitely doesn't exist in the
+        /// specified column family, otherwise returns `true`.
+        pub fn key_may_exist_cf[K: AsRef<[u8]>](&self, cf: &impl AsColumnFamilyRef, key: K) -> bool;
+    }
+
+    pub fn iterator<'a: 'b, 'b>(
+        &'a self,
+        mode: IteratorMode,
+    ) -> DBIteratorWithThreadMode<'b, Self> {
+        self.iterator_opt(mode, ReadOptions::default())
+    }
+
+    pub fn iterator_opt<'a: 'b, 'b>(
+        &'a self,
+        mode: IteratorMode,
+        readopts: ReadOptions,
+    ) -> DBIteratorWithThreadMode<'b, Self> {
+        DBIteratorWithThreadMode::new(self, readopts, mode)
+    }
+
+    pub fn iterator_cf<'a: 'b, 'b>(
+        &'a self,
+        cf_handle: &impl AsColumnFamilyRef,
+        mode: IteratorMode,
+    ) -> DBIteratorWithThreadMode<'b, Self> {
+        self.iterator_cf_opt(cf_handle, ReadOptions::default(), mode)
+    }
+
+    pub fn iterator_cf_opt<'a: 'b, 'b>(
+        &'a self,
+        cf_handle: &impl AsColumnFamilyRef,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> DBIteratorWithThreadMode<'b, Self> {
+        DBIteratorWithThreadMode::new_cf(self, cf_handle.inner(), readopts, mode)
+    }
+
+    /// Opens a raw iterator over the database, using the default read options
+    pub fn raw_iterator<'a: 'b, 'b>(&'a self) -> DBRawIteratorWithThreadMode<'b, Self> {
+        self.raw_iterator_opt(ReadOptions::default())
+    }
+
+    /// Opens a raw iterator over the database, using the given read options
+    pub fn raw_iterator_opt<'a: 'b, 'b>(
+        &'a self,
+        readopts: ReadOptions,
+    ) -> DBRawIteratorWithThreadMode<'b, Self> {
+        DBRawIteratorWithThreadMode::new(self, readopts)
+    }
+
+    /// Opens a raw iterator over the given column family, using the default
+    /// read options
+    pub fn raw_iterator_cf<'a: 'b, 'b>(
+        &'a self,
+        cf_handle: &impl AsColumnFamilyRef,
+    ) -> DBRawIteratorWithThreadMode<'b, Self> {
+        self.raw_iterator_cf_opt(cf_handle, ReadOptions::default())
+    }
+
+    /// Opens a raw iterator over the given column family, using the given
+    /// read options
+    pub fn raw_iterator_cf_opt<'a: 'b, 'b>(
+        &'a self,
+        cf_handle: &impl AsColumnFamilyRef,
+        readopts: ReadOptions,
+    ) -> DBRawIteratorWithThreadMode<'b, Self> {
+        DBRawIteratorWithThreadMode::new_cf(self, cf_handle.inner(), readopts)
+    }
+
+    pub fn snapshot(&self) -> SnapshotWithThreadMode<Self> {
+        SnapshotWithThreadMode::new(self)
+    }
+
+    forward_to_db! {
+        /// Retrieves a RocksDB property by name, see [`DB::property_value`].
+        pub fn property_value[](&self, name: impl CStrLike) -> Result<Option<String>, Error>;
+
+        /// Retrieves a RocksDB property by name, for a specific column family.
+        pub fn property_value_cf[](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            name: impl CStrLike,
+        ) -> Result<Option<String>, Error>;
+
+        /// Retrieves a RocksDB property and casts it to an integer.
+        pub fn property_int_value[](&self, name: impl CStrLike) -> Result<Option<u64>, Error>;
+
+        /// Retrieves a RocksDB property for a specific column family and casts
+        /// it to an integer.
+        pub fn property_int_value_cf[](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            name: impl CStrLike,
+        ) -> Result<Option<u64>, Error>;
+
+        /// The sequence number of the most recent transaction.
+        pub fn latest_sequence_number[](&self) -> u64;
+
+        /// Returns a list of all table files with their level, start key
+        /// and end key
+        pub fn live_files[](&self) -> Result<Vec<LiveFile>, Error>;
+    }
+}
+
+impl DBAccess for ReadOnlyDB {
+    unsafe fn create_snapshot(&self) -> *const ffi::rocksdb_snapshot_t {
+        self.db.create_snapshot()
+    }
+
+    unsafe fn release_snapshot(&self, snapshot: *const ffi::rocksdb_snapshot_t) {
+        self.db.release_snapshot(snapshot);
+    }
+
+    unsafe fn create_iterator(&self, readopts: &ReadOptions) -> *mut ffi::rocksdb_iterator_t {
+        self.db.create_iterator(readopts)
+    }
+
+    unsafe fn create_iterator_cf(
+        &self,
+        cf_handle: *mut ffi::rocksdb_column_family_handle_t,
+        readopts: &ReadOptions,
+    ) -> *mut ffi::rocksdb_iterator_t {
+        self.db.create_iterator_cf(cf_handle, readopts)
+    }
+
+    forward_to_db! {
+        fn get_opt[K: AsRef<[u8]>](
+            &self,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<Vec<u8>>, Error>;
+
+        fn get_cf_opt[K: AsRef<[u8]>](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<Vec<u8>>, Error>;
+
+        fn get_pinned_opt[K: AsRef<[u8]>](
+            &self,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<DBPinnableSlice>, Error>;
+
+        fn get_pinned_cf_opt[K: AsRef<[u8]>](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> Result<Option<DBPinnableSlice>, Error>;
+
+        fn multi_get_opt[K, I](
+            &self,
+            keys: I,
+            readopts: &ReadOptions,
+        ) -> Vec<Result<Option<Vec<u8>>, Error>>
+        [where
+            K: AsRef<[u8]>,
+            I: IntoIterator<Item = K>,];
+
+        fn multi_get_cf_opt['b, K, I, W](
+            &self,
+            keys_cf: I,
+            readopts: &ReadOptions,
+        ) -> Vec<Result<Option<Vec<u8>>, Error>>
+        [where
+            K: AsRef<[u8]>,
+            I: IntoIterator<Item = (&'b W, K)>,
+            W: AsColumnFamilyRef + 'b,];
+
+        fn key_may_exist_opt[K: AsRef<[u8]>](&self, key: K, readopts: &ReadOptions) -> bool;
+
+        fn key_may_exist_cf_opt[K: AsRef<[u8]>](
+            &self,
+            cf: &impl AsColumnFamilyRef,
+            key: K,
+            readopts: &ReadOptions,
+        ) -> bool;
+
+        fn open_handles[](&self) -> Option<&Arc<OpenHandles>>;
+    }
+}
+
+/// A secondary instance of a DB, which only has the read methods of [`DB`]
+/// and can catch up with the writes of its primary.
+///
+/// The read methods are those of [`ReadOnlyDB`], which this type
+/// dereferences to.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{Options, SecondaryDB, DB};
+///
+/// let path = "_path_for_rocksdb_storage_secondary_db";
+/// let secondary_path = "_path_for_rocksdb_storage_secondary_db_secondary";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     let mut opts = Options::default();
+///     opts.set_max_open_files(-1);
+///     let secondary = SecondaryDB::open(&opts, path, secondary_path).unwrap();
+///
+///     db.put(b"key", b"value").unwrap();
+///     secondary.try_catch_up_with_primary().unwrap();
+///     assert_eq!(secondary.get(b"key").unwrap().unwrap(), b"value");
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// let _ = DB::destroy(&Options::default(), secondary_path);
+/// ```
+#[derive(Debug)]
+pub struct SecondaryDB {
+    db: ReadOnlyDB,
+}
+
+impl SecondaryDB {
+    /// Opens the database as a secondary, see [`DB::open_as_secondary`].
+    pub fn open<P: AsRef<Path>>(
+        opts: &Options,
+        primary_path: P,
+        secondary_path: P,
+    ) -> Result<Self, Error> {
+        Self::open_cf(opts, primary_path, secondary_path, None::<&str>)
+    }
+
+    /// Opens the database as a secondary with the given column family names,
+    /// see [`DB::open_cf_as_secondary`].
+    pub fn open_cf<P, I, N>(
+        opts: &Options,
+        primary_path: P,
+        secondary_path: P,
+        cfs: I,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = N>,
+        N: AsRef<str>,
+    {
+        let db = DB::open_cf_as_secondary(opts, primary_path, secondary_path, cfs)?;
+        Ok(Self {
+            db: ReadOnlyDB { db },
+        })
+    }
+
+    /// Opens the database as a secondary with the given column family
+    /// descriptors, see [`DB::open_cf_descriptors_as_secondary`].
+    pub fn open_cf_descriptors<P, I>(
+        opts: &Options,
+        primary_path: P,
+        secondary_path: P,
+        cfs: I,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        let db = DB::open_cf_descriptors_as_secondary(opts, primary_path, secondary_path, cfs)?;
+        Ok(Self {
+            db: ReadOnlyDB { db },
+        })
+    }
+
+    /// Tries to catch up with the primary by reading as much as possible from
+    /// the log files.
+    pub fn try_catch_up_with_primary(&self) -> Result<(), Error> {
+        self.db.db.try_catch_up_with_primary()
+    }
+
+    /// Spawns a thread calling
+    /// [`try_catch_up_with_primary`](Self::try_catch_up_with_primary) on `db`
+    /// every `interval`.
+    pub fn spawn_catch_up_with_primary(
+        db: Arc<Self>,
+        interval: Duration,
+    ) -> Result<CatchUpThread, Error> {
+        CatchUpThread::spawn(move || db.try_catch_up_with_primary(), interval)
+    }
+}
+
+impl Deref for SecondaryDB {
+    type Target = ReadOnlyDB;
+
+    fn deref(&self) -> &ReadOnlyDB {
+        &self.db
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{DBWithThreadMode, Error, ThreadMode, WriteBatch, WriteOptions};
+
+impl<T: ThreadMode> DBWithThreadMode<T> {
+    /// Applies a write batch of a leader on a follower, checking that it
+    /// gets the same sequence number `seqno` it was given on the leader.
+    ///
+    /// RocksDB does not let a write choose its sequence numbers: they stay
+    /// aligned with the leader as long as the follower applies every batch
+    /// of the leader in order, e.g. read with
+    /// [`get_updates_since`](crate::DBCommon::get_updates_since), and
+    /// nothing else writes to it. This checks the alignment instead of
+    /// assuming it: the batch is not written, and an error is returned, if
+    /// `seqno` is not the next sequence number of the follower.
+    ///
+    /// The check and the write are not atomic: the caller must make sure no
+    /// other write happens concurrently, otherwise the batch may be written
+    /// with other sequence numbers than the leader's, which is reported by
+    /// an error after the write.
+    pub fn write_replicated(&self, seqno: u64, batch: WriteBatch) -> Result<(), Error> {
+        self.write_replicated_opt(seqno, batch, &WriteOptions::default())
+    }
+
+    /// Applies a write batch of a leader on a follower with the given write
+    /// options, see [`write_replicated`](Self::write_replicated).
+    pub fn write_replicated_opt(
+        &self,
+        seqno: u64,
+        batch: WriteBatch,
+        writeopts: &WriteOptions,
+    ) -> Result<(), Error> {
+        let next = self.latest_sequence_number() + 1;
+        if seqno != next {
+            return Err(Error::new(format!(
+                "Invalid argument: the batch has sequence number {seqno} on the leader \
+                 but would get {next} on the follower"
+            )));
+        }
+        let empty = batch.is_empty();
+        let written = self.write_opt_seq(batch, writeopts)?;
+        if !empty && written != seqno {
+            return Err(Error::new(format!(
+                "Corruption: the batch with sequence number {seqno} was written with sequence \
+                 number {written}, concurrently with other writes"
+            )));
+        }
+        Ok(())
+    }
+}
@@ -13,9 +13,13 @@
 // limitations under the License.
 
 use crate::{
-    db::DBAccess, ffi, AsColumnFamilyRef, DBIteratorWithThreadMode, DBPinnableSlice,
-    DBRawIteratorWithThreadMode, Error, IteratorMode, ReadOptions, DB,
+    db::DBAccess,
+    ffi,
+    open_handles::{HandleGuard, HandleKind},
+    AsColumnFamilyRef, DBIteratorWithThreadMode, DBPinnableSlice, DBRawIteratorWithThreadMode,
+    Error, IteratorMode, ReadOptions, DB,
 };
+use std::{mem::ManuallyDrop, sync::Arc};
 
 /// A type alias to keep compatibility. See [`SnapshotWithThreadMode`] for details
 pub type Snapshot<'a> = SnapshotWithThreadMode<'a, DB>;
@@ -36,9 +40,16 @@ pub type Snapshot<'a> = SnapshotWithThreadMode<'a, DB>;
 /// let _ = DB::destroy(&Options::default(), path);
 /// ```
 ///
+/// The `_opt` methods read with the given [`ReadOptions`] as they are, except
+/// for their snapshot which is replaced with this one: bounds, checksum
+/// verification, fill cache and every other read option apply to the reads
+/// of the snapshot.
+///
 pub struct SnapshotWithThreadMode<'a, D: DBAccess> {
     db: &'a D,
     pub(crate) inner: *const ffi::rocksdb_snapshot_t,
+    /// Counts the snapshot as alive in its DB
+    _handle: Option<HandleGuard>,
 }
 
 impl<'a, D: DBAccess> SnapshotWithThreadMode<'a, D> {
@@ -48,6 +59,9 @@ impl<'a, D: DBAccess> SnapshotWithThreadMode<'a, D> {
         Self {
             db,
             inner: snapshot,
+            _handle: db
+                .open_handles()
+                .map(|handles| handles.track(HandleKind::Snapshot)),
         }
     }
 
@@ -253,6 +267,39 @@ impl<'a, D: DBAccess> SnapshotWithThreadMode<'a, D> {
         readopts.set_snapshot(self);
         self.db.multi_get_cf_opt(keys_cf, &readopts)
     }
+
+    /// Returns `false` if the given key definitely doesn't exist in this
+    /// snapshot, otherwise returns `true`. This function uses default
+    /// `ReadOptions`.
+    pub fn key_may_exist<K: AsRef<[u8]>>(&self, key: K) -> bool {
+        self.key_may_exist_opt(key, ReadOptions::default())
+    }
+
+    /// Returns `false` if the given key definitely doesn't exist in this
+    /// snapshot under the given column family, otherwise returns `true`. This
+    /// function uses default `ReadOptions`.
+    pub fn key_may_exist_cf<K: AsRef<[u8]>>(&self, cf: &impl AsColumnFamilyRef, key: K) -> bool {
+        self.key_may_exist_cf_opt(cf, key, ReadOptions::default())
+    }
+
+    /// Returns `false` if the given key definitely doesn't exist in this
+    /// snapshot, otherwise returns `true`.
+    pub fn key_may_exist_opt<K: AsRef<[u8]>>(&self, key: K, mut readopts: ReadOptions) -> bool {
+        readopts.set_snapshot(self);
+        self.db.key_may_exist_opt(key, &readopts)
+    }
+
+    /// Returns `false` if the given key definitely doesn't exist in this
+    /// snapshot under the given column family, otherwise returns `true`.
+    pub fn key_may_exist_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> bool {
+        readopts.set_snapshot(self);
+        self.db.key_may_exist_cf_opt(cf, key, &readopts)
+    }
 }
 
 impl<'a, D: DBAccess> Drop for SnapshotWithThreadMode<'a, D> {
@@ -267,3 +314,70 @@ impl<'a, D: DBAccess> Drop for SnapshotWithThreadMode<'a, D> {
 /// immutable and can be safely shared between threads.
 unsafe impl<'a, D: DBAccess> Send for SnapshotWithThreadMode<'a, D> {}
 unsafe impl<'a, D: DBAccess> Sync for SnapshotWithThreadMode<'a, D> {}
+
+/// A snapshot of a DB shared with an `Arc`, keeping the DB open as long as
+/// the snapshot lives.
+///
+/// Unlike [`SnapshotWithThreadMode`], it does not borrow the DB, so it can
+/// be moved to other threads, e.g. to read a consistent view of the DB from
+/// a background export job. It is read through the snapshot returned by
+/// [`snapshot`](Self::snapshot), whose iterators cannot outlive it.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{IteratorMode, Options, SharedSnapshot, DB};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let path = "_path_for_rocksdb_storage_shared_snapshot";
+/// {
+///     let db = Arc::new(DB::open_default(path).unwrap());
+///     db.put(b"k1", b"v1").unwrap();
+///     let snapshot = SharedSnapshot::new(Arc::clone(&db));
+///     db.put(b"k2", b"v2").unwrap();
+///     let count = thread::spawn(move || {
+///         snapshot.snapshot().iterator(IteratorMode::Start).count()
+///     })
+///     .join()
+///     .unwrap();
+///     assert_eq!(count, 1);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct SharedSnapshot<D: DBAccess> {
+    // released before the DB
+    snapshot: ManuallyDrop<SnapshotWithThreadMode<'static, D>>,
+    db: Arc<D>,
+}
+
+impl<D: DBAccess> SharedSnapshot<D> {
+    /// Creates a new snapshot of the shared database `db`.
+    pub fn new(db: Arc<D>) -> Self {
+        // SAFETY: the DB outlives the snapshot, which is released first on
+        // drop, and `snapshot` never lends it for longer than `&self`
+        let shared: &'static D = unsafe { &*Arc::as_ptr(&db) };
+        Self {
+            snapshot: ManuallyDrop::new(SnapshotWithThreadMode::new(shared)),
+            db,
+        }
+    }
+
+    /// Returns the snapshot to read, borrowed for the lifetime of `self`.
+    pub fn snapshot(&self) -> &SnapshotWithThreadMode<'_, D> {
+        &self.snapshot
+    }
+
+    /// Returns the database of the snapshot.
+    pub fn db(&self) -> &Arc<D> {
+        &self.db
+    }
+}
+
+impl<D: DBAccess> Drop for SharedSnapshot<D> {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.snapshot);
+        }
+    }
+}
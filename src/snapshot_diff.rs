@@ -0,0 +1,220 @@
+use std::cmp::Ordering;
+use std::iter::FusedIterator;
+
+use crate::{
+    column_family::column_family_name,
+    db::{DBAccess, DBInner},
+    options_file::KeyOrder,
+    AsColumnFamilyRef, DBCommon, DBRawIteratorWithThreadMode, Error, IterateBounds, ReadOptions,
+    SnapshotWithThreadMode, ThreadMode, DEFAULT_COLUMN_FAMILY_NAME,
+};
+
+/// A key whose value differs between two snapshots, see
+/// [`diff_ranges`](DBCommon::diff_ranges).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChange {
+    /// The key
+    pub key: Box<[u8]>,
+    /// Value of the key in the old snapshot, `None` if it did not exist
+    pub old_value: Option<Box<[u8]>>,
+    /// Value of the key in the new snapshot, `None` if it was deleted
+    pub new_value: Option<Box<[u8]>>,
+}
+
+impl KeyChange {
+    /// Returns `true` if the key did not exist in the old snapshot.
+    pub fn is_added(&self) -> bool {
+        self.old_value.is_none()
+    }
+
+    /// Returns `true` if the key does not exist in the new snapshot.
+    pub fn is_removed(&self) -> bool {
+        self.new_value.is_none()
+    }
+}
+
+/// An iterator over the keys changed between two snapshots, in key order,
+/// see [`diff_ranges`](DBCommon::diff_ranges).
+pub struct SnapshotDiff<'a, D: DBAccess> {
+    old: DBRawIteratorWithThreadMode<'a, D>,
+    new: DBRawIteratorWithThreadMode<'a, D>,
+    order: KeyOrder,
+    done: bool,
+}
+
+impl<'a, D: DBAccess> SnapshotDiff<'a, D> {
+    fn new(
+        mut old: DBRawIteratorWithThreadMode<'a, D>,
+        mut new: DBRawIteratorWithThreadMode<'a, D>,
+        order: KeyOrder,
+    ) -> Self {
+        old.seek_to_first();
+        new.seek_to_first();
+        Self {
+            old,
+            new,
+            order,
+            done: false,
+        }
+    }
+
+    fn next_change(&mut self) -> Result<Option<KeyChange>, Error> {
+        loop {
+            self.old.status()?;
+            self.new.status()?;
+            let change = match (self.old.item(), self.new.item()) {
+                (None, None) => return Ok(None),
+                (Some((key, value)), None) => removed(key, value),
+                (None, Some((key, value))) => added(key, value),
+                (Some((old_key, old_value)), Some((new_key, new_value))) => {
+                    match self.order.compare(old_key, new_key) {
+                        Ordering::Less => removed(old_key, old_value),
+                        Ordering::Greater => added(new_key, new_value),
+                        Ordering::Equal if old_value == new_value => {
+                            self.old.next();
+                            self.new.next();
+                            continue;
+                        }
+                        Ordering::Equal => KeyChange {
+                            key: Box::from(old_key),
+                            old_value: Some(Box::from(old_value)),
+                            new_value: Some(Box::from(new_value)),
+                        },
+                    }
+                }
+            };
+            if change.old_value.is_some() {
+                self.old.next();
+            }
+            if change.new_value.is_some() {
+                self.new.next();
+            }
+            return Ok(Some(change));
+        }
+    }
+}
+
+fn added(key: &[u8], value: &[u8]) -> KeyChange {
+    KeyChange {
+        key: Box::from(key),
+        old_value: None,
+        new_value: Some(Box::from(value)),
+    }
+}
+
+fn removed(key: &[u8], value: &[u8]) -> KeyChange {
+    KeyChange {
+        key: Box::from(key),
+        old_value: Some(Box::from(value)),
+        new_value: None,
+    }
+}
+
+impl<'a, D: DBAccess> Iterator for SnapshotDiff<'a, D> {
+    type Item = Result<KeyChange, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let change = self.next_change().transpose();
+        // stops after the last change or the first error
+        self.done = !matches!(change, Some(Ok(_)));
+        change
+    }
+}
+
+impl<'a, D: DBAccess> FusedIterator for SnapshotDiff<'a, D> {}
+
+impl<T: ThreadMode, I: DBInner> DBCommon<T, I> {
+    /// Returns an iterator over the keys of `range` whose value differs
+    /// between the `old` and `new` snapshots, in key order, e.g. to export
+    /// the changes since a previous export.
+    ///
+    /// The range is scanned in both snapshots, which costs reading all of its
+    /// entries even if few of them changed. The changes of a range can be
+    /// read faster from the WAL with
+    /// [`get_updates_since`](DBCommon::get_updates_since) as long as it
+    /// retains the writes since the old snapshot.
+    ///
+    /// The keys of both snapshots are merged in the order of the comparator
+    /// of the column family, which must be the bytewise comparator or its
+    /// reverse: the order of a custom comparator is unknown, and fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::DB;
+    ///
+    /// let path = "_path_for_rocksdb_storage_diff_ranges";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     db.put(b"a", b"1").unwrap();
+    ///     let old = db.snapshot();
+    ///     db.put(b"b", b"2").unwrap();
+    ///     let new = db.snapshot();
+    ///     let changes: Vec<_> = db
+    ///         .diff_ranges(&old, &new, ..)
+    ///         .unwrap()
+    ///         .map(Result::unwrap)
+    ///         .collect();
+    ///     assert_eq!(changes.len(), 1);
+    ///     assert!(changes[0].is_added());
+    /// }
+    /// let _ = DB::destroy(&rocksdb::Options::default(), path);
+    /// ```
+    pub fn diff_ranges<'a>(
+        &'a self,
+        old: &'a SnapshotWithThreadMode<'a, Self>,
+        new: &'a SnapshotWithThreadMode<'a, Self>,
+        range: impl IterateBounds,
+    ) -> Result<SnapshotDiff<'a, Self>, Error> {
+        let order = KeyOrder::of_cf(self.path(), DEFAULT_COLUMN_FAMILY_NAME)?;
+        let (old_opts, new_opts) = diff_read_options(old, new, range);
+        Ok(SnapshotDiff::new(
+            self.raw_iterator_opt(old_opts),
+            self.raw_iterator_opt(new_opts),
+            order,
+        ))
+    }
+
+    /// Returns an iterator over the keys of `range` in the column family `cf`
+    /// whose value differs between the `old` and `new` snapshots, see
+    /// [`diff_ranges`](Self::diff_ranges).
+    pub fn diff_ranges_cf<'a>(
+        &'a self,
+        cf: &impl AsColumnFamilyRef,
+        old: &'a SnapshotWithThreadMode<'a, Self>,
+        new: &'a SnapshotWithThreadMode<'a, Self>,
+        range: impl IterateBounds,
+    ) -> Result<SnapshotDiff<'a, Self>, Error> {
+        let order = KeyOrder::of_cf(self.path(), &column_family_name(cf.inner()))?;
+        let (old_opts, new_opts) = diff_read_options(old, new, range);
+        Ok(SnapshotDiff::new(
+            self.raw_iterator_cf_opt(cf, old_opts),
+            self.raw_iterator_cf_opt(cf, new_opts),
+            order,
+        ))
+    }
+}
+
+fn diff_read_options<D: DBAccess>(
+    old: &SnapshotWithThreadMode<D>,
+    new: &SnapshotWithThreadMode<D>,
+    range: impl IterateBounds,
+) -> (ReadOptions, ReadOptions) {
+    let (lower, upper) = range.into_bounds();
+    let read_options = |snapshot: &SnapshotWithThreadMode<D>| {
+        let mut readopts = ReadOptions::default();
+        readopts.fill_cache(false);
+        readopts.set_snapshot(snapshot);
+        if let Some(ref lower) = lower {
+            readopts.set_iterate_lower_bound(lower.clone());
+        }
+        if let Some(ref upper) = upper {
+            readopts.set_iterate_upper_bound(upper.clone());
+        }
+        readopts
+    };
+    (read_options(old), read_options(new))
+}
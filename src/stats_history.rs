@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    db::DBInner, AsColumnFamilyRef, DBCommon, Direction, Error, IteratorMode, MultiThreaded,
+    ReadOptions, SingleThreaded, ThreadMode,
+};
+
+/// Name of the column family RocksDB persists statistics to when
+/// [`Options::set_persist_stats_to_disk`](crate::Options::set_persist_stats_to_disk)
+/// is enabled.
+///
+/// The DB must be opened with this column family, e.g. by creating it with
+/// [`Options::create_missing_column_families`](crate::Options::create_missing_column_families),
+/// to read the persisted statistics with
+/// [`get_stats_history`](DBCommon::get_stats_history).
+pub const STATS_HISTORY_COLUMN_FAMILY_NAME: &str = "___rocksdb_stats_history___";
+
+/// Length of the zero-padded timestamp prefixing the persisted keys.
+const TIMESTAMP_LEN: usize = 10;
+
+/// Statistics persisted by RocksDB at a point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsHistoryEntry {
+    /// Time the statistics were persisted, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Increase of each statistic since the previous entry, keyed by name,
+    /// e.g. `"rocksdb.number.keys.written"`
+    pub stats: BTreeMap<String, u64>,
+}
+
+impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
+    fn stats_history_impl(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<StatsHistoryEntry>, Error> {
+        if end <= start {
+            return Ok(Vec::new());
+        }
+        let mut readopts = ReadOptions::default();
+        readopts.fill_cache(false);
+        let start_key = format!("{start:0width$}", width = TIMESTAMP_LEN);
+        readopts.set_iterate_upper_bound(format!("{end:0width$}", width = TIMESTAMP_LEN));
+
+        let mut entries: Vec<StatsHistoryEntry> = Vec::new();
+        let mode = IteratorMode::From(start_key.as_bytes(), Direction::Forward);
+        for item in self.iterator_cf_opt(cf, readopts, mode) {
+            let (key, value) = item?;
+            let (timestamp, name) = match parse_key(&key) {
+                Some(parsed) => parsed,
+                // keys recording the format version
+                None => continue,
+            };
+            let value = std::str::from_utf8(&value)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| {
+                    Error::new(format!(
+                        "Corruption: invalid value of persisted statistic {name}"
+                    ))
+                })?;
+            match entries.last_mut() {
+                Some(entry) if entry.timestamp == timestamp => {
+                    entry.stats.insert(name.to_owned(), value);
+                }
+                _ => entries.push(StatsHistoryEntry {
+                    timestamp,
+                    stats: BTreeMap::from([(name.to_owned(), value)]),
+                }),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+impl<I: DBInner> DBCommon<SingleThreaded, I> {
+    /// Returns the statistics persisted between `start` (inclusive) and `end`
+    /// (exclusive), in seconds since the Unix epoch, oldest first.
+    ///
+    /// Statistics are persisted every
+    /// [`stats_persist_period_sec`](crate::Options::set_stats_persist_period_sec)
+    /// when [`enable_statistics`](crate::Options::enable_statistics) and
+    /// [`set_persist_stats_to_disk`](crate::Options::set_persist_stats_to_disk)
+    /// are set, and the DB is opened with the
+    /// [`STATS_HISTORY_COLUMN_FAMILY_NAME`] column family. The statistics
+    /// kept in memory otherwise cannot be read through the C API.
+    pub fn get_stats_history(&self, start: u64, end: u64) -> Result<Vec<StatsHistoryEntry>, Error> {
+        let cf = self
+            .cf_handle(STATS_HISTORY_COLUMN_FAMILY_NAME)
+            .ok_or_else(stats_cf_not_opened)?;
+        self.stats_history_impl(cf, start, end)
+    }
+}
+
+impl<I: DBInner> DBCommon<MultiThreaded, I> {
+    /// Returns the statistics persisted between `start` (inclusive) and `end`
+    /// (exclusive), in seconds since the Unix epoch, oldest first.
+    ///
+    /// Statistics are persisted every
+    /// [`stats_persist_period_sec`](crate::Options::set_stats_persist_period_sec)
+    /// when [`enable_statistics`](crate::Options::enable_statistics) and
+    /// [`set_persist_stats_to_disk`](crate::Options::set_persist_stats_to_disk)
+    /// are set, and the DB is opened with the
+    /// [`STATS_HISTORY_COLUMN_FAMILY_NAME`] column family. The statistics
+    /// kept in memory otherwise cannot be read through the C API.
+    pub fn get_stats_history(&self, start: u64, end: u64) -> Result<Vec<StatsHistoryEntry>, Error> {
+        let cf = self
+            .cf_handle(STATS_HISTORY_COLUMN_FAMILY_NAME)
+            .ok_or_else(stats_cf_not_opened)?;
+        self.stats_history_impl(&cf, start, end)
+    }
+}
+
+fn stats_cf_not_opened() -> Error {
+    Error::new(format!(
+        "Invalid argument: column family {STATS_HISTORY_COLUMN_FAMILY_NAME} is not opened"
+    ))
+}
+
+/// Splits a persisted key `<timestamp>#<name>` into its parts.
+fn parse_key(key: &[u8]) -> Option<(u64, &str)> {
+    let key = std::str::from_utf8(key).ok()?;
+    let (timestamp, name) = key.split_once('#')?;
+    if timestamp.len() != TIMESTAMP_LEN {
+        return None;
+    }
+    Some((timestamp.parse().ok()?, name))
+}
@@ -0,0 +1,94 @@
+//! Helpers for tests of code using RocksDB, enabled by the `test-util`
+//! feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use rocksdb::test_util::TestDb;
+//!
+//! let db = TestDb::open_default().unwrap();
+//! db.put(b"key", b"value").unwrap();
+//! assert_eq!(db.get(b"key").unwrap().unwrap(), b"value");
+//! // the DB is closed and its directory removed when `db` is dropped
+//! ```
+
+use std::ops::Deref;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+use crate::{Env, Error, Options, DB};
+
+/// A DB in a unique temporary directory, which is destroyed and removed when
+/// the `TestDb` is dropped.
+///
+/// `TestDb` dereferences to the [`DB`].
+pub struct TestDb {
+    db: Option<DB>,
+    opts: Options,
+    dir: TempDir,
+}
+
+impl TestDb {
+    /// Opens a new DB with default options.
+    pub fn open_default() -> Result<Self, Error> {
+        Self::open(Options::default())
+    }
+
+    /// Opens a new DB with the given options. `create_if_missing` is enabled
+    /// whatever its value in `opts`.
+    pub fn open(mut opts: Options) -> Result<Self, Error> {
+        opts.create_if_missing(true);
+        let dir = tempfile::Builder::new()
+            .prefix("rocksdb-test-")
+            .tempdir()
+            .map_err(|err| Error::new(format!("Failed to create a temporary directory: {err}")))?;
+        let db = DB::open(&opts, dir.path())?;
+        Ok(Self {
+            db: Some(db),
+            opts,
+            dir,
+        })
+    }
+
+    /// Opens a new DB with the given options, keeping all its files in
+    /// memory with [`Env::mem_env`]. Nothing is written to disk, and the
+    /// content of the DB is lost when it is dropped.
+    pub fn open_in_memory(mut opts: Options) -> Result<Self, Error> {
+        opts.set_env(&Env::mem_env()?);
+        Self::open(opts)
+    }
+
+    /// Returns the path of the DB.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Returns the options the DB was opened with.
+    pub fn options(&self) -> &Options {
+        &self.opts
+    }
+
+    /// Closes the DB and opens it again with the same options, e.g. to test
+    /// recovery. The DB is left closed if reopening fails.
+    pub fn reopen(&mut self) -> Result<(), Error> {
+        drop(self.db.take());
+        self.db = Some(DB::open(&self.opts, self.dir.path())?);
+        Ok(())
+    }
+}
+
+impl Deref for TestDb {
+    type Target = DB;
+
+    fn deref(&self) -> &DB {
+        self.db.as_ref().expect("the DB failed to reopen")
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        drop(self.db.take());
+        let _ = DB::destroy(&self.opts, self.dir.path());
+    }
+}
@@ -19,6 +19,8 @@ mod transaction;
 mod transaction_db;
 
 pub use optimistic_transaction_db::OptimisticTransactionDB;
-pub use options::{OptimisticTransactionOptions, TransactionDBOptions, TransactionOptions};
-pub use transaction::Transaction;
+pub use options::{
+    OptimisticTransactionOptions, RetryOptions, TransactionDBOptions, TransactionOptions,
+};
+pub use transaction::{Transaction, TransactionForUpdateIterator};
 pub use transaction_db::TransactionDB;
@@ -13,13 +13,14 @@
 // limitations under the License.
 //
 
-use std::{collections::BTreeMap, ffi::CString, fs, iter, marker::PhantomData, path::Path, ptr};
+use std::{collections::BTreeMap, ffi::CString, fs, iter, path::Path, ptr, thread};
 
 use libc::{c_char, c_int};
 
 use crate::{
-    db::DBCommon, db::DBInner, ffi, ffi_util::to_cpath, write_batch::WriteBatchWithTransaction,
-    ColumnFamilyDescriptor, Error, OptimisticTransactionOptions, Options, ThreadMode, Transaction,
+    db::DBCommon, db::DBInner, ffi, ffi_util::to_cpath, options_file::check_merge_operators,
+    write_batch::WriteBatchWithTransaction, AsColumnFamilyRef, ColumnFamilyDescriptor, Error,
+    ErrorKind, OptimisticTransactionOptions, Options, RetryOptions, ThreadMode, Transaction,
     WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
 };
 
@@ -126,6 +127,9 @@ impl<T: ThreadMode> OptimisticTransactionDB<T> {
         I: IntoIterator<Item = ColumnFamilyDescriptor>,
     {
         let cfs: Vec<_> = cfs.into_iter().collect();
+        for cf in &cfs {
+            cf.check_supported_by("OptimisticTransactionDB")?;
+        }
         let outlive = iter::once(opts.outlive.clone())
             .chain(cfs.iter().map(|cf| cf.options.outlive.clone()))
             .collect();
@@ -138,6 +142,8 @@ impl<T: ThreadMode> OptimisticTransactionDB<T> {
             )));
         }
 
+        check_merge_operators(path.as_ref(), opts, &cfs)?;
+
         let db: *mut ffi::rocksdb_optimistictransactiondb_t;
         let mut cf_map = BTreeMap::new();
 
@@ -147,10 +153,10 @@ impl<T: ThreadMode> OptimisticTransactionDB<T> {
             let mut cfs_v = cfs;
             // Always open the default column family.
             if !cfs_v.iter().any(|cf| cf.name == DEFAULT_COLUMN_FAMILY_NAME) {
-                cfs_v.push(ColumnFamilyDescriptor {
-                    name: String::from(DEFAULT_COLUMN_FAMILY_NAME),
-                    options: Options::default(),
-                });
+                cfs_v.push(ColumnFamilyDescriptor::new(
+                    DEFAULT_COLUMN_FAMILY_NAME,
+                    Options::default(),
+                ));
             }
             // We need to store our CStrings in an intermediate vector
             // so that their pointers remain valid.
@@ -253,17 +259,14 @@ impl<T: ThreadMode> OptimisticTransactionDB<T> {
         writeopts: &WriteOptions,
         otxn_opts: &OptimisticTransactionOptions,
     ) -> Transaction<Self> {
-        Transaction {
-            inner: unsafe {
-                ffi::rocksdb_optimistictransaction_begin(
-                    self.inner.db,
-                    writeopts.inner,
-                    otxn_opts.inner,
-                    std::ptr::null_mut(),
-                )
-            },
-            _marker: PhantomData::default(),
-        }
+        Transaction::new(unsafe {
+            ffi::rocksdb_optimistictransaction_begin(
+                self.inner.db,
+                writeopts.inner,
+                otxn_opts.inner,
+                std::ptr::null_mut(),
+            )
+        })
     }
 
     pub fn write_opt(
@@ -290,4 +293,144 @@ impl<T: ThreadMode> OptimisticTransactionDB<T> {
         wo.disable_wal(true);
         self.write_opt(batch, &wo)
     }
+
+    /// Atomically replaces the value of `key` by the one computed by `f` from
+    /// the current value, and returns the new value.
+    ///
+    /// `f` is called with the current value, or `None` if there is none, and
+    /// returns the new value, or `None` to delete the key. It runs in an
+    /// optimistic transaction which is retried, calling `f` again, if another
+    /// write to `key` conflicts with it. Use [`update_opt`] to configure the
+    /// retries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DB, Options, OptimisticTransactionDB};
+    /// use std::convert::TryInto;
+    ///
+    /// let path = "_path_for_optimistic_transaction_db_update";
+    /// {
+    ///     let db: OptimisticTransactionDB = OptimisticTransactionDB::open_default(path).unwrap();
+    ///     let increment = |old: Option<&[u8]>| {
+    ///         let count = old.map_or(0, |old| u64::from_le_bytes(old.try_into().unwrap()));
+    ///         Some((count + 1).to_le_bytes().to_vec())
+    ///     };
+    ///     db.update(b"count", increment).unwrap();
+    ///     let new = db.update(b"count", increment).unwrap();
+    ///     assert_eq!(new.unwrap(), 2_u64.to_le_bytes());
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    ///
+    /// [`update_opt`]: Self::update_opt
+    pub fn update<K, F>(&self, key: K, f: F) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        self.update_opt(key, &RetryOptions::default(), f)
+    }
+
+    /// Atomically replaces the value of `key`, retrying conflicting
+    /// transactions as configured by `retry_opts`. See [`update`](Self::update)
+    /// and [`update_cf_opt`](Self::update_cf_opt).
+    pub fn update_opt<K, F>(
+        &self,
+        key: K,
+        retry_opts: &RetryOptions,
+        f: F,
+    ) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        self.update_with_retries(
+            retry_opts,
+            f,
+            |txn| txn.get_for_update(key.as_ref(), true),
+            |txn, value| match value {
+                Some(value) => txn.put(key.as_ref(), value),
+                None => txn.delete(key.as_ref()),
+            },
+        )
+    }
+
+    /// Atomically replaces the value of `key` in the given column family, see
+    /// [`update`](Self::update).
+    pub fn update_cf<K, F>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        f: F,
+    ) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        self.update_cf_opt(cf, key, &RetryOptions::default(), f)
+    }
+
+    /// Atomically replaces the value of `key` in the given column family,
+    /// retrying conflicting transactions as configured by `retry_opts`. See
+    /// [`update`](Self::update).
+    ///
+    /// Returns the error of the last attempt if all of them conflicted, of
+    /// kind [`Busy`] or [`TryAgain`].
+    ///
+    /// [`Busy`]: crate::ErrorKind::Busy
+    /// [`TryAgain`]: crate::ErrorKind::TryAgain
+    pub fn update_cf_opt<K, F>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        retry_opts: &RetryOptions,
+        f: F,
+    ) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        self.update_with_retries(
+            retry_opts,
+            f,
+            |txn| txn.get_for_update_cf(cf, key.as_ref(), true),
+            |txn, value| match value {
+                Some(value) => txn.put_cf(cf, key.as_ref(), value),
+                None => txn.delete_cf(cf, key.as_ref()),
+            },
+        )
+    }
+
+    fn update_with_retries<F, R, W>(
+        &self,
+        retry_opts: &RetryOptions,
+        mut f: F,
+        read: R,
+        write: W,
+    ) -> Result<Option<Vec<u8>>, Error>
+    where
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+        R: Fn(&Transaction<Self>) -> Result<Option<Vec<u8>>, Error>,
+        W: Fn(&Transaction<Self>, Option<&[u8]>) -> Result<(), Error>,
+    {
+        let mut attempt = 0;
+        loop {
+            let txn = self.transaction();
+            let old = read(&txn)?;
+            let new = f(old.as_deref());
+            write(&txn, new.as_deref())?;
+            match txn.commit() {
+                Ok(()) => return Ok(new),
+                Err(err)
+                    if matches!(err.kind(), ErrorKind::Busy | ErrorKind::TryAgain)
+                        && attempt < retry_opts.max_retries =>
+                {
+                    thread::sleep(retry_opts.backoff(attempt));
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
 }
@@ -13,6 +13,8 @@
 // limitations under the License.
 //
 
+use std::time::Duration;
+
 use crate::ffi;
 
 pub struct TransactionOptions {
@@ -294,3 +296,55 @@ impl Drop for OptimisticTransactionOptions {
         }
     }
 }
+
+/// How [`OptimisticTransactionDB::update`] retries transactions which failed
+/// to commit because of a conflict.
+///
+/// [`OptimisticTransactionDB::update`]: crate::OptimisticTransactionDB::update
+#[derive(Debug, Clone)]
+pub struct RetryOptions {
+    pub(crate) max_retries: usize,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryOptions {
+    pub fn new() -> RetryOptions {
+        RetryOptions::default()
+    }
+
+    /// Sets the number of retries after the first attempt. The error of the
+    /// last attempt is returned once they are all used.
+    ///
+    /// Default: 10
+    pub fn set_max_retries(&mut self, max_retries: usize) {
+        self.max_retries = max_retries;
+    }
+
+    /// Sets how long to wait before retrying: `initial` before the first
+    /// retry, doubling for every retry up to `max`.
+    ///
+    /// Default: 1ms, up to 100ms
+    pub fn set_backoff(&mut self, initial: Duration, max: Duration) {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+    }
+
+    /// Returns the wait before the retry following `attempt` failed ones.
+    pub(crate) fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 1_u32.checked_shl(attempt as u32).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
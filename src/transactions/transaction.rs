@@ -13,10 +13,15 @@
 // limitations under the License.
 //
 
-use std::{marker::PhantomData, ptr};
+use std::{
+    marker::PhantomData,
+    ptr,
+    time::{Duration, Instant},
+};
 
 use crate::{
     db::{convert_values, DBAccess},
+    db_iterator::KVBytes,
     ffi, AsColumnFamilyRef, DBIteratorWithThreadMode, DBPinnableSlice, DBRawIteratorWithThreadMode,
     Direction, Error, IteratorMode, ReadOptions, SnapshotWithThreadMode, WriteBatchWithTransaction,
 };
@@ -30,6 +35,7 @@ use libc::{c_char, c_void, size_t};
 /// [`OptimisticTransactionDB`]: crate::OptimisticTransactionDB
 pub struct Transaction<'db, DB> {
     pub(crate) inner: *mut ffi::rocksdb_transaction_t,
+    begun_at: Instant,
     pub(crate) _marker: PhantomData<&'db DB>,
 }
 
@@ -117,6 +123,14 @@ impl<'db, DB> DBAccess for Transaction<'db, DB> {
 }
 
 impl<'db, DB> Transaction<'db, DB> {
+    pub(crate) fn new(inner: *mut ffi::rocksdb_transaction_t) -> Self {
+        Self {
+            inner,
+            begun_at: Instant::now(),
+            _marker: PhantomData::default(),
+        }
+    }
+
     /// Write all batched keys to the DB atomically.
     ///
     /// May return any error that could be returned by `DB::write`.
@@ -145,6 +159,22 @@ impl<'db, DB> Transaction<'db, DB> {
         Ok(())
     }
 
+    /// Writes all batched keys to the DB atomically, like
+    /// [`commit`](Self::commit), with the commit timestamp `ts`.
+    ///
+    /// The timestamp is given to the writes of the column families with
+    /// user-defined timestamps, which must be 8 bytes wide. It is ignored by
+    /// the transactions of an [`OptimisticTransactionDB`], and by the
+    /// column families without user-defined timestamps.
+    ///
+    /// [`OptimisticTransactionDB`]: crate::OptimisticTransactionDB
+    pub fn commit_with_timestamp(self, ts: u64) -> Result<(), Error> {
+        unsafe {
+            ffi::rocksdb_transaction_set_commit_timestamp(self.inner, ts);
+        }
+        self.commit()
+    }
+
     pub fn set_name(&self, name: &[u8]) -> Result<(), Error> {
         let ptr = name.as_ptr();
         let len = name.len();
@@ -208,9 +238,16 @@ impl<'db, DB> Transaction<'db, DB> {
     /// Undo all operations in this transaction since the most recent call to [`set_savepoint`]
     /// and removes the most recent [`set_savepoint`].
     ///
+    /// For a transaction of a [`TransactionDB`], the locks taken since the savepoint, e.g.
+    /// by [`get_for_update`], are released unless the keys were locked before. Setting a
+    /// savepoint before locking a key speculatively allows releasing its lock without
+    /// rolling back the whole transaction.
+    ///
     /// Returns error if there is no previous call to [`set_savepoint`].
     ///
     /// [`set_savepoint`]: Self::set_savepoint
+    /// [`get_for_update`]: Self::get_for_update
+    /// [`TransactionDB`]: crate::TransactionDB
     pub fn rollback_to_savepoint(&self) -> Result<(), Error> {
         unsafe {
             ffi_try!(ffi::rocksdb_transaction_rollback_to_savepoint(self.inner));
@@ -259,6 +296,11 @@ impl<'db, DB> Transaction<'db, DB> {
     ///
     /// See [`get_for_update_cf_opt`] for details.
     ///
+    /// Only single keys can be locked: the C API does not expose the range
+    /// lock manager of RocksDB. To protect a key range from concurrent
+    /// inserts, lock a key standing for the range with this method in every
+    /// transaction writing to the range.
+    ///
     /// [`get_for_update_cf_opt`]: Self::get_for_update_cf_opt
     pub fn get_for_update<K: AsRef<[u8]>>(
         &self,
@@ -729,6 +771,13 @@ impl<'db, DB> Transaction<'db, DB> {
         Ok(())
     }
 
+    /// Opens an iterator over the database, using the default read options.
+    ///
+    /// Like every iterator of a transaction, it sees the writes of the
+    /// transaction that have not been committed yet. To iterate over the
+    /// snapshot of the transaction, use the iterators of [`snapshot`].
+    ///
+    /// [`snapshot`]: Self::snapshot
     pub fn iterator<'a: 'b, 'b>(
         &'a self,
         mode: IteratorMode,
@@ -737,6 +786,7 @@ impl<'db, DB> Transaction<'db, DB> {
         self.iterator_opt(mode, readopts)
     }
 
+    /// Opens an iterator over the database, using the given read options.
     pub fn iterator_opt<'a: 'b, 'b>(
         &'a self,
         mode: IteratorMode,
@@ -768,6 +818,9 @@ impl<'db, DB> Transaction<'db, DB> {
         DBIteratorWithThreadMode::new(self, opts, mode)
     }
 
+    /// Opens an iterator with `set_prefix_same_as_start` enabled, starting at
+    /// `prefix`. Only keys with the same prefix as `prefix`, as extracted by
+    /// the prefix extractor, are returned.
     pub fn prefix_iterator<'a: 'b, 'b, P: AsRef<[u8]>>(
         &'a self,
         prefix: P,
@@ -781,6 +834,8 @@ impl<'db, DB> Transaction<'db, DB> {
         )
     }
 
+    /// Opens an iterator over the given column family, using the default
+    /// read options.
     pub fn iterator_cf<'a: 'b, 'b>(
         &'a self,
         cf_handle: &impl AsColumnFamilyRef,
@@ -790,6 +845,8 @@ impl<'db, DB> Transaction<'db, DB> {
         DBIteratorWithThreadMode::new_cf(self, cf_handle.inner(), opts, mode)
     }
 
+    /// Same as [`full_iterator`](Self::full_iterator) but over the given
+    /// column family.
     pub fn full_iterator_cf<'a: 'b, 'b>(
         &'a self,
         cf_handle: &impl AsColumnFamilyRef,
@@ -800,6 +857,8 @@ impl<'db, DB> Transaction<'db, DB> {
         DBIteratorWithThreadMode::new_cf(self, cf_handle.inner(), opts, mode)
     }
 
+    /// Same as [`prefix_iterator`](Self::prefix_iterator) but over the given
+    /// column family.
     pub fn prefix_iterator_cf<'a, P: AsRef<[u8]>>(
         &'a self,
         cf_handle: &impl AsColumnFamilyRef,
@@ -847,6 +906,48 @@ impl<'db, DB> Transaction<'db, DB> {
         DBRawIteratorWithThreadMode::new_cf(self, cf_handle.inner(), readopts)
     }
 
+    /// Opens an iterator which locks every key it visits, as if each key had
+    /// been read with [`get_for_update`]. Scanning keys and then modifying
+    /// them within the transaction is thus safe from concurrent writes.
+    ///
+    /// The yielded value is the one read when the key was locked. Keys
+    /// deleted by another transaction between being visited and being locked
+    /// are skipped. For optimistic transactions the keys are tracked for the
+    /// conflict check on commit instead of being locked.
+    ///
+    /// RocksDB has no iterator taking locks itself, so each key costs an
+    /// additional point lookup.
+    ///
+    /// [`get_for_update`]: Self::get_for_update
+    pub fn iterator_for_update<'a: 'b, 'b>(
+        &'a self,
+        mode: IteratorMode,
+        exclusive: bool,
+    ) -> TransactionForUpdateIterator<'b, 'db, DB> {
+        TransactionForUpdateIterator {
+            txn: self,
+            cf: None,
+            iter: self.iterator(mode),
+            exclusive,
+        }
+    }
+
+    /// Same as [`iterator_for_update`](Self::iterator_for_update) but over
+    /// the given column family.
+    pub fn iterator_for_update_cf<'a: 'b, 'b>(
+        &'a self,
+        cf_handle: &impl AsColumnFamilyRef,
+        mode: IteratorMode,
+        exclusive: bool,
+    ) -> TransactionForUpdateIterator<'b, 'db, DB> {
+        TransactionForUpdateIterator {
+            txn: self,
+            cf: Some(cf_handle.inner()),
+            iter: self.iterator_cf(cf_handle, mode),
+            exclusive,
+        }
+    }
+
     pub fn get_writebatch(&self) -> WriteBatchWithTransaction<true> {
         unsafe {
             let wi = ffi::rocksdb_transaction_get_writebatch_wi(self.inner);
@@ -858,6 +959,42 @@ impl<'db, DB> Transaction<'db, DB> {
         }
     }
 
+    /// Returns the size in bytes of the writes of this transaction, i.e. of
+    /// its write batch.
+    ///
+    /// Together with [`elapsed`](Self::elapsed), this lets a watchdog roll
+    /// back transactions growing too large or living too long before they
+    /// stall other writers.
+    pub fn get_write_batch_size(&self) -> usize {
+        unsafe {
+            let wi = ffi::rocksdb_transaction_get_writebatch_wi(self.inner);
+            let mut len: usize = 0;
+            ffi::rocksdb_writebatch_wi_data(wi, &mut len as _);
+            ffi::rocksdb_free(wi as *mut c_void);
+            len
+        }
+    }
+
+    /// Returns the number of writes of this transaction, i.e. of its write
+    /// batch.
+    pub fn get_write_batch_count(&self) -> usize {
+        unsafe {
+            let wi = ffi::rocksdb_transaction_get_writebatch_wi(self.inner);
+            let count = ffi::rocksdb_writebatch_wi_count(wi);
+            ffi::rocksdb_free(wi as *mut c_void);
+            count as usize
+        }
+    }
+
+    /// Returns the time elapsed since this transaction began, or since it
+    /// was recovered for a prepared transaction of
+    /// [`TransactionDB::prepared_transactions`].
+    ///
+    /// [`TransactionDB::prepared_transactions`]: crate::TransactionDB::prepared_transactions
+    pub fn elapsed(&self) -> Duration {
+        self.begun_at.elapsed()
+    }
+
     pub fn rebuild_from_writebatch(
         &self,
         writebatch: &WriteBatchWithTransaction<true>,
@@ -879,3 +1016,60 @@ impl<'db, DB> Drop for Transaction<'db, DB> {
         }
     }
 }
+
+/// An iterator locking the keys it visits, see
+/// [`Transaction::iterator_for_update`].
+pub struct TransactionForUpdateIterator<'a, 'db, DB> {
+    txn: &'a Transaction<'db, DB>,
+    cf: Option<*mut ffi::rocksdb_column_family_handle_t>,
+    iter: DBIteratorWithThreadMode<'a, Transaction<'db, DB>>,
+    exclusive: bool,
+}
+
+impl<'a, 'db, DB> TransactionForUpdateIterator<'a, 'db, DB> {
+    fn get_for_update(&self, key: &[u8]) -> Result<Option<DBPinnableSlice<'a>>, Error> {
+        let opts = ReadOptions::default();
+        unsafe {
+            let val = match self.cf {
+                Some(cf) => ffi_try!(ffi::rocksdb_transaction_get_pinned_for_update_cf(
+                    self.txn.inner,
+                    opts.inner,
+                    cf,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    u8::from(self.exclusive),
+                )),
+                None => ffi_try!(ffi::rocksdb_transaction_get_pinned_for_update(
+                    self.txn.inner,
+                    opts.inner,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    u8::from(self.exclusive),
+                )),
+            };
+            if val.is_null() {
+                Ok(None)
+            } else {
+                Ok(Some(DBPinnableSlice::from_c(val)))
+            }
+        }
+    }
+}
+
+impl<'a, 'db, DB> Iterator for TransactionForUpdateIterator<'a, 'db, DB> {
+    type Item = Result<KVBytes, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (key, _) = match self.iter.next()? {
+                Ok(item) => item,
+                Err(err) => return Some(Err(err)),
+            };
+            match self.get_for_update(&key) {
+                Ok(Some(value)) => return Some(Ok((key, Box::from(value.as_ref())))),
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
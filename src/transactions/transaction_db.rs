@@ -29,11 +29,12 @@ use crate::{
     db_options::OptionsMustOutliveDB,
     ffi,
     ffi_util::to_cpath,
+    options_file::check_merge_operators,
     AsColumnFamilyRef, BoundColumnFamily, ColumnFamily, ColumnFamilyDescriptor,
-    DBIteratorWithThreadMode, DBPinnableSlice, DBRawIteratorWithThreadMode, Direction, Error,
-    IteratorMode, MultiThreaded, Options, ReadOptions, SingleThreaded, SnapshotWithThreadMode,
-    ThreadMode, Transaction, TransactionDBOptions, TransactionOptions, WriteBatchWithTransaction,
-    WriteOptions, DB, DEFAULT_COLUMN_FAMILY_NAME,
+    DBIteratorWithThreadMode, DBPinnableSlice, DBRawIteratorWithThreadMode, DBWALIterator,
+    Direction, Error, IteratorMode, MultiThreaded, Options, ReadOptions, SingleThreaded,
+    SnapshotWithThreadMode, ThreadMode, Transaction, TransactionDBOptions, TransactionOptions,
+    WriteBatchWithTransaction, WriteOptions, DB, DEFAULT_COLUMN_FAMILY_NAME,
 };
 use ffi::rocksdb_transaction_t;
 use libc::{c_char, c_int, c_void, size_t};
@@ -226,6 +227,9 @@ impl<T: ThreadMode> TransactionDB<T> {
         I: IntoIterator<Item = ColumnFamilyDescriptor>,
     {
         let cfs: Vec<_> = cfs.into_iter().collect();
+        for cf in &cfs {
+            cf.check_supported_by("TransactionDB")?;
+        }
         let outlive = iter::once(opts.outlive.clone())
             .chain(cfs.iter().map(|cf| cf.options.outlive.clone()))
             .collect();
@@ -238,6 +242,8 @@ impl<T: ThreadMode> TransactionDB<T> {
             )));
         }
 
+        check_merge_operators(path.as_ref(), opts, &cfs)?;
+
         let db: *mut ffi::rocksdb_transactiondb_t;
         let mut cf_map = BTreeMap::new();
 
@@ -247,10 +253,10 @@ impl<T: ThreadMode> TransactionDB<T> {
             let mut cfs_v = cfs;
             // Always open the default column family.
             if !cfs_v.iter().any(|cf| cf.name == DEFAULT_COLUMN_FAMILY_NAME) {
-                cfs_v.push(ColumnFamilyDescriptor {
-                    name: String::from(DEFAULT_COLUMN_FAMILY_NAME),
-                    options: Options::default(),
-                });
+                cfs_v.push(ColumnFamilyDescriptor::new(
+                    DEFAULT_COLUMN_FAMILY_NAME,
+                    Options::default(),
+                ));
             }
             // We need to store our CStrings in an intermediate vector
             // so that their pointers remain valid.
@@ -399,17 +405,14 @@ impl<T: ThreadMode> TransactionDB<T> {
         write_opts: &WriteOptions,
         txn_opts: &TransactionOptions,
     ) -> Transaction<'a, Self> {
-        Transaction {
-            inner: unsafe {
-                ffi::rocksdb_transaction_begin(
-                    self.inner,
-                    write_opts.inner,
-                    txn_opts.inner,
-                    std::ptr::null_mut(),
-                )
-            },
-            _marker: PhantomData::default(),
-        }
+        Transaction::new(unsafe {
+            ffi::rocksdb_transaction_begin(
+                self.inner,
+                write_opts.inner,
+                txn_opts.inner,
+                std::ptr::null_mut(),
+            )
+        })
     }
 
     /// Get all prepared transactions for recovery.
@@ -421,10 +424,7 @@ impl<T: ThreadMode> TransactionDB<T> {
             .lock()
             .unwrap()
             .drain(0..)
-            .map(|inner| Transaction {
-                inner,
-                _marker: PhantomData::default(),
-            })
+            .map(Transaction::new)
             .collect()
     }
 
@@ -928,6 +928,38 @@ impl<T: ThreadMode> TransactionDB<T> {
         SnapshotWithThreadMode::<Self>::new(self)
     }
 
+    /// The sequence number of the most recent transaction.
+    pub fn latest_sequence_number(&self) -> u64 {
+        unsafe { ffi::rocksdb_get_latest_sequence_number(self.inner as *mut ffi::rocksdb_t) }
+    }
+
+    /// Iterate over batches of write operations since a given sequence.
+    ///
+    /// Committed transactions show up as a single batch each.
+    ///
+    /// See [`DBCommon::get_updates_since`](crate::DBCommon::get_updates_since)
+    /// for details.
+    pub fn get_updates_since(&self, seq_number: u64) -> Result<DBWALIterator<'_>, Error> {
+        unsafe {
+            // rocksdb_wal_readoptions_t does not appear to have any functions
+            // for creating and destroying it; fortunately we can pass a nullptr
+            // here to get the default behavior
+            let opts: *const ffi::rocksdb_wal_readoptions_t = ptr::null();
+            let iter = ffi_try!(ffi::rocksdb_get_updates_since(
+                self.inner as *mut ffi::rocksdb_t,
+                seq_number,
+                opts
+            ));
+            Ok(DBWALIterator {
+                inner: iter,
+                start_seq_number: seq_number,
+                column_families: None,
+                _handle: None,
+                _db: PhantomData,
+            })
+        }
+    }
+
     fn drop_column_family<C>(
         &self,
         cf_inner: *mut ffi::rocksdb_column_family_handle_t,
@@ -0,0 +1,553 @@
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::vec;
+
+use crate::{Error, WriteBatch};
+
+/// Whether a WAL file is still in use or has been moved to the archive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum WalFileType {
+    /// The file is in the DB directory and may still be written to
+    Alive,
+    /// The file has been moved to the `archive` directory, see
+    /// [`Options::set_wal_ttl_seconds`](crate::Options::set_wal_ttl_seconds)
+    Archived,
+}
+
+/// A WAL file of a DB, as returned by
+/// [`DBCommon::get_sorted_wal_files`](crate::DBCommon::get_sorted_wal_files).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalFile {
+    /// Path of the file
+    pub path: PathBuf,
+    /// Number of the file, increasing with every new WAL file
+    pub number: u64,
+    /// Whether the file is alive or archived
+    pub file_type: WalFileType,
+    /// Sequence number of the first write batch in the file, 0 if the file
+    /// is empty or compressed with
+    /// [`Options::set_wal_compression_type`](crate::Options::set_wal_compression_type),
+    /// whose batches cannot be read
+    pub start_sequence: u64,
+    /// Size of the file in bytes, which for a recycled file includes the
+    /// records left over from its previous use
+    pub size: u64,
+}
+
+impl WalFile {
+    /// Reads all write batches of the file, with their sequence numbers.
+    ///
+    /// The file is read as is: reading the file which is currently being
+    /// written can stop at a partially written batch.
+    pub fn read_batches(&self) -> Result<Vec<(u64, WriteBatch)>, Error> {
+        let mut reader = RecordReader::new(open_log(&self.path)?, self.number, 0)?;
+        let mut batches = Vec::new();
+        while let Some((_, payload)) = reader.next()? {
+            let record = reader.payload(payload);
+            batches.push((sequence_number(&record)?, WriteBatch::from_data(&record)));
+        }
+        Ok(batches)
+    }
+}
+
+/// A position in the WAL: the number of a WAL file and an offset in it.
+///
+/// Positions are ordered like the records they point to, so the position
+/// after the last record processed can be stored to resume reading with
+/// [`DBCommon::read_wal`](crate::DBCommon::read_wal).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct WalPosition {
+    pub file_number: u64,
+    pub offset: u64,
+}
+
+/// A write batch read from a WAL file by a [`WalReader`].
+///
+/// The record borrows the content of the file held by the reader, unless it
+/// was fragmented over several blocks of the file. Use
+/// [`to_write_batch`](Self::to_write_batch) to copy it.
+#[derive(Debug, Clone)]
+pub struct WalRecord<'a> {
+    data: Cow<'a, [u8]>,
+    position: WalPosition,
+    next_position: WalPosition,
+}
+
+impl WalRecord<'_> {
+    /// Returns the sequence number of the first update of the batch.
+    pub fn sequence(&self) -> u64 {
+        u64::from_le_bytes(self.data[..8].try_into().unwrap())
+    }
+
+    /// Returns the number of updates in the batch.
+    pub fn count(&self) -> u32 {
+        u32::from_le_bytes(self.data[8..12].try_into().unwrap())
+    }
+
+    /// Returns the serialized write batch, in the format of
+    /// [`WriteBatch::data`].
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the position of the record.
+    pub fn position(&self) -> WalPosition {
+        self.position
+    }
+
+    /// Returns the position right after the record, to resume reading with
+    /// the next record.
+    pub fn next_position(&self) -> WalPosition {
+        self.next_position
+    }
+
+    /// Copies the record into a write batch, e.g. to iterate over its
+    /// updates.
+    pub fn to_write_batch(&self) -> WriteBatch {
+        WriteBatch::from_data(&self.data)
+    }
+}
+
+/// Reads the write batches of the WAL files from a [`WalPosition`], see
+/// [`DBCommon::read_wal`](crate::DBCommon::read_wal).
+///
+/// The files are read block by block, and the records yielded borrow from
+/// the block they are in. Records appended to the last file once the reader
+/// reached its end, and files created after the reader, are not guaranteed
+/// to be read: create a new reader from the
+/// [`next_position`](WalRecord::next_position) of the last record to read
+/// them.
+pub struct WalReader {
+    files: vec::IntoIter<WalFile>,
+    current: Option<RecordReader<File>>,
+    offset: u64,
+}
+
+impl WalReader {
+    /// Returns the next write batch, or `None` if all files have been read.
+    ///
+    /// A file being written can end with a partially written batch, which
+    /// is not returned.
+    pub fn next_record(&mut self) -> Option<Result<WalRecord<'_>, Error>> {
+        let (start, payload) = loop {
+            if self.current.is_none() {
+                let file = self.files.next()?;
+                let offset = std::mem::take(&mut self.offset);
+                match open_log(&file.path)
+                    .and_then(|input| RecordReader::new(input, file.number, offset))
+                {
+                    Ok(reader) => self.current = Some(reader),
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+            match self.current.as_mut().unwrap().next() {
+                Ok(Some(record)) => break record,
+                Ok(None) if !self.files.as_slice().is_empty() => self.current = None,
+                Ok(None) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        };
+
+        let reader = self.current.as_ref().unwrap();
+        let number = reader.number;
+        let next_offset = reader.offset();
+        let data = reader.payload(payload);
+        if data.len() < 12 {
+            return Some(Err(Error::new(
+                "Corruption: WAL record too short".to_owned(),
+            )));
+        }
+        Some(Ok(WalRecord {
+            data,
+            position: WalPosition {
+                file_number: number,
+                offset: start,
+            },
+            next_position: WalPosition {
+                file_number: number,
+                offset: next_offset,
+            },
+        }))
+    }
+}
+
+/// Creates a reader of the WAL files in `dir` starting at `position`.
+///
+/// A position with a zero offset starts at the first file numbered from it,
+/// while other positions must be in a file which still exists.
+pub(crate) fn read_wal_from(dir: &Path, position: WalPosition) -> Result<WalReader, Error> {
+    let files: Vec<WalFile> = list_wal_files(dir)?
+        .into_iter()
+        .filter(|file| file.number >= position.file_number)
+        .collect();
+    let offset = match files.first() {
+        Some(file) if file.number == position.file_number => position.offset,
+        _ if position.offset == 0 => 0,
+        _ => {
+            return Err(Error::new(format!(
+                "Not found: WAL file {} does not exist anymore",
+                position.file_number
+            )))
+        }
+    };
+    Ok(WalReader {
+        files: files.into_iter(),
+        current: None,
+        offset,
+    })
+}
+
+/// Lists the WAL files in `dir` and its `archive` directory, sorted by number.
+pub(crate) fn list_wal_files(dir: &Path) -> Result<Vec<WalFile>, Error> {
+    let mut files = Vec::new();
+    for (dir, file_type) in [
+        (dir.to_path_buf(), WalFileType::Alive),
+        (dir.join("archive"), WalFileType::Archived),
+    ] {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) if file_type == WalFileType::Archived => continue,
+            Err(err) => {
+                return Err(Error::new(format!(
+                    "Failed to list {}: {err}",
+                    dir.display()
+                )))
+            }
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let number = match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".log"))
+                .and_then(|number| number.parse().ok())
+            {
+                Some(number) => number,
+                None => continue,
+            };
+            let (start_sequence, size) = read_header(&path, number)?;
+            files.push(WalFile {
+                path,
+                number,
+                file_type,
+                start_sequence,
+                size,
+            });
+        }
+    }
+    files.sort_by_key(|file| file.number);
+    Ok(files)
+}
+
+/// Reads the sequence number of the first write batch and the size of a WAL
+/// file, only reading the blocks of the first batch.
+///
+/// The sequence number of a compressed file is 0, as its batches cannot be
+/// decompressed.
+fn read_header(path: &Path, number: u64) -> Result<(u64, u64), Error> {
+    let input = open_log(path)?;
+    let size = input
+        .metadata()
+        .map_err(|err| Error::new(format!("Failed to read {}: {err}", path.display())))?
+        .len();
+    let mut reader = RecordReader::new(input, number, 0)?;
+    // the compression type is recorded at the start of compressed files
+    if reader.block.get(6) == Some(&SET_COMPRESSION_TYPE) {
+        return Ok((0, size));
+    }
+    let start_sequence = match reader.next()? {
+        Some((_, payload)) => sequence_number(&reader.payload(payload))?,
+        None => 0,
+    };
+    Ok((start_sequence, size))
+}
+
+fn open_log(path: &Path) -> Result<File, Error> {
+    File::open(path).map_err(|err| Error::new(format!("Failed to read {}: {err}", path.display())))
+}
+
+fn sequence_number(record: &[u8]) -> Result<u64, Error> {
+    match record.get(..8) {
+        Some(bytes) => Ok(u64::from_le_bytes(bytes.try_into().unwrap())),
+        None => Err(Error::new("Corruption: WAL record too short".to_owned())),
+    }
+}
+
+const BLOCK_SIZE: usize = 32768;
+const HEADER_SIZE: usize = 7;
+const RECYCLABLE_HEADER_SIZE: usize = 11;
+
+const ZERO_TYPE: u8 = 0;
+const FULL_TYPE: u8 = 1;
+const FIRST_TYPE: u8 = 2;
+const MIDDLE_TYPE: u8 = 3;
+const LAST_TYPE: u8 = 4;
+const RECYCLABLE_FULL_TYPE: u8 = 5;
+const RECYCLABLE_LAST_TYPE: u8 = 8;
+const SET_COMPRESSION_TYPE: u8 = 9;
+const USER_DEFINED_TIMESTAMP_SIZE_TYPE: u8 = 10;
+const RECYCLABLE_USER_DEFINED_TIMESTAMP_SIZE_TYPE: u8 = 11;
+
+/// Splits the content of a WAL file into the write batches it holds, or of
+/// a MANIFEST into its version edits, which uses the same log format.
+pub(crate) fn read_records(data: &[u8], number: u64) -> Result<Vec<Vec<u8>>, Error> {
+    let mut reader = RecordReader::new(Cursor::new(data), number, 0)?;
+    let mut records = Vec::new();
+    while let Some((_, payload)) = reader.next()? {
+        records.push(reader.payload(payload).into_owned());
+    }
+    Ok(records)
+}
+
+/// A record of a WAL file, which is either a range of the current block or
+/// assembled from fragments spread over several blocks.
+enum Payload {
+    Range(Range<usize>),
+    Assembled(Vec<u8>),
+}
+
+/// Reads the records of a WAL file following the log format of RocksDB: the
+/// file is made of 32KB blocks holding records which may be fragmented over
+/// several blocks. The file is read one block at a time.
+///
+/// Files reused with [`Options::set_recycle_log_file_num`] hold records of
+/// the previous file after the ones of the current file, which are told
+/// apart by the log number stored in the header of recyclable records.
+///
+/// [`Options::set_recycle_log_file_num`]: crate::Options::set_recycle_log_file_num
+struct RecordReader<R> {
+    input: R,
+    number: u64,
+    /// The block being read, shorter than `BLOCK_SIZE` at the end of the file
+    block: Vec<u8>,
+    /// Offset of the block in the file
+    block_offset: u64,
+    /// Offset of the next record in the block, only moved past complete
+    /// records so reading can resume there once the file has grown
+    position: usize,
+}
+
+impl<R: Read + Seek> RecordReader<R> {
+    fn new(input: R, number: u64, offset: u64) -> Result<Self, Error> {
+        let mut reader = Self {
+            input,
+            number,
+            block: Vec::with_capacity(BLOCK_SIZE),
+            block_offset: 0,
+            position: 0,
+        };
+        reader.seek(offset)?;
+        Ok(reader)
+    }
+
+    /// Returns the offset of the next record in the file.
+    fn offset(&self) -> u64 {
+        self.block_offset + self.position as u64
+    }
+
+    /// Returns the content of a record returned by [`next`](Self::next)
+    /// before reading the next one.
+    fn payload(&self, payload: Payload) -> Cow<'_, [u8]> {
+        match payload {
+            Payload::Range(range) => Cow::Borrowed(&self.block[range]),
+            Payload::Assembled(record) => Cow::Owned(record),
+        }
+    }
+
+    /// Reads the block holding `offset` and moves to it.
+    fn seek(&mut self, offset: u64) -> Result<(), Error> {
+        let block_offset = offset - offset % BLOCK_SIZE as u64;
+        self.input
+            .seek(SeekFrom::Start(block_offset))
+            .map_err(|err| self.read_error(&err))?;
+        self.block_offset = block_offset;
+        self.read_block()?;
+        self.position = (offset - block_offset) as usize;
+        Ok(())
+    }
+
+    /// Reads the block following the current one, returns `false` at the end
+    /// of the file.
+    fn next_block(&mut self) -> Result<bool, Error> {
+        if self.block.len() < BLOCK_SIZE {
+            return Ok(false);
+        }
+        self.block_offset += BLOCK_SIZE as u64;
+        self.position = 0;
+        self.read_block()?;
+        Ok(!self.block.is_empty())
+    }
+
+    fn read_block(&mut self) -> Result<(), Error> {
+        self.block.resize(BLOCK_SIZE, 0);
+        let mut len = 0;
+        while len < BLOCK_SIZE {
+            match self.input.read(&mut self.block[len..]) {
+                Ok(0) => break,
+                Ok(read) => len += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(self.read_error(&err)),
+            }
+        }
+        self.block.truncate(len);
+        Ok(())
+    }
+
+    fn read_error(&self, err: &io::Error) -> Error {
+        Error::new(format!(
+            "IO error: failed to read log file {}: {err}",
+            self.number
+        ))
+    }
+
+    /// Returns the offset of the next record and its content, or `None` if
+    /// there is no complete record left.
+    fn next(&mut self) -> Result<Option<(u64, Payload)>, Error> {
+        let resume_offset = self.offset();
+        let mut fragments: Option<(u64, Vec<u8>)> = None;
+
+        loop {
+            if BLOCK_SIZE - self.position < HEADER_SIZE {
+                // the trailer of a block is padding
+                if self.next_block()? {
+                    continue;
+                }
+                break;
+            }
+            let offset = self.position;
+            if offset + HEADER_SIZE > self.block.len() {
+                // the end of the file
+                break;
+            }
+
+            let header = &self.block[offset..];
+            let checksum = u32::from_le_bytes(header[..4].try_into().unwrap());
+            let length = usize::from(u16::from_le_bytes(header[4..6].try_into().unwrap()));
+            let record_type = header[6];
+            if record_type == ZERO_TYPE && length == 0 {
+                // recyclable logs pad the trailers shorter than their header
+                if BLOCK_SIZE - offset < RECYCLABLE_HEADER_SIZE && self.next_block()? {
+                    continue;
+                }
+                // preallocated space which was never written to
+                break;
+            }
+
+            // recyclable records have the types of the other records plus 4,
+            // except for the user-defined timestamp size
+            let (fragment_type, recyclable) = match record_type {
+                RECYCLABLE_FULL_TYPE..=RECYCLABLE_LAST_TYPE => (record_type - 4, true),
+                RECYCLABLE_USER_DEFINED_TIMESTAMP_SIZE_TYPE => {
+                    (USER_DEFINED_TIMESTAMP_SIZE_TYPE, true)
+                }
+                _ => (record_type, false),
+            };
+            let header_size = if recyclable {
+                RECYCLABLE_HEADER_SIZE
+            } else {
+                HEADER_SIZE
+            };
+            let end = offset + header_size + length;
+            if end > self.block.len() {
+                // a record being written or truncated by a crash
+                break;
+            }
+            if recyclable {
+                let log_number = u32::from_le_bytes(header[7..11].try_into().unwrap());
+                if log_number != self.number as u32 {
+                    // a leftover of the file this one was recycled from
+                    break;
+                }
+            }
+            // the checksum covers the type, the log number of recyclable records
+            // and the payload
+            if unmask_crc(checksum) != crc32c(&self.block[offset + 6..end]) {
+                return Err(Error::new(format!(
+                    "Corruption: checksum mismatch in log record at offset {}",
+                    self.offset()
+                )));
+            }
+            let start = self.offset();
+            let payload = offset + header_size..end;
+            self.position = end;
+
+            match fragment_type {
+                FULL_TYPE => return Ok(Some((start, Payload::Range(payload)))),
+                FIRST_TYPE => fragments = Some((start, self.block[payload].to_vec())),
+                MIDDLE_TYPE | LAST_TYPE => {
+                    let (start, mut record) = fragments.take().ok_or_else(|| {
+                        Error::new("Corruption: WAL record fragment without a start".to_owned())
+                    })?;
+                    record.extend_from_slice(&self.block[payload]);
+                    if fragment_type == LAST_TYPE {
+                        return Ok(Some((start, Payload::Assembled(record))));
+                    }
+                    fragments = Some((start, record));
+                }
+                // metadata of the following records
+                USER_DEFINED_TIMESTAMP_SIZE_TYPE => {}
+                SET_COMPRESSION_TYPE => {
+                    return Err(Error::new(
+                        "Not implemented: compressed WAL files cannot be read".to_owned(),
+                    ))
+                }
+                _ => {
+                    return Err(Error::new(format!(
+                        "Corruption: unknown WAL record type {record_type}"
+                    )))
+                }
+            }
+        }
+
+        // resume at the start of the incomplete record
+        if self.offset() != resume_offset {
+            self.seek(resume_offset)?;
+        }
+        Ok(None)
+    }
+}
+
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82F6_3B78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+pub(crate) fn crc32c(data: &[u8]) -> u32 {
+    crc32c_extend(0, data)
+}
+
+/// Extends the CRC32c `crc` of some data with the data that follows it.
+pub(crate) fn crc32c_extend(crc: u32, data: &[u8]) -> u32 {
+    !data.iter().fold(!crc, |crc, byte| {
+        CRC32C_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Reverts the masking RocksDB applies to stored checksums.
+fn unmask_crc(masked: u32) -> u32 {
+    let rot = masked.wrapping_sub(0xa282_ead8);
+    (rot >> 17) | (rot << 15)
+}
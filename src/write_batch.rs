@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{ffi, AsColumnFamilyRef};
+use crate::{coding, ffi, AsColumnFamilyRef, Error};
 use libc::{c_char, c_void, size_t};
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
 use std::slice;
 
 /// A type alias to keep compatibility. See [`WriteBatchWithTransaction`] for details
@@ -129,6 +131,24 @@ impl<const TRANSACTION: bool> WriteBatchWithTransaction<TRANSACTION> {
         self.len() == 0
     }
 
+    /// Returns the sequence number in the header of the batch, which is set
+    /// when the batch is written.
+    pub(crate) fn sequence_number(&self) -> u64 {
+        let mut sequence = [0; 8];
+        sequence.copy_from_slice(&self.data()[..8]);
+        u64::from_le_bytes(sequence)
+    }
+
+    /// Returns the ids of the column families the batch writes to, sorted and
+    /// without duplicates, the records without a column family writing to the
+    /// default column family of id 0.
+    ///
+    /// The ids of the opened column families are returned by
+    /// [`DBCommon::column_family_id`](crate::DBCommon::column_family_id).
+    pub fn column_family_ids(&self) -> Result<Vec<u32>, Error> {
+        column_family_ids(self.data()).map(|ids| ids.into_iter().collect())
+    }
+
     /// Iterate the put and delete operations within this write batch. Note that
     /// this does _not_ return an `Iterator` but instead will invoke the `put()`
     /// and `delete()` member functions of the provided `WriteBatchIterator`
@@ -188,6 +208,50 @@ impl<const TRANSACTION: bool> WriteBatchWithTransaction<TRANSACTION> {
         }
     }
 
+    /// Inserts all key-value pairs of `pairs`, in order.
+    ///
+    /// This is equivalent to calling [`put`](Self::put) for each pair, for
+    /// building large batches from an iterator.
+    pub fn extend_from_pairs<I, K, V>(&mut self, pairs: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        for (key, value) in pairs {
+            self.put(key, value);
+        }
+    }
+
+    /// Inserts all key-value pairs of `pairs` in the given column family, in
+    /// order.
+    ///
+    /// The column family handle is resolved once for the whole batch rather
+    /// than for every pair.
+    pub fn extend_from_pairs_cf<I, K, V>(&mut self, cf: &impl AsColumnFamilyRef, pairs: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let cf = cf.inner();
+        for (key, value) in pairs {
+            let key = key.as_ref();
+            let value = value.as_ref();
+
+            unsafe {
+                ffi::rocksdb_writebatch_put_cf(
+                    self.inner,
+                    cf,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    value.as_ptr() as *const c_char,
+                    value.len() as size_t,
+                );
+            }
+        }
+    }
+
     pub fn merge<K, V>(&mut self, key: K, value: V)
     where
         K: AsRef<[u8]>,
@@ -227,6 +291,23 @@ impl<const TRANSACTION: bool> WriteBatchWithTransaction<TRANSACTION> {
         }
     }
 
+    /// Adds `delta` to the counter stored at `key`, see
+    /// [`DBCommon::increment_u64`](crate::DBCommon::increment_u64).
+    pub fn increment_u64<K: AsRef<[u8]>>(&mut self, key: K, delta: u64) {
+        self.merge(key, delta.to_le_bytes());
+    }
+
+    /// Adds `delta` to the counter stored at `key` in the given column family,
+    /// see [`DBCommon::increment_u64`](crate::DBCommon::increment_u64).
+    pub fn increment_u64_cf<K: AsRef<[u8]>>(
+        &mut self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+        delta: u64,
+    ) {
+        self.merge_cf(cf, key, delta.to_le_bytes());
+    }
+
     /// Removes the database entry for key. Does nothing if the key was not found.
     pub fn delete<K: AsRef<[u8]>>(&mut self, key: K) {
         let key = key.as_ref();
@@ -253,12 +334,121 @@ impl<const TRANSACTION: bool> WriteBatchWithTransaction<TRANSACTION> {
         }
     }
 
+    /// Removes the database entry for key, which must have been written at
+    /// most once since it was last deleted, with a single put and no merge.
+    ///
+    /// Unlike a deletion, a single deletion is dropped by compactions along
+    /// with the put it deletes, so keys which are written once and deleted,
+    /// e.g. the entries of a queue, leave no tombstones behind. The result is
+    /// undefined if the key was overwritten.
+    pub fn single_delete<K: AsRef<[u8]>>(&mut self, key: K) {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_singledelete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+    }
+
+    /// Removes the database entry for key in a column family, see
+    /// [`single_delete`](Self::single_delete).
+    pub fn single_delete_cf<K: AsRef<[u8]>>(&mut self, cf: &impl AsColumnFamilyRef, key: K) {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_singledelete_cf(
+                self.inner,
+                cf.inner(),
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+    }
+
     /// Clear all updates buffered in this batch.
     pub fn clear(&mut self) {
         unsafe {
             ffi::rocksdb_writebatch_clear(self.inner);
         }
     }
+
+    /// Appends the operations of `other` to this batch, e.g. to write batches
+    /// built in different threads with a single atomic write.
+    ///
+    /// The batch is rebuilt from its serialized data, so it loses the save
+    /// points it may have.
+    pub fn append(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        let count = (self.len() + other.len()) as u32;
+        let mut data = Vec::with_capacity(self.size_in_bytes() + other.size_in_bytes());
+        data.extend_from_slice(self.data());
+        data.extend_from_slice(&other.data()[WRITE_BATCH_HEADER_SIZE..]);
+        data[8..WRITE_BATCH_HEADER_SIZE].copy_from_slice(&count.to_le_bytes());
+        *self = Self::from_data(&data);
+    }
+}
+
+/// The size of the header of a serialized write batch: an 8 bytes sequence
+/// number followed by a 4 bytes count of operations.
+const WRITE_BATCH_HEADER_SIZE: usize = 12;
+
+/// A write operation, to build a [`WriteBatch`] from an iterator.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{BatchOp, WriteBatch};
+///
+/// let batch: WriteBatch = vec![
+///     BatchOp::Put(b"k1", b"v1"),
+///     BatchOp::Delete(b"k2"),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(batch.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp<K, V = K> {
+    /// Inserts the value of a key, see [`WriteBatch::put`].
+    Put(K, V),
+    /// Merges an operand into the value of a key, see [`WriteBatch::merge`].
+    Merge(K, V),
+    /// Deletes a key, see [`WriteBatch::delete`].
+    Delete(K),
+}
+
+impl<const TRANSACTION: bool, K, V> Extend<BatchOp<K, V>> for WriteBatchWithTransaction<TRANSACTION>
+where
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    fn extend<I: IntoIterator<Item = BatchOp<K, V>>>(&mut self, ops: I) {
+        for op in ops {
+            match op {
+                BatchOp::Put(key, value) => self.put(key, value),
+                BatchOp::Merge(key, value) => self.merge(key, value),
+                BatchOp::Delete(key) => self.delete(key),
+            }
+        }
+    }
+}
+
+impl<const TRANSACTION: bool, K, V> FromIterator<BatchOp<K, V>>
+    for WriteBatchWithTransaction<TRANSACTION>
+where
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    fn from_iter<I: IntoIterator<Item = BatchOp<K, V>>>(ops: I) -> Self {
+        let mut batch = Self::default();
+        batch.extend(ops);
+        batch
+    }
 }
 
 impl WriteBatchWithTransaction<false> {
@@ -319,3 +509,63 @@ impl<const TRANSACTION: bool> Drop for WriteBatchWithTransaction<TRANSACTION> {
 }
 
 unsafe impl<const TRANSACTION: bool> Send for WriteBatchWithTransaction<TRANSACTION> {}
+
+/// The column family written by a record of a serialized batch.
+enum RecordColumnFamily {
+    /// The record writes to the default column family
+    Default,
+    /// The record writes to the column family whose id follows its tag
+    Id,
+    /// The record does not write data, e.g. a transaction marker
+    None,
+}
+
+/// Parses the ids of the column families written by the serialized batch
+/// `data`, following the record format of `WriteBatchInternal`.
+fn column_family_ids(data: &[u8]) -> Result<BTreeSet<u32>, Error> {
+    if data.len() < WRITE_BATCH_HEADER_SIZE {
+        return Err(corrupted_batch("too small"));
+    }
+    let mut input = &data[WRITE_BATCH_HEADER_SIZE..];
+    let mut ids = BTreeSet::new();
+    while let Some((&tag, rest)) = input.split_first() {
+        input = rest;
+        // the column family of the record and its number of length-prefixed
+        // fields
+        let (cf, fields) = match tag {
+            // deletion, single deletion and deletion with timestamp
+            0x0 | 0x7 | 0x14 => (RecordColumnFamily::Default, 1),
+            0x4 | 0x8 => (RecordColumnFamily::Id, 1),
+            // value, merge, range deletion, blob index and wide-column entity
+            0x1 | 0x2 | 0xF | 0x11 | 0x16 => (RecordColumnFamily::Default, 2),
+            0x5 | 0x6 | 0xE | 0x10 | 0x17 => (RecordColumnFamily::Id, 2),
+            // log data, end of prepare, commit and rollback
+            0x3 | 0xA | 0xB | 0xC => (RecordColumnFamily::None, 1),
+            // commit with timestamp
+            0x15 => (RecordColumnFamily::None, 2),
+            // begin of prepare and no-op
+            0x9 | 0xD | 0x12 | 0x13 => (RecordColumnFamily::None, 0),
+            _ => return Err(corrupted_batch(&format!("unknown record tag {tag:#x}"))),
+        };
+        match cf {
+            RecordColumnFamily::Default => {
+                ids.insert(0);
+            }
+            RecordColumnFamily::Id => {
+                let id = coding::read_varint32(&mut input)
+                    .ok_or_else(|| corrupted_batch("invalid column family id"))?;
+                ids.insert(id);
+            }
+            RecordColumnFamily::None => {}
+        }
+        for _ in 0..fields {
+            coding::read_length_prefixed_slice(&mut input)
+                .ok_or_else(|| corrupted_batch("truncated record"))?;
+        }
+    }
+    Ok(ids)
+}
+
+fn corrupted_batch(reason: &str) -> Error {
+    Error::new(format!("Corruption: invalid write batch: {reason}"))
+}
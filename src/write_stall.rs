@@ -0,0 +1,274 @@
+use std::convert::TryFrom;
+
+use crate::{
+    column_family::column_family_name,
+    db::DBInner,
+    options_file::OptionsFile,
+    properties::{self, PropName},
+    AsColumnFamilyRef, DBCommon, Error, ThreadMode,
+};
+
+/// The write stall conditions of a DB, as returned by
+/// [`DBCommon::get_write_stall_info`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteStallInfo {
+    /// Whether writes have been stopped
+    pub is_write_stopped: bool,
+    /// The current delayed write rate in bytes per second, 0 means no delay
+    pub actual_delayed_write_rate: u64,
+    /// Estimated number of bytes compaction needs to rewrite to get all
+    /// levels down to under target size
+    pub estimate_pending_compaction_bytes: u64,
+    /// The conditions of the column families stalling the writes, empty if
+    /// writes are neither stopped nor delayed
+    pub causes: Vec<WriteStallReason>,
+}
+
+impl WriteStallInfo {
+    /// Returns `true` if writes are currently stopped or delayed.
+    pub fn is_stalled(&self) -> bool {
+        self.is_write_stopped || self.actual_delayed_write_rate != 0
+    }
+}
+
+/// The condition of a column family which slows down or stops the writes,
+/// see [`WriteStallInfo::causes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WriteStallCause {
+    /// Too many memtables are waiting to be flushed, limited by
+    /// [`Options::set_max_write_buffer_number`](crate::Options::set_max_write_buffer_number)
+    MemtableLimit,
+    /// Too many files are in level 0, limited by
+    /// [`Options::set_level_zero_slowdown_writes_trigger`](crate::Options::set_level_zero_slowdown_writes_trigger)
+    /// and
+    /// [`Options::set_level_zero_stop_writes_trigger`](crate::Options::set_level_zero_stop_writes_trigger)
+    L0FileCountLimit,
+    /// Too many bytes are waiting to be compacted, limited by
+    /// [`Options::set_soft_pending_compaction_bytes_limit`](crate::Options::set_soft_pending_compaction_bytes_limit)
+    /// and
+    /// [`Options::set_hard_pending_compaction_bytes_limit`](crate::Options::set_hard_pending_compaction_bytes_limit)
+    PendingCompactionBytes,
+}
+
+/// How the writes are stalled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WriteStallCondition {
+    /// Writes are slowed down to the delayed write rate
+    Delayed,
+    /// Writes are blocked until the condition clears
+    Stopped,
+}
+
+/// A write stall of a column family with its cause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteStallReason {
+    /// Name of the column family
+    pub column_family: String,
+    /// Cause of the stall
+    pub cause: WriteStallCause,
+    /// Whether writes are delayed or stopped
+    pub condition: WriteStallCondition,
+    /// Current value of the cause, e.g. the number of files in level 0
+    pub value: u64,
+    /// Limit reached by the value
+    pub limit: u64,
+}
+
+/// The options of a column family limiting its writes.
+struct StallLimits {
+    max_write_buffer_number: u64,
+    min_write_buffer_number_to_merge: u64,
+    disable_auto_compactions: bool,
+    level0_slowdown_writes_trigger: i64,
+    level0_stop_writes_trigger: i64,
+    soft_pending_compaction_bytes_limit: u64,
+    hard_pending_compaction_bytes_limit: u64,
+}
+
+impl StallLimits {
+    fn from_options_file(file: &OptionsFile, cf: &str) -> Result<Self, Error> {
+        Ok(Self {
+            max_write_buffer_number: file.cf_option(cf, "max_write_buffer_number")?,
+            min_write_buffer_number_to_merge: file
+                .cf_option(cf, "min_write_buffer_number_to_merge")?,
+            disable_auto_compactions: file.cf_option(cf, "disable_auto_compactions")?,
+            level0_slowdown_writes_trigger: file.cf_option(cf, "level0_slowdown_writes_trigger")?,
+            level0_stop_writes_trigger: file.cf_option(cf, "level0_stop_writes_trigger")?,
+            soft_pending_compaction_bytes_limit: file
+                .cf_option(cf, "soft_pending_compaction_bytes_limit")?,
+            hard_pending_compaction_bytes_limit: file
+                .cf_option(cf, "hard_pending_compaction_bytes_limit")?,
+        })
+    }
+}
+
+impl<T: ThreadMode, D: DBInner> DBCommon<T, D> {
+    fn write_stall_info_impl(
+        int_property: impl Fn(&PropName) -> Result<Option<u64>, Error>,
+    ) -> Result<WriteStallInfo, Error> {
+        let value = |name: &PropName| int_property(name).map(Option::unwrap_or_default);
+        Ok(WriteStallInfo {
+            is_write_stopped: value(properties::IS_WRITE_STOPPED)? != 0,
+            actual_delayed_write_rate: value(properties::ACTUAL_DELAYED_WRITE_RATE)?,
+            estimate_pending_compaction_bytes: value(
+                properties::ESTIMATE_PENDING_COMPACTION_BYTES,
+            )?,
+            causes: Vec::new(),
+        })
+    }
+
+    /// Returns the write stall conditions of the DB, with the pending
+    /// compaction bytes of the default column family and the causes of the
+    /// stall in all the opened column families, so writers can apply
+    /// backpressure matching the cause, e.g. pausing the writes of a column
+    /// family whose level 0 is full.
+    ///
+    /// The C API does not expose event listeners, so the stalls are not
+    /// pushed to the application: whether writes are stalled is read from
+    /// the properties of the DB, and only then are the causes evaluated from
+    /// the properties of the column families and their limits in the latest
+    /// OPTIONS file, as RocksDB evaluates them after each flush and
+    /// compaction. The causes can be empty if the stall ended in between. The
+    /// counts of past stalls per cause are reported by
+    /// [`get_cf_stats`](Self::get_cf_stats).
+    pub fn get_write_stall_info(&self) -> Result<WriteStallInfo, Error> {
+        let mut info = Self::write_stall_info_impl(|name| self.property_int_value(name))?;
+        if info.is_stalled() {
+            let file = self.read_latest_options_file()?;
+            for name in self.cfs.cf_names_internal() {
+                if let Some(causes) = self
+                    .cfs
+                    .with_cf_internal(&name, |cf| self.stall_causes(&cf, &name, &file))
+                {
+                    info.causes.extend(causes?);
+                }
+            }
+        }
+        Ok(info)
+    }
+
+    /// Returns the write stall conditions of the DB, with the pending
+    /// compaction bytes and the causes of the stall of the given column
+    /// family, see [`get_write_stall_info`](Self::get_write_stall_info).
+    pub fn get_write_stall_info_cf(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+    ) -> Result<WriteStallInfo, Error> {
+        let mut info = Self::write_stall_info_impl(|name| self.property_int_value_cf(cf, name))?;
+        if info.is_stalled() {
+            let file = self.read_latest_options_file()?;
+            info.causes = self.stall_causes(cf, &column_family_name(cf.inner()), &file)?;
+        }
+        Ok(info)
+    }
+
+    /// Returns `true` if writes are currently stopped or delayed.
+    ///
+    /// This only reads two integer properties, so it is cheap enough to be
+    /// checked before every batch by backpressure-aware writers.
+    pub fn is_write_stalled(&self) -> Result<bool, Error> {
+        let stopped = self.property_int_value(properties::IS_WRITE_STOPPED)?;
+        if stopped.unwrap_or_default() != 0 {
+            return Ok(true);
+        }
+        let delayed_write_rate = self.property_int_value(properties::ACTUAL_DELAYED_WRITE_RATE)?;
+        Ok(delayed_write_rate.unwrap_or_default() != 0)
+    }
+
+    fn read_latest_options_file(&self) -> Result<OptionsFile, Error> {
+        OptionsFile::read_latest(self.path())?
+            .ok_or_else(|| Error::new("Not found: the DB has no OPTIONS file".to_owned()))
+    }
+
+    fn stall_causes(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        name: &str,
+        file: &OptionsFile,
+    ) -> Result<Vec<WriteStallReason>, Error> {
+        let limits = StallLimits::from_options_file(file, name)?;
+        let memtables = self
+            .property_int_value_cf(cf, properties::NUM_IMMUTABLE_MEM_TABLE)?
+            .unwrap_or_default();
+        let l0_files = self
+            .property_int_value_cf(cf, properties::num_files_at_level(0))?
+            .unwrap_or_default();
+        let pending_bytes = self
+            .property_int_value_cf(cf, properties::ESTIMATE_PENDING_COMPACTION_BYTES)?
+            .unwrap_or_default();
+
+        let mut reasons = Vec::new();
+        let mut report = |cause, condition, value, limit| {
+            reasons.push(WriteStallReason {
+                column_family: name.to_owned(),
+                cause,
+                condition,
+                value,
+                limit,
+            });
+        };
+
+        // the same conditions as ColumnFamilyData::GetWriteStallConditionAndCause
+        if memtables >= limits.max_write_buffer_number {
+            report(
+                WriteStallCause::MemtableLimit,
+                WriteStallCondition::Stopped,
+                memtables,
+                limits.max_write_buffer_number,
+            );
+        } else if limits.max_write_buffer_number > 3
+            && memtables >= limits.max_write_buffer_number - 1
+            && memtables > limits.min_write_buffer_number_to_merge
+        {
+            report(
+                WriteStallCause::MemtableLimit,
+                WriteStallCondition::Delayed,
+                memtables,
+                limits.max_write_buffer_number - 1,
+            );
+        }
+        // level 0 and pending compaction bytes only stall writes when
+        // compactions can reduce them
+        if !limits.disable_auto_compactions {
+            let l0_trigger = |trigger: i64| u64::try_from(trigger).ok();
+            if let Some(stop) =
+                l0_trigger(limits.level0_stop_writes_trigger).filter(|stop| l0_files >= *stop)
+            {
+                report(
+                    WriteStallCause::L0FileCountLimit,
+                    WriteStallCondition::Stopped,
+                    l0_files,
+                    stop,
+                );
+            } else if let Some(slowdown) = l0_trigger(limits.level0_slowdown_writes_trigger)
+                .filter(|slowdown| l0_files >= *slowdown)
+            {
+                report(
+                    WriteStallCause::L0FileCountLimit,
+                    WriteStallCondition::Delayed,
+                    l0_files,
+                    slowdown,
+                );
+            }
+            let hard = limits.hard_pending_compaction_bytes_limit;
+            let soft = limits.soft_pending_compaction_bytes_limit;
+            if hard > 0 && pending_bytes >= hard {
+                report(
+                    WriteStallCause::PendingCompactionBytes,
+                    WriteStallCondition::Stopped,
+                    pending_bytes,
+                    hard,
+                );
+            } else if soft > 0 && pending_bytes >= soft {
+                report(
+                    WriteStallCause::PendingCompactionBytes,
+                    WriteStallCondition::Delayed,
+                    pending_bytes,
+                    soft,
+                );
+            }
+        }
+        Ok(reasons)
+    }
+}
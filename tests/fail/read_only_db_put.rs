@@ -0,0 +1,6 @@
+use rocksdb::{Options, ReadOnlyDB};
+
+fn main() {
+    let db = ReadOnlyDB::open(&Options::default(), "foo", false).unwrap();
+    db.put(b"key", b"value").unwrap();
+}
@@ -14,11 +14,15 @@
 
 mod util;
 
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
 use pretty_assertions::assert_eq;
 
 use rocksdb::{
     backup::{BackupEngine, BackupEngineOptions, RestoreOptions},
-    Env, DB,
+    ChecksumGenType, Env, DB,
 };
 use util::DBPath;
 
@@ -107,3 +111,164 @@ fn restore_from_backup() {
         }
     }
 }
+
+#[test]
+fn restore_from_backup_with_progress() {
+    let path = DBPath::new("restore_with_progress_test");
+    let restore_path = DBPath::new("restore_with_progress_path");
+    let backup_path = DBPath::new("restore_with_progress_test_backup");
+    {
+        let db = DB::open_default(&path).unwrap();
+        for i in 0..1000_u32 {
+            db.put(i.to_be_bytes(), [0; 100]).unwrap();
+        }
+
+        let env = Env::new().unwrap();
+        let mut backup_opts = BackupEngineOptions::new(&backup_path).unwrap();
+        backup_opts.set_backup_rate_limit(64 * 1024 * 1024);
+        backup_opts.set_restore_rate_limit(64 * 1024 * 1024);
+        backup_opts.set_backup_log_files(false);
+        let mut backup_engine = BackupEngine::open(&backup_opts, &env).unwrap();
+        backup_engine.create_new_backup_flush(&db, true).unwrap();
+        let info = backup_engine.get_backup_info();
+        let backup_id = info[0].backup_id;
+
+        let mut reports = Vec::new();
+        backup_engine
+            .restore_from_backup_with_progress(
+                &restore_path,
+                &restore_path,
+                &RestoreOptions::default(),
+                backup_id,
+                Duration::from_millis(1),
+                |progress| reports.push(progress),
+            )
+            .unwrap();
+        let last = reports.last().unwrap();
+        assert_eq!(last.bytes_restored, info[0].size);
+        assert_eq!(last.total_bytes, info[0].size);
+        assert!(reports
+            .iter()
+            .all(|progress| progress.bytes_restored <= progress.total_bytes));
+
+        assert!(backup_engine
+            .restore_from_backup_with_progress(
+                &restore_path,
+                &restore_path,
+                &RestoreOptions::default(),
+                backup_id + 1,
+                Duration::from_millis(1),
+                |_| {},
+            )
+            .is_err());
+
+        let db_restore = DB::open_default(&restore_path).unwrap();
+        assert_eq!(
+            db_restore.get(5_u32.to_be_bytes()).unwrap().unwrap(),
+            [0; 100]
+        );
+    }
+}
+
+#[test]
+fn backup_statistics_and_checksums() {
+    let path = DBPath::new("backup_statistics_test");
+    let backup_path = DBPath::new("backup_statistics_test_backup");
+    {
+        let db = DB::open_default(&path).unwrap();
+        let env = Env::new().unwrap();
+        let backup_opts = BackupEngineOptions::new(&backup_path).unwrap();
+        let mut backup_engine = BackupEngine::open(&backup_opts, &env).unwrap();
+
+        db.put(b"k1", [1; 1000]).unwrap();
+        backup_engine.create_new_backup_flush(&db, true).unwrap();
+        db.put(b"k2", [2; 1000]).unwrap();
+        backup_engine.create_new_backup_flush(&db, true).unwrap();
+
+        let statistics = backup_engine.get_backup_statistics().unwrap();
+        assert_eq!(statistics.len(), 2);
+        assert_eq!(statistics[0].incremental_size, statistics[0].size);
+        // the SST file of the first backup is shared by the second one
+        assert!(statistics[1].incremental_size < statistics[1].size);
+        assert!(statistics[1].num_files > statistics[0].num_files);
+
+        for s in &statistics {
+            backup_engine
+                .verify_backup_with_checksums(s.backup_id)
+                .unwrap();
+        }
+
+        let files = backup_engine
+            .get_backup_files(statistics[1].backup_id)
+            .unwrap();
+        assert!(!files.is_empty());
+        for file in &files {
+            let file_path = std::path::Path::new("backup_statistics_test_backup").join(&file.path);
+            assert_eq!(
+                ChecksumGenType::Crc32c.checksum_file(file_path).unwrap(),
+                file.file_checksum
+            );
+        }
+
+        // corrupt an SST file without changing its size
+        let shared = std::path::Path::new("backup_statistics_test_backup").join("shared_checksum");
+        let sst = std::fs::read_dir(shared)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().map_or(false, |ext| ext == "sst"))
+            .unwrap();
+        let mut data = std::fs::read(&sst).unwrap();
+        data[0] ^= 0xff;
+        std::fs::write(&sst, data).unwrap();
+
+        let latest = statistics[1].backup_id;
+        assert!(backup_engine.verify_backup(latest).is_ok());
+        assert!(backup_engine.verify_backup_with_checksums(latest).is_err());
+    }
+}
+
+#[test]
+fn backup_from_background_thread() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<BackupEngine>();
+    assert_send_sync::<BackupEngineOptions>();
+    assert_send_sync::<RestoreOptions>();
+
+    let path = DBPath::new("backup_from_background_thread_test");
+    let backup_path = DBPath::new("backup_from_background_thread_test_backup");
+    {
+        let db = Arc::new(DB::open_default(&path).unwrap());
+        db.put(b"k1", b"v1").unwrap();
+
+        let backup_opts = BackupEngineOptions::new(&backup_path).unwrap();
+        let env = Env::new().unwrap();
+        let backup_engine = Arc::new(std::sync::Mutex::new(
+            BackupEngine::open(&backup_opts, &env).unwrap(),
+        ));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let db = Arc::clone(&db);
+                let backup_engine = Arc::clone(&backup_engine);
+                thread::spawn(move || {
+                    backup_engine
+                        .lock()
+                        .unwrap()
+                        .create_new_backup_flush(&db, true)
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        let backup_engine = backup_engine.lock().unwrap();
+        let info = backup_engine.get_backup_info();
+        assert_eq!(info.len(), 2);
+        thread::scope(|scope| {
+            for i in &info {
+                let backup_engine = &*backup_engine;
+                scope.spawn(move || backup_engine.verify_backup(i.backup_id).unwrap());
+            }
+        });
+    }
+}
@@ -14,9 +14,18 @@
 
 mod util;
 
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
 use pretty_assertions::assert_eq;
 
-use rocksdb::{checkpoint::Checkpoint, Options, DB};
+use rocksdb::{
+    checkpoint::{Checkpoint, CheckpointManager},
+    IteratorMode, Options, DB,
+};
 use util::DBPath;
 
 #[test]
@@ -100,6 +109,211 @@ pub fn test_multi_checkpoints() {
     assert_eq!(cp.get(b"k6").unwrap().unwrap(), b"v6");
 }
 
+#[test]
+pub fn test_checkpoint_without_flush() {
+    const PATH_PREFIX: &str = "_rust_rocksdb_cp_without_flush_";
+
+    let db_path = DBPath::new(&format!("{PATH_PREFIX}db1"));
+
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let db = DB::open(&opts, &db_path).unwrap();
+
+    db.put(b"k1", b"v1").unwrap();
+    db.put(b"k2", b"v2").unwrap();
+
+    let cp1 = Checkpoint::new(&db).unwrap();
+    let cp1_path = DBPath::new(&format!("{PATH_PREFIX}cp1"));
+    let sequence_number = cp1
+        .create_checkpoint_with_log_size_for_flush(&cp1_path, u64::MAX)
+        .unwrap();
+    assert_eq!(sequence_number, 2);
+
+    // the memtable was not flushed
+    assert_eq!(
+        db.property_int_value("rocksdb.num-files-at-level0")
+            .unwrap(),
+        Some(0)
+    );
+
+    // the writes are recovered from the copied WAL
+    let cp = DB::open_default(&cp1_path).unwrap();
+    assert_eq!(cp.latest_sequence_number(), sequence_number);
+    assert_eq!(cp.get(b"k1").unwrap().unwrap(), b"v1");
+    assert_eq!(cp.get(b"k2").unwrap().unwrap(), b"v2");
+
+    // with a flush, the sequence number is read from the MANIFEST
+    db.put(b"k3", b"v3").unwrap();
+    let cp2_path = DBPath::new(&format!("{PATH_PREFIX}cp2"));
+    let sequence_number = cp1
+        .create_checkpoint_with_log_size_for_flush(&cp2_path, 0)
+        .unwrap();
+    assert_eq!(sequence_number, 3);
+    let cp = DB::open_default(&cp2_path).unwrap();
+    assert_eq!(cp.latest_sequence_number(), sequence_number);
+}
+
+#[test]
+pub fn test_checkpoint_stream() {
+    const PATH_PREFIX: &str = "_rust_rocksdb_cp_stream_";
+
+    let db_path = DBPath::new(&format!("{PATH_PREFIX}db1"));
+    let db = DB::open_default(&db_path).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.put(b"k2", b"v2").unwrap();
+
+    let staging_path = DBPath::new(&format!("{PATH_PREFIX}staging"));
+    let mut archive = Vec::new();
+    let cp = Checkpoint::new(&db).unwrap();
+    assert_eq!(
+        cp.create_checkpoint_stream(&staging_path, &mut archive)
+            .unwrap(),
+        2
+    );
+    assert!(!std::path::Path::new(&format!("{PATH_PREFIX}staging")).exists());
+
+    // extract the archive
+    let restored_path = DBPath::new(&format!("{PATH_PREFIX}restored"));
+    let restored_dir = format!("{PATH_PREFIX}restored");
+    std::fs::create_dir_all(&restored_dir).unwrap();
+    let mut names = Vec::new();
+    let mut offset = 0;
+    while archive[offset..offset + 512].iter().any(|b| *b != 0) {
+        let header = &archive[offset..offset + 512];
+        let name_len = header[..100].iter().position(|b| *b == 0).unwrap();
+        let name = std::str::from_utf8(&header[..name_len]).unwrap().to_owned();
+        let size_field = std::str::from_utf8(&header[124..135]).unwrap();
+        let size = usize::from_str_radix(size_field, 8).unwrap();
+        offset += 512;
+        std::fs::write(
+            format!("{restored_dir}/{name}"),
+            &archive[offset..offset + size],
+        )
+        .unwrap();
+        offset += (size + 511) / 512 * 512;
+        names.push(name);
+    }
+    assert_eq!(archive.len(), offset + 1024);
+    assert!(names.contains(&"CURRENT".to_owned()));
+
+    let restored = DB::open_default(&restored_path).unwrap();
+    assert_eq!(restored.get(b"k1").unwrap().unwrap(), b"v1");
+    assert_eq!(restored.get(b"k2").unwrap().unwrap(), b"v2");
+}
+
+#[test]
+fn test_open_checkpoint_read_only() {
+    const PATH_PREFIX: &str = "_rust_rocksdb_cp_read_only_";
+
+    let db_path = DBPath::new(&format!("{PATH_PREFIX}db1"));
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &db_path, ["cf"]).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.put_cf(&db.cf_handle("cf").unwrap(), b"k2", b"v2")
+        .unwrap();
+
+    let cp_path = DBPath::new(&format!("{PATH_PREFIX}cp1"));
+    Checkpoint::new(&db)
+        .unwrap()
+        .create_checkpoint(&cp_path)
+        .unwrap();
+    {
+        let cp = DB::open_checkpoint_read_only(&Options::default(), &cp_path).unwrap();
+        assert_eq!(cp.get(b"k1").unwrap().unwrap(), b"v1");
+        let cf = cp.cf_handle("cf").unwrap();
+        assert_eq!(cp.get_cf(cf, b"k2").unwrap().unwrap(), b"v2");
+        assert_eq!(cp.iterator_cf(cf, IteratorMode::Start).count(), 1);
+    }
+
+    let cp_dir: &Path = (&cp_path).as_ref();
+    let current = fs::read_to_string(cp_dir.join("CURRENT")).unwrap();
+    fs::remove_file(cp_dir.join(current.trim_end())).unwrap();
+    let err = DB::open_checkpoint_read_only(&Options::default(), &cp_path).unwrap_err();
+    assert!(err.to_string().contains("incomplete"), "{err}");
+
+    let err =
+        DB::open_checkpoint_read_only(&Options::default(), "_rust_rocksdb_cp_missing").unwrap_err();
+    assert!(err.to_string().contains("no CURRENT file"), "{err}");
+}
+
+#[test]
+fn test_shared_checkpoint_from_thread() {
+    const PATH_PREFIX: &str = "_rust_rocksdb_cp_shared_";
+
+    let db_path = DBPath::new(&format!("{PATH_PREFIX}db"));
+    let cp1_path = DBPath::new(&format!("{PATH_PREFIX}cp1"));
+    let cp2_path = DBPath::new(&format!("{PATH_PREFIX}cp2"));
+    {
+        let db = Arc::new(DB::open_default(&db_path).unwrap());
+        db.put(b"k1", b"v1").unwrap();
+
+        let checkpoint = Arc::new(Checkpoint::new_shared(Arc::clone(&db)).unwrap());
+        // the checkpoint object keeps the DB open
+        drop(db);
+        let handles: Vec<_> = [&cp1_path, &cp2_path]
+            .iter()
+            .map(|path| {
+                let checkpoint = Arc::clone(&checkpoint);
+                let path = path.as_ref().to_owned();
+                thread::spawn(move || checkpoint.create_checkpoint(path))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+    }
+
+    for path in [&cp1_path, &cp2_path] {
+        let cp = DB::open_default(path).unwrap();
+        assert_eq!(cp.get(b"k1").unwrap().unwrap(), b"v1");
+    }
+}
+
+#[test]
+fn test_checkpoint_manager_rotation() {
+    const PATH_PREFIX: &str = "_rust_rocksdb_cp_manager_";
+
+    let db_path = DBPath::new(&format!("{PATH_PREFIX}db"));
+    let checkpoints_path = DBPath::new(&format!("{PATH_PREFIX}checkpoints"));
+    let checkpoints_dir: &Path = (&checkpoints_path).as_ref();
+    let db = Arc::new(DB::open_default(&db_path).unwrap());
+    let manager = CheckpointManager::new(Arc::clone(&db), checkpoints_dir, 2)
+        .unwrap()
+        .verify(true);
+    assert_eq!(manager.checkpoints().unwrap(), vec![]);
+
+    let mut created = Vec::new();
+    for i in 0..3_u8 {
+        db.put(b"k", [i]).unwrap();
+        created.push(manager.run_once().unwrap());
+    }
+    let ids: Vec<_> = created.iter().map(|checkpoint| checkpoint.id).collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+    // the oldest checkpoint was rotated out
+    assert_eq!(manager.checkpoints().unwrap(), created[1..].to_vec());
+    assert!(!created[0].path.exists());
+
+    let latest = DB::open_checkpoint_read_only(&Options::default(), &created[2].path).unwrap();
+    assert_eq!(latest.get(b"k").unwrap().unwrap(), [2]);
+    drop(latest);
+
+    // every checkpoint older than the max age is deleted, except the latest
+    let manager = manager.max_age(Duration::ZERO).verify(false);
+    let latest = manager.run_once().unwrap();
+    assert_eq!(latest.id, 4);
+    assert_eq!(manager.checkpoints().unwrap(), vec![latest]);
+
+    let thread = manager.spawn(Duration::from_secs(3600)).unwrap();
+    let checkpoints = CheckpointManager::new(db, checkpoints_dir, 2).unwrap();
+    while checkpoints.checkpoints().unwrap().last().unwrap().id < 5 {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(thread.take_error().is_none());
+    thread.stop();
+}
+
 #[test]
 fn test_checkpoint_outlive_db() {
     let t = trybuild::TestCases::new();
@@ -16,13 +16,17 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{ColumnFamilyDescriptor, MergeOperands, Options, DB, DEFAULT_COLUMN_FAMILY_NAME};
+use rocksdb::{
+    ColumnFamilyDescriptor, MemtableFactory, MergeOperands, Options, SliceTransform, WriteBatch,
+    DB, DEFAULT_COLUMN_FAMILY_NAME,
+};
 use rocksdb::{TransactionDB, TransactionDBOptions};
 use util::DBPath;
 
 use std::fs;
 use std::io;
 use std::path::Path;
+use std::time::Duration;
 
 #[cfg(feature = "multi-threaded-cf")]
 use rocksdb::MultiThreaded;
@@ -498,3 +502,238 @@ fn test_no_leaked_column_family() {
         drop(db);
     }
 }
+
+#[test]
+fn test_cf_handles() {
+    let n = DBPath::new("_rust_rocksdb_cf_handles");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let cfs = vec![
+            ColumnFamilyDescriptor::new("cf1", Options::default()),
+            ColumnFamilyDescriptor::new("cf2", Options::default()),
+        ];
+        let db = DB::open_cf_descriptors(&opts, &n, cfs).unwrap();
+        let handles = db.cf_handles();
+        assert_eq!(handles.len(), 3);
+        assert!(handles.contains_key(DEFAULT_COLUMN_FAMILY_NAME));
+
+        db.put_cf(&handles["cf1"], b"k1", b"v1").unwrap();
+        db.put_cf(&handles["cf2"], b"k2", b"v2").unwrap();
+        assert_eq!(db.get_cf(&handles["cf1"], b"k1").unwrap().unwrap(), b"v1");
+        assert!(db.get_cf(&handles["cf2"], b"k1").unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_write_batch_extend_from_pairs_cf() {
+    let n = DBPath::new("_rust_rocksdb_write_batch_extend_from_pairs_cf");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let db = DB::open_cf(&opts, &n, ["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let mut batch = WriteBatch::default();
+        batch.extend_from_pairs_cf(&cf1, vec![("k1", "v1"), ("k2", "v2")]);
+        assert_eq!(batch.len(), 2);
+        db.write(batch).unwrap();
+
+        assert_eq!(db.get_cf(&cf1, b"k1").unwrap().unwrap(), b"v1");
+        assert_eq!(db.get_cf(&cf1, b"k2").unwrap().unwrap(), b"v2");
+        assert!(db.get(b"k1").unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_cf_memtable_factories() {
+    let path = DBPath::new("_rust_rocksdb_cf_memtable_factories");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+
+    let mut skip_list_opts = Options::default();
+    skip_list_opts.set_skip_list_memtable(8).unwrap();
+    let mut hash_link_list_opts = Options::default();
+    hash_link_list_opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(2));
+    hash_link_list_opts
+        .set_hash_link_list_memtable(1024, 0, 64, false, 16)
+        .unwrap();
+    let mut hash_skip_list_opts = Options::default();
+    hash_skip_list_opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(2));
+    let descriptors = || {
+        vec![
+            ColumnFamilyDescriptor::new("skip_list", skip_list_opts.clone()),
+            ColumnFamilyDescriptor::new("hash_link_list", hash_link_list_opts.clone()),
+            ColumnFamilyDescriptor::new("hash_skip_list", hash_skip_list_opts.clone())
+                .with_memtable_factory(MemtableFactory::HashSkipList {
+                    bucket_count: 1024,
+                    height: 4,
+                    branching_factor: 4,
+                }),
+        ]
+    };
+
+    // the hash memtables do not support concurrent writes
+    assert!(DB::open_cf_descriptors(&opts, &path, descriptors()).is_err());
+
+    opts.set_allow_concurrent_memtable_write(false);
+    let db = DB::open_cf_descriptors(&opts, &path, descriptors()).unwrap();
+    for name in ["skip_list", "hash_link_list", "hash_skip_list"] {
+        let cf = db.cf_handle(name).unwrap();
+        for i in 0..100_u32 {
+            db.put_cf(&cf, format!("k{}{i}", i % 3), i.to_le_bytes())
+                .unwrap();
+        }
+        assert_eq!(
+            db.get_cf(&cf, b"k143").unwrap().unwrap(),
+            43_u32.to_le_bytes()
+        );
+        let keys = db
+            .prefix_iterator_cf(&cf, b"k1")
+            .map(|item| item.unwrap().0)
+            .take_while(|key| key.starts_with(b"k1"))
+            .count();
+        assert_eq!(keys, 33);
+    }
+}
+
+#[test]
+fn test_create_cfs() {
+    let n = DBPath::new("_rust_rocksdb_create_cfs");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    #[cfg(feature = "multi-threaded-cf")]
+    let db = DB::open(&opts, &n).unwrap();
+    #[cfg(not(feature = "multi-threaded-cf"))]
+    let mut db = DB::open(&opts, &n).unwrap();
+
+    let mut cf_opts = Options::default();
+    cf_opts.set_write_buffer_size(8 << 20);
+    cf_opts.set_num_levels(4);
+    db.create_cfs(&["cf1", "cf2", "cf3"], &cf_opts).unwrap();
+    assert!(db.cf_exists("cf2"));
+    assert!(!db.cf_exists("cf4"));
+    db.put_cf(&db.cf_handle("cf3").unwrap(), b"k1", b"v1")
+        .unwrap();
+
+    // cf2 exists already, cf4 is created before the failure
+    assert!(db.create_cfs(&["cf4", "cf2"], &cf_opts).is_err());
+    assert!(db.cf_exists("cf4"));
+
+    assert_eq!(db.compare_cf_options("cf1", &cf_opts).unwrap(), vec![]);
+    let mismatches = db.compare_cf_options("cf1", &Options::default()).unwrap();
+    let names: Vec<&str> = mismatches.iter().map(|m| m.name).collect();
+    assert_eq!(names, vec!["write_buffer_size", "num_levels"]);
+    assert_eq!(mismatches[0].existing, (8 << 20).to_string());
+    assert!(db.compare_cf_options("missing", &cf_opts).is_err());
+}
+
+#[test]
+fn test_cf_handles_shared_between_threads() {
+    let path = DBPath::new("_rust_rocksdb_cf_handles_shared_between_threads");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db =
+        rocksdb::DBWithThreadMode::<rocksdb::MultiThreaded>::open_cf(&opts, &path, ["cf"]).unwrap();
+    let cf = db.cf_handle("cf").unwrap();
+    let id = cf.id();
+
+    std::thread::scope(|scope| {
+        for i in 0..4u8 {
+            let cf = cf.clone();
+            let db = &db;
+            scope.spawn(move || {
+                db.put_cf(&cf, [i], b"arc").unwrap();
+                db.put_cf(&id, [i, i], b"id").unwrap();
+            });
+        }
+    });
+
+    assert_eq!(db.get_cf(&cf, [3u8]).unwrap().unwrap(), b"arc");
+    assert_eq!(db.get_cf(&id, [3u8, 3]).unwrap().unwrap(), b"id");
+    assert_eq!(db.iterator_cf(&id, rocksdb::IteratorMode::Start).count(), 8);
+}
+
+#[test]
+fn test_cf_descriptor_builder() {
+    let path = DBPath::new("_rust_rocksdb_cf_descriptor_builder");
+    let mut opts = Options::default();
+    opts.set_write_buffer_size(4 << 20);
+    let descriptor = ColumnFamilyDescriptor::builder("sessions")
+        .options(opts)
+        .ttl(Duration::from_secs(3600))
+        .merge_operator_associative("test operator", test_provided_merge)
+        .build();
+    let copy = descriptor.clone();
+    assert_eq!(copy.name(), "sessions");
+    assert_eq!(copy.ttl(), Some(Duration::from_secs(3600)));
+    assert!(!copy.is_lazy());
+    assert!(format!("{:?}", copy.options()).contains("write_buffer_size"));
+
+    let mut db_opts = Options::default();
+    db_opts.create_if_missing(true);
+    db_opts.create_missing_column_families(true);
+
+    // the time to live needs a DB opened with one
+    assert!(DB::open_cf_descriptors(&db_opts, &path, [copy.clone()]).is_err());
+
+    let lazy = ColumnFamilyDescriptor::builder("archive")
+        .lazy(true)
+        .build();
+    {
+        let db = DB::open_cf_descriptors_with_ttl(
+            &db_opts,
+            &path,
+            [descriptor, lazy.clone()],
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert!(db.cf_handle("archive").is_none());
+        let cf = db.cf_handle("sessions").unwrap();
+        db.merge_cf(&cf, b"k", b"a").unwrap();
+        db.merge_cf(&cf, b"k", b"b").unwrap();
+        assert_eq!(db.get_cf(&cf, b"k").unwrap().unwrap(), b"ab");
+    }
+
+    {
+        #[cfg(feature = "multi-threaded-cf")]
+        let db = DB::open_cf_descriptors_with_ttl(&db_opts, &path, [copy.clone()], Duration::ZERO)
+            .unwrap();
+        #[cfg(not(feature = "multi-threaded-cf"))]
+        let mut db =
+            DB::open_cf_descriptors_with_ttl(&db_opts, &path, [copy.clone()], Duration::ZERO)
+                .unwrap();
+        db.create_cf(lazy.name(), lazy.options()).unwrap();
+    }
+
+    {
+        let db = DB::open_cf_descriptors_with_ttl(
+            &db_opts,
+            &path,
+            [copy.clone(), lazy.clone()],
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert!(db.cf_handle("archive").is_some());
+    }
+
+    // transaction DBs support neither
+    let txn_db_opts = TransactionDBOptions::default();
+    for descriptor in [copy, lazy] {
+        let err = TransactionDB::<rocksdb::SingleThreaded>::open_cf_descriptors(
+            &db_opts,
+            &txn_db_opts,
+            &path,
+            [descriptor],
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("TransactionDB does not support"));
+    }
+}
@@ -0,0 +1,64 @@
+mod util;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::{CompactionPolicy, CompactionScheduler, DB};
+use util::DBPath;
+
+fn key(prefix: &str, i: u32) -> Vec<u8> {
+    format!("{prefix}:{i:03}").into_bytes()
+}
+
+#[test]
+fn compaction_scheduler_test() {
+    let path = DBPath::new("_rust_rocksdb_compaction_scheduler_test");
+    let db = Arc::new(DB::open_default(&path).unwrap());
+    for i in 0..100 {
+        db.put(key("a", i), b"value").unwrap();
+        db.put(key("b", i), b"value").unwrap();
+    }
+    db.flush().unwrap();
+    for i in 0..100 {
+        db.delete(key("b", i)).unwrap();
+    }
+    db.flush().unwrap();
+
+    let idle = CompactionScheduler::new(Arc::clone(&db))
+        .add_policy(CompactionPolicy::new().min_age(Duration::from_secs(3600)))
+        .add_policy(CompactionPolicy::new().column_family("missing"));
+    assert!(idle.run_once().unwrap().is_empty());
+
+    let scheduler = CompactionScheduler::new(Arc::clone(&db))
+        .add_policy(CompactionPolicy::new().min_tombstone_ratio(0.5));
+    let compactions = scheduler.run_once().unwrap();
+    assert_eq!(compactions.len(), 1);
+    assert_eq!(compactions[0].column_family, "default");
+    assert_eq!(compactions[0].start, Some(key("b", 0)));
+    assert_eq!(compactions[0].end, Some(key("b", 99)));
+    assert_eq!(compactions[0].files.len(), 1);
+
+    // the deletions were compacted away
+    assert!(db
+        .live_files()
+        .unwrap()
+        .iter()
+        .all(|f| f.num_deletions == 0));
+    assert!(scheduler.run_once().unwrap().is_empty());
+
+    let prefix = CompactionScheduler::new(Arc::clone(&db))
+        .add_policy(CompactionPolicy::new().key_prefix(b"a:".to_vec()))
+        .add_policy(CompactionPolicy::new().filter(|file| file.num_entries > 0));
+    let compactions = prefix.run_once().unwrap();
+    assert_eq!(compactions.len(), 1);
+    assert_eq!(compactions[0].start, Some(key("a", 0)));
+    assert_eq!(compactions[0].end, Some(key("a", 99)));
+
+    let thread = scheduler.spawn(Duration::from_millis(10)).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(thread.take_error().is_none());
+    thread.stop();
+    assert_eq!(db.get(key("a", 1)).unwrap().unwrap(), b"value");
+}
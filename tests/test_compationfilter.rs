@@ -16,7 +16,13 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{CompactionDecision, Options, DB};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rocksdb::{
+    compaction_filter_factory::{RemoveKeysBefore, ValueTimestampTtl},
+    CompactionDecision, IteratorMode, Options, DB,
+};
 use util::DBPath;
 
 #[cfg(test)]
@@ -47,3 +53,90 @@ fn compaction_filter_test() {
         assert_eq!(&*db.get(b"%k").unwrap().unwrap(), b"secret");
     }
 }
+
+#[test]
+fn compaction_filter_factory_fn_test() {
+    let path = DBPath::new("_rust_rocksdb_filter_factory_fn_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    // panicking in the callback would abort, the contexts are checked after
+    // the compaction
+    let manual_compactions = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&manual_compactions);
+    opts.set_compaction_filter_factory_fn("test", move |context| {
+        recorded.lock().unwrap().push(context.is_manual_compaction);
+        |_level: u32, key: &[u8], value: &[u8]| match key.first() {
+            Some(&b'_') => CompactionDecision::Remove,
+            Some(&b'^') => CompactionDecision::ChangeValue(value.to_ascii_uppercase()),
+            _ => CompactionDecision::Keep,
+        }
+    });
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"k1", b"a").unwrap();
+        db.put(b"_k", b"b").unwrap();
+        db.put(b"^k", b"lower").unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"a");
+        assert!(db.get(b"_k").unwrap().is_none());
+        assert_eq!(&*db.get(b"^k").unwrap().unwrap(), b"LOWER");
+    }
+    let manual_compactions = manual_compactions.lock().unwrap();
+    assert!(!manual_compactions.is_empty());
+    assert!(manual_compactions.iter().all(|manual| *manual));
+}
+
+#[test]
+fn value_timestamp_ttl_test() {
+    let path = DBPath::new("_rust_rocksdb_value_timestamp_ttl_test");
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let value = |timestamp: u64| [&timestamp.to_be_bytes()[..], b"value"].concat();
+
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_compaction_filter_factory(ValueTimestampTtl::new(Duration::from_secs(3600)));
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"expired", value(now - 7200)).unwrap();
+        db.put(b"fresh", value(now)).unwrap();
+        db.put(b"short", b"v").unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        assert!(db.get(b"expired").unwrap().is_none());
+        assert_eq!(db.get(b"fresh").unwrap().unwrap(), value(now));
+        assert_eq!(db.get(b"short").unwrap().unwrap(), b"v");
+    }
+
+    let mut opts = Options::default();
+    opts.set_compaction_filter_factory(
+        ValueTimestampTtl::new(Duration::from_secs(3600)).with_min_level(100),
+    );
+    let db = DB::open(&opts, &path).unwrap();
+    db.put(b"expired", value(now - 7200)).unwrap();
+    db.compact_range(None::<&[u8]>, None::<&[u8]>);
+    assert!(db.get(b"expired").unwrap().is_some());
+}
+
+#[test]
+fn remove_keys_before_test() {
+    let path = DBPath::new("_rust_rocksdb_remove_keys_before_test");
+    let truncation = RemoveKeysBefore::new(0_u64.to_be_bytes());
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_compaction_filter_factory(truncation.clone());
+    let db = DB::open(&opts, &path).unwrap();
+    for i in 0..10_u64 {
+        db.put(i.to_be_bytes(), b"entry").unwrap();
+    }
+    db.compact_range(None::<&[u8]>, None::<&[u8]>);
+    assert_eq!(db.iterator(IteratorMode::Start).count(), 10);
+
+    truncation.set_boundary(4_u64.to_be_bytes());
+    assert_eq!(truncation.boundary(), 4_u64.to_be_bytes());
+    db.compact_range(None::<&[u8]>, None::<&[u8]>);
+    let first = db.iterator(IteratorMode::Start).next().unwrap().unwrap().0;
+    assert_eq!(&*first, 4_u64.to_be_bytes());
+    assert_eq!(db.iterator(IteratorMode::Start).count(), 6);
+}
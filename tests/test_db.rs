@@ -20,12 +20,16 @@ use std::{mem, sync::Arc, thread, time::Duration};
 use pretty_assertions::assert_eq;
 
 use rocksdb::{
-    perf::get_memory_usage_stats, BlockBasedOptions, BottommostLevelCompaction, Cache,
-    ColumnFamilyDescriptor, CompactOptions, CuckooTableOptions, DBAccess, DBCompactionStyle,
-    DBWithThreadMode, Env, Error, ErrorKind, FifoCompactOptions, IteratorMode, MultiThreaded,
-    Options, PerfContext, PerfMetric, ReadOptions, SingleThreaded, SliceTransform, Snapshot,
-    UniversalCompactOptions, UniversalCompactionStopStyle, WaitForCompactOptions, WriteBatch, DB,
-    DEFAULT_COLUMN_FAMILY_NAME,
+    manifest,
+    perf::{get_memory_usage_stats, set_perf_stats, FilterEffectiveness, FilterStatsRecorder},
+    properties, BlockBasedOptions, BottommostLevelCompaction, Cache, ColumnFamilyDescriptor,
+    CompactOptions, CuckooTableOptions, DBAccess, DBCompactionStyle, DBRole, DBWithThreadMode,
+    DeletionEstimate, DiagnosticKind, Env, Error, ErrorKind, FifoCompactOptions, IteratorMode,
+    KeyChange, MergeOperands, MultiThreaded, Options, PerfContext, PerfMetric, PerfStatsLevel,
+    ReadOnlyDB, ReadOptions, SecondaryDB, SharedSnapshot, SingleThreaded, SliceTransform, Snapshot,
+    UniversalCompactOptions, UniversalCompactionStopStyle, WaitForCompactOptions, WalFile,
+    WalFileType, WalPosition, WriteBatch, WriteOptions, DB, DEFAULT_COLUMN_FAMILY_NAME,
+    STATS_HISTORY_COLUMN_FAMILY_NAME,
 };
 use util::{assert_iter, pair, DBPath};
 
@@ -82,6 +86,28 @@ fn errors_do_stuff() {
     }
 }
 
+#[test]
+fn protected_memtable_works() {
+    let path = DBPath::new("_rust_rocksdb_protected_memtable_works");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_memtable_protection_bytes_per_key(8).unwrap();
+    assert!(opts.set_memtable_protection_bytes_per_key(3).is_err());
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        let mut batch = WriteBatch::default();
+        batch.put(b"k1", b"v1");
+        batch.put(b"k2", b"v2");
+        batch.delete(b"k1");
+        db.write(batch).unwrap();
+
+        assert!(db.get(b"k1").unwrap().is_none());
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+        db.flush().unwrap();
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+    }
+}
+
 #[test]
 fn writebatch_works() {
     let path = DBPath::new("_rust_rocksdb_writebacktest");
@@ -261,6 +287,183 @@ fn snapshot_test() {
     }
 }
 
+#[test]
+fn snapshot_cf_reads_test() {
+    let path = DBPath::new("_rust_rocksdb_snapshot_cf_reads_test");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, ["cf"]).unwrap();
+        let cf = db.cf_handle("cf").unwrap();
+        db.put_cf(&cf, b"k1", b"v1").unwrap();
+        db.put_cf(&cf, b"k2", b"v2").unwrap();
+
+        let snap = db.snapshot();
+        db.put_cf(&cf, b"k1", b"changed").unwrap();
+        db.delete_cf(&cf, b"k2").unwrap();
+        db.put_cf(&cf, b"k3", b"v3").unwrap();
+
+        let values: Vec<_> = snap
+            .multi_get_cf([(&cf, b"k1"), (&cf, b"k2"), (&cf, b"k3")])
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            values,
+            vec![Some(b"v1".to_vec()), Some(b"v2".to_vec()), None]
+        );
+
+        assert!(snap.key_may_exist_cf(&cf, b"k1"));
+        assert!(!snap.key_may_exist(b"k1"));
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_upper_bound(b"k2".to_vec());
+        let iter = snap.iterator_cf_opt(&cf, readopts, IteratorMode::Start);
+        assert_iter(iter, &[pair(b"k1", b"v1")]);
+    }
+}
+
+#[test]
+fn snapshot_count_test() {
+    let path = DBPath::new("_rust_rocksdb_snapshot_count_test");
+    {
+        let db = DB::open_default(&path).unwrap();
+        assert_eq!(db.get_snapshot_count().unwrap(), 0);
+        assert_eq!(db.get_oldest_snapshot_sequence_number().unwrap(), None);
+        assert_eq!(db.get_oldest_snapshot_age().unwrap(), None);
+
+        db.put(b"k1", b"v1").unwrap();
+        let _snap1 = db.snapshot();
+        db.put(b"k2", b"v2").unwrap();
+        let _snap2 = db.snapshot();
+
+        assert_eq!(db.get_snapshot_count().unwrap(), 2);
+        assert_eq!(db.get_oldest_snapshot_sequence_number().unwrap(), Some(1));
+        assert!(db.get_oldest_snapshot_age().unwrap().unwrap() < Duration::from_secs(60));
+
+        drop(snap1);
+        assert_eq!(db.get_snapshot_count().unwrap(), 1);
+        assert_eq!(db.get_oldest_snapshot_sequence_number().unwrap(), Some(2));
+        drop(snap2);
+        assert_eq!(db.get_snapshot_count().unwrap(), 0);
+    }
+}
+
+#[test]
+fn snapshot_diff_test() {
+    let path = DBPath::new("_rust_rocksdb_snapshot_diff_test");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, ["cf"]).unwrap();
+        let cf = db.cf_handle("cf").unwrap();
+        for key in [b"k1", b"k2", b"k3", b"k4"] {
+            db.put_cf(&cf, key, b"v").unwrap();
+        }
+        let old = db.snapshot();
+        db.put_cf(&cf, b"k1", b"changed").unwrap();
+        db.delete_cf(&cf, b"k2").unwrap();
+        db.put_cf(&cf, b"k3", b"v").unwrap();
+        db.put_cf(&cf, b"k5", b"new").unwrap();
+        db.put(b"k6", b"default").unwrap();
+        let new = db.snapshot();
+
+        let changes: Vec<_> = db
+            .diff_ranges_cf(&cf, &old, &new, ..)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let expected = vec![
+            KeyChange {
+                key: Box::from(&b"k1"[..]),
+                old_value: Some(Box::from(&b"v"[..])),
+                new_value: Some(Box::from(&b"changed"[..])),
+            },
+            KeyChange {
+                key: Box::from(&b"k2"[..]),
+                old_value: Some(Box::from(&b"v"[..])),
+                new_value: None,
+            },
+            KeyChange {
+                key: Box::from(&b"k5"[..]),
+                old_value: None,
+                new_value: Some(Box::from(&b"new"[..])),
+            },
+        ];
+        assert_eq!(changes, expected);
+        assert!(changes[1].is_removed());
+        assert!(changes[2].is_added());
+
+        let keys: Vec<_> = db
+            .diff_ranges_cf(&cf, &old, &new, b"k2".to_vec()..b"k5".to_vec())
+            .unwrap()
+            .map(|change| change.unwrap().key)
+            .collect();
+        assert_eq!(keys, vec![Box::from(&b"k2"[..])]);
+
+        // the snapshots are swapped
+        let changes: Vec<_> = db
+            .diff_ranges_cf(&cf, &new, &old, ..)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert!(changes[1].is_added());
+        assert!(changes[2].is_removed());
+
+        let keys: Vec<_> = db
+            .diff_ranges(&old, &new, ..)
+            .unwrap()
+            .map(|change| change.unwrap().key)
+            .collect();
+        assert_eq!(keys, vec![Box::from(&b"k6"[..])]);
+        assert_eq!(db.diff_ranges(&new, &new, ..).unwrap().count(), 0);
+    }
+
+    // the order of the keys of a custom comparator is unknown
+    let path = DBPath::new("_rust_rocksdb_snapshot_diff_comparator_test");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_comparator("reverse", Box::new(|a: &[u8], b: &[u8]| b.cmp(a)));
+        let db = DB::open(&opts, &path).unwrap();
+        let snapshot = db.snapshot();
+        assert!(db.diff_ranges(&snapshot, &snapshot, ..).is_err());
+    }
+}
+
+#[test]
+fn shared_snapshot_test() {
+    let path = DBPath::new("_rust_rocksdb_shared_snapshot_test");
+    {
+        let db = Arc::new(DB::open_default(&path).unwrap());
+        db.put(b"k1", b"v1").unwrap();
+        let snapshot = Arc::new(SharedSnapshot::new(Arc::clone(&db)));
+        db.put(b"k1", b"changed").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let snapshot = Arc::clone(&snapshot);
+                thread::spawn(move || {
+                    let snapshot = snapshot.snapshot();
+                    assert_eq!(snapshot.get(b"k1").unwrap().unwrap(), b"v1");
+                    snapshot.iterator(IteratorMode::Start).count()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 1);
+        }
+
+        // the snapshot keeps the DB open
+        drop(db);
+        let db = Arc::clone(snapshot.db());
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"changed");
+    }
+}
+
 #[derive(Clone)]
 struct SnapshotWrapper {
     snapshot: Arc<Snapshot<'static>>,
@@ -376,6 +579,32 @@ fn set_option_cf_test() {
     }
 }
 
+#[test]
+fn background_job_options_test() {
+    let path = DBPath::new("_rust_rocksdb_background_job_options_test");
+    {
+        let mut env = Env::new().unwrap();
+        env.set_low_priority_background_threads(4);
+        env.set_high_priority_background_threads(2);
+        assert_eq!(env.get_low_priority_background_threads(), 4);
+        assert_eq!(env.get_high_priority_background_threads(), 2);
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_env(&env);
+        opts.set_max_background_jobs(2);
+        opts.set_max_subcompactions(1);
+        let db = DB::open(&opts, &path).unwrap();
+        assert_eq!(db.max_background_jobs().unwrap(), 2);
+        assert_eq!(db.max_subcompactions().unwrap(), 1);
+
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+    }
+}
+
 #[test]
 fn set_column_family_metadata_test() {
     let path = DBPath::new("_set_column_family_metadata_test");
@@ -573,6 +802,61 @@ fn test_get_updates_since_nothing() {
     assert!(iter.next().is_none());
 }
 
+#[test]
+fn test_get_updates_since_cf() {
+    let path = DBPath::new("_rust_rocksdb_test_get_updates_since_cf");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &path, ["cf1", "cf2"]).unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+    let cf2 = db.cf_handle("cf2").unwrap();
+    let (id1, id2) = (db.column_family_id(&cf1), db.column_family_id(&cf2));
+    assert_ne!(id1, id2);
+
+    db.put(b"key0", b"value0").unwrap();
+    db.put_cf(&cf1, b"key1", b"value1").unwrap();
+    db.put_cf(&cf2, b"key2", b"value2").unwrap();
+    let mut batch = WriteBatch::default();
+    batch.put_cf(&cf2, b"key3", b"value3");
+    batch.delete_cf(&cf1, b"key1");
+    batch.delete_range_cf(&cf2, b"a", b"b");
+    db.write(batch).unwrap();
+
+    let batches: Vec<_> = db
+        .get_updates_since(0)
+        .unwrap()
+        .map(|item| item.unwrap().1.column_family_ids().unwrap())
+        .collect();
+    let mut both = vec![id1, id2];
+    both.sort_unstable();
+    assert_eq!(batches, vec![vec![0], vec![id1], vec![id2], both.clone()]);
+
+    let batches: Vec<_> = db
+        .get_updates_since_cf(0, ["cf1"])
+        .unwrap()
+        .map(|item| {
+            let (seq, batch) = item.unwrap();
+            (seq, batch.column_family_ids().unwrap())
+        })
+        .collect();
+    assert_eq!(batches, vec![(2, vec![id1]), (4, both)]);
+
+    let seqs: Vec<_> = db
+        .get_updates_since_cf(2, [DEFAULT_COLUMN_FAMILY_NAME, "cf2"])
+        .unwrap()
+        .map(|item| item.unwrap().0)
+        .collect();
+    assert_eq!(seqs, vec![3, 4]);
+
+    assert!(db
+        .get_updates_since_cf(0, Vec::<String>::new())
+        .unwrap()
+        .next()
+        .is_none());
+    assert!(db.get_updates_since_cf(0, ["missing"]).is_err());
+}
+
 #[test]
 fn test_get_updates_since_out_of_range() {
     let path = DBPath::new("_rust_rocksdb_test_get_updates_since_out_of_range");
@@ -606,6 +890,49 @@ fn test_open_as_secondary() {
     assert_eq!(get_byte_slice(&result), b"value2");
 }
 
+#[test]
+fn test_secondary_catch_up() {
+    let primary_path = DBPath::new("_rust_rocksdb_test_secondary_catch_up_primary");
+    let db = DB::open_default(&primary_path).unwrap();
+    assert_eq!(db.role(), DBRole::Primary);
+    db.put(b"key1", b"value1").unwrap();
+
+    let mut opts = Options::default();
+    opts.set_max_open_files(-1);
+    let secondary_path = DBPath::new("_rust_rocksdb_test_secondary_catch_up_secondary");
+    let secondary = DB::open_as_secondary(&opts, &primary_path, &secondary_path).unwrap();
+    assert_eq!(secondary.role(), DBRole::Secondary);
+
+    // read your writes
+    db.put(b"key2", b"value2").unwrap();
+    let seq = db.latest_sequence_number();
+    assert!(secondary
+        .catch_up_to_sequence_number(seq, Duration::from_secs(5))
+        .unwrap());
+    assert_eq!(secondary.get(b"key2").unwrap().unwrap(), b"value2");
+
+    // background catch up
+    let secondary = Arc::new(secondary);
+    let catch_up =
+        DB::spawn_catch_up_with_primary(Arc::clone(&secondary), Duration::from_millis(10)).unwrap();
+    db.put(b"key3", b"value3").unwrap();
+    let mut found = false;
+    for _ in 0..500 {
+        if secondary.get(b"key3").unwrap().is_some() {
+            found = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(found);
+    assert!(catch_up.take_error().is_none());
+    catch_up.stop();
+
+    let read_only = DB::open_for_read_only(&opts, &primary_path, false).unwrap();
+    assert_eq!(read_only.role(), DBRole::ReadOnly);
+    assert!(DB::spawn_catch_up_with_primary(Arc::new(read_only), Duration::from_secs(1)).is_err());
+}
+
 #[test]
 fn test_open_cf_descriptors_as_secondary() {
     let primary_path = DBPath::new("_rust_rocksdb_test_open_cf_descriptors_as_secondary_primary");
@@ -775,6 +1102,121 @@ fn compact_range_test() {
     }
 }
 
+#[test]
+fn verify_range_checksum_test() {
+    let path = DBPath::new("_rust_rocksdb_verify_range_checksum_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_compression_type(rocksdb::DBCompressionType::None);
+    let corrupt_file = {
+        let db = DB::open(&opts, &path).unwrap();
+        for prefix in [b'a', b'b'] {
+            for i in 0..1000_u32 {
+                db.put([&[prefix][..], &i.to_be_bytes()].concat(), [prefix; 100])
+                    .unwrap();
+            }
+            db.flush().unwrap();
+        }
+        let report = db.verify_range_checksum(..).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.keys_verified, 2000);
+        let cf = db.cf_handle(DEFAULT_COLUMN_FAMILY_NAME).unwrap();
+        let report = db.verify_range_checksum_cf(&cf, b"b".to_vec()..).unwrap();
+        assert_eq!(report.keys_verified, 1000);
+
+        let files = db.live_files().unwrap();
+        let file = files
+            .into_iter()
+            .find(|file| file.start_key.as_deref().unwrap()[0] == b'a')
+            .unwrap();
+        file.name
+    };
+
+    // overwrite some bytes of the first data block of the file of `a` keys
+    {
+        use std::io::{Seek, SeekFrom, Write};
+        let db_dir: &std::path::Path = (&path).as_ref();
+        let file_path = db_dir.join(corrupt_file.trim_start_matches('/'));
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(file_path)
+            .unwrap();
+        file.seek(SeekFrom::Start(100)).unwrap();
+        file.write_all(&[0xff; 16]).unwrap();
+    }
+
+    let db = DB::open(&opts, &path).unwrap();
+    let report = db.verify_range_checksum(..).unwrap();
+    assert_eq!(report.corrupt_blocks.len(), 1);
+    let block = &report.corrupt_blocks[0];
+    assert!(block.file.as_ref().unwrap().ends_with(&corrupt_file));
+    assert_eq!(block.offset, Some(0));
+    assert_eq!(block.error.kind(), ErrorKind::Corruption);
+    // the scan resumed after the corrupt file
+    assert_eq!(report.keys_verified, 1000);
+}
+
+#[test]
+fn compaction_result_test() {
+    let path = DBPath::new("_rust_rocksdb_compaction_result_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &path, ["cf1"]).unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+    for round in 0..2_u8 {
+        for i in 0..100_u8 {
+            db.put_cf(&cf1, [i], [round; 100]).unwrap();
+        }
+        db.flush_cf(&cf1).unwrap();
+    }
+    db.put(b"k1", b"v1").unwrap();
+    db.flush().unwrap();
+
+    let result = db
+        .compact_range_cf_opt_with_result(
+            &cf1,
+            None::<&[u8]>,
+            None::<&[u8]>,
+            &CompactOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(result.input_files.len(), 2);
+    assert_eq!(result.output_files.len(), 1);
+    assert!(result
+        .input_files
+        .iter()
+        .chain(&result.output_files)
+        .all(|file| file.column_family_name == "cf1"));
+    assert_eq!(
+        result.bytes_read,
+        result
+            .input_files
+            .iter()
+            .map(|f| f.size as u64)
+            .sum::<u64>()
+    );
+    assert!(result.bytes_written > 0);
+    assert!(result.bytes_written < result.bytes_read);
+    assert_eq!(result.output_files[0].num_entries, 100);
+
+    let result = db
+        .compact_range_cf_opt_with_result(
+            &cf1,
+            None::<&[u8]>,
+            None::<&[u8]>,
+            &CompactOptions::default(),
+        )
+        .unwrap();
+    assert!(result.input_files.is_empty() && result.output_files.is_empty());
+
+    let mut compact_opts = CompactOptions::default();
+    compact_opts.set_exclusive_manual_compaction(false);
+    assert!(db
+        .compact_range_cf_opt_with_result(&cf1, None::<&[u8]>, None::<&[u8]>, &compact_opts)
+        .is_err());
+}
+
 #[test]
 fn fifo_compaction_test() {
     let path = DBPath::new("_rust_rocksdb_fifo_compaction_test");
@@ -1282,6 +1724,51 @@ fn delete_range_test() {
     }
 }
 
+#[test]
+fn purge_range_test() {
+    let path = DBPath::new("_rust_rocksdb_purge_range_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &path, ["cf1"]).unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+
+    let key = |i: u32| format!("{i:0>4}");
+    // three files below L0 holding 0000-0099, 0100-0199 and 0200-0299
+    for file in 0..3 {
+        for i in file * 100..(file + 1) * 100 {
+            db.put_cf(&cf1, key(i), b"value").unwrap();
+        }
+        db.flush_cf(&cf1).unwrap();
+        db.compact_range_cf(&cf1, Some(key(file * 100)), Some(key(file * 100 + 99)));
+    }
+    let files = || -> Vec<_> {
+        db.live_files()
+            .unwrap()
+            .into_iter()
+            .filter(|f| f.column_family_name == "cf1")
+            .collect()
+    };
+    assert_eq!(files().len(), 3);
+    assert!(files().iter().all(|f| f.level > 0));
+
+    // the file holding the range is dropped, the one holding its end is kept
+    db.purge_range_cf(&cf1, key(100), key(200), false).unwrap();
+    assert_eq!(files().len(), 2);
+    assert!(db.get_cf(&cf1, key(99)).unwrap().is_some());
+    assert!(db.get_cf(&cf1, key(150)).unwrap().is_none());
+    assert!(db.get_cf(&cf1, key(200)).unwrap().is_some());
+
+    // the range only covers part of a file, which is rewritten
+    db.purge_range_cf(&cf1, key(0), key(50), true).unwrap();
+    assert!(files()
+        .iter()
+        .all(|f| f.start_key.as_deref().unwrap() >= key(50).as_bytes()));
+    assert!(db.get_cf(&cf1, key(49)).unwrap().is_none());
+    assert!(db.get_cf(&cf1, key(50)).unwrap().is_some());
+    assert_eq!(db.iterator_cf(&cf1, IteratorMode::Start).count(), 150);
+}
+
 #[test]
 fn multi_get() {
     let path = DBPath::new("_rust_rocksdb_multi_get");
@@ -1401,6 +1888,67 @@ fn batched_multi_get_cf() {
     }
 }
 
+#[test]
+fn multi_get_pinned_cf() {
+    let path = DBPath::new("_rust_rocksdb_multi_get_pinned_cf");
+
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, ["cf0"]).unwrap();
+
+        let cf = db.cf_handle("cf0").unwrap();
+        db.put_cf(&cf, b"k1", b"v1").unwrap();
+        db.put_cf(&cf, b"k2", b"v2").unwrap();
+        db.flush_cf(&cf).unwrap();
+
+        let keys = vec![b"k2".to_vec(), b"k0".to_vec(), b"k1".to_vec()];
+        let values = db
+            .multi_get_pinned_cf(&cf, keys)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0].as_deref(), Some(&b"v2"[..]));
+        assert!(values[1].is_none());
+        assert_eq!(values[2].as_deref(), Some(&b"v1"[..]));
+    }
+}
+
+#[test]
+fn batched_multi_get_cfs() {
+    let path = DBPath::new("_rust_rocksdb_batched_multi_get_cfs");
+
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, ["cf0", "cf1"]).unwrap();
+
+        let cf0 = db.cf_handle("cf0").unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put_cf(&cf0, b"k1", b"v1").unwrap();
+        db.put_cf(&cf1, b"k1", b"w1").unwrap();
+        db.put_cf(&cf1, b"k2", b"w2").unwrap();
+
+        let values = db
+            .batched_multi_get_cfs(vec![(&cf1, "k2"), (&cf0, "k1"), (&cf0, "k2"), (&cf1, "k1")])
+            .into_iter()
+            .map(|value| value.unwrap().map(|v| v.to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Some(b"w2".to_vec()),
+                Some(b"v1".to_vec()),
+                None,
+                Some(b"w1".to_vec()),
+            ]
+        );
+    }
+}
+
 #[test]
 fn key_may_exist() {
     let path = DBPath::new("_rust_key_may_exist");
@@ -1428,6 +1976,31 @@ fn key_may_exist_cf() {
     }
 }
 
+#[test]
+fn keys_may_exist_cf() {
+    let path = DBPath::new("_rust_keys_may_exist_cf");
+
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf(&opts, &path, ["cf"]).unwrap();
+        let cf = db.cf_handle("cf").unwrap();
+
+        db.put_cf(&cf, "k1", "v1").unwrap();
+        db.put(b"k3", b"v3").unwrap();
+
+        assert_eq!(
+            db.keys_may_exist_cf(&cf, ["k1", "k2", "k3"]),
+            vec![true, false, false]
+        );
+        assert_eq!(
+            db.keys_may_exist(["k1", "k2", "k3"]),
+            vec![false, false, true]
+        );
+    }
+}
+
 #[test]
 fn key_may_exist_cf_value() {
     let path = DBPath::new("_rust_key_may_exist_cf_value");
@@ -1540,3 +2113,982 @@ fn test_atomic_flush_cfs() {
         );
     }
 }
+
+#[test]
+fn test_bulk_load() {
+    let path = DBPath::new("_rust_rocksdb_test_bulk_load");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_level_zero_file_num_compaction_trigger(2);
+        let db = DB::open(&opts, &path).unwrap();
+        db.set_options(&[("level0_file_num_compaction_trigger", "3")])
+            .unwrap();
+
+        let settings = db.prepare_for_bulk_load().unwrap();
+        for i in 0..5_u32 {
+            db.put(i.to_be_bytes(), b"value").unwrap();
+            db.flush().unwrap();
+        }
+        // auto compactions are disabled while loading
+        assert_eq!(
+            db.property_int_value(properties::num_files_at_level(0))
+                .unwrap(),
+            Some(5)
+        );
+
+        db.finish_bulk_load(settings).unwrap();
+        assert_eq!(
+            db.property_int_value(properties::num_files_at_level(0))
+                .unwrap(),
+            Some(0)
+        );
+        for i in 0..5_u32 {
+            assert_eq!(db.get(i.to_be_bytes()).unwrap().unwrap(), b"value");
+        }
+
+        // the options are restored to their values before the load, not
+        // to the ones the DB was opened with
+        opts.set_level_zero_file_num_compaction_trigger(3);
+        assert!(db
+            .compare_cf_options(DEFAULT_COLUMN_FAMILY_NAME, &opts)
+            .unwrap()
+            .is_empty());
+    }
+}
+
+#[test]
+fn test_best_efforts_recovery() {
+    let path = DBPath::new("_rust_rocksdb_test_best_efforts_recovery");
+    {
+        let db = DB::open_default(&path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        db.flush().unwrap();
+    }
+
+    // lose the most recent SST file
+    let mut ssts: Vec<_> = std::fs::read_dir(&path)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "sst"))
+        .collect();
+    ssts.sort();
+    std::fs::remove_file(ssts.last().unwrap()).unwrap();
+
+    assert!(DB::open_default(&path).is_err());
+
+    let mut opts = Options::default();
+    opts.set_best_efforts_recovery(true).unwrap();
+    let db = DB::open(&opts, &path).unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+#[test]
+fn test_suggest_split_keys() {
+    let path = DBPath::new("_rust_rocksdb_test_suggest_split_keys");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_disable_auto_compactions(true);
+        let db = DB::open(&opts, &path).unwrap();
+        let cf = db.cf_handle(DEFAULT_COLUMN_FAMILY_NAME).unwrap();
+
+        assert!(db.suggest_split_keys(&cf, 1).unwrap().is_empty());
+
+        // four files holding disjoint key ranges
+        for file in 0..4_u32 {
+            for i in 0..100_u32 {
+                db.put((file * 100 + i).to_be_bytes(), [0; 100]).unwrap();
+            }
+            db.flush().unwrap();
+        }
+
+        let smallest_file = db.live_files().unwrap().iter().map(|f| f.size).min();
+        let split_keys = db
+            .suggest_split_keys(&cf, smallest_file.unwrap() as u64)
+            .unwrap();
+        assert_eq!(
+            split_keys,
+            vec![
+                99_u32.to_be_bytes().to_vec(),
+                199_u32.to_be_bytes().to_vec(),
+                299_u32.to_be_bytes().to_vec(),
+            ]
+        );
+
+        // everything fits into a single shard
+        assert!(db.suggest_split_keys(&cf, u64::MAX).unwrap().is_empty());
+    }
+
+    // the keys are ordered by the comparator
+    let path = DBPath::new("_rust_rocksdb_test_suggest_split_keys_reverse");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_disable_auto_compactions(true);
+        opts.set_comparator(
+            "rocksdb.ReverseBytewiseComparator",
+            Box::new(|a: &[u8], b: &[u8]| b.cmp(a)),
+        );
+        let db = DB::open(&opts, &path).unwrap();
+        let cf = db.cf_handle(DEFAULT_COLUMN_FAMILY_NAME).unwrap();
+        for file in 0..4_u32 {
+            for i in 0..100_u32 {
+                db.put((file * 100 + i).to_be_bytes(), [0; 100]).unwrap();
+            }
+            db.flush().unwrap();
+        }
+
+        let smallest_file = db.live_files().unwrap().iter().map(|f| f.size).min();
+        let split_keys = db
+            .suggest_split_keys(&cf, smallest_file.unwrap() as u64)
+            .unwrap();
+        assert_eq!(
+            split_keys,
+            vec![
+                300_u32.to_be_bytes().to_vec(),
+                200_u32.to_be_bytes().to_vec(),
+                100_u32.to_be_bytes().to_vec(),
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_get_live_files_with_file_deletions_disabled() {
+    let path = DBPath::new("_rust_rocksdb_test_get_live_files");
+    {
+        let db = DB::open_default(&path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+
+        db.disable_file_deletions().unwrap();
+        let live_files = db.get_live_files().unwrap();
+        assert!(live_files.files.contains(&"/CURRENT".to_owned()));
+        assert!(live_files.files.iter().any(|f| f.starts_with("/MANIFEST-")));
+        assert!(live_files.files.iter().any(|f| f.starts_with("/OPTIONS-")));
+        assert!(live_files.files.iter().any(|f| f.ends_with(".sst")));
+        assert!(live_files.manifest_file_size > 0);
+
+        // the compaction makes the listed SST file obsolete, but it is kept
+        db.put(b"k2", b"v2").unwrap();
+        db.flush().unwrap();
+        db.compact_range::<&[u8], &[u8]>(None, None);
+        let db_path = (&path).as_ref().to_str().unwrap();
+        for file in &live_files.files {
+            assert!(std::path::Path::new(&format!("{db_path}{file}")).exists());
+        }
+
+        db.enable_file_deletions(false).unwrap();
+    }
+}
+
+#[test]
+fn test_get_sorted_wal_files() {
+    let path = DBPath::new("_rust_rocksdb_test_get_sorted_wal_files");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_ttl_seconds(1000);
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        let mut batch = WriteBatch::default();
+        batch.put(b"k2", b"v2");
+        batch.delete(b"k1");
+        db.write(batch).unwrap();
+
+        let current = db.get_current_wal_file().unwrap().unwrap();
+        assert_eq!(current.file_type, WalFileType::Alive);
+        assert_eq!(current.start_sequence, 1);
+        assert!(current.size > 0);
+        let batches = current.read_batches().unwrap();
+        let batches: Vec<(u64, usize)> = batches
+            .iter()
+            .map(|(seq, batch)| (*seq, batch.len()))
+            .collect();
+        assert_eq!(batches, vec![(1, 1), (2, 2)]);
+
+        // the flush switches to a new WAL file and archives the previous one
+        db.flush().unwrap();
+        db.put(b"k3", b"v3").unwrap();
+        let files = db.get_sorted_wal_files().unwrap();
+        let last = files.last().unwrap();
+        assert_eq!(last.file_type, WalFileType::Alive);
+        assert_eq!(last.start_sequence, 4);
+        assert!(files
+            .iter()
+            .any(|file| file.file_type == WalFileType::Archived && file.start_sequence == 1));
+        assert!(files.windows(2).all(|w| w[0].number < w[1].number));
+    }
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+fn test_get_sorted_compressed_wal_files() {
+    let path = DBPath::new("_rust_rocksdb_test_get_sorted_compressed_wal_files");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_compression_type(rocksdb::DBCompressionType::Zstd);
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+
+        // the files are listed, but their batches cannot be read
+        let current = db.get_current_wal_file().unwrap().unwrap();
+        assert_eq!(current.file_type, WalFileType::Alive);
+        assert_eq!(current.start_sequence, 0);
+        assert!(current.size > 0);
+        assert_eq!(db.get_sorted_wal_files().unwrap(), vec![current.clone()]);
+        let err = current.read_batches().unwrap_err();
+        assert!(err.as_ref().starts_with("Not implemented"), "{err}");
+        let mut reader = db.read_wal(WalPosition::default()).unwrap();
+        assert!(reader.next_record().unwrap().is_err());
+    }
+}
+
+#[test]
+fn test_read_recycled_wal_file() {
+    let path = DBPath::new("_rust_rocksdb_test_read_recycled_wal_file");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_recycle_log_file_num(1);
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        for i in 0..100_u32 {
+            db.put(i.to_be_bytes(), b"value").unwrap();
+        }
+        let first = db.get_current_wal_file().unwrap().unwrap();
+        db.flush().unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        db.put(b"k2", b"v2").unwrap();
+
+        // the WAL file reuses the one holding the first 100 writes, which are
+        // still in the file after the new write
+        let current = db.get_current_wal_file().unwrap().unwrap();
+        assert_ne!(current.number, first.number);
+        assert!(!first.path.exists());
+        assert!(current.size >= first.size);
+        assert_eq!(current.start_sequence, 102);
+        let batches = current.read_batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, 102);
+    }
+    let db = DB::open(&opts, &path).unwrap();
+    assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+}
+
+#[test]
+fn test_read_recycled_wal_file_padding() {
+    // recyclable records have an 11-byte header: the checksum, the length,
+    // the type and the log number
+    fn record(log_number: u32, payload: &[u8]) -> Vec<u8> {
+        const RECYCLABLE_FULL_TYPE: u8 = 5;
+        let mut checked = vec![RECYCLABLE_FULL_TYPE];
+        checked.extend_from_slice(&log_number.to_le_bytes());
+        checked.extend_from_slice(payload);
+        let crc = crc32c(&checked);
+        let masked = ((crc >> 15) | (crc << 17)).wrapping_add(0xa282_ead8);
+        let mut record = masked.to_le_bytes().to_vec();
+        record.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        record.extend_from_slice(&checked);
+        record
+    }
+    fn crc32c(data: &[u8]) -> u32 {
+        let mut crc = !0_u32;
+        for &byte in data {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0x82F6_3B78
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+    fn batch_data(sequence: u64, key: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut batch = WriteBatch::default();
+        batch.put(key, value);
+        let mut data = batch.data().to_vec();
+        data[..8].copy_from_slice(&sequence.to_le_bytes());
+        data
+    }
+
+    // the first record leaves an 8-byte trailer in the 32KB block, too short
+    // for a recyclable header, which is padded with zeros
+    let first = batch_data(1, b"k1", &[7; 32_749 - 19]);
+    assert_eq!(first.len(), 32_749);
+    let mut content = record(5, &first);
+    content.extend_from_slice(&[0; 8]);
+    assert_eq!(content.len(), 32_768);
+    content.extend(record(5, &batch_data(2, b"k2", b"v2")));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("000005.log");
+    std::fs::write(&path, &content).unwrap();
+    let file = WalFile {
+        path,
+        number: 5,
+        file_type: WalFileType::Alive,
+        start_sequence: 1,
+        size: content.len() as u64,
+    };
+    let batches = file.read_batches().unwrap();
+    let sequences: Vec<u64> = batches.iter().map(|(seq, _)| *seq).collect();
+    assert_eq!(sequences, vec![1, 2]);
+    assert_eq!(batches[1].1.len(), 1);
+}
+
+#[test]
+fn test_read_wal() {
+    let path = DBPath::new("_rust_rocksdb_test_read_wal");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_ttl_seconds(1000);
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        // fragmented over several blocks of the file
+        db.put(b"k2", vec![7; 100_000]).unwrap();
+        db.flush().unwrap();
+        db.put(b"k3", b"v3").unwrap();
+
+        let mut reader = db.read_wal(WalPosition::default()).unwrap();
+        let mut records = Vec::new();
+        while let Some(record) = reader.next_record() {
+            let record = record.unwrap();
+            assert_eq!(record.to_write_batch().len(), 1);
+            records.push((record.sequence(), record.position(), record.next_position()));
+        }
+        let sequences: Vec<u64> = records.iter().map(|(seq, _, _)| *seq).collect();
+        assert_eq!(sequences, vec![1, 2, 3]);
+        assert!(records[2].1.file_number > records[1].1.file_number);
+        assert!(records.windows(2).all(|w| w[0].2 <= w[1].1));
+
+        // resume after the second record
+        let mut reader = db.read_wal(records[1].2).unwrap();
+        let record = reader.next_record().unwrap().unwrap();
+        assert_eq!(record.sequence(), 3);
+        assert!(reader.next_record().is_none());
+
+        let mut reader = db.read_wal(records[1].1).unwrap();
+        let record = reader.next_record().unwrap().unwrap();
+        assert_eq!(record.sequence(), 2);
+        assert_eq!(record.data().len(), record.to_write_batch().data().len());
+
+        assert!(db
+            .read_wal(WalPosition {
+                file_number: records[0].1.file_number - 1,
+                offset: 10,
+            })
+            .is_err());
+    }
+}
+
+#[test]
+fn test_get_disk_usage() {
+    let path = DBPath::new("_rust_rocksdb_test_get_disk_usage");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        for i in 0..100_u32 {
+            db.put(i.to_be_bytes(), [0; 100]).unwrap();
+        }
+        db.flush().unwrap();
+        let usage = db.get_disk_usage().unwrap();
+        assert!(usage.live_sst_bytes > 0);
+        assert_eq!(usage.obsolete_sst_bytes, 0);
+        assert!(usage.other_bytes > 0);
+
+        db.put(b"k1", b"v1").unwrap();
+        assert!(db.get_disk_usage().unwrap().wal_bytes > 0);
+
+        // the compacted files are kept while file deletions are disabled
+        db.disable_file_deletions().unwrap();
+        for i in 0..100_u32 {
+            db.put(i.to_be_bytes(), [1; 100]).unwrap();
+        }
+        db.flush().unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        let usage = db.get_disk_usage().unwrap();
+        assert!(usage.live_sst_bytes > 0);
+        assert!(usage.obsolete_sst_bytes > 0);
+        assert_eq!(
+            usage.total_bytes(),
+            usage.live_sst_bytes
+                + usage.obsolete_sst_bytes
+                + usage.trash_bytes
+                + usage.wal_bytes
+                + usage.blob_bytes
+                + usage.other_bytes
+        );
+        db.enable_file_deletions(true).unwrap();
+    }
+}
+
+#[test]
+fn test_warm_cache_range() {
+    let path = DBPath::new("_rust_rocksdb_test_warm_cache_range");
+    let cache = Cache::new_lru_cache(16 * 1024 * 1024);
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let mut block_opts = BlockBasedOptions::default();
+    block_opts.set_block_cache(&cache);
+    opts.set_block_based_table_factory(&block_opts);
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        for i in 0..1000_u32 {
+            db.put(i.to_be_bytes(), [0; 100]).unwrap();
+        }
+        db.flush().unwrap();
+    }
+
+    let db = DB::open(&opts, &path).unwrap();
+    let usage = cache.get_usage();
+    db.warm_cache_range(0_u32.to_be_bytes()..500_u32.to_be_bytes())
+        .unwrap();
+    let half_usage = cache.get_usage();
+    assert!(half_usage > usage);
+    db.warm_cache_range(..).unwrap();
+    assert!(cache.get_usage() > half_usage);
+}
+
+#[test]
+fn preflight_test() {
+    let path = DBPath::new("_rust_rocksdb_preflight_test");
+    let kinds = |diagnostics: Vec<rocksdb::Diagnostic>| -> Vec<DiagnosticKind> {
+        diagnostics.into_iter().map(|d| d.kind).collect()
+    };
+
+    let mut opts = Options::default();
+    assert_eq!(
+        kinds(DB::preflight(&opts, &path)),
+        vec![DiagnosticKind::DbMissing]
+    );
+    opts.create_if_missing(true);
+    assert!(DB::preflight(&opts, &path).is_empty());
+
+    opts.create_missing_column_families(true);
+    {
+        let _db = DB::open_cf(&opts, &path, ["cf1"]).unwrap();
+        let mut expected = vec![DiagnosticKind::UnopenedColumnFamilies(vec![
+            "cf1".to_owned()
+        ])];
+        if cfg!(target_os = "linux") {
+            expected.insert(
+                0,
+                DiagnosticKind::Locked {
+                    pid: Some(std::process::id()),
+                },
+            );
+        }
+        assert_eq!(kinds(DB::preflight(&opts, &path)), expected);
+    }
+
+    assert!(DB::preflight_cf(&opts, &path, ["default", "cf1", "cf2"]).is_empty());
+    opts.create_missing_column_families(false);
+    assert_eq!(
+        kinds(DB::preflight_cf(&opts, &path, ["cf1", "cf2"])),
+        vec![DiagnosticKind::MissingColumnFamilies(
+            vec!["cf2".to_owned()]
+        )]
+    );
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    assert_eq!(
+        kinds(DB::preflight(&opts, file.path())),
+        vec![DiagnosticKind::NotADirectory]
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_db_lock() {
+    let path = DBPath::new("_rust_rocksdb_test_db_lock");
+    let lock_file = (&path).as_ref().join("LOCK");
+    assert!(!DB::is_locked(&path).unwrap());
+    {
+        let _db = DB::open_default(&path).unwrap();
+        assert!(DB::is_locked(&path).unwrap());
+
+        let err = DB::open_default(&path).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::IOError);
+        assert!(err.as_ref().contains("opened by this process"), "{err}");
+        assert!(DB::force_unlock_risking_corruption(&path).is_err());
+        assert!(lock_file.exists());
+
+        // a read-only instance does not lock the DB
+        let _read_only = DB::open_for_read_only(&Options::default(), &path, false).unwrap();
+    }
+    // the lock is released with the DB, not the file
+    assert!(lock_file.exists());
+    assert!(!DB::is_locked(&path).unwrap());
+    DB::force_unlock_risking_corruption(&path).unwrap();
+    assert!(!lock_file.exists());
+    DB::force_unlock_risking_corruption(&path).unwrap();
+    DB::open_default(&path).unwrap();
+}
+
+#[test]
+fn test_manifest_describe() {
+    let path = DBPath::new("_rust_rocksdb_test_manifest_describe");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let manifest_name = {
+        let mut db = DB::open_cf(&opts, &path, ["cf", "dropped"]).unwrap();
+        {
+            let cf = db.cf_handle("cf").unwrap();
+            for i in 0..3_u8 {
+                db.put_cf(&cf, [i], b"value").unwrap();
+                db.flush_cf(&cf).unwrap();
+            }
+            db.compact_range_cf(&cf, None::<&[u8]>, None::<&[u8]>);
+        }
+        db.put(b"a", b"value").unwrap();
+        db.put(b"z", b"value").unwrap();
+        db.flush().unwrap();
+        db.drop_cf("dropped").unwrap();
+
+        let manifest = manifest::describe(&path).unwrap();
+        let names: Vec<_> = manifest
+            .column_families
+            .iter()
+            .map(|cf| cf.name.as_str())
+            .collect();
+        assert_eq!(names, ["default", "cf"]);
+        assert_eq!(manifest.last_sequence, db.latest_sequence_number());
+
+        // the current version has the live files of the DB
+        let mut live: Vec<_> = db
+            .live_files()
+            .unwrap()
+            .into_iter()
+            .map(|file| {
+                let name = file.name.trim_start_matches('/').to_owned();
+                (file.column_family_name, name, file.level as usize)
+            })
+            .collect();
+        let mut described = Vec::new();
+        for cf in &manifest.column_families {
+            for (level, files) in cf.levels.iter().enumerate() {
+                for file in files {
+                    assert!(file.number < manifest.next_file_number);
+                    described.push((cf.name.clone(), file.name(), level));
+                }
+            }
+        }
+        live.sort();
+        described.sort();
+        assert_eq!(described, live);
+
+        let default = &manifest.column_families[0];
+        assert_eq!(default.levels.len(), 1);
+        let file = &default.levels[0][0];
+        assert_eq!(file.smallest_key, b"a");
+        assert_eq!(file.largest_key, b"z");
+        assert!(file.smallest_sequence <= file.largest_sequence);
+        assert!(file.file_creation_time.is_some());
+        assert_eq!(default.total_size(), file.size);
+
+        // the compaction merged the files of level 0
+        let cf = &manifest.column_families[1];
+        assert_eq!(cf.file_count(), 1);
+        assert!(cf.levels[0].is_empty());
+        manifest.manifest_name
+    };
+
+    let manifest = manifest::describe(&path).unwrap();
+    assert_eq!(manifest.manifest_name, manifest_name);
+    let size = std::fs::metadata((&path).as_ref().join(&manifest_name))
+        .unwrap()
+        .len();
+    assert_eq!(manifest.manifest_size, size);
+    assert!(manifest.edit_count > 0);
+}
+
+#[test]
+fn test_filter_effectiveness() {
+    let path = DBPath::new("_rust_rocksdb_test_filter_effectiveness");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.enable_statistics();
+    assert!(FilterEffectiveness::from_statistics(&Options::default()).is_none());
+
+    let mut block_opts = BlockBasedOptions::default();
+    block_opts.set_bloom_filter(10.0, false);
+    let mut filtered_opts = Options::default();
+    filtered_opts.set_block_based_table_factory(&block_opts);
+    let cfs = vec![
+        ColumnFamilyDescriptor::new("filtered", filtered_opts),
+        ColumnFamilyDescriptor::new("unfiltered", Options::default()),
+    ];
+    let db = DB::open_cf_descriptors(&opts, &path, cfs).unwrap();
+    for name in ["filtered", "unfiltered"] {
+        let cf = db.cf_handle(name).unwrap();
+        for i in 0..100 {
+            db.put_cf(&cf, format!("key{i}"), b"value").unwrap();
+        }
+        db.flush_cf(&cf).unwrap();
+    }
+
+    set_perf_stats(PerfStatsLevel::EnableCount);
+    let recorder = FilterStatsRecorder::new();
+    for name in ["filtered", "unfiltered"] {
+        let cf = db.cf_handle(name).unwrap();
+        for i in 0..100 {
+            let found = recorder.record(name, || db.get_cf(&cf, format!("key{i}")).unwrap());
+            assert!(found.is_some());
+            let missing = recorder.record(name, || db.get_cf(&cf, format!("missing{i}")).unwrap());
+            assert!(missing.is_none());
+        }
+    }
+    set_perf_stats(PerfStatsLevel::Disable);
+
+    let filtered = recorder.get("filtered");
+    assert_eq!(filtered.checked(), 200);
+    assert!(filtered.useful >= 90, "{filtered:?}");
+    assert_eq!(filtered.full_true_positive, None);
+    assert!(filtered.useful_ratio().unwrap() > 0.4);
+    assert_eq!(recorder.get("unfiltered").checked(), 0);
+    assert_eq!(recorder.get("unknown"), FilterEffectiveness::default());
+    assert_eq!(recorder.snapshot().len(), 2);
+
+    // the statistics only count the checks of the filtered column family
+    let stats = FilterEffectiveness::from_statistics(&opts).unwrap();
+    assert_eq!(stats.useful, filtered.useful);
+    assert_eq!(stats.full_positive, filtered.full_positive);
+    assert_eq!(stats.full_true_positive, Some(100));
+    assert!(stats.false_positive_rate().unwrap() < 0.1, "{stats:?}");
+
+    recorder.reset();
+    assert!(recorder.snapshot().is_empty());
+}
+
+#[test]
+fn test_estimate_deletions_in_range() {
+    let path = DBPath::new("_rust_rocksdb_test_estimate_deletions_in_range");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.set_disable_auto_compactions(true);
+    let db = DB::open_cf(&opts, &path, ["queue"]).unwrap();
+    let queue = db.cf_handle("queue").unwrap();
+    for i in 0..100 {
+        db.put_cf(&queue, format!("a{i:03}"), b"value").unwrap();
+        db.put(format!("a{i:03}"), b"value").unwrap();
+    }
+    db.flush_cf(&queue).unwrap();
+    db.flush().unwrap();
+    for i in 0..50 {
+        db.delete_cf(&queue, format!("a{i:03}")).unwrap();
+    }
+    // not flushed yet
+    assert_eq!(
+        db.estimate_deletions_in_range(&queue, ..).unwrap(),
+        DeletionEstimate {
+            file_count: 1,
+            num_entries: 100,
+            num_deletions: 0,
+        }
+    );
+    db.flush_cf(&queue).unwrap();
+
+    let estimate = db.estimate_deletions_in_range(&queue, ..).unwrap();
+    assert_eq!(
+        estimate,
+        DeletionEstimate {
+            file_count: 2,
+            num_entries: 150,
+            num_deletions: 50,
+        }
+    );
+    assert!((estimate.deletion_ratio() - 1.0 / 3.0).abs() < 1e-9);
+
+    // the file of the deletions ends at a049
+    let estimate = db.estimate_deletions_in_range(&queue, "a050"..).unwrap();
+    assert_eq!(estimate.file_count, 1);
+    assert_eq!(estimate.num_deletions, 0);
+    let estimate = db.estimate_deletions_in_range(&queue, .."a000").unwrap();
+    assert_eq!(estimate, DeletionEstimate::default());
+    assert_eq!(estimate.deletion_ratio(), 0.0);
+
+    let cf = db.cf_handle(DEFAULT_COLUMN_FAMILY_NAME).unwrap();
+    let estimate = db.estimate_deletions_in_range(&cf, "a000".."a050").unwrap();
+    assert_eq!(estimate.num_deletions, 0);
+    assert_eq!(estimate.num_entries, 100);
+}
+
+#[test]
+fn test_close() {
+    let path = DBPath::new("_rust_rocksdb_test_close");
+    let db = DB::open_default(&path).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    {
+        let _snapshot = db.snapshot();
+        let mut iter = db.raw_iterator();
+        iter.seek_to_first();
+        assert!(iter.valid());
+    }
+    db.close().unwrap();
+    assert_eq!(
+        DB::open_default(&path)
+            .unwrap()
+            .get(b"k1")
+            .unwrap()
+            .unwrap(),
+        b"v1"
+    );
+
+    // the leaked handles keep the DB open, which is given back
+    let dir = tempfile::tempdir().unwrap();
+    let db = DB::open_default(dir.path()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    mem::forget(db.snapshot());
+    mem::forget(db.get_updates_since(0).unwrap());
+    let (db, err) = db.close().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Busy);
+    assert!(
+        err.as_ref().contains("1 iterator(s) and 1 snapshot(s)"),
+        "{err}"
+    );
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+
+    // dropping it leaks it, keeping its directory locked
+    drop(db);
+    assert!(DB::open_default(dir.path()).is_err());
+}
+
+#[test]
+fn test_db_identity_and_session_id() {
+    let path = DBPath::new("_rust_rocksdb_test_db_identity_and_session_id");
+    let (identity, session_id) = {
+        let db = DB::open_default(&path).unwrap();
+        let identity = db.get_db_identity().unwrap();
+        let session_id = db.get_db_session_id().unwrap().unwrap();
+        assert!(!identity.is_empty());
+        assert!(!session_id.is_empty());
+        (identity, session_id)
+    };
+
+    let db = DB::open_default(&path).unwrap();
+    assert_eq!(db.get_db_identity().unwrap(), identity);
+    assert_ne!(db.get_db_session_id().unwrap().unwrap(), session_id);
+}
+
+#[test]
+fn test_read_only_and_secondary_db() {
+    let primary_path = DBPath::new("_rust_rocksdb_test_read_only_and_secondary_db_primary");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    {
+        let db = DB::open_cf(&opts, &primary_path, ["cf"]).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put_cf(&db.cf_handle("cf").unwrap(), b"k2", b"v2")
+            .unwrap();
+    }
+
+    {
+        let read_only = ReadOnlyDB::open_cf(&opts, &primary_path, ["cf"], false).unwrap();
+        assert_eq!(read_only.get(b"k1").unwrap().unwrap(), b"v1");
+        let cf = read_only.cf_handle("cf").unwrap();
+        assert_eq!(read_only.get_cf(cf, b"k2").unwrap().unwrap(), b"v2");
+        assert_eq!(read_only.iterator(IteratorMode::Start).count(), 1);
+    }
+
+    let db = DB::open_cf(&opts, &primary_path, ["cf"]).unwrap();
+    let mut secondary_opts = Options::default();
+    secondary_opts.set_max_open_files(-1);
+    let secondary_path = DBPath::new("_rust_rocksdb_test_read_only_and_secondary_db_secondary");
+    let secondary =
+        SecondaryDB::open_cf(&secondary_opts, &primary_path, &secondary_path, ["cf"]).unwrap();
+    db.put(b"k3", b"v3").unwrap();
+    assert!(secondary.get(b"k3").unwrap().is_none());
+    secondary.try_catch_up_with_primary().unwrap();
+    assert_eq!(secondary.get(b"k3").unwrap().unwrap(), b"v3");
+}
+
+#[test]
+fn test_read_only_db_put() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/fail/read_only_db_put.rs");
+}
+
+#[test]
+fn test_write_seq() {
+    let path = DBPath::new("_rust_rocksdb_test_write_seq");
+    let db = DB::open_default(&path).unwrap();
+    db.put(b"k0", b"v0").unwrap();
+
+    let mut batch = WriteBatch::default();
+    batch.put(b"k1", b"v1");
+    batch.put(b"k2", b"v2");
+    assert_eq!(db.write_seq(batch).unwrap(), 2);
+
+    let mut batch = WriteBatch::default();
+    batch.delete(b"k1");
+    assert_eq!(db.write_seq(batch).unwrap(), 4);
+    assert_eq!(db.latest_sequence_number(), 4);
+
+    let db = Arc::new(db);
+    let mut sequences: Vec<u64> = (0..4u8)
+        .map(|i| {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                let mut batch = WriteBatch::default();
+                batch.put([i], b"v");
+                db.write_seq(batch).unwrap()
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    sequences.sort_unstable();
+    assert_eq!(sequences, vec![5, 6, 7, 8]);
+}
+
+fn concat_merge(_: &[u8], existing: Option<&[u8]>, operands: &MergeOperands) -> Option<Vec<u8>> {
+    let mut value = existing.map(<[u8]>::to_vec).unwrap_or_default();
+    for operand in operands {
+        value.extend_from_slice(operand);
+    }
+    Some(value)
+}
+
+#[test]
+fn test_put_delete_merge_seq() {
+    let path = DBPath::new("_rust_rocksdb_test_put_delete_merge_seq");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.set_merge_operator_associative("concat", concat_merge);
+    let db = DB::open_cf(&opts, &path, ["cf"]).unwrap();
+    let cf = db.cf_handle("cf").unwrap();
+    let writeopts = WriteOptions::default();
+
+    assert_eq!(db.put_opt_seq(b"k1", b"v1", &writeopts).unwrap(), 1);
+    assert_eq!(db.put_cf_opt_seq(&cf, b"k1", b"v1", &writeopts).unwrap(), 2);
+    assert_eq!(db.merge_opt_seq(b"k1", b"v2", &writeopts).unwrap(), 3);
+    assert_eq!(
+        db.merge_cf_opt_seq(&cf, b"k1", b"v2", &writeopts).unwrap(),
+        4
+    );
+    assert_eq!(db.delete_opt_seq(b"k1", &writeopts).unwrap(), 5);
+    assert_eq!(db.delete_cf_opt_seq(&cf, b"k2", &writeopts).unwrap(), 6);
+
+    assert!(db.get(b"k1").unwrap().is_none());
+    assert_eq!(db.get_cf(&cf, b"k1").unwrap().unwrap(), b"v1v2");
+}
+
+#[test]
+fn test_stats_history() {
+    let path = DBPath::new("_rust_rocksdb_test_stats_history");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.enable_statistics();
+    opts.set_stats_persist_period_sec(1);
+    opts.set_stats_history_buffer_size(1 << 20).unwrap();
+    opts.set_persist_stats_to_disk(true).unwrap();
+    {
+        let db = DB::open_default(&path).unwrap();
+        assert!(db.get_stats_history(0, u64::MAX).is_err());
+    }
+
+    let db = DB::open_cf(&opts, &path, [STATS_HISTORY_COLUMN_FAMILY_NAME]).unwrap();
+    for i in 0..100u32 {
+        db.put(i.to_be_bytes(), b"value").unwrap();
+    }
+    thread::sleep(Duration::from_millis(2500));
+
+    let history = db.get_stats_history(0, u64::MAX).unwrap();
+    assert!(!history.is_empty());
+    assert!(history.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    assert!(history.iter().all(|entry| !entry.stats.is_empty()));
+
+    let first = history[0].timestamp;
+    assert!(db.get_stats_history(0, first).unwrap().is_empty());
+    assert_eq!(db.get_stats_history(first, first + 1).unwrap().len(), 1);
+}
+
+#[test]
+fn test_flush_and_sync_all() {
+    let path = DBPath::new("_rust_rocksdb_test_flush_and_sync_all");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.set_atomic_flush(true);
+    {
+        let db = DB::open_cf(&opts, &path, ["cf1"]).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        let no_wal = WriteOptions::no_wal();
+        assert!(no_wal.get_disable_wal());
+        assert!(WriteOptions::synced().get_sync());
+
+        db.put_opt(b"k1", b"v1", &no_wal).unwrap();
+        db.put_cf_opt(&cf1, b"k2", b"v2", &no_wal).unwrap();
+        let seqno = db.flush_and_sync_all().unwrap();
+        assert_eq!(seqno, db.latest_sequence_number());
+        assert_eq!(seqno, 2);
+
+        let live_files = db.live_files().unwrap();
+        assert!(live_files
+            .iter()
+            .any(|file| file.column_family_name == "cf1"));
+        assert!(live_files
+            .iter()
+            .any(|file| file.column_family_name == DEFAULT_COLUMN_FAMILY_NAME));
+    }
+    {
+        // the default column family is flushed on its own when the DB is
+        // opened without naming column families
+        let path = DBPath::new("_rust_rocksdb_test_flush_and_sync_all_default");
+        let db = DB::open(&opts, &path).unwrap();
+        db.put_opt(b"k3", b"v3", &WriteOptions::no_wal()).unwrap();
+        assert_eq!(db.flush_and_sync_all().unwrap(), 1);
+        assert_eq!(
+            db.property_int_value(properties::NUM_ENTRIES_ACTIVE_MEM_TABLE)
+                .unwrap(),
+            Some(0)
+        );
+    }
+}
+
+#[test]
+fn test_open_with_report() {
+    let path = DBPath::new("_rust_rocksdb_test_open_with_report");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    {
+        let (db, report) = DB::open_with_report(&opts, &path, true).unwrap();
+        assert!(report.wal_files_replayed.is_empty());
+        assert_eq!(report.wal_records_replayed, Some(0));
+        assert_eq!(report.column_families, [DEFAULT_COLUMN_FAMILY_NAME]);
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+    }
+    {
+        let (db, report) = DB::open_with_report(&opts, &path, true).unwrap();
+        assert!(!report.wal_files_replayed.is_empty());
+        assert_eq!(report.wal_records_replayed, Some(2));
+        assert!(report.manifest_load.is_some());
+        assert!(report.wal_recovery.is_some());
+        assert!(report.total >= report.manifest_load.unwrap());
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v2");
+    }
+    {
+        // the WAL files are only inspected on demand
+        let (_db, report) = DB::open_with_report(&opts, &path, false).unwrap();
+        assert!(report.wal_files_replayed.is_empty());
+        assert_eq!(report.wal_records_replayed, None);
+        assert!(report.manifest_load.is_some());
+    }
+}
@@ -0,0 +1,59 @@
+mod util;
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::{Cache, DbGroup, Options, WriteBufferManager};
+use util::DBPath;
+
+#[test]
+fn db_group_test() {
+    let path1 = DBPath::new("_rust_rocksdb_db_group_test_1");
+    let path2 = DBPath::new("_rust_rocksdb_db_group_test_2");
+
+    let cache = Cache::new_lru_cache(1024 * 1024);
+    let manager = WriteBufferManager::new(16 * 1024 * 1024, false);
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_row_cache(&cache);
+    opts.set_write_buffer_manager(&manager);
+
+    let mut group = DbGroup::new(opts);
+    let db1 = group.open("db1", &path1).unwrap();
+    let db2 = group.open("db2", &path2).unwrap();
+    assert!(group.open("db1", &path1).is_err());
+    assert_eq!(group.names().collect::<Vec<_>>(), vec!["db1", "db2"]);
+
+    db1.put(b"k1", b"v1").unwrap();
+    db2.put(b"k2", b"v2").unwrap();
+    // the memtables of both DBs are accounted in the shared manager
+    assert!(manager.get_usage() > 0);
+
+    let stats = group.memory_usage_stats(&[&cache]).unwrap();
+    assert!(stats.mem_table_total > 0);
+
+    group.flush_all().unwrap();
+    assert_eq!(
+        db1.property_int_value("rocksdb.num-files-at-level0")
+            .unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        db2.property_int_value("rocksdb.num-files-at-level0")
+            .unwrap(),
+        Some(1)
+    );
+
+    assert!(group.remove("db2").is_some());
+    assert_eq!(group.len(), 1);
+    assert!(group.get("db2").is_none());
+
+    // db1 is still used, so it is kept open
+    assert_eq!(group.close_all(), vec!["db1"]);
+    assert_eq!(group.len(), 1);
+    db1.put(b"k3", b"v3").unwrap();
+    assert_eq!(db1.get(b"k1").unwrap().unwrap(), b"v1");
+
+    drop(db1);
+    assert!(group.close_all().is_empty());
+    assert!(group.is_empty());
+}
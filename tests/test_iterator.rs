@@ -16,7 +16,7 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{Direction, IteratorMode, MemtableFactory, Options, DB};
+use rocksdb::{Direction, IteratorMode, MemtableFactory, Options, ReadOptions, DB};
 use util::{assert_iter, assert_iter_reversed, pair, DBPath};
 
 #[test]
@@ -243,6 +243,46 @@ fn test_custom_iterator() {
     }
 }
 
+#[test]
+fn test_scan_builder() {
+    let path = DBPath::new("_rust_rocksdb_scan_builder_test");
+    {
+        let db = DB::open_default(&path).unwrap();
+        for key in ["a1", "a2", "a3", "b1", "b2", "c1"] {
+            db.put(key, key).unwrap();
+        }
+        let p = |key: &str| pair(key.as_bytes(), key.as_bytes());
+
+        assert_iter(
+            db.scan().build(),
+            &["a1", "a2", "a3", "b1", "b2", "c1"].map(p),
+        );
+        assert_iter(
+            db.scan().from("a2").to("b2").build(),
+            &["a2", "a3", "b1"].map(p),
+        );
+        assert_iter(db.scan().prefix("b").build(), &["b1", "b2"].map(p));
+        assert_iter_reversed(
+            db.scan().prefix("a").reverse().build(),
+            &["a1", "a2", "a3"].map(p),
+        );
+
+        // the prefix is combined with the other bounds
+        assert_iter(
+            db.scan().prefix("a").from("a2").build(),
+            &["a2", "a3"].map(p),
+        );
+        assert_iter(
+            db.scan().prefix("a").to("b2").build(),
+            &["a1", "a2", "a3"].map(p),
+        );
+        assert_iter(
+            db.scan().prefix("b").to("b2").fill_cache(false).build(),
+            &["b1"].map(p),
+        );
+    }
+}
+
 #[test]
 fn test_iterator_outlive_db() {
     let t = trybuild::TestCases::new();
@@ -379,3 +419,95 @@ fn test_iter_range() {
         check(&db, b"b0", prefix(b"\xff"), 0..0);
     }
 }
+
+#[test]
+fn test_iter_scoped_bounds() {
+    let path = DBPath::new("_rust_rocksdb_iter_scoped_bounds");
+    let db = DB::open_default(&path).unwrap();
+    for key in [b"a0", b"a1", b"b0", b"b1", b"c0"] {
+        db.put(key, key).unwrap();
+    }
+    let cf = db.cf_handle("default").unwrap();
+    let keys = |iter: rocksdb::DBIterator| -> Vec<Box<[u8]>> {
+        iter.map(|item| item.unwrap().0).collect()
+    };
+
+    let (lower, upper) = (b"a1".to_vec(), b"b1".to_vec());
+    let readopts = ReadOptions::default().scoped_bounds(Some(&lower), Some(&upper));
+    let got = keys(db.iterator_scoped(IteratorMode::Start, readopts));
+    assert_eq!(got, [&b"a1"[..], b"b0"].map(Box::from));
+
+    let upper = b"b".to_vec();
+    let readopts = ReadOptions::default().scoped_bounds(None, Some(&upper));
+    let mut iter = db.raw_iterator_cf_scoped(&cf, readopts);
+    iter.seek_to_last();
+    assert_eq!(iter.key(), Some(&b"a1"[..]));
+
+    // bounds set on the options before are replaced
+    let mut readopts = ReadOptions::default();
+    readopts.set_iterate_range(b"a".to_vec()..b"b".to_vec());
+    let lower = b"b".to_vec();
+    let readopts = readopts.scoped_bounds(Some(&lower), None);
+    let got = keys(db.iterator_cf_scoped(&cf, readopts, IteratorMode::End));
+    assert_eq!(got, [&b"c0"[..], b"b1", b"b0"].map(Box::from));
+}
+
+#[test]
+fn test_iter_cursor_resume() {
+    let path = DBPath::new("_rust_rocksdb_iter_cursor_resume");
+    let db = DB::open_default(&path).unwrap();
+    for i in 0..10_u8 {
+        db.put([b'k', b'0' + i], [i]).unwrap();
+    }
+    fn keys(cursor: &mut rocksdb::Cursor<'_, DB>, n: usize) -> Vec<Box<[u8]>> {
+        cursor.take(n).map(|item| item.unwrap().0).collect()
+    }
+
+    // forward pages within bounds
+    let mut readopts = ReadOptions::default();
+    readopts.set_iterate_range(b"k2".to_vec()..b"k8".to_vec());
+    let mut cursor = db.iterator_opt(IteratorMode::Start, readopts).into_cursor();
+    assert_eq!(
+        keys(&mut cursor, 4),
+        [&b"k2"[..], b"k3", b"k4", b"k5"].map(Box::from)
+    );
+    let token = cursor.resume_token();
+    drop(cursor);
+    assert_eq!(token.last_key(), Some(&b"k5"[..]));
+    assert_eq!(token.direction(), Direction::Forward);
+    assert_eq!(token.lower_bound(), Some(&b"k2"[..]));
+    assert_eq!(token.upper_bound(), Some(&b"k8"[..]));
+    assert!(!token.is_finished());
+
+    // the resumed cursor can be moved to another thread
+    let db = std::sync::Arc::new(db);
+    let page = {
+        let db = std::sync::Arc::clone(&db);
+        std::thread::spawn(move || {
+            let mut cursor = rocksdb::Cursor::resume(&*db, &token);
+            let page = keys(&mut cursor, 4);
+            (page, cursor.resume_token())
+        })
+        .join()
+        .unwrap()
+    };
+    assert_eq!(page.0, [&b"k6"[..], b"k7"].map(Box::from));
+    assert!(page.1.is_finished());
+    let mut cursor = rocksdb::Cursor::resume(&*db, &page.1);
+    assert!(cursor.next().is_none());
+
+    // reverse from a key, resumed before anything is returned
+    let cursor = db
+        .iterator(IteratorMode::From(b"k4", Direction::Reverse))
+        .into_cursor();
+    let token = cursor.resume_token();
+    assert_eq!(token.last_key(), None);
+    let cf = db.cf_handle("default").unwrap();
+    let mut cursor = rocksdb::Cursor::resume_cf(&*db, &cf, &token);
+    assert_eq!(keys(&mut cursor, 2), [&b"k4"[..], b"k3"].map(Box::from));
+    let mut cursor = rocksdb::Cursor::resume(&*db, &cursor.resume_token());
+    assert_eq!(
+        keys(&mut cursor, 10),
+        [&b"k2"[..], b"k1", b"k0"].map(Box::from)
+    );
+}
@@ -0,0 +1,107 @@
+mod util;
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::{
+    keys::{KeyDecoder, KeyEncoder},
+    IteratorMode, DB,
+};
+use util::DBPath;
+
+/// Writes the keys to a DB in reverse order and returns them in the order of
+/// the bytewise comparator.
+fn sorted_by_db(name: &str, keys: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let path = DBPath::new(name);
+    let db = DB::open_default(&path).unwrap();
+    for key in keys.iter().rev() {
+        db.put(key, b"").unwrap();
+    }
+    db.iterator(IteratorMode::Start)
+        .map(|item| item.unwrap().0.to_vec())
+        .collect()
+}
+
+#[test]
+fn test_integer_and_float_keys_order() {
+    let ints = [i64::MIN, -1000, -1, 0, 1, 255, 256, i64::MAX];
+    let keys: Vec<Vec<u8>> = ints
+        .iter()
+        .map(|i| {
+            let mut encoder = KeyEncoder::new();
+            encoder.push(*i);
+            encoder.into_bytes()
+        })
+        .collect();
+    assert_eq!(sorted_by_db("_rust_rocksdb_test_keys_ints", &keys), keys);
+
+    let floats = [
+        f64::NEG_INFINITY,
+        -1e300,
+        -1.5,
+        -f64::MIN_POSITIVE,
+        -0.0,
+        0.0,
+        f64::MIN_POSITIVE,
+        0.5,
+        1e300,
+        f64::INFINITY,
+    ];
+    let keys: Vec<Vec<u8>> = floats
+        .iter()
+        .map(|f| {
+            let mut encoder = KeyEncoder::new();
+            encoder.push(*f);
+            encoder.into_bytes()
+        })
+        .collect();
+    assert_eq!(sorted_by_db("_rust_rocksdb_test_keys_floats", &keys), keys);
+    for (key, f) in keys.iter().zip(floats.iter()) {
+        let decoded: f64 = KeyDecoder::new(key).read().unwrap();
+        assert_eq!(decoded.to_bits(), f.to_bits());
+    }
+}
+
+#[test]
+fn test_composite_keys_order() {
+    // (name ascending, version descending, id ascending)
+    let tuples: Vec<(&[u8], u64, i64)> = vec![
+        (&b""[..], 1, 0),
+        (&b"\x00"[..], 1, 0),
+        (&b"\x00\x00"[..], 1, 0),
+        (&b"\x00\x01"[..], 1, 0),
+        (&b"a"[..], 2, -1),
+        (&b"a"[..], 2, 5),
+        (&b"a"[..], 1, -3),
+        (&b"a\x00"[..], 9, 0),
+        (&b"ab"[..], 3, 0),
+        (&b"b"[..], 0, 0),
+        (&b"\xff"[..], 0, 0),
+    ];
+    let keys: Vec<Vec<u8>> = tuples
+        .iter()
+        .map(|(name, version, id)| {
+            let mut encoder = KeyEncoder::new();
+            encoder.push(*name).push_desc(*version).push(*id);
+            encoder.into_bytes()
+        })
+        .collect();
+    assert_eq!(
+        sorted_by_db("_rust_rocksdb_test_keys_composite", &keys),
+        keys
+    );
+
+    for (key, (name, version, id)) in keys.iter().zip(tuples) {
+        let mut decoder = KeyDecoder::new(key);
+        assert_eq!(decoder.read::<Vec<u8>>().unwrap(), name);
+        assert_eq!(decoder.read_desc::<u64>().unwrap(), version);
+        assert_eq!(decoder.read::<i64>().unwrap(), id);
+        assert!(decoder.is_empty());
+    }
+}
+
+#[test]
+fn test_invalid_keys() {
+    assert!(KeyDecoder::new(b"\x00\x00\x00").read::<u64>().is_err());
+    assert!(KeyDecoder::new(b"abc").read::<Vec<u8>>().is_err());
+    assert!(KeyDecoder::new(b"\xff\x00\x01").read::<String>().is_err());
+}
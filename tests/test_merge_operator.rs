@@ -15,7 +15,11 @@
 mod util;
 
 use pretty_assertions::assert_eq;
-use rocksdb::{merge_operator::MergeFn, DBCompactionStyle, MergeOperands, Options, DB};
+use rocksdb::{
+    merge_operator::MergeFn, perf::set_perf_stats, BuiltinMergeOperator, ColumnFamilyDescriptor,
+    ColumnFamilyOperators, CompactionDecision, DBCompactionStyle, ErrorKind, MergeOperands,
+    Options, PerfContext, PerfMetric, PerfStatsLevel, WriteBatch, DB,
+};
 use serde::{Deserialize, Serialize};
 use util::DBPath;
 
@@ -345,3 +349,215 @@ fn test_merge_state() {
     }
     assert!(DB::destroy(&opts, path).is_ok());
 }
+
+#[test]
+fn builtin_merge_operator_test() {
+    let path = DBPath::new("_rust_rocksdb_builtin_merge_operator_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.set_builtin_merge_operator(BuiltinMergeOperator::UInt64Add)
+        .unwrap();
+
+    let mut max_opts = Options::default();
+    max_opts
+        .set_builtin_merge_operator(BuiltinMergeOperator::Max)
+        .unwrap();
+    let mut append_opts = Options::default();
+    append_opts
+        .set_builtin_merge_operator(BuiltinMergeOperator::StringAppend(";".to_owned()))
+        .unwrap();
+
+    let db = DB::open_cf_descriptors(
+        &opts,
+        &path,
+        vec![
+            ColumnFamilyDescriptor::new("max", max_opts),
+            ColumnFamilyDescriptor::new("append", append_opts),
+        ],
+    )
+    .unwrap();
+
+    db.put(b"counter", 5_u64.to_le_bytes()).unwrap();
+    db.merge(b"counter", 1_u64.to_le_bytes()).unwrap();
+    db.merge(b"counter", 2_u64.to_le_bytes()).unwrap();
+    assert_eq!(db.get(b"counter").unwrap().unwrap(), 8_u64.to_le_bytes());
+
+    let max = db.cf_handle("max").unwrap();
+    db.merge_cf(&max, b"k", b"b").unwrap();
+    db.merge_cf(&max, b"k", b"c").unwrap();
+    db.merge_cf(&max, b"k", b"a").unwrap();
+    assert_eq!(db.get_cf(&max, b"k").unwrap().unwrap(), b"c");
+
+    let append = db.cf_handle("append").unwrap();
+    db.merge_cf(&append, b"k", b"a").unwrap();
+    db.merge_cf(&append, b"k", b"b").unwrap();
+    db.flush_cf(&append).unwrap();
+    db.merge_cf(&append, b"k", b"c").unwrap();
+    assert_eq!(db.get_cf(&append, b"k").unwrap().unwrap(), b"a;b;c");
+
+    let mut invalid_opts = Options::default();
+    let err = invalid_opts
+        .set_builtin_merge_operator(BuiltinMergeOperator::StringAppend("};".to_owned()))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidArgument);
+}
+
+#[test]
+fn increment_u64_test() {
+    let path = DBPath::new("_rust_rocksdb_increment_u64_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.set_builtin_merge_operator(BuiltinMergeOperator::UInt64Add)
+        .unwrap();
+    let db = DB::open_cf_descriptors(
+        &opts,
+        &path,
+        vec![ColumnFamilyDescriptor::new("counters", opts.clone())],
+    )
+    .unwrap();
+    let cf = db.cf_handle("counters").unwrap();
+
+    assert_eq!(db.get_u64(b"hits").unwrap(), None);
+    db.increment_u64(b"hits", 3).unwrap();
+    db.increment_u64(b"hits", 4).unwrap();
+    assert_eq!(db.get_u64(b"hits").unwrap(), Some(7));
+
+    let mut batch = WriteBatch::default();
+    batch.increment_u64(b"hits", 1);
+    batch.increment_u64_cf(&cf, b"hits", 10);
+    batch.increment_u64_cf(&cf, b"hits", u64::MAX);
+    db.write(batch).unwrap();
+    assert_eq!(db.get_u64(b"hits").unwrap(), Some(8));
+    // the sum wraps on overflow
+    assert_eq!(db.get_u64_cf(&cf, b"hits").unwrap(), Some(9));
+
+    db.flush_cf(&cf).unwrap();
+    db.increment_u64_cf(&cf, b"hits", 1).unwrap();
+    assert_eq!(db.get_u64_cf(&cf, b"hits").unwrap(), Some(10));
+
+    db.put(b"name", b"not a counter").unwrap();
+    assert!(db.get_u64(b"name").is_err());
+}
+
+#[test]
+fn merge_operator_mismatch_test() {
+    let path = DBPath::new("_rust_rocksdb_merge_operator_mismatch_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts.set_merge_operator_associative("test operator", test_provided_merge);
+    opts.set_compaction_filter("test filter", |_: u32, _: &[u8], _: &[u8]| {
+        CompactionDecision::Keep
+    });
+    let plain_opts = Options::default();
+    {
+        let db = DB::open_cf_descriptors(
+            &opts,
+            &path,
+            vec![
+                ColumnFamilyDescriptor::new("default", opts.clone()),
+                ColumnFamilyDescriptor::new("plain", plain_opts.clone()),
+            ],
+        )
+        .unwrap();
+        let operators = db.column_family_operators().unwrap();
+        assert_eq!(operators.merge_operator.as_deref(), Some("test operator"));
+        assert_eq!(operators.compaction_filter.as_deref(), Some("test filter"));
+        assert_eq!(operators.compaction_filter_factory, None);
+        let plain = db.cf_handle("plain").unwrap();
+        assert_eq!(
+            db.column_family_operators_cf(&plain).unwrap(),
+            ColumnFamilyOperators::default()
+        );
+    }
+
+    let open = |default_opts: &Options, verify: bool| {
+        let mut db_opts = opts.clone();
+        db_opts.set_verify_merge_operators(verify);
+        DB::open_cf_descriptors(
+            &db_opts,
+            &path,
+            vec![
+                ColumnFamilyDescriptor::new("default", default_opts.clone()),
+                ColumnFamilyDescriptor::new("plain", plain_opts.clone()),
+            ],
+        )
+    };
+    // the check is opt-in
+    assert!(open(&plain_opts, false).is_ok());
+
+    let err = open(&plain_opts, true).err().unwrap();
+    assert!(
+        err.to_string().contains("without a merge operator"),
+        "{err}"
+    );
+    let mut other_opts = Options::default();
+    other_opts.set_merge_operator_associative("other operator", test_provided_merge);
+    let err = open(&other_opts, true).err().unwrap();
+    assert!(err.to_string().contains("other operator"), "{err}");
+    assert!(open(&opts, true).is_ok());
+}
+
+#[test]
+fn merge_operand_count_test() {
+    let path = DBPath::new("_rust_rocksdb_merge_operand_count_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_merge_operator_associative("test operator", test_provided_merge);
+    let db = DB::open(&opts, &path).unwrap();
+
+    for _ in 0..3 {
+        db.merge(b"a", b"x").unwrap();
+    }
+    db.put(b"b", b"y").unwrap();
+    db.merge(b"b", b"z").unwrap();
+    db.merge(b"c", b"x").unwrap();
+    assert_eq!(db.merge_operand_count(b"a", b"c").unwrap(), 4);
+    assert_eq!(db.merge_operand_count(b"b", b"d").unwrap(), 2);
+    assert_eq!(db.merge_operand_count(b"d", b"e").unwrap(), 0);
+
+    // the perf stats level of the thread is restored
+    set_perf_stats(PerfStatsLevel::Disable);
+    assert_eq!(db.merge_operand_count(b"a", b"c").unwrap(), 4);
+    let ctx = PerfContext::default();
+    let before = ctx.metric(PerfMetric::InternalMergeCount);
+    assert_eq!(db.get(b"a").unwrap().unwrap(), b"xxx");
+    assert_eq!(ctx.metric(PerfMetric::InternalMergeCount), before);
+    set_perf_stats(PerfStatsLevel::EnableCount);
+
+    db.compact_range(None::<&[u8]>, None::<&[u8]>);
+    assert_eq!(db.merge_operand_count(b"a", b"d").unwrap(), 0);
+    assert_eq!(db.get(b"a").unwrap().unwrap(), b"xxx");
+}
+
+#[test]
+fn merge_operands_iter_test() {
+    fn check_operands(
+        _key: &[u8],
+        existing_val: Option<&[u8]>,
+        operands: &MergeOperands,
+    ) -> Option<Vec<u8>> {
+        let mut iter = operands.iter();
+        assert_eq!(iter.len(), operands.len());
+        assert_eq!(iter.next_back(), operands.operands_back());
+        assert_eq!(iter.len(), operands.len() - 1);
+        // the newest operand first, then the existing value
+        let mut result: Vec<u8> = operands.iter().rev().flatten().copied().collect();
+        result.extend_from_slice(existing_val.unwrap_or_default());
+        Some(result)
+    }
+
+    let path = DBPath::new("_rust_rocksdb_merge_operands_iter_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_merge_operator_associative("reverse operator", check_operands);
+    let db = DB::open(&opts, &path).unwrap();
+
+    db.put(b"k1", b"a").unwrap();
+    db.merge(b"k1", b"b").unwrap();
+    db.merge(b"k1", b"c").unwrap();
+    db.merge(b"k1", b"d").unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"dcba");
+}
@@ -17,9 +17,12 @@ mod util;
 
 use rocksdb::{
     CuckooTableOptions, DBAccess, Direction, Error, ErrorKind, IteratorMode,
-    OptimisticTransactionDB, OptimisticTransactionOptions, Options, ReadOptions, SingleThreaded,
-    SliceTransform, SnapshotWithThreadMode, WriteBatchWithTransaction, WriteOptions, DB,
+    OptimisticTransactionDB, OptimisticTransactionOptions, Options, ReadOptions, RetryOptions,
+    SingleThreaded, SliceTransform, SnapshotWithThreadMode, WriteBatchWithTransaction,
+    WriteOptions, DB,
 };
+use std::convert::TryInto;
+use std::thread;
 use util::DBPath;
 
 #[test]
@@ -581,3 +584,96 @@ fn transaction_snapshot() {
         assert_eq!(snapshot.get(b"k3").unwrap().unwrap(), b"v3");
     }
 }
+
+#[test]
+fn get_updates_since() {
+    let path = DBPath::new("_rust_rocksdb_optimistic_transaction_db_get_updates_since");
+    {
+        let db: OptimisticTransactionDB = OptimisticTransactionDB::open_default(&path).unwrap();
+        let seq0 = db.latest_sequence_number();
+
+        db.put(b"k1", b"v1").unwrap();
+        let txn = db.transaction();
+        txn.put(b"k2", b"v2").unwrap();
+        txn.put(b"k3", b"v3").unwrap();
+        txn.commit().unwrap();
+        assert_eq!(db.latest_sequence_number(), seq0 + 3);
+
+        let batches: Vec<_> = db
+            .get_updates_since(seq0)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].0, seq0 + 1);
+        assert_eq!(batches[0].1.len(), 1);
+        assert_eq!(batches[1].0, seq0 + 2);
+        assert_eq!(batches[1].1.len(), 2);
+    }
+}
+
+#[test]
+fn update() {
+    let path = DBPath::new("_rust_rocksdb_optimistic_transaction_db_update");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db: OptimisticTransactionDB =
+        OptimisticTransactionDB::open_cf(&opts, &path, ["cf"]).unwrap();
+    let cf = db.cf_handle("cf").unwrap();
+
+    let increment = |old: Option<&[u8]>| {
+        let count = old.map_or(0, |old| u64::from_le_bytes(old.try_into().unwrap()));
+        Some((count + 1).to_le_bytes().to_vec())
+    };
+    let mut retry_opts = RetryOptions::default();
+    retry_opts.set_max_retries(1000);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..50 {
+                    db.update_opt(b"count", &retry_opts, increment).unwrap();
+                }
+            });
+        }
+    });
+    assert_eq!(db.get(b"count").unwrap().unwrap(), 200_u64.to_le_bytes());
+
+    db.update_cf(&cf, b"count", increment).unwrap();
+    assert_eq!(
+        db.get_cf(&cf, b"count").unwrap().unwrap(),
+        1_u64.to_le_bytes()
+    );
+    // returning None deletes the key
+    assert_eq!(db.update_cf(&cf, b"count", |_| None).unwrap(), None);
+    assert!(db.get_cf(&cf, b"count").unwrap().is_none());
+
+    // a write between the read and the commit makes the transaction retry
+    db.put(b"k", b"0").unwrap();
+    let mut calls = 0;
+    let new = db
+        .update(b"k", |old| {
+            calls += 1;
+            if calls == 1 {
+                db.put(b"k", b"1").unwrap();
+            }
+            let mut new = old.unwrap().to_vec();
+            new.push(b'+');
+            Some(new)
+        })
+        .unwrap();
+    assert_eq!(calls, 2);
+    assert_eq!(new.unwrap(), b"1+");
+    assert_eq!(db.get(b"k").unwrap().unwrap(), b"1+");
+
+    let mut no_retry = RetryOptions::default();
+    no_retry.set_max_retries(0);
+    let err = db
+        .update_cf_opt(&cf, b"k", &no_retry, |_| {
+            db.put_cf(&cf, b"k", b"conflict").unwrap();
+            Some(b"lost".to_vec())
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Busy);
+    assert_eq!(db.get_cf(&cf, b"k").unwrap().unwrap(), b"conflict");
+}
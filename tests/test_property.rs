@@ -16,7 +16,10 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{properties, Options, DB};
+use rocksdb::{
+    perf::CacheEntryRole, properties, BlockBasedOptions, Cache, Options, WriteStallCause,
+    WriteStallCondition, WriteStallInfo, WriteStallReason, DB,
+};
 use util::DBPath;
 
 #[test]
@@ -95,3 +98,161 @@ fn property_int_cf_test() {
         assert_eq!(total_keys, Some(0));
     }
 }
+
+#[test]
+fn background_job_info_test() {
+    let n = DBPath::new("_rust_rocksdb_background_job_info_test");
+    {
+        let opts = Options::default();
+        #[cfg(feature = "multi-threaded-cf")]
+        let db = DB::open_default(&n).unwrap();
+        #[cfg(not(feature = "multi-threaded-cf"))]
+        let mut db = DB::open_default(&n).unwrap();
+        db.create_cf("cf1", &opts).unwrap();
+
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        let info = db.get_background_job_info().unwrap();
+        assert!(!info.flush_pending);
+        assert_eq!(info.num_immutable_mem_tables, 0);
+        assert_eq!(info.background_errors, 0);
+
+        let cf = db.cf_handle("cf1").unwrap();
+        db.put_cf(&cf, b"k1", b"v1").unwrap();
+        let info = db.get_background_job_info_cf(&cf).unwrap();
+        assert!(!info.flush_pending);
+        assert_eq!(info.num_immutable_mem_tables, 0);
+
+        db.flush_cf(&cf).unwrap();
+        let info = db.get_background_job_info_cf(&cf).unwrap();
+        assert_eq!(info.num_running_flushes, 0);
+        assert_eq!(info.background_errors, 0);
+    }
+}
+
+#[test]
+fn cf_stats_test() {
+    let n = DBPath::new("_rust_rocksdb_cf_stats_test");
+    {
+        let opts = Options::default();
+        #[cfg(feature = "multi-threaded-cf")]
+        let db = DB::open_default(&n).unwrap();
+        #[cfg(not(feature = "multi-threaded-cf"))]
+        let mut db = DB::open_default(&n).unwrap();
+        db.create_cf("cf1", &opts).unwrap();
+        let cf = db.cf_handle("cf1").unwrap();
+
+        for i in 0..10_u32 {
+            db.put_cf(&cf, i.to_be_bytes(), b"value").unwrap();
+        }
+        db.flush_cf(&cf).unwrap();
+
+        let stats = db.get_cf_stats(&cf).unwrap();
+        let l0 = &stats.levels[&0];
+        assert_eq!(l0.num_files, 1);
+        assert_eq!(l0.num_files_being_compacted, 0);
+        assert!(l0.size_bytes > 0);
+        assert_eq!(stats.sum.num_files, 1);
+        assert_eq!(stats.write_stalls.get("total-stops"), Some(&0));
+    }
+}
+
+#[test]
+fn write_stall_info_test() {
+    let n = DBPath::new("_rust_rocksdb_write_stall_info_test");
+    {
+        let db = DB::open_default(&n).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+
+        let info = db.get_write_stall_info().unwrap();
+        assert_eq!(info, WriteStallInfo::default());
+        assert!(!info.is_stalled());
+        assert!(!db.is_write_stalled().unwrap());
+    }
+}
+
+#[test]
+fn write_stall_causes_test() {
+    let n = DBPath::new("_rust_rocksdb_write_stall_causes_test");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_level_zero_file_num_compaction_trigger(4);
+        let db = DB::open(&opts, &n).unwrap();
+        for key in [b"k1", b"k2"] {
+            db.put(key, b"v").unwrap();
+            db.flush().unwrap();
+        }
+        assert_eq!(db.get_write_stall_info().unwrap().causes, vec![]);
+
+        db.set_options(&[("level0_slowdown_writes_trigger", "2")])
+            .unwrap();
+        let reason = WriteStallReason {
+            column_family: "default".to_owned(),
+            cause: WriteStallCause::L0FileCountLimit,
+            condition: WriteStallCondition::Delayed,
+            value: 2,
+            limit: 2,
+        };
+        assert_eq!(
+            db.get_write_stall_info().unwrap().causes,
+            vec![reason.clone()]
+        );
+        assert!(db.is_write_stalled().unwrap());
+
+        db.set_options(&[("level0_stop_writes_trigger", "2")])
+            .unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        assert_eq!(
+            db.get_write_stall_info_cf(&cf).unwrap().causes,
+            vec![WriteStallReason {
+                condition: WriteStallCondition::Stopped,
+                ..reason
+            }]
+        );
+
+        db.set_options(&[
+            ("level0_slowdown_writes_trigger", "20"),
+            ("level0_stop_writes_trigger", "36"),
+        ])
+        .unwrap();
+        assert_eq!(db.get_write_stall_info().unwrap().causes, vec![]);
+    }
+}
+
+#[test]
+fn block_cache_usage_by_role_test() {
+    let n = DBPath::new("_rust_rocksdb_block_cache_usage_by_role_test");
+    let cache = Cache::new_lru_cache(8 * 1024 * 1024);
+    assert_eq!(cache.get_capacity(), 8 * 1024 * 1024);
+    let mut block_opts = BlockBasedOptions::default();
+    block_opts.set_block_cache(&cache);
+    block_opts.set_bloom_filter(10.0, false);
+    block_opts.set_cache_index_and_filter_blocks(true);
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_block_based_table_factory(&block_opts);
+
+    let db = DB::open(&opts, &n).unwrap();
+    for i in 0..1000_u32 {
+        db.put(i.to_be_bytes(), [0; 100]).unwrap();
+    }
+    db.flush().unwrap();
+    for i in 0..1000_u32 {
+        db.get(i.to_be_bytes()).unwrap();
+    }
+
+    let stats = db.get_block_cache_usage_by_role().unwrap();
+    assert_eq!(stats.capacity, 8 * 1024 * 1024);
+    assert!(stats.usage > 0);
+    for role in [
+        CacheEntryRole::DataBlock,
+        CacheEntryRole::FilterBlock,
+        CacheEntryRole::IndexBlock,
+    ] {
+        let usage = stats.get(role);
+        assert!(usage.count > 0, "no {role:?} cached");
+        assert!(usage.bytes > 0, "no {role:?} cached");
+    }
+    assert_eq!(stats.get(CacheEntryRole::BlobValue).count, 0);
+}
@@ -0,0 +1,140 @@
+#![cfg(feature = "collections")]
+
+mod util;
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::collections::{queue_options, Queue};
+use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+use util::DBPath;
+
+fn open_db(path: &DBPath) -> Arc<DB> {
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let cf = ColumnFamilyDescriptor::new("queue", queue_options());
+    Arc::new(DB::open_cf_descriptors(&opts, path, vec![cf]).unwrap())
+}
+
+#[test]
+fn test_queue_push_pop() {
+    let path = DBPath::new("_rust_rocksdb_test_queue_push_pop");
+    let queue = Queue::open(open_db(&path), "queue").unwrap();
+    assert!(queue.is_empty());
+    assert!(queue.peek().unwrap().is_none());
+    assert!(queue.pop().unwrap().is_none());
+    assert!(queue.pop_batch(10).unwrap().is_empty());
+
+    for i in 0..5 {
+        queue.push(format!("item{i}")).unwrap();
+    }
+    assert_eq!(queue.len(), 5);
+    assert_eq!(queue.peek().unwrap().unwrap(), b"item0");
+    assert_eq!(queue.pop().unwrap().unwrap(), b"item0");
+    assert_eq!(
+        queue.pop_batch(2).unwrap(),
+        vec![b"item1".to_vec(), b"item2".to_vec()]
+    );
+    queue.push(b"item5").unwrap();
+    assert_eq!(queue.peek().unwrap().unwrap(), b"item3");
+    assert_eq!(
+        queue.pop_batch(10).unwrap(),
+        vec![b"item3".to_vec(), b"item4".to_vec(), b"item5".to_vec()]
+    );
+    assert!(queue.is_empty());
+
+    assert!(Queue::open(
+        open_db(&DBPath::new("_rust_rocksdb_test_queue_missing")),
+        "missing"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_queue_full() {
+    let path = DBPath::new("_rust_rocksdb_test_queue_full");
+    let db = open_db(&path);
+    let cf = db.cf_handle("queue").unwrap();
+    db.put_cf(&cf, (u64::MAX - 1).to_be_bytes(), b"last")
+        .unwrap();
+    let queue = Queue::open(Arc::clone(&db), "queue").unwrap();
+    assert_eq!(queue.len(), 1);
+    assert!(queue.push(b"overflow").is_err());
+    assert_eq!(queue.pop().unwrap().unwrap(), b"last");
+
+    db.put_cf(&cf, u64::MAX.to_be_bytes(), b"overflow").unwrap();
+    assert!(Queue::open(db, "queue").is_err());
+}
+
+#[test]
+fn test_queue_recovery() {
+    let path = DBPath::new("_rust_rocksdb_test_queue_recovery");
+    {
+        // the writes are only recovered from the WAL
+        let queue = Queue::open(open_db(&path), "queue").unwrap();
+        for i in 0..10 {
+            queue.push(format!("item{i}")).unwrap();
+        }
+        assert_eq!(queue.pop_batch(3).unwrap().len(), 3);
+    }
+    {
+        let db = open_db(&path);
+        let queue = Queue::open(Arc::clone(&db), "queue").unwrap();
+        assert_eq!(queue.len(), 7);
+        assert_eq!(queue.peek().unwrap().unwrap(), b"item3");
+        queue.push(b"item10").unwrap();
+        assert_eq!(queue.pop_batch(2).unwrap().len(), 2);
+        let cf = db.cf_handle("queue").unwrap();
+        db.flush_cf(&cf).unwrap();
+        db.compact_range_cf(&cf, None::<&[u8]>, None::<&[u8]>);
+    }
+    {
+        // the writes are recovered from the table files
+        let queue = Queue::open(open_db(&path), "queue").unwrap();
+        assert_eq!(queue.len(), 6);
+        let items: Vec<_> = queue.pop_batch(100).unwrap();
+        let expected: Vec<_> = (5..=10).map(|i| format!("item{i}").into_bytes()).collect();
+        assert_eq!(items, expected);
+    }
+    {
+        // pushing to a drained queue reuses the keys of the popped items
+        let queue = Queue::open(open_db(&path), "queue").unwrap();
+        assert!(queue.is_empty());
+        queue.push(b"again").unwrap();
+        assert_eq!(queue.pop().unwrap().unwrap(), b"again");
+        assert!(queue.is_empty());
+    }
+}
+
+#[test]
+fn test_queue_concurrent_pops() {
+    let path = DBPath::new("_rust_rocksdb_test_queue_concurrent_pops");
+    let queue = Arc::new(Queue::open(open_db(&path), "queue").unwrap());
+    for i in 0..100_u32 {
+        queue.push(i.to_be_bytes()).unwrap();
+    }
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            std::thread::spawn(move || {
+                let mut popped = Vec::new();
+                while let Some(item) = queue.pop().unwrap() {
+                    popped.push(u32::from_be_bytes(item.try_into().unwrap()));
+                }
+                popped
+            })
+        })
+        .collect();
+    let mut popped: Vec<u32> = Vec::new();
+    for handle in handles {
+        let items = handle.join().unwrap();
+        // every thread pops its items in order
+        assert!(items.windows(2).all(|w| w[0] < w[1]));
+        popped.extend(items);
+    }
+    popped.sort_unstable();
+    assert_eq!(popped, (0..100).collect::<Vec<_>>());
+}
@@ -16,7 +16,9 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{DBAccess, DBRawIteratorWithThreadMode, DB};
+use rocksdb::{
+    DBAccess, DBRawIteratorWithThreadMode, ErrorKind, IteratorState, ReadOptions, ReadTier, DB,
+};
 use util::DBPath;
 
 fn assert_item<D: DBAccess>(iter: &DBRawIteratorWithThreadMode<'_, D>, key: &[u8], value: &[u8]) {
@@ -150,3 +152,40 @@ pub fn test_next_without_seek() {
         iter.next();
     }
 }
+
+#[test]
+pub fn test_iterator_state() {
+    let n = DBPath::new("iterator_state");
+    {
+        let db = DB::open_default(&n).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        db.put(b"k3", b"v3").unwrap();
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_upper_bound(b"k3".to_vec());
+        let mut iter = db.raw_iterator_opt(readopts);
+        assert!(matches!(iter.state(), IteratorState::Exhausted));
+        iter.seek_to_first();
+        assert!(iter.state().is_valid());
+        iter.next();
+        iter.next();
+        assert!(matches!(iter.state(), IteratorState::Exhausted));
+        assert!(!iter.state().into_result().unwrap());
+
+        db.flush().unwrap();
+    }
+    {
+        // the data blocks are not in the block cache of the reopened DB
+        let db = DB::open_default(&n).unwrap();
+        let mut readopts = ReadOptions::default();
+        readopts.set_read_tier(ReadTier::BlockCache);
+        let mut iter = db.raw_iterator_opt(readopts);
+        iter.seek_to_first();
+        match iter.state() {
+            IteratorState::Failed(err) => assert_eq!(err.kind(), ErrorKind::Incomplete),
+            state => panic!("unexpected iterator state {state:?}"),
+        }
+        assert!(iter.state().into_result().is_err());
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "replication")]
+
+mod util;
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::{WriteBatch, DB};
+use util::DBPath;
+
+#[test]
+fn test_write_replicated() {
+    let leader_path = DBPath::new("_rust_rocksdb_test_write_replicated_leader");
+    let follower_path = DBPath::new("_rust_rocksdb_test_write_replicated_follower");
+    let leader = DB::open_default(&leader_path).unwrap();
+    let follower = DB::open_default(&follower_path).unwrap();
+
+    leader.put(b"k1", b"v1").unwrap();
+    let mut batch = WriteBatch::default();
+    batch.put(b"k2", b"v2");
+    batch.delete(b"k1");
+    leader.write(batch).unwrap();
+    leader.put(b"k3", b"v3").unwrap();
+
+    for update in leader.get_updates_since(0).unwrap() {
+        let (seqno, batch) = update.unwrap();
+        follower.write_replicated(seqno, batch).unwrap();
+    }
+    assert_eq!(
+        follower.latest_sequence_number(),
+        leader.latest_sequence_number()
+    );
+    assert!(follower.get(b"k1").unwrap().is_none());
+    assert_eq!(follower.get(b"k3").unwrap().unwrap(), b"v3");
+
+    // a batch applied twice, or a skipped batch, is rejected
+    let mut batch = WriteBatch::default();
+    batch.put(b"k4", b"v4");
+    let next = leader.latest_sequence_number() + 1;
+    assert!(follower.write_replicated(next - 1, batch).is_err());
+    let mut batch = WriteBatch::default();
+    batch.put(b"k4", b"v4");
+    assert!(follower.write_replicated(next + 1, batch).is_err());
+    assert!(follower.get(b"k4").unwrap().is_none());
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "rich-errors")]
+
+mod util;
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::{ErrorKind, Options, DB};
+use util::DBPath;
+
+#[test]
+fn test_error_context() {
+    let path = DBPath::new("_rust_rocksdb_test_error_context");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        DB::open_cf(&opts, &path, ["cf1"]).unwrap();
+    }
+
+    let db = DB::open_cf_for_read_only(&Options::default(), &path, ["cf1"], false).unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+
+    let err = db.put_cf(&cf1, b"key", b"value").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotSupported);
+    let context = err.context().unwrap();
+    assert_eq!(context.op(), "put_cf");
+    assert_eq!(context.column_family(), Some("cf1"));
+    assert_eq!(context.key_len(), Some(3));
+    assert!(err
+        .to_string()
+        .starts_with("put_cf (column family cf1, key of 3 bytes): Not implemented"));
+    assert!(err.as_ref().starts_with("Not implemented"));
+
+    let err = db.delete(b"k").unwrap_err();
+    let context = err.context().unwrap();
+    assert_eq!(context.op(), "delete");
+    assert_eq!(context.column_family(), None);
+    assert_eq!(context.key_len(), Some(1));
+
+    let err = db.flush().unwrap_err();
+    assert_eq!(err.context().unwrap().op(), "flush");
+    assert_eq!(err.context().unwrap().key_len(), None);
+}
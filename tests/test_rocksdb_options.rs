@@ -17,7 +17,11 @@ mod util;
 use std::{fs, io::Read as _};
 
 use rocksdb::{
-    BlockBasedOptions, Cache, DBCompressionType, DataBlockIndexType, Env, Options, ReadOptions, DB,
+    perf::CacheUsageReport, properties, BlockBasedIndexType, BlockBasedOptions,
+    BottommostLevelCompaction, Cache, ChecksumGenType, CompactOptions, DBCompactionStyle,
+    DBCompressionType, DBRecoveryMode, DataBlockIndexType, Env, FlushOptions, IndexShorteningMode,
+    LogLevel, Options, RateLimiter, ReadOptions, ReadOptionsBuilder, ReadTier,
+    WaitForCompactOptions, WriteOptions, DB,
 };
 use util::DBPath;
 
@@ -106,6 +110,90 @@ fn test_block_based_options() {
     }
 }
 
+#[test]
+fn test_partitioned_filters() {
+    let n = DBPath::new("_rust_rocksdb_test_partitioned_filters");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+
+        let mut block_opts = BlockBasedOptions::default();
+        block_opts.set_ribbon_filter(10.0);
+        block_opts.set_index_type(BlockBasedIndexType::TwoLevelIndexSearch);
+        block_opts.set_partition_filters(true);
+        block_opts.set_metadata_block_size(8192);
+        block_opts.set_cache_index_and_filter_blocks(true);
+        block_opts.set_pin_top_level_index_and_filter(true);
+        block_opts.set_optimize_filters_for_memory(true);
+
+        opts.set_block_based_table_factory(&block_opts);
+        let db = DB::open(&opts, &n).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+
+        let mut rocksdb_log = fs::File::open(format!("{}/LOG", (&n).as_ref().to_str().unwrap()))
+            .expect("rocksdb creates a LOG file");
+        let mut settings = String::new();
+        rocksdb_log.read_to_string(&mut settings).unwrap();
+
+        assert!(settings.contains("index_type: 2"));
+        assert!(settings.contains("partition_filters: 1"));
+        assert!(settings.contains("metadata_block_size: 8192"));
+        assert!(settings.contains("pin_top_level_index_and_filter: 1"));
+        assert!(settings.contains("optimize_filters_for_memory: 1"));
+    }
+}
+
+#[test]
+fn test_block_based_presets() {
+    fn open_with_preset(name: &str, block_opts: &BlockBasedOptions) -> String {
+        let n = DBPath::new(name);
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_block_based_table_factory(block_opts);
+        let db = DB::open(&opts, &n).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+        assert_eq!(db.iterator(rocksdb::IteratorMode::Start).count(), 1);
+
+        let mut rocksdb_log = fs::File::open(format!("{}/LOG", (&n).as_ref().to_str().unwrap()))
+            .expect("rocksdb creates a LOG file");
+        let mut settings = String::new();
+        rocksdb_log.read_to_string(&mut settings).unwrap();
+        settings
+    }
+
+    let settings = open_with_preset(
+        "_rust_rocksdb_test_preset_low_memory",
+        &BlockBasedOptions::preset_low_memory(),
+    );
+    assert!(settings.contains("index_type: 2"));
+    assert!(settings.contains("partition_filters: 1"));
+    assert!(settings.contains("cache_index_and_filter_blocks: 1"));
+    assert!(settings.contains("pin_top_level_index_and_filter: 1"));
+    assert!(settings.contains("block_size: 16384"));
+
+    let cache = Cache::new_lru_cache(4 * 1024 * 1024);
+    let settings = open_with_preset(
+        "_rust_rocksdb_test_preset_point_lookup",
+        &BlockBasedOptions::preset_point_lookup(&cache),
+    );
+    assert!(settings.contains("data_block_index_type: 1"));
+    assert!(settings.contains("pin_l0_filter_and_index_blocks_in_cache: 1"));
+    assert!(settings.contains("whole_key_filtering: 1"));
+    assert!(settings.contains("capacity : 4194304"));
+
+    let settings = open_with_preset(
+        "_rust_rocksdb_test_preset_range_scan",
+        &BlockBasedOptions::preset_range_scan(),
+    );
+    assert!(settings.contains("index_type: 0"));
+    assert!(settings.contains("block_size: 65536"));
+    assert!(settings.contains("filter_policy: nullptr"));
+}
+
 #[test]
 fn test_read_options() {
     let mut read_opts = ReadOptions::default();
@@ -171,6 +259,41 @@ fn set_compression_options_zstd_max_train_bytes() {
     }
 }
 
+#[test]
+#[cfg(feature = "zstd")]
+fn set_per_level_and_bottommost_dictionary_compression() {
+    let path = DBPath::new("_rust_set_per_level_and_bottommost_dictionary_compression");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_num_levels(3);
+        opts.set_compression_per_level(&[
+            DBCompressionType::None,
+            DBCompressionType::Zstd,
+            DBCompressionType::Zstd,
+        ]);
+        opts.set_compression_options(-14, 3, 0, 16 * 1024);
+        opts.set_zstd_max_train_bytes(100 * 16 * 1024);
+        opts.set_compression_options_max_dict_buffer_bytes(1024 * 1024);
+        opts.set_compression_options_use_zstd_dict_trainer(false);
+        opts.set_bottommost_compression_type(DBCompressionType::Zstd);
+        opts.set_bottommost_compression_options(-14, 9, 0, 32 * 1024, true);
+        opts.set_bottommost_zstd_max_train_bytes(100 * 32 * 1024, true);
+        opts.set_bottommost_compression_options_max_dict_buffer_bytes(1024 * 1024, true);
+        opts.set_bottommost_compression_options_use_zstd_dict_trainer(true, true);
+        let db = DB::open(&opts, &path).unwrap();
+        for i in 0..100_u32 {
+            db.put(i.to_be_bytes(), b"some repetitive value").unwrap();
+        }
+        db.flush().unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        assert_eq!(
+            db.get(7_u32.to_be_bytes()).unwrap().unwrap(),
+            b"some repetitive value"
+        );
+    }
+}
+
 #[test]
 fn set_wal_compression_zstd() {
     let path = DBPath::new("_set_wal_compression_zstd");
@@ -276,3 +399,417 @@ fn test_set_periodic_compaction_seconds() {
         let _db = DB::open(&opts, &path).unwrap();
     }
 }
+
+#[test]
+fn test_enable_file_checksums() {
+    let path = DBPath::new("_rust_rocksdb_test_enable_file_checksums");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.enable_file_checksums(ChecksumGenType::Crc32c).unwrap();
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+
+        let files = db.live_files().unwrap();
+        assert_eq!(files.len(), 1);
+        let checksum = files[0].file_checksum.clone().unwrap();
+        assert_eq!(checksum.func_name, ChecksumGenType::Crc32c.func_name());
+        let db_dir: &std::path::Path = (&path).as_ref();
+        let file_path = db_dir.join(files[0].name.trim_start_matches('/'));
+        assert_eq!(
+            ChecksumGenType::Crc32c.checksum_file(&file_path).unwrap(),
+            checksum
+        );
+        assert_ne!(
+            ChecksumGenType::XxHash64.checksum_file(&file_path).unwrap(),
+            checksum
+        );
+    }
+
+    let mut opts = Options::default();
+    let err = opts
+        .enable_file_checksums(ChecksumGenType::XxHash64)
+        .unwrap_err();
+    assert_eq!(err.kind(), rocksdb::ErrorKind::NotSupported);
+}
+
+#[test]
+fn test_checksum_file_xxhash64() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("data");
+    for (content, expected) in [
+        (&b""[..], 0xef46_db37_51d8_e999_u64),
+        (
+            &b"The quick brown fox jumps over the lazy dog"[..],
+            0x0b24_2d36_1fda_71bc,
+        ),
+    ] {
+        fs::write(&file_path, content).unwrap();
+        let checksum = ChecksumGenType::XxHash64.checksum_file(&file_path).unwrap();
+        assert_eq!(checksum.value, expected.to_be_bytes());
+        assert_eq!(checksum.func_name, "FileChecksumXxh64");
+    }
+}
+
+#[test]
+fn test_compact_on_deletion_collector_triggers_compaction() {
+    let n = DBPath::new("_rust_rocksdb_test_compact_on_deletion_collector_triggers");
+
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.add_compact_on_deletion_collector_factory(10, 5, 0.0);
+    let db = DB::open(&opts, &n).unwrap();
+
+    for i in 0..100_u32 {
+        db.put(i.to_be_bytes(), b"value").unwrap();
+    }
+    db.flush().unwrap();
+    for i in 0..100_u32 {
+        db.delete(i.to_be_bytes()).unwrap();
+    }
+    db.flush().unwrap();
+
+    let mut wait_opts = WaitForCompactOptions::default();
+    wait_opts.set_flush(true);
+    db.wait_for_compact(&wait_opts).unwrap();
+
+    // the tombstone-heavy L0 file has been compacted away
+    assert_eq!(
+        db.property_int_value(properties::num_files_at_level(0))
+            .unwrap(),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_cache_usage_report() {
+    let path = DBPath::new("_rust_rocksdb_test_cache_usage_report");
+    let shared = Cache::new_lru_cache(16 * 1024 * 1024);
+    let isolated = Cache::new_lru_cache(16 * 1024 * 1024);
+    let options_with_cache = |cache: &Cache| {
+        let mut block_opts = BlockBasedOptions::default();
+        block_opts.set_block_cache(cache);
+        let mut opts = Options::default();
+        opts.set_block_based_table_factory(&block_opts);
+        opts
+    };
+
+    let mut db_opts = options_with_cache(&shared);
+    db_opts.create_if_missing(true);
+    db_opts.create_missing_column_families(true);
+    let cf1_opts = options_with_cache(&shared);
+    let cf2_opts = options_with_cache(&isolated);
+    let cf3_opts = Options::default();
+    assert!(Cache::ptr_eq(
+        &db_opts.get_block_cache().unwrap(),
+        &cf1_opts.get_block_cache().unwrap()
+    ));
+    assert!(cf3_opts.get_block_cache().is_none());
+
+    let db = DB::open_cf_with_opts(
+        &db_opts,
+        &path,
+        [
+            ("cf1", cf1_opts.clone()),
+            ("cf2", cf2_opts.clone()),
+            ("cf3", cf3_opts.clone()),
+        ],
+    )
+    .unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+    db.put_cf(&cf1, b"k1", b"v1").unwrap();
+    db.flush_cf(&cf1).unwrap();
+    db.get_cf(&cf1, b"k1").unwrap();
+
+    let report = CacheUsageReport::new([
+        ("default", &db_opts),
+        ("cf1", &cf1_opts),
+        ("cf2", &cf2_opts),
+        ("cf3", &cf3_opts),
+    ]);
+    assert_eq!(report.caches.len(), 2);
+    assert_eq!(report.caches[0].column_families, vec!["default", "cf1"]);
+    assert!(report.caches[0].is_shared());
+    assert!(report.caches[0].usage > 0);
+    assert_eq!(report.caches[1].column_families, vec!["cf2"]);
+    assert!(!report.caches[1].is_shared());
+    assert_eq!(report.internal_cache_column_families, vec!["cf3"]);
+}
+
+#[test]
+fn test_options_getters() {
+    let mut opts = Options::default();
+    assert!(!opts.get_create_if_missing());
+    assert_eq!(opts.get_bottommost_compression_type(), None);
+
+    opts.create_if_missing(true);
+    opts.set_max_background_jobs(6);
+    opts.set_write_buffer_size(32 << 20);
+    opts.set_compression_type(DBCompressionType::Lz4);
+    opts.set_bottommost_compression_type(DBCompressionType::Zstd);
+    opts.set_compaction_style(DBCompactionStyle::Universal);
+    opts.set_wal_recovery_mode(DBRecoveryMode::AbsoluteConsistency);
+    opts.set_log_level(LogLevel::Warn);
+    opts.set_memtable_prefix_bloom_ratio(0.25);
+
+    assert!(opts.get_create_if_missing());
+    assert_eq!(opts.get_max_background_jobs(), 6);
+    assert_eq!(opts.get_write_buffer_size(), 32 << 20);
+    assert_eq!(opts.get_compression_type(), Some(DBCompressionType::Lz4));
+    assert_eq!(
+        opts.get_bottommost_compression_type(),
+        Some(DBCompressionType::Zstd)
+    );
+    assert_eq!(
+        opts.get_compaction_style(),
+        Some(DBCompactionStyle::Universal)
+    );
+    assert_eq!(
+        opts.get_wal_recovery_mode(),
+        DBRecoveryMode::AbsoluteConsistency
+    );
+    assert_eq!(opts.get_log_level(), LogLevel::Warn);
+    assert_eq!(opts.get_memtable_prefix_bloom_ratio(), 0.25);
+
+    let mut write_opts = WriteOptions::default();
+    write_opts.set_sync(true);
+    write_opts.disable_wal(true);
+    assert!(write_opts.get_sync());
+    assert!(write_opts.get_disable_wal());
+
+    let mut read_opts = ReadOptions::default();
+    assert!(read_opts.get_fill_cache());
+    read_opts.fill_cache(false);
+    read_opts.set_read_tier(ReadTier::BlockCache);
+    read_opts.set_readahead_size(4 << 20);
+    assert!(!read_opts.get_fill_cache());
+    assert_eq!(read_opts.get_read_tier(), Some(ReadTier::BlockCache));
+    assert_eq!(read_opts.get_readahead_size(), 4 << 20);
+}
+
+#[test]
+fn test_options_clone_and_debug() {
+    let mut write_opts = WriteOptions::default();
+    write_opts.set_sync(true);
+    write_opts.set_no_slowdown(true);
+    let cloned = write_opts.clone();
+    drop(write_opts);
+    assert!(cloned.get_sync());
+    assert!(cloned.get_no_slowdown());
+    assert!(format!("{cloned:?}").contains("sync: true"));
+
+    let mut flush_opts = FlushOptions::default();
+    flush_opts.set_wait(false);
+    assert!(!flush_opts.clone().get_wait());
+    assert_eq!(format!("{flush_opts:?}"), "FlushOptions { wait: false }");
+
+    let mut compact_opts = CompactOptions::default();
+    compact_opts.set_bottommost_level_compaction(BottommostLevelCompaction::Force);
+    compact_opts.set_target_level(2);
+    let cloned = compact_opts.clone();
+    assert_eq!(
+        cloned.get_bottommost_level_compaction(),
+        Some(BottommostLevelCompaction::Force)
+    );
+    assert_eq!(cloned.get_target_level(), 2);
+
+    let mut wait_opts = WaitForCompactOptions::default();
+    wait_opts.set_timeout(1000);
+    assert_eq!(wait_opts.clone().get_timeout(), 1000);
+
+    let mut read_opts = ReadOptions::default();
+    read_opts.set_iterate_upper_bound(b"z".to_vec());
+    assert!(format!("{read_opts:?}").contains("iterate_upper_bound: Some([122])"));
+
+    let mut opts = Options::default();
+    opts.set_write_buffer_size(1 << 20);
+    opts.create_if_missing(true);
+    let debug = format!("{opts:?}");
+    assert!(debug.contains("write_buffer_size: 1048576"));
+    assert!(debug.contains("create_if_missing: true"));
+}
+
+#[test]
+fn test_read_options_clone_and_builder() {
+    let path = DBPath::new("_rust_rocksdb_test_read_options_clone_and_builder");
+    let db = DB::open_default(&path).unwrap();
+    for key in [b"a", b"b", b"c", b"d"] {
+        db.put(key, b"v").unwrap();
+    }
+    let keys = |readopts: ReadOptions| -> Vec<Box<[u8]>> {
+        db.iterator_opt(rocksdb::IteratorMode::Start, readopts)
+            .map(|item| item.unwrap().0)
+            .collect()
+    };
+
+    let mut read_opts = ReadOptions::default();
+    read_opts.fill_cache(false);
+    read_opts.set_read_tier(ReadTier::BlockCache);
+    read_opts.set_iterate_range(b"b".to_vec()..b"d".to_vec());
+    let snapshot = db.snapshot();
+    read_opts.set_snapshot(&snapshot);
+    db.put(b"bb", b"v").unwrap();
+
+    let cloned = read_opts.clone();
+    drop(read_opts);
+    assert!(!cloned.get_fill_cache());
+    assert_eq!(cloned.get_read_tier(), Some(ReadTier::BlockCache));
+    let mut all_tier = cloned.clone();
+    all_tier.set_read_tier(ReadTier::All);
+    assert_eq!(
+        keys(all_tier),
+        vec![
+            b"b".to_vec().into_boxed_slice(),
+            b"c".to_vec().into_boxed_slice()
+        ]
+    );
+
+    let builder = ReadOptionsBuilder::new()
+        .fill_cache(false)
+        .readahead_size(1 << 20)
+        .iterate_range(b"b".to_vec()..);
+    for _ in 0..2 {
+        let readopts = builder.build();
+        assert!(!readopts.get_fill_cache());
+        assert_eq!(readopts.get_readahead_size(), 1 << 20);
+        assert_eq!(keys(readopts).len(), 4);
+    }
+}
+
+#[test]
+fn test_block_based_layout_options() {
+    let path = DBPath::new("_rust_rocksdb_test_block_based_layout_options");
+    let mut block_opts = BlockBasedOptions::default();
+    assert_eq!(block_opts.get_format_version(), 5);
+    block_opts.set_format_version(4);
+    assert_eq!(block_opts.get_format_version(), 4);
+    block_opts.set_use_delta_encoding(false);
+
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_compression_type(DBCompressionType::None);
+    opts.set_block_based_table_factory(&block_opts);
+    opts.set_block_based_table_layout(true, IndexShorteningMode::NoShortening, 32)
+        .unwrap();
+    {
+        let db = DB::open(&opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+    }
+
+    let dir: &std::path::Path = (&path).as_ref();
+    let options_file = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("OPTIONS-")
+        })
+        .max()
+        .unwrap();
+    let text = fs::read_to_string(options_file).unwrap();
+    for option in [
+        "format_version=4",
+        "block_align=true",
+        "index_shortening=kNoShortening",
+        "use_delta_encoding=false",
+        "read_amp_bytes_per_bit=32",
+    ] {
+        assert!(
+            text.contains(option),
+            "{option} not found in the OPTIONS file"
+        );
+    }
+}
+
+#[test]
+fn test_shared_rate_limiter() {
+    fn rate_limiter_in_log(path: &DBPath) -> String {
+        let mut rocksdb_log = fs::File::open(format!("{}/LOG", path.as_ref().to_str().unwrap()))
+            .expect("rocksdb creates a LOG file");
+        let mut settings = String::new();
+        rocksdb_log.read_to_string(&mut settings).unwrap();
+        settings
+            .lines()
+            .find_map(|line| line.split("Options.rate_limiter: ").nth(1))
+            .expect("the rate limiter is logged")
+            .trim()
+            .to_owned()
+    }
+
+    let limiter = RateLimiter::auto_tuned(64 * 1024 * 1024, 100 * 1000, 10);
+    let paths = [
+        DBPath::new("_rust_rocksdb_test_shared_rate_limiter_1"),
+        DBPath::new("_rust_rocksdb_test_shared_rate_limiter_2"),
+    ];
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_rate_limiter(&limiter);
+    drop(limiter);
+    for path in &paths {
+        let db = DB::open(&opts, path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+        db.compact_range(None::<&[u8]>, None::<&[u8]>);
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+    }
+
+    let limiters: Vec<_> = paths.iter().map(rate_limiter_in_log).collect();
+    assert_ne!(limiters[0], "(nil)");
+    assert_eq!(limiters[0], limiters[1]);
+}
+
+#[test]
+fn test_describe_levels() {
+    const MB: u64 = 1024 * 1024;
+    let mut opts = Options::default();
+    assert!(opts.get_level_compaction_dynamic_level_bytes());
+    assert!(opts
+        .get_max_bytes_for_level_multiplier_additional()
+        .is_empty());
+
+    opts.set_level_compaction_dynamic_level_bytes(false);
+    opts.set_num_levels(4);
+    opts.set_max_bytes_for_level_base(100 * MB);
+    opts.set_max_bytes_for_level_multiplier(10.0);
+    opts.set_max_bytes_for_level_multiplier_additional(&[1, 1, 2]);
+    opts.set_target_file_size_base(8 * MB);
+    opts.set_target_file_size_multiplier(2);
+    assert_eq!(
+        opts.get_max_bytes_for_level_multiplier_additional(),
+        [1, 1, 2]
+    );
+    assert_eq!(
+        opts.clone().get_max_bytes_for_level_multiplier_additional(),
+        [1, 1, 2]
+    );
+
+    let levels = opts.describe_levels();
+    let capacities: Vec<_> = levels.iter().map(|level| level.max_bytes).collect();
+    assert_eq!(
+        capacities,
+        [None, Some(100 * MB), Some(1000 * MB), Some(20000 * MB)]
+    );
+    let file_sizes: Vec<_> = levels.iter().map(|level| level.target_file_size).collect();
+    assert_eq!(file_sizes, [8 * MB, 8 * MB, 16 * MB, 32 * MB]);
+    // the size of the data does not change static capacities
+    assert_eq!(opts.describe_levels_for_size(1 << 40), levels);
+
+    // dynamic capacities are derived from the last level, and the levels
+    // above the one level 0 is compacted into are not used
+    opts.set_level_compaction_dynamic_level_bytes(true);
+    assert!(opts
+        .describe_levels()
+        .iter()
+        .all(|level| level.max_bytes.is_none()));
+    let levels = opts.describe_levels_for_size(500 * MB);
+    let capacities: Vec<_> = levels.iter().map(|level| level.max_bytes).collect();
+    assert_eq!(capacities, [None, None, Some(100 * MB), Some(500 * MB)]);
+    let file_sizes: Vec<_> = levels.iter().map(|level| level.target_file_size).collect();
+    assert_eq!(file_sizes, [8 * MB, 8 * MB, 8 * MB, 8 * MB]);
+}
@@ -0,0 +1,181 @@
+#![cfg(feature = "collections")]
+
+mod util;
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::collections::{IndexEntry, SecondaryIndex};
+use rocksdb::{OptimisticTransactionDB, Options, WriteBatch, DB};
+use util::DBPath;
+
+/// Indexes the comma-separated tags of a value.
+fn tags(_key: &[u8], value: &[u8]) -> Vec<Vec<u8>> {
+    value
+        .split(|b| *b == b',')
+        .filter(|tag| !tag.is_empty())
+        .map(<[u8]>::to_vec)
+        .collect()
+}
+
+fn entry(value: &[u8], key: &[u8]) -> IndexEntry {
+    IndexEntry {
+        value: value.to_vec(),
+        key: key.to_vec(),
+    }
+}
+
+fn open_opts() -> Options {
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    opts
+}
+
+#[test]
+fn test_secondary_index_maintenance() {
+    let path = DBPath::new("_rust_rocksdb_test_secondary_index_maintenance");
+    let db = DB::open_cf(&open_opts(), &path, ["items", "items_by_tag"]).unwrap();
+    let index = SecondaryIndex::new("items", "items_by_tag", tags);
+
+    index.put(&db, b"k1", b"red,round").unwrap();
+    index.put(&db, b"k2", b"red").unwrap();
+    index.put(&db, b"k3", b"blue").unwrap();
+    let all: Vec<_> = index.range(&db, ..).map(Result::unwrap).collect();
+    assert_eq!(
+        all,
+        vec![
+            entry(b"blue", b"k3"),
+            entry(b"red", b"k1"),
+            entry(b"red", b"k2"),
+            entry(b"round", b"k1"),
+        ]
+    );
+    assert_eq!(
+        index.keys(&db, b"red").unwrap(),
+        vec![b"k1".to_vec(), b"k2".to_vec()]
+    );
+
+    // updating a key replaces its entries, deleting it removes them
+    index.put(&db, b"k1", b"blue").unwrap();
+    index.delete(&db, b"k2").unwrap();
+    index.delete(&db, b"missing").unwrap();
+    assert!(index.keys(&db, b"red").unwrap().is_empty());
+    assert_eq!(
+        index.keys(&db, b"blue").unwrap(),
+        vec![b"k1".to_vec(), b"k3".to_vec()]
+    );
+    let all: Vec<_> = index.range(&db, ..).map(Result::unwrap).collect();
+    assert_eq!(all, vec![entry(b"blue", b"k1"), entry(b"blue", b"k3")]);
+    let items = db.cf_handle("items").unwrap();
+    assert_eq!(db.get_cf(&items, b"k1").unwrap().unwrap(), b"blue");
+    assert!(db.get_cf(&items, b"k2").unwrap().is_none());
+
+    let missing = SecondaryIndex::new("items", "missing", tags);
+    assert!(missing.put(&db, b"k1", b"red").is_err());
+    assert!(missing.range(&db, ..).next().unwrap().is_err());
+}
+
+#[test]
+fn test_secondary_index_range() {
+    let path = DBPath::new("_rust_rocksdb_test_secondary_index_range");
+    let db = DB::open_cf(&open_opts(), &path, ["items", "items_by_tag"]).unwrap();
+    let index = SecondaryIndex::new("items", "items_by_tag", |_key: &[u8], value: &[u8]| {
+        vec![value.to_vec()]
+    });
+
+    // values which are prefixes of each other or hold zero bytes
+    let values: [&[u8]; 6] = [b"a", b"a\0", b"a\0\0", b"a\x01", b"ab", b"b"];
+    for (i, value) in values.iter().enumerate().rev() {
+        index.put(&db, [i as u8], value).unwrap();
+    }
+    let all: Vec<_> = index.range(&db, ..).map(|e| e.unwrap().value).collect();
+    assert_eq!(all, values.iter().map(|v| v.to_vec()).collect::<Vec<_>>());
+
+    let range: Vec<_> = index
+        .range(&db, &b"a\0"[..]..&b"ab"[..])
+        .map(|e| e.unwrap().key)
+        .collect();
+    assert_eq!(range, vec![vec![1], vec![2], vec![3]]);
+    let range: Vec<_> = index
+        .range(&db, &b"ab"[..]..)
+        .map(|e| e.unwrap().key)
+        .collect();
+    assert_eq!(range, vec![vec![4], vec![5]]);
+    assert_eq!(index.keys(&db, b"a\0").unwrap(), vec![vec![1]]);
+}
+
+#[test]
+fn test_secondary_index_batch() {
+    let path = DBPath::new("_rust_rocksdb_test_secondary_index_batch");
+    let db = DB::open_cf(&open_opts(), &path, ["items", "items_by_tag", "other"]).unwrap();
+    let index = SecondaryIndex::new("items", "items_by_tag", tags);
+    index.put(&db, b"k1", b"red").unwrap();
+
+    let mut batch = WriteBatch::default();
+    index
+        .put_in_batch(&db, &mut batch, b"k1", b"green")
+        .unwrap();
+    index
+        .put_in_batch(&db, &mut batch, b"k2", b"green")
+        .unwrap();
+    let other = db.cf_handle("other").unwrap();
+    batch.put_cf(&other, b"count", b"2");
+    // nothing is written until the batch is
+    assert_eq!(index.keys(&db, b"red").unwrap(), vec![b"k1".to_vec()]);
+    db.write(batch).unwrap();
+    assert!(index.keys(&db, b"red").unwrap().is_empty());
+    assert_eq!(
+        index.keys(&db, b"green").unwrap(),
+        vec![b"k1".to_vec(), b"k2".to_vec()]
+    );
+
+    let mut batch = WriteBatch::default();
+    index.delete_in_batch(&db, &mut batch, b"k1").unwrap();
+    db.write(batch).unwrap();
+    assert_eq!(index.keys(&db, b"green").unwrap(), vec![b"k2".to_vec()]);
+}
+
+#[test]
+fn test_secondary_index_transaction() {
+    let path = DBPath::new("_rust_rocksdb_test_secondary_index_transaction");
+    let db: OptimisticTransactionDB =
+        OptimisticTransactionDB::open_cf(&open_opts(), &path, ["items", "items_by_tag"]).unwrap();
+    let index = SecondaryIndex::new("items", "items_by_tag", tags);
+    let items = db.cf_handle("items").unwrap();
+    let items_by_tag = db.cf_handle("items_by_tag").unwrap();
+
+    let txn = db.transaction();
+    index
+        .put_in_transaction(&txn, &items, &items_by_tag, b"k1", b"red")
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(index.keys(&db, b"red").unwrap(), vec![b"k1".to_vec()]);
+
+    // concurrent updates of the same key conflict instead of leaving
+    // stale entries behind
+    let txn1 = db.transaction();
+    let txn2 = db.transaction();
+    index
+        .put_in_transaction(&txn1, &items, &items_by_tag, b"k1", b"green")
+        .unwrap();
+    index
+        .put_in_transaction(&txn2, &items, &items_by_tag, b"k1", b"blue")
+        .unwrap();
+    txn1.commit().unwrap();
+    assert!(txn2.commit().is_err());
+    let all: Vec<_> = index.range(&db, ..).map(Result::unwrap).collect();
+    assert_eq!(all, vec![entry(b"green", b"k1")]);
+
+    let txn = db.transaction();
+    index
+        .delete_in_transaction(&txn, &items, &items_by_tag, b"k1")
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(index.range(&db, ..).count(), 0);
+
+    // the handles must be those of the column families of the index
+    let txn = db.transaction();
+    assert!(index
+        .put_in_transaction(&txn, &items_by_tag, &items, b"k1", b"red")
+        .is_err());
+}
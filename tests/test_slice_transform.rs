@@ -16,7 +16,10 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{Options, SliceTransform, DB};
+use rocksdb::{
+    perf::set_perf_stats, Options, PerfContext, PerfMetric, PerfStatsLevel, ReadOptions,
+    SliceTransform, DB,
+};
 use util::{assert_iter, pair, DBPath};
 
 #[test]
@@ -78,3 +81,39 @@ fn test_no_in_domain() {
         assert_eq!(db.get(b"key_sfx1").unwrap().unwrap(), b"a");
     }
 }
+
+#[test]
+fn test_memtable_prefix_bloom() {
+    let db_path = DBPath::new("_rust_rocksdb_memtable_prefix_bloom_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(3));
+    opts.set_memtable_prefix_bloom_ratio(0.1);
+    opts.set_memtable_whole_key_filtering(true);
+    let db = DB::open(&opts, &db_path).unwrap();
+    db.put(b"aaa1", b"a1").unwrap();
+    db.put(b"bbb1", b"b1").unwrap();
+
+    set_perf_stats(PerfStatsLevel::EnableCount);
+    let mut ctx = PerfContext::default();
+
+    // a prefix which was never written is skipped by the filter
+    let mut readopts = ReadOptions::default();
+    readopts.set_prefix_same_as_start(true);
+    let mut iter = db.raw_iterator_opt(readopts);
+    iter.seek(b"ccc");
+    assert!(!iter.valid());
+    assert_eq!(ctx.metric(PerfMetric::BloomMemtableMissCount), 1);
+
+    // and so is a missing key with an existing prefix
+    ctx.reset();
+    assert!(db.get(b"aaa2").unwrap().is_none());
+    assert_eq!(ctx.metric(PerfMetric::BloomMemtableMissCount), 1);
+
+    ctx.reset();
+    assert_eq!(db.get(b"aaa1").unwrap().unwrap(), b"a1");
+    assert_eq!(ctx.metric(PerfMetric::BloomMemtableMissCount), 0);
+    assert_eq!(ctx.metric(PerfMetric::BloomMemtableHitCount), 1);
+
+    set_perf_stats(PerfStatsLevel::Disable);
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "test-util")]
+
+use pretty_assertions::assert_eq;
+
+use rocksdb::{test_util::TestDb, Options};
+
+#[test]
+fn test_db_is_removed_on_drop() {
+    let mut db = TestDb::open_default().unwrap();
+    let path = db.path().to_path_buf();
+    db.put(b"k1", b"v1").unwrap();
+    db.reopen().unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+    assert!(path.join("CURRENT").exists());
+
+    // every DB gets its own directory
+    let other = TestDb::open_default().unwrap();
+    assert_ne!(other.path(), path);
+    assert!(other.get(b"k1").unwrap().is_none());
+
+    drop(db);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_db_in_memory() {
+    let mut db = TestDb::open_in_memory(Options::default()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.flush().unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+    assert!(!db.path().join("CURRENT").exists());
+
+    // the files are kept in the memory of the env, which survives a reopen
+    db.reopen().unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+}
@@ -15,12 +15,14 @@
 
 mod util;
 
+use std::{thread, time::Duration};
+
 use pretty_assertions::assert_eq;
 
 use rocksdb::{
-    CuckooTableOptions, DBAccess, Direction, Error, ErrorKind, IteratorMode, Options, ReadOptions,
-    SliceTransform, TransactionDB, TransactionDBOptions, TransactionOptions,
-    WriteBatchWithTransaction, WriteOptions, DB,
+    CuckooTableOptions, DBAccess, Database, Direction, Error, ErrorKind, ErrorSubCode,
+    IteratorMode, Options, ReadOptions, SliceTransform, TransactionDB, TransactionDBOptions,
+    TransactionOptions, WriteBatchWithTransaction, WriteOptions, DB,
 };
 use util::DBPath;
 
@@ -92,6 +94,20 @@ fn put_get() {
     }
 }
 
+#[test]
+fn commit_with_timestamp() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_commit_with_timestamp");
+    {
+        let db: TransactionDB = TransactionDB::open_default(&path).unwrap();
+        let txn = db.transaction();
+        txn.put(b"k1", b"v1").unwrap();
+        // the default column family has no timestamps, so the timestamp is
+        // not used
+        txn.commit_with_timestamp(42).unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+    }
+}
+
 #[test]
 fn multi_get() {
     let path = DBPath::new("_rust_rocksdb_multi_get");
@@ -445,6 +461,135 @@ fn transaction() {
     }
 }
 
+#[test]
+fn transaction_iterator_for_update() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_iterator_for_update");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let mut txn_db_opts = TransactionDBOptions::default();
+        txn_db_opts.set_txn_lock_timeout(10);
+
+        let db: TransactionDB = TransactionDB::open(&opts, &txn_db_opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        db.put(b"k3", b"v3").unwrap();
+
+        let txn1 = db.transaction();
+        let items: Vec<_> = txn1
+            .iterator_for_update(IteratorMode::From(b"k2", Direction::Forward), true)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (
+                    b"k2".to_vec().into_boxed_slice(),
+                    b"v2".to_vec().into_boxed_slice()
+                ),
+                (
+                    b"k3".to_vec().into_boxed_slice(),
+                    b"v3".to_vec().into_boxed_slice()
+                ),
+            ]
+        );
+
+        // the visited keys are locked
+        let txn2 = db.transaction();
+        let err = txn2.put(b"k2", b"v4").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        let err = txn2.put(b"k3", b"v4").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        // the others are not
+        txn2.put(b"k1", b"v4").unwrap();
+        txn2.commit().unwrap();
+
+        txn1.put(b"k2", b"v5").unwrap();
+        txn1.commit().unwrap();
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v5");
+    }
+}
+
+#[test]
+fn transaction_release_lock_with_savepoint() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_release_lock_with_savepoint");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let mut txn_db_opts = TransactionDBOptions::default();
+        txn_db_opts.set_txn_lock_timeout(10);
+
+        let db: TransactionDB = TransactionDB::open(&opts, &txn_db_opts, &path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+
+        let txn1 = db.transaction();
+        txn1.get_for_update(b"k1", true).unwrap();
+        txn1.set_savepoint();
+        txn1.get_for_update(b"k2", true).unwrap();
+        txn1.rollback_to_savepoint().unwrap();
+
+        // only the lock taken after the savepoint is released
+        let txn2 = db.transaction();
+        let err = txn2.put(b"k1", b"v3").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        txn2.put(b"k2", b"v3").unwrap();
+        txn2.commit().unwrap();
+
+        txn1.put(b"k1", b"v4").unwrap();
+        txn1.commit().unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v4");
+        assert_eq!(db.get(b"k2").unwrap().unwrap(), b"v3");
+    }
+}
+
+#[test]
+fn transaction_write_batch_size_and_elapsed() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_write_batch_size_and_elapsed");
+    {
+        let db: TransactionDB = TransactionDB::open_default(&path).unwrap();
+        let txn = db.transaction();
+        assert_eq!(txn.get_write_batch_count(), 0);
+        let empty_size = txn.get_write_batch_size();
+
+        txn.put(b"k1", b"v1").unwrap();
+        txn.delete(b"k2").unwrap();
+        assert_eq!(txn.get_write_batch_count(), 2);
+        assert!(txn.get_write_batch_size() > empty_size);
+
+        thread::sleep(Duration::from_millis(10));
+        assert!(txn.elapsed() >= Duration::from_millis(10));
+        txn.commit().unwrap();
+    }
+}
+
+#[test]
+fn transaction_error_sub_code() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_error_sub_code");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let mut txn_db_opts = TransactionDBOptions::default();
+        txn_db_opts.set_txn_lock_timeout(10);
+
+        let db: TransactionDB = TransactionDB::open(&opts, &txn_db_opts, &path).unwrap();
+
+        let txn1 = db.transaction();
+        txn1.put(b"k1", b"v1").unwrap();
+
+        let txn2 = db.transaction();
+        let err = txn2.put(b"k1", b"v2").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        assert_eq!(err.sub_code(), ErrorSubCode::LockTimeout);
+        assert!(err.is_retryable());
+        assert!(!err.is_data_loss());
+
+        txn1.commit().unwrap();
+        txn2.put(b"k1", b"v2").unwrap();
+        txn2.commit().unwrap();
+    }
+}
+
 #[test]
 fn transaction_iterator() {
     let path = DBPath::new("_rust_rocksdb_transaction_db_transaction_iterator");
@@ -600,6 +745,69 @@ fn transaction_snapshot() {
     }
 }
 
+#[test]
+fn transaction_iterator_variants() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_iterator_variants");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(2));
+        let db: TransactionDB =
+            TransactionDB::open_cf(&opts, &TransactionDBOptions::default(), &path, ["cf1"])
+                .unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        db.put(b"aa1", b"v1").unwrap();
+        db.put(b"bb1", b"v2").unwrap();
+        db.put_cf(&cf1, b"k1", b"v1").unwrap();
+
+        let mut txn_opts = TransactionOptions::default();
+        txn_opts.set_snapshot(true);
+        let txn = db.transaction_opt(&WriteOptions::default(), &txn_opts);
+        txn.put(b"aa2", b"v3").unwrap();
+        txn.put_cf(&cf1, b"k2", b"v2").unwrap();
+        db.put_cf(&cf1, b"k3", b"v3").unwrap();
+
+        // the iterators see the uncommitted writes of the transaction
+        let keys: Vec<_> = txn
+            .prefix_iterator(b"aa")
+            .map(|item| item.unwrap().0)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                b"aa1".to_vec().into_boxed_slice(),
+                b"aa2".to_vec().into_boxed_slice()
+            ]
+        );
+
+        let keys: Vec<_> = txn
+            .full_iterator_cf(&cf1, IteratorMode::Start)
+            .map(|item| item.unwrap().0)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                b"k1".to_vec().into_boxed_slice(),
+                b"k2".to_vec().into_boxed_slice(),
+                b"k3".to_vec().into_boxed_slice()
+            ]
+        );
+
+        // the iterators of the snapshot do not see the later writes to the DB
+        let snapshot = txn.snapshot();
+        let mut iter = snapshot.raw_iterator_cf_opt(&cf1, ReadOptions::default());
+        iter.seek_to_first();
+        let mut keys = Vec::new();
+        while iter.valid() {
+            keys.push(iter.key().unwrap().to_vec());
+            iter.next();
+        }
+        assert_eq!(keys, vec![b"k1".to_vec(), b"k2".to_vec()]);
+    }
+}
+
 #[test]
 fn two_phase_commit() {
     let path = DBPath::new("_rust_rocksdb_transaction_db_2pc");
@@ -693,3 +901,66 @@ fn test_snapshot_outlive_txn() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/fail/snapshot_outlive_transaction.rs");
 }
+
+#[test]
+fn transaction_db_get_updates_since() {
+    let path = DBPath::new("_rust_rocksdb_transaction_db_get_updates_since");
+    {
+        let db: TransactionDB = TransactionDB::open_default(&path).unwrap();
+        let seq0 = db.latest_sequence_number();
+
+        db.put(b"k1", b"v1").unwrap();
+        let txn = db.transaction();
+        txn.put(b"k2", b"v2").unwrap();
+        txn.put(b"k3", b"v3").unwrap();
+        txn.commit().unwrap();
+        assert_eq!(db.latest_sequence_number(), seq0 + 3);
+
+        let batches: Vec<_> = db
+            .get_updates_since(seq0)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].0, seq0 + 1);
+        assert_eq!(batches[0].1.len(), 1);
+        assert_eq!(batches[1].0, seq0 + 2);
+        assert_eq!(batches[1].1.len(), 2);
+    }
+}
+
+fn move_counter<D: Database>(db: &D, from: &[u8], to: &[u8]) -> Result<(), Error> {
+    let value = db.get(from)?.unwrap_or_default();
+    db.delete(from)?;
+    db.put(to, &value)?;
+    let keys: Vec<_> = db
+        .iterator(IteratorMode::Start)
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(keys, vec![to.to_vec().into_boxed_slice()]);
+    Ok(())
+}
+
+#[test]
+fn database_trait() {
+    let path = DBPath::new("_rust_rocksdb_database_trait");
+    {
+        let db = DB::open_default(&path).unwrap();
+        db.put(b"a", b"1").unwrap();
+        move_counter(&db, b"a", b"b").unwrap();
+        assert_eq!(db.get(b"b").unwrap(), Some(b"1".to_vec()));
+    }
+
+    let path = DBPath::new("_rust_rocksdb_database_trait_txn_db");
+    let db: TransactionDB = TransactionDB::open_default(&path).unwrap();
+    db.put(b"a", b"1").unwrap();
+    move_counter(&db, b"a", b"b").unwrap();
+
+    let txn = db.transaction();
+    move_counter(&txn, b"b", b"c").unwrap();
+    // the writes of the transaction are not visible before the commit
+    assert_eq!(db.get(b"b").unwrap(), Some(b"1".to_vec()));
+    txn.commit().unwrap();
+    assert_eq!(db.get(b"b").unwrap(), None);
+    assert_eq!(db.get(b"c").unwrap(), Some(b"1".to_vec()));
+}
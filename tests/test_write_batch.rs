@@ -16,7 +16,7 @@ use std::collections::HashMap;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{WriteBatch, WriteBatchIterator};
+use rocksdb::{BatchOp, WriteBatch, WriteBatchIterator};
 
 #[test]
 fn test_write_batch_clear() {
@@ -66,3 +66,46 @@ fn test_write_batch_with_serialized_data() {
     let mut it = Iterator { data: kvs };
     b2.iterate(&mut it);
 }
+
+#[test]
+fn test_write_batch_extend_from_pairs() {
+    let mut batch = WriteBatch::default();
+    batch.put(b"first", b"0");
+    batch.extend_from_pairs((1..=100_u32).map(|i| (i.to_be_bytes(), i.to_le_bytes())));
+    assert_eq!(batch.len(), 101);
+
+    let mut expected = WriteBatch::default();
+    expected.put(b"first", b"0");
+    for i in 1..=100_u32 {
+        expected.put(i.to_be_bytes(), i.to_le_bytes());
+    }
+    assert_eq!(batch.data(), expected.data());
+}
+
+#[test]
+fn test_write_batch_from_ops_and_append() {
+    let ops = vec![
+        BatchOp::Put(b"k1".to_vec(), b"v1".to_vec()),
+        BatchOp::Merge(b"k2".to_vec(), b"v2".to_vec()),
+        BatchOp::Delete(b"k3".to_vec()),
+    ];
+    let mut batch: WriteBatch = ops.into_iter().collect();
+    assert_eq!(batch.len(), 3);
+
+    let mut expected = WriteBatch::default();
+    expected.put(b"k1", b"v1");
+    expected.merge(b"k2", b"v2");
+    expected.delete(b"k3");
+    assert_eq!(batch.data(), expected.data());
+
+    let mut other = WriteBatch::default();
+    other.put(b"k4", b"v4");
+    other.delete(b"k5");
+    batch.append(&other);
+    batch.append(&WriteBatch::default());
+    assert_eq!(batch.len(), 5);
+
+    expected.put(b"k4", b"v4");
+    expected.delete(b"k5");
+    assert_eq!(batch.data(), expected.data());
+}